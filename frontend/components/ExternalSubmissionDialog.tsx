@@ -25,11 +25,8 @@ const ExternalSubmissionDialog: React.FC<ExternalSubmissionDialogProps> = ({
   const { submitToGerrit, submitToCodeArts, submitToCustomApi } = useLocalTasks();
 
   const [gerritConfig, setGerritConfig] = useState({
-    gerritUrl: 'https://gerrit-review.example.com',
-    username: '',
-    password: '',
+    gerritUrl: '',
     changeId: '',
-    revisionId: '',
     score: 0,
   });
 
@@ -56,9 +53,7 @@ const ExternalSubmissionDialog: React.FC<ExternalSubmissionDialogProps> = ({
           result = await submitToGerrit(
             taskId,
             gerritConfig.gerritUrl,
-            gerritConfig.username,
             gerritConfig.changeId,
-            gerritConfig.revisionId,
             gerritConfig.score,
           );
           break;
@@ -101,32 +96,8 @@ const ExternalSubmissionDialog: React.FC<ExternalSubmissionDialogProps> = ({
           onChange={(e) => setGerritConfig({ ...gerritConfig, gerritUrl: e.target.value })}
           className="w-full px-3 py-2 bg-editor-input border border-editor-line rounded text-editor-fg focus:outline-none focus:border-orange-500 text-sm"
           placeholder="https://gerrit.example.com"
-          required
-        />
-      </div>
-      <div>
-        <label className="block text-xs font-medium mb-1 text-editor-fg">Username</label>
-        <input
-          type="text"
-          value={gerritConfig.username}
-          onChange={(e) => setGerritConfig({ ...gerritConfig, username: e.target.value })}
-          className="w-full px-3 py-2 bg-editor-input border border-editor-line rounded text-editor-fg focus:outline-none focus:border-orange-500 text-sm"
-          placeholder="your-username"
-          required
-        />
-      </div>
-      <div>
-        <label className="block text-xs font-medium mb-1 text-editor-fg">
-          Password / HTTP Password
-        </label>
-        <input
-          type="password"
-          value={gerritConfig.password}
-          onChange={(e) => setGerritConfig({ ...gerritConfig, password: e.target.value })}
-          className="w-full px-3 py-2 bg-editor-input border border-editor-line rounded text-editor-fg focus:outline-none focus:border-orange-500 text-sm"
-          placeholder="your-password"
-          required
         />
+        <p className="text-xs text-editor-fg/60 mt-1">A configured Gerrit server; leave empty for the active one</p>
       </div>
       <div>
         <label className="block text-xs font-medium mb-1 text-editor-fg">Change ID</label>
@@ -139,17 +110,6 @@ const ExternalSubmissionDialog: React.FC<ExternalSubmissionDialogProps> = ({
           required
         />
       </div>
-      <div>
-        <label className="block text-xs font-medium mb-1 text-editor-fg">Revision ID</label>
-        <input
-          type="text"
-          value={gerritConfig.revisionId}
-          onChange={(e) => setGerritConfig({ ...gerritConfig, revisionId: e.target.value })}
-          className="w-full px-3 py-2 bg-editor-input border border-editor-line rounded text-editor-fg focus:outline-none focus:border-orange-500 text-sm"
-          placeholder="1a2b3c..."
-          required
-        />
-      </div>
       <div>
         <label className="block text-xs font-medium mb-1 text-editor-fg">Score (optional)</label>
         <input
@@ -69,17 +69,13 @@ export function useLocalTasks() {
   const submitToGerrit = async (
     taskId: string,
     gerritUrl: string,
-    username: string,
     changeId: string,
-    revisionId: string,
     score?: number,
   ): Promise<any> => {
     return await invoke('submit_task_to_gerrit', {
       taskId,
-      gerrit_url: gerritUrl,
-      username,
-      change_id: changeId,
-      revision_id: revisionId,
+      gerritUrl,
+      changeId,
       score,
     });
   };
@@ -0,0 +1,58 @@
+// Review Bundle Commands
+// Export and import .hrbundle archives for air-gapped review
+
+use std::path::Path;
+use tauri::State;
+use log::info;
+
+use crate::AppState;
+use crate::services::file_storage::{FileStorage, FileStorageConfig};
+use crate::services::review_bundle::{self, BundleImport, BundleManifest};
+
+fn file_storage() -> Result<FileStorage, String> {
+    FileStorage::new(FileStorageConfig::default())
+        .map_err(|e| format!("Failed to open file storage: {}", e))
+}
+
+/// Pack a downloaded change with its files and comments for offline review elsewhere
+#[tauri::command]
+pub async fn export_review_bundle(
+    change_id: String,
+    output_path: String,
+    state: State<'_, AppState>,
+) -> Result<BundleManifest, String> {
+    info!("Exporting review bundle for change {} to {}", change_id, output_path);
+
+    let mut storage = file_storage()?;
+    let database = state.database.lock().unwrap();
+    review_bundle::export_review_bundle(&database, &mut storage, &change_id, Path::new(&output_path))
+        .map_err(|e| format!("Failed to export review bundle: {}", e))
+}
+
+/// Pack the comments made on an imported bundle, to be merged back where the change came from
+#[tauri::command]
+pub async fn export_return_bundle(
+    change_id: String,
+    output_path: String,
+    state: State<'_, AppState>,
+) -> Result<BundleManifest, String> {
+    info!("Exporting return bundle for change {} to {}", change_id, output_path);
+
+    let database = state.database.lock().unwrap();
+    review_bundle::export_return_bundle(&database, &change_id, Path::new(&output_path))
+        .map_err(|e| format!("Failed to export return bundle: {}", e))
+}
+
+/// Import a review bundle, or merge the comments of a return bundle
+#[tauri::command]
+pub async fn import_review_bundle(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<BundleImport, String> {
+    info!("Importing bundle {}", path);
+
+    let mut storage = file_storage()?;
+    let database = state.database.lock().unwrap();
+    review_bundle::import_bundle(&database, &mut storage, Path::new(&path))
+        .map_err(|e| format!("Failed to import bundle: {}", e))
+}
@@ -5,6 +5,7 @@ use tauri::State;
 use log::{info, error};
 
 use crate::services::change_downloader::{DownloadResult, DownloadStatus, UpdateResult};
+use crate::commands::gerrit_simple::client_for_instance;
 use crate::remote::gerrit_client::GerritClient;
 use crate::storage::sqlite::Database;
 use crate::AppState;
@@ -15,25 +16,21 @@ pub async fn gerrit_download_change(
     instance_id: String,
     change_id: String,
     patch_set_number: Option<u32>,
+    state: State<'_, AppState>,
 ) -> Result<DownloadResult, String> {
     info!("Download change command: {} from instance {}", change_id, instance_id);
 
+    // Get Gerrit instance configuration and a client with its stored credentials
+    let instance = state.database.lock().unwrap().get_gerrit_instance(&instance_id)
+        .map_err(|e| format!("Failed to get instance: {}", e))?
+        .ok_or_else(|| "Gerrit instance not found".to_string())?;
+    let gerrit_client = Arc::new(client_for_instance(state.inner(), &instance)?);
+
     tokio::task::spawn_blocking(move || {
         // Create database connection
         let database = Database::new("hyper_review.db")
             .map_err(|e| format!("Database error: {}", e))?;
 
-        // Get Gerrit instance configuration
-        let instance = database.get_gerrit_instance(&instance_id)
-            .map_err(|e| format!("Failed to get instance: {}", e))?
-            .ok_or_else(|| "Gerrit instance not found".to_string())?;
-
-        // Create Gerrit client
-        let gerrit_client = Arc::new(
-            GerritClient::new(&instance.url)
-                .with_auth(instance.username.clone(), instance.password_encrypted.clone())
-        );
-
         // Create database reference
         let db_ref = Arc::new(database);
 
@@ -104,25 +101,21 @@ pub async fn gerrit_get_download_status(
 pub async fn gerrit_update_change(
     instance_id: String,
     change_id: String,
+    state: State<'_, AppState>,
 ) -> Result<UpdateResult, String> {
     info!("Update change command: {} from instance {}", change_id, instance_id);
 
+    // Get Gerrit instance configuration and a client with its stored credentials
+    let instance = state.database.lock().unwrap().get_gerrit_instance(&instance_id)
+        .map_err(|e| format!("Failed to get instance: {}", e))?
+        .ok_or_else(|| "Gerrit instance not found".to_string())?;
+    let gerrit_client = Arc::new(client_for_instance(state.inner(), &instance)?);
+
     tokio::task::spawn_blocking(move || {
         // Create database connection
         let database = Database::new("hyper_review.db")
             .map_err(|e| format!("Database error: {}", e))?;
 
-        // Get Gerrit instance configuration
-        let instance = database.get_gerrit_instance(&instance_id)
-            .map_err(|e| format!("Failed to get instance: {}", e))?
-            .ok_or_else(|| "Gerrit instance not found".to_string())?;
-
-        // Create Gerrit client
-        let gerrit_client = Arc::new(
-            GerritClient::new(&instance.url)
-                .with_auth(instance.username.clone(), instance.password_encrypted.clone())
-        );
-
         // Create database reference
         let db_ref = Arc::new(database);
 
@@ -234,6 +227,8 @@ mod tests {
             old_patch_set: 1,
             new_patch_set: 2,
             files_changed: 3,
+            comments_moved: 0,
+            comments_outdated: 0,
             message: "Updated successfully".to_string(),
         };
 
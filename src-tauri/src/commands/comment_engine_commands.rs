@@ -2,20 +2,79 @@
 // Tauri commands for comment creation, editing, threading, and management
 
 use tauri::State;
-use log::{info, error};
+use log::{info, warn, error};
 
 use crate::AppState;
 use crate::services::comment_engine::{
     CommentEngine, CommentEngineConfig, CreateCommentParams, UpdateCommentParams,
     CommentSearchCriteria, CommentStats, CommentOperationResult, CommentThread,
-    CreateInlineCommentParams, InlineComment, LineComments, DiffSide
+    CreateInlineCommentParams, InlineComment, LineComments, DiffSide, ReanchorSummary
 };
-use crate::models::gerrit::{ReviewComment, CommentStatus};
+use crate::models::gerrit::{
+    ReviewComment, CommentStatus, CommentAnchor, CommentSeverity, CommentType, SeverityCategory,
+    OperationPriority, ReviewMode, SuggestedChange, ThreadState, ThreadStateChange,
+};
+use crate::models::search::{ReviewSearchHit, SearchSourceKind};
+use crate::services::diff_engine::ProcessedDiff;
+use crate::services::operation_executor::{add_comment_operation, thread_state_operation};
+use crate::services::suggested_changes::{apply_suggestions, SuggestionApplyResult};
+use crate::storage::draft_journal::JournaledDraft;
+use crate::storage::operation_queue::QueuedOperation;
+
+/// Close the journaled draft a comment was typed in once the comment is saved,
+/// so it is not offered for recovery. The comment is saved either way.
+fn commit_draft(state: &AppState, draft_key: Option<String>, result: &CommentOperationResult) {
+    let Some(draft_key) = draft_key.filter(|_| result.success) else {
+        return;
+    };
+    if let Err(e) = state.draft_journal.commit(&draft_key) {
+        warn!("Failed to close draft {}: {}", draft_key, e);
+    }
+}
+
+/// Queue a comment saved in an offline session, so the operation executor posts it to
+/// the change once the instance is reachable. The comment is saved either way.
+fn queue_offline_comment(state: &AppState, result: &CommentOperationResult) {
+    let Some(comment_id) = result.comment_id.as_deref().filter(|_| result.success) else {
+        return;
+    };
+    let operation = {
+        let database = state.database.lock().unwrap();
+        let offline = database.get_review_comment(comment_id).ok().flatten()
+            .and_then(|comment| database.get_review_session(&comment.session_id).ok().flatten())
+            .is_some_and(|session| session.mode == ReviewMode::Offline);
+        if !offline {
+            return;
+        }
+        add_comment_operation(&database, comment_id, OperationPriority::Normal)
+    };
+    if let Err(e) = operation.and_then(|operation| state.operation_queue.enqueue(operation)) {
+        error!("Failed to queue offline comment {}: {}", comment_id, e);
+    }
+}
 
-/// Create a new comment
+/// Queue the reply that tells Gerrit a thread was resolved or reopened here.
+/// The new state is saved either way.
+fn queue_thread_state(state: &AppState, change: &ThreadStateChange) {
+    let operation = {
+        let database = state.database.lock().unwrap();
+        thread_state_operation(&database, change, OperationPriority::Normal)
+    };
+    let queued = match operation {
+        Ok(Some(operation)) => state.operation_queue.enqueue(operation).map(|_| ()),
+        Ok(None) => Ok(()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = queued {
+        error!("Failed to queue state of thread {}: {}", change.root_comment_id, e);
+    }
+}
+
+/// Create a new comment; `draft_key` is the journaled draft it was typed in
 #[tauri::command]
 pub async fn comment_create(
     params: CreateCommentParams,
+    draft_key: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<CommentOperationResult, String> {
     info!("Creating comment for session: {}, file: {}", params.session_id, params.file_path);
@@ -31,14 +90,17 @@ pub async fn comment_create(
         format!("Failed to create comment: {}", e)
     }).map(|result| {
         info!("Successfully created comment");
+        commit_draft(&state, draft_key, &result);
+        queue_offline_comment(&state, &result);
         result
     })
 }
 
-/// Update an existing comment
+/// Update an existing comment; `draft_key` is the journaled draft it was edited in
 #[tauri::command]
 pub async fn comment_update(
     params: UpdateCommentParams,
+    draft_key: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<CommentOperationResult, String> {
     info!("Updating comment: {}", params.comment_id);
@@ -54,6 +116,7 @@ pub async fn comment_update(
         format!("Failed to update comment: {}", e)
     }).map(|result| {
         info!("Successfully updated comment");
+        commit_draft(&state, draft_key, &result);
         result
     })
 }
@@ -174,6 +237,102 @@ pub async fn comment_get_thread(
     })
 }
 
+/// Get the threads of a session in the given states, or all threads if none are given
+#[tauri::command]
+pub async fn comment_get_session_threads(
+    session_id: String,
+    states: Vec<ThreadState>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CommentThread>, String> {
+    let database = state.database.clone();
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        engine.get_session_threads(&session_id, &states, &database)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to get session threads: {}", e);
+        format!("Failed to get session threads: {}", e)
+    })
+}
+
+/// Get the unresolved threads that block submitting a change
+#[tauri::command]
+pub async fn comment_get_blocking_threads(
+    change_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CommentThread>, String> {
+    let database = state.database.clone();
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        engine.get_blocking_threads(&change_id, &database)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to get blocking threads: {}", e);
+        format!("Failed to get blocking threads: {}", e)
+    })
+}
+
+/// Move a thread to a new state, and queue the reply that carries it to Gerrit
+#[tauri::command]
+pub async fn comment_set_thread_state(
+    root_comment_id: String,
+    thread_state: ThreadState,
+    changed_by: String,
+    state: State<'_, AppState>,
+) -> Result<Option<ThreadStateChange>, String> {
+    info!("Setting thread {} to {}", root_comment_id, thread_state);
+
+    let database = state.database.clone();
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        engine.set_thread_state(&root_comment_id, thread_state, &changed_by, &database)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to set thread state: {}", e);
+        format!("Failed to set thread state: {}", e)
+    }).map(|change| {
+        if let Some(change) = &change {
+            queue_thread_state(&state, change);
+        }
+        change
+    })
+}
+
+/// Apply Gerrit's `unresolved` flag for a thread
+#[tauri::command]
+pub async fn comment_sync_gerrit_unresolved(
+    root_comment_id: String,
+    unresolved: bool,
+    gerrit_author: String,
+    state: State<'_, AppState>,
+) -> Result<Option<ThreadStateChange>, String> {
+    let database = state.database.clone();
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        engine.sync_gerrit_unresolved(&root_comment_id, unresolved, &gerrit_author, &database)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to sync thread state: {}", e);
+        format!("Failed to sync thread state: {}", e)
+    })
+}
+
+/// Get who changed a thread's state, and when
+#[tauri::command]
+pub async fn comment_get_thread_history(
+    root_comment_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<ThreadStateChange>, String> {
+    let database = state.database.lock().unwrap();
+    CommentEngine::new(CommentEngineConfig::default())
+        .get_thread_history(&root_comment_id, &database)
+        .map_err(|e| format!("Failed to get thread history: {}", e))
+}
+
 /// Search comments
 #[tauri::command]
 pub async fn comment_search(
@@ -197,6 +356,27 @@ pub async fn comment_search(
     })
 }
 
+/// Full-text search over every comment, local task comment and change ever reviewed
+#[tauri::command]
+pub async fn search_review_history(
+    query: String,
+    kinds: Vec<SearchSourceKind>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ReviewSearchHit>, String> {
+    info!("Searching review history: {}", query);
+
+    let database = state.database.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        crate::search::review_history::search_review_history(&database, &query, &kinds, limit)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to search review history: {}", e);
+        format!("Failed to search review history: {}", e)
+    })
+}
+
 /// Get comment statistics
 #[tauri::command]
 pub async fn comment_get_stats(
@@ -220,6 +400,49 @@ pub async fn comment_get_stats(
     })
 }
 
+/// Get comment statistics across all review sessions of a change
+#[tauri::command]
+pub async fn comment_get_change_stats(
+    change_id: String,
+    state: State<'_, AppState>,
+) -> Result<CommentStats, String> {
+    info!("Getting comment statistics for change: {}", change_id);
+
+    let database = state.database.clone();
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        engine.get_change_comment_stats(&change_id, &database)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to get change comment stats: {}", e);
+        format!("Failed to get change comment stats: {}", e)
+    })
+}
+
+/// Get the comment severity taxonomy
+#[tauri::command]
+pub async fn comment_get_severity_taxonomy(
+    state: State<'_, AppState>,
+) -> Result<Vec<SeverityCategory>, String> {
+    let database = state.database.lock().unwrap();
+    database.get_severity_taxonomy()
+        .map_err(|e| format!("Failed to get severity taxonomy: {}", e))
+}
+
+/// Replace the comment severity taxonomy
+#[tauri::command]
+pub async fn comment_set_severity_taxonomy(
+    categories: Vec<SeverityCategory>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Configuring {} comment severity categories", categories.len());
+
+    let database = state.database.lock().unwrap();
+    database.store_severity_taxonomy(&categories)
+        .map_err(|e| format!("Failed to store severity taxonomy: {}", e))
+}
+
 /// Publish all draft comments
 #[tauri::command]
 pub async fn comment_publish_all_drafts(
@@ -258,6 +481,7 @@ pub async fn comment_update_status(
         content: None,
         status: Some(comment_status),
         comment_type: None,
+        severity: None,
     };
 
     let database = state.database.clone();
@@ -298,6 +522,7 @@ pub async fn comment_update_config(
 #[tauri::command]
 pub async fn comment_create_inline(
     params: CreateInlineCommentParams,
+    draft_key: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<CommentOperationResult, String> {
     info!("Creating inline comment for {}:{} in session: {}", 
@@ -314,6 +539,8 @@ pub async fn comment_create_inline(
         format!("Failed to create inline comment: {}", e)
     }).map(|result| {
         info!("Successfully created inline comment");
+        commit_draft(&state, draft_key, &result);
+        queue_offline_comment(&state, &result);
         result
     })
 }
@@ -431,6 +658,122 @@ pub async fn comment_update_inline_position(
     })
 }
 
+/// Re-anchor a session's inline comments onto another downloaded patch set
+#[tauri::command]
+pub async fn comment_reanchor_session(
+    session_id: String,
+    patch_set_number: u32,
+    state: State<'_, AppState>,
+) -> Result<ReanchorSummary, String> {
+    info!("Re-anchoring comments of session {} to PS{}", session_id, patch_set_number);
+
+    let database = state.database.clone();
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        engine.reanchor_session(&session_id, patch_set_number, &database)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to re-anchor comments: {}", e);
+        format!("Failed to re-anchor comments: {}", e)
+    })
+}
+
+/// Get the suggested change of a suggestion comment
+#[tauri::command]
+pub async fn comment_get_suggestion(
+    comment_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<SuggestedChange>, String> {
+    let database = state.database.lock().unwrap();
+    database.get_suggested_change(&comment_id)
+        .map_err(|e| format!("Failed to get suggestion: {}", e))
+}
+
+/// Queue a suggestion comment to be posted to its change as a draft with a fix suggestion
+#[tauri::command]
+pub async fn comment_queue_suggestion(
+    comment_id: String,
+    state: State<'_, AppState>,
+) -> Result<QueuedOperation, String> {
+    info!("Queueing suggestion comment {}", comment_id);
+
+    let operation = {
+        let database = state.database.lock().unwrap();
+        if database.get_suggested_change(&comment_id).map_err(|e| e.to_string())?.is_none() {
+            return Err(format!("Comment {} has no suggested change", comment_id));
+        }
+        add_comment_operation(&database, &comment_id, OperationPriority::Normal)
+            .map_err(|e| format!("Failed to queue suggestion: {}", e))?
+    };
+    state.operation_queue.enqueue(operation.clone())
+        .map_err(|e| format!("Failed to queue suggestion: {}", e))?;
+    Ok(operation)
+}
+
+/// Render a suggestion comment as a mini-diff
+#[tauri::command]
+pub async fn comment_get_suggestion_diff(
+    comment_id: String,
+    state: State<'_, AppState>,
+) -> Result<ProcessedDiff, String> {
+    let database = state.database.lock().unwrap();
+    CommentEngine::new(CommentEngineConfig::default())
+        .get_suggestion_diff(&comment_id, &database)
+        .map_err(|e| format!("Failed to render suggestion: {}", e))
+}
+
+/// Apply one or more suggestion comments to the working tree of the loaded repository,
+/// committed together as one commit
+#[tauri::command]
+pub async fn comment_apply_suggestions(
+    comment_ids: Vec<String>,
+    message: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SuggestionApplyResult, String> {
+    info!("Applying {} suggested changes", comment_ids.len());
+
+    let repo = state.git_service.lock().unwrap().get_repository()
+        .ok_or_else(|| "No repository loaded to apply the suggestions to".to_string())?;
+
+    let database = state.database.lock().unwrap();
+    let mut suggestions = Vec::with_capacity(comment_ids.len());
+    for comment_id in &comment_ids {
+        let suggestion = database.get_suggested_change(comment_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Comment {} has no suggested change", comment_id))?;
+        suggestions.push(suggestion);
+    }
+
+    let result = apply_suggestions(&repo, &suggestions, message.as_deref()).map_err(|e| {
+        error!("Failed to apply suggestions: {}", e);
+        format!("Failed to apply suggestions: {}", e)
+    })?;
+    for comment_id in &result.applied {
+        database.mark_suggested_change_applied(comment_id, &result.commit_id)
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(result)
+}
+
+/// Get the anchors of a session's inline comments, including outdated ones
+#[tauri::command]
+pub async fn comment_get_anchors(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CommentAnchor>, String> {
+    let database = state.database.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        database.get_comment_anchors_for_session(&session_id)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to get comment anchors: {}", e);
+        format!("Failed to get comment anchors: {}", e)
+    })
+}
+
 /// Highlight comments in a line range
 #[tauri::command]
 pub async fn comment_highlight_range(
@@ -458,10 +801,89 @@ pub async fn comment_highlight_range(
     })
 }
 
+/// Journal the text of a comment being typed; writes are debounced
+#[tauri::command]
+pub async fn comment_record_draft(
+    draft: JournaledDraft,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if !CommentEngineConfig::default().auto_save_drafts {
+        return Ok(());
+    }
+
+    state.draft_journal.record(draft)
+        .map_err(|e| format!("Failed to record draft: {}", e))
+}
+
+/// Get drafts that were being typed when the app last went down
+#[tauri::command]
+pub async fn comment_get_unsaved_drafts(
+    state: State<'_, AppState>,
+) -> Result<Vec<JournaledDraft>, String> {
+    state.draft_journal.unsaved_drafts()
+        .map_err(|e| format!("Failed to get unsaved drafts: {}", e))
+}
+
+/// Save an unsaved draft as a comment, or into the comment it was editing
+#[tauri::command]
+pub async fn comment_restore_draft(
+    draft_key: String,
+    state: State<'_, AppState>,
+) -> Result<CommentOperationResult, String> {
+    info!("Restoring draft: {}", draft_key);
+
+    let database = state.database.clone();
+    let journal = state.draft_journal.clone();
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+
+    tokio::task::spawn_blocking(move || {
+        let draft = journal.get_unsaved(&draft_key)?
+            .ok_or_else(|| crate::errors::HyperReviewError::other(format!("Draft not found: {}", draft_key)))?;
+
+        let database = database.lock().unwrap();
+        let result = match draft.comment_id {
+            Some(comment_id) => engine.update_comment(UpdateCommentParams {
+                comment_id,
+                content: Some(draft.content),
+                status: None,
+                comment_type: None,
+                severity: None,
+            }, &database)?,
+            None => engine.create_comment(CreateCommentParams {
+                session_id: draft.session_id,
+                file_path: draft.file_path,
+                line_number: draft.line_number,
+                content: draft.content,
+                comment_type: if draft.line_number.is_some() { CommentType::Inline } else { CommentType::FileLevel },
+                severity: CommentSeverity::default(),
+                parent_comment_id: draft.parent_comment_id,
+            }, &database)?,
+        };
+
+        if result.success {
+            journal.commit(&draft_key)?;
+        }
+        Ok::<_, crate::errors::HyperReviewError>(result)
+    }).await.map_err(|e| format!("Task join error: {}", e))?.map_err(|e| {
+        error!("Failed to restore draft: {}", e);
+        format!("Failed to restore draft: {}", e)
+    })
+}
+
+/// Throw away an unsaved draft
+#[tauri::command]
+pub async fn comment_discard_draft(
+    draft_key: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Discarding draft: {}", draft_key);
+    state.draft_journal.discard(&draft_key)
+        .map_err(|e| format!("Failed to discard draft: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::gerrit::CommentType;
 
     #[tokio::test]
     async fn test_comment_config_creation() {
@@ -479,6 +901,7 @@ mod tests {
             line_number: Some(42),
             content: "This needs improvement".to_string(),
             comment_type: CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
         };
 
@@ -495,6 +918,7 @@ mod tests {
             content: Some("Updated content".to_string()),
             status: Some(CommentStatus::Published),
             comment_type: None,
+            severity: None,
         };
 
         assert_eq!(params.comment_id, "comment-123");
@@ -513,7 +937,10 @@ mod tests {
             side: DiffSide::Right,
             content: "This line needs improvement".to_string(),
             comment_type: crate::models::gerrit::CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
+            end_line_number: None,
+            suggestion: None,
         };
 
         assert_eq!(params.session_id, "test-session");
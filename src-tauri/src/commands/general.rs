@@ -9,7 +9,6 @@ use tauri::State;
 // Import modules
 use crate::git;
 use crate::analysis;
-use crate::remote;
 
 /// Opens a repository selection dialog
 /// NOTE: This is deprecated. Dialogs should be opened from the frontend using @tauri-apps/plugin-dialog
@@ -256,17 +255,31 @@ pub async fn get_review_stats(state: State<'_, AppState>) -> Result<ReviewStats,
 }
 
 /// Gets quality gates status
+///
+/// Checks the providers configured for the open repository against `commit_sha`,
+/// or HEAD when no commit is given. `refresh` bypasses cached results.
 #[tauri::command]
-pub async fn get_quality_gates(state: State<'_, AppState>) -> Result<Vec<QualityGate>, String> {
+pub async fn get_quality_gates(
+    commit_sha: Option<String>,
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<QualityGate>, String> {
     log::info!("Getting quality gates status");
 
-    let git_service = state.git_service.lock().unwrap();
-    let repo_path = git_service.get_current_path()
-        .unwrap_or_else(|| ".".to_string());
+    let (repo_path, head) = {
+        let git_service = state.git_service.lock().unwrap();
+        let repo_path = git_service.get_current_path()
+            .unwrap_or_else(|| ".".to_string());
+        let head = git_service.get_repository()
+            .and_then(|repo| repo.head().ok()?.target())
+            .map(|oid| oid.to_string());
+        (repo_path, head)
+    };
+    let commit_sha = commit_sha.or(head)
+        .ok_or_else(|| "No commit to check quality gates for".to_string())?;
 
-    let remote_client = remote::client::RemoteClient::new();
-    remote_client.check_quality_gates(&repo_path)
-        .map_err(|e| e.to_string())
+    let checker = crate::commands::quality_gate_commands::quality_gate_checker(&state, &[repo_path.as_str()])?;
+    Ok(checker.check(&commit_sha, refresh.unwrap_or(false)).await)
 }
 
 /// Gets review templates
@@ -284,6 +297,9 @@ pub async fn get_review_templates(state: State<'_, AppState>) -> Result<Vec<Revi
 pub async fn create_template(
     name: String,
     content: String,
+    category: Option<String>,
+    languages: Option<Vec<String>>,
+    file_globs: Option<Vec<String>>,
     state: State<'_, AppState>,
 ) -> Result<ReviewTemplate, String> {
     log::info!("Creating new review template: {}", name);
@@ -291,22 +307,18 @@ pub async fn create_template(
     let template_id = uuid::Uuid::new_v4().to_string();
     let timestamp = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-    // Extract placeholders from content (e.g., {{placeholder}})
-    let placeholders: Vec<String> = regex::Regex::new(r"\{\{(\w+)\}\}")
-        .map(|re| {
-            re.captures_iter(&content)
-                .filter_map(|cap| cap.get(1).map(|m| m.as_str().to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
+    // Extract placeholders from content (e.g., ${file} or {{placeholder}})
+    let placeholders = crate::services::snippets::extract_placeholders(&content);
 
     let template = ReviewTemplate {
         id: template_id,
         name,
         content,
         placeholders,
-        category: None,
+        category,
         usage_count: 0,
+        languages: languages.unwrap_or_default(),
+        file_globs: file_globs.unwrap_or_default(),
         created_at: timestamp.clone(),
         updated_at: timestamp,
     };
@@ -512,37 +524,59 @@ pub async fn scan_security(file_path: String) -> Result<Vec<SecurityIssue>, Stri
 }
 
 /// Submits review to external system
+///
+/// `system` names the platform; `review_data["instance_id"]` picks the configured
+/// instance when several exist.
 #[tauri::command]
 pub async fn submit_review(
     system: String,
     review_data: serde_json::Value,
     state: State<'_, AppState>,
 ) -> Result<SubmitResult, String> {
+    use crate::commands::platform_commands::platform_registry;
+    use crate::models::platform::PlatformKind;
+    use crate::remote::platform::{ChangeRef, PlatformReview, ReviewVote};
+
     log::info!("Submitting review to system: {}", system);
 
-    // Get comments from database for this review
-    let database = state.database.lock().unwrap();
+    let kind = PlatformKind::from_string(&system)
+        .ok_or_else(|| format!("Unsupported review system: {}", system))?;
 
     // Parse review data
     let project_id = review_data.get("project_id")
         .and_then(|v| v.as_str())
         .unwrap_or("");
-    let mr_id = review_data.get("merge_request_id")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(0);
-
-    match system.as_str() {
-        "gitlab" => {
-            let client = remote::gitlab_client::GitLabClient::new("https://gitlab.com");
-            client.submit_review(project_id, mr_id, Vec::new())
-                .map_err(|e| e.to_string())
-        }
-        "gerrit" => {
-            // Gerrit client needs async support, temporarily disabled
-            Err("Gerrit integration temporarily disabled".to_string())
-        }
-        _ => Err(format!("Unsupported review system: {}", system)),
-    }
+    let change_id = ["merge_request_id", "change_id"].iter()
+        .filter_map(|key| review_data.get(*key))
+        .find_map(|v| match v {
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            serde_json::Value::String(s) => Some(s.clone()),
+            _ => None,
+        })
+        .ok_or_else(|| "Review data needs a merge_request_id or change_id".to_string())?;
+    let comments: Vec<Comment> = review_data.get("comments")
+        .map(|v| serde_json::from_value(v.clone()))
+        .transpose()
+        .map_err(|e| format!("Invalid review comments: {}", e))?
+        .unwrap_or_default();
+    let message = ["summary", "message"].iter()
+        .find_map(|key| review_data.get(*key).and_then(|v| v.as_str()))
+        .unwrap_or("")
+        .to_string();
+    let vote = match review_data.get("approval").or_else(|| review_data.get("event")) {
+        Some(serde_json::Value::Bool(true)) => Some(ReviewVote::Approve),
+        Some(serde_json::Value::String(s)) => Some(ReviewVote::from_string(s)
+            .ok_or_else(|| format!("Unknown approval: {}", s))?),
+        _ => None,
+    };
+
+    let registry = platform_registry(&state)?;
+    let instance_id = review_data.get("instance_id").and_then(|v| v.as_str());
+    let target = registry.resolve(kind, instance_id).map_err(|e| e.to_string())?;
+
+    let review = PlatformReview { message, comments, vote };
+    target.platform.post_review(&ChangeRef::new(project_id, change_id), &review).await
+        .map_err(|e| e.to_string())
 }
 
 /// Syncs repository with remote
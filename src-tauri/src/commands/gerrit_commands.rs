@@ -9,7 +9,9 @@ use chrono::Utc;
 
 use crate::AppState;
 use crate::errors::HyperReviewError;
+use crate::models::gerrit::{GerritInstance, GerritAuthMethod, OperationPriority, OperationType};
 use crate::remote::gerrit_client::GerritClient;
+use crate::storage::operation_queue::QueuedOperation;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateCommentParams {
@@ -137,13 +139,40 @@ pub async fn gerrit_submit_review_simple(
         return Err("Review message cannot be empty".to_string());
     }
     
-    // TODO: Get active Gerrit instance and credentials
-    // TODO: Use GerritClient to submit review via API
-    
-    // For now, return mock success
+    // The review is queued rather than sent, so it also works offline. Its comments are
+    // queued first and become drafts the review publishes.
+    let change = state.database.lock().unwrap().get_gerrit_change(&params.change_id)
+        .map_err(|e| format!("Failed to load change: {}", e))?
+        .ok_or_else(|| format!("Change not found: {}", params.change_id))?;
+
+    let mut operations: Vec<QueuedOperation> = params.comments.iter()
+        .map(|comment| QueuedOperation::new(
+            &change.instance_id,
+            &change.change_id,
+            OperationType::AddComment,
+            serde_json::json!({
+                "file_path": comment.file_path,
+                "line": comment.line_number,
+                "message": comment.content,
+            }),
+            OperationPriority::Normal,
+        ))
+        .collect();
+    operations.push(QueuedOperation::new(
+        &change.instance_id,
+        &change.change_id,
+        OperationType::SubmitReview,
+        serde_json::json!({ "message": params.message, "labels": params.labels }),
+        OperationPriority::Normal,
+    ));
+    for operation in operations {
+        state.operation_queue.enqueue(operation)
+            .map_err(|e| format!("Failed to queue review: {}", e))?;
+    }
+
     Ok(SubmitReviewResult {
         success: true,
-        message: "Review submitted successfully".to_string(),
+        message: "Review queued; it is submitted as soon as the instance is reachable".to_string(),
     })
 }
 
@@ -156,12 +185,10 @@ pub async fn gerrit_test_connection_by_id(
     info!("Testing connection to Gerrit instance: {}", instance_id);
     
     // Get instance from database
-    let (url, username, password) = {
+    let instance = {
         let database = state.database.lock().unwrap();
         match database.get_gerrit_instance(&instance_id) {
-            Ok(Some(instance)) => {
-                (instance.url, instance.username, instance.password_encrypted)
-            }
+            Ok(Some(instance)) => instance,
             Ok(None) => {
                 return Ok(TestConnectionResult {
                     success: false,
@@ -179,9 +206,18 @@ pub async fn gerrit_test_connection_by_id(
         }
     };
     
-    // Test connection using the instance credentials
-    let client = GerritClient::new(&url)
-        .with_auth(username, password);
+    // Test connection using the instance's auth method and stored secret
+    let client = match crate::commands::gerrit_simple::client_for_instance(state.inner(), &instance) {
+        Ok(client) => client,
+        Err(message) => {
+            crate::commands::gerrit_simple::mark_auth_failed(state.inner(), &instance_id);
+            return Ok(TestConnectionResult {
+                success: false,
+                message,
+                version: None,
+            });
+        }
+    };
     
     match client.test_connection().await {
         Ok(result) => {
@@ -223,6 +259,16 @@ pub async fn gerrit_test_connection_by_id(
                 })
             }
         }
+        Err(e) if e.is_authentication_required() => {
+            warn!("Gerrit instance {} rejected credentials: {}", instance_id, e);
+            crate::commands::gerrit_simple::mark_auth_failed(state.inner(), &instance_id);
+            
+            Ok(TestConnectionResult {
+                success: false,
+                message: e.to_string(),
+                version: None,
+            })
+        }
         Err(e) => {
             warn!("Failed to connect to Gerrit instance {}: {}", instance_id, e);
             
@@ -250,12 +296,19 @@ pub async fn gerrit_test_connection(
     url: String,
     username: String,
     password: String,
-    _state: State<'_, AppState>,
+    auth_method: Option<String>,
+    state: State<'_, AppState>,
 ) -> Result<TestConnectionResult, String> {
     info!("Testing connection to Gerrit server: {}", url);
     
-    let client = GerritClient::new(&url)
-        .with_auth(username, password);
+    let mut instance = GerritInstance::new(url.clone(), url.clone(), username, password.clone());
+    instance.auth_method = auth_method.as_deref()
+        .map(GerritAuthMethod::from_string)
+        .unwrap_or_default();
+    let network = crate::commands::gerrit_simple::network_settings_for(state.inner(), None);
+    let client = GerritClient::for_instance(&instance, Some(password.as_str()))
+        .map_err(|e| e.to_string())?
+        .with_network(network);
     
     match client.test_connection().await {
         Ok(result) => {
@@ -9,8 +9,37 @@ use chrono::Utc;
 
 use crate::AppState;
 use crate::errors::HyperReviewError;
-use crate::models::gerrit::{GerritInstance, ConnectionStatus};
+use crate::models::gerrit::{GerritInstance, GerritAuthMethod, ConnectionStatus};
+use crate::models::network::NetworkSettings;
 use crate::remote::gerrit_client::GerritClient;
+use crate::remote::gerrit_auth::GerritAuth;
+use crate::remote::platform::PlatformRegistry;
+
+/// Build a client for a stored instance using its configured auth method
+pub(crate) fn client_for_instance(state: &AppState, instance: &GerritInstance) -> Result<GerritClient, String> {
+    let database = state.database.lock().unwrap();
+    let credential_store = state.credential_store.lock().unwrap();
+    PlatformRegistry::gerrit_client(&database, &credential_store, instance)
+        .map_err(|e| e.to_string())
+}
+
+/// Effective proxy/TLS settings for an instance, or the global settings when `instance_id` is None
+pub(crate) fn network_settings_for(state: &AppState, instance_id: Option<&str>) -> NetworkSettings {
+    let database = state.database.lock().unwrap();
+    database.get_effective_network_settings(instance_id).unwrap_or_else(|e| {
+        warn!("Failed to load network settings, using defaults: {}", e);
+        NetworkSettings::default()
+    })
+}
+
+/// Flag an instance as needing re-authentication after the server rejected its credentials
+pub(crate) fn mark_auth_failed(state: &AppState, instance_id: &str) {
+    let database = state.database.lock().unwrap();
+    if let Ok(Some(mut instance)) = database.get_gerrit_instance(instance_id) {
+        instance.mark_disconnected(ConnectionStatus::AuthenticationFailed);
+        let _ = database.store_gerrit_instance(&instance);
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SimpleGerritInstance {
@@ -28,6 +57,8 @@ pub struct SimpleCreateParams {
     pub url: String,
     pub username: String,
     pub password: String,
+    #[serde(default)]
+    pub auth_method: Option<String>, // basic | bearer | cookie | netrc
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -123,61 +154,76 @@ pub async fn gerrit_create_instance_simple(
     if params.url.trim().is_empty() {
         return Err("Instance URL cannot be empty".to_string());
     }
-    if params.username.trim().is_empty() {
+    let auth_method = params.auth_method.as_deref()
+        .map(GerritAuthMethod::from_string)
+        .unwrap_or_default();
+    if auth_method == GerritAuthMethod::Basic && params.username.trim().is_empty() {
         return Err("Username cannot be empty".to_string());
     }
     
-    // Test connection to the Gerrit server
-    let client = GerritClient::new(&params.url)
-        .with_auth(params.username.clone(), params.password.clone());
-    
-    let connection_status = match client.test_connection().await {
-        Ok(_) => {
-            info!("Successfully connected to Gerrit server: {}", params.url);
-            ConnectionStatus::Connected
-        }
-        Err(e) => {
-            warn!("Failed to connect to Gerrit server {}: {}", params.url, e);
-            ConnectionStatus::Disconnected
-        }
-    };
-    
-    // Store credentials securely
-    let mut credential_store = state.credential_store.lock().unwrap();
-    if let Err(e) = credential_store.store(
-        "gerrit",
-        &params.username,
-        &params.password,
-    ) {
-        error!("Failed to store credentials: {}", e);
-    }
-    
-    // Create the Gerrit instance
     let instance_id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     
-    let gerrit_instance = GerritInstance {
+    let mut gerrit_instance = GerritInstance {
         id: instance_id.clone(),
         name: params.name.clone(),
         url: params.url.clone(),
         username: params.username.clone(),
-        password_encrypted: params.password, // TODO: Encrypt this properly
+        password_encrypted: String::new(), // The secret is kept in the credential store
+        auth_method,
         version: "".to_string(),
         is_active: true,
-        last_connected: if connection_status == ConnectionStatus::Connected {
-            Some(now.clone())
-        } else {
-            None
-        },
-        connection_status: connection_status.clone(),
+        last_connected: None,
+        connection_status: ConnectionStatus::Disconnected,
         polling_interval: 300,
         max_changes: 100,
         created_at: now.clone(),
         updated_at: now,
     };
     
+    // Test connection to the Gerrit server
+    let secret = Some(params.password.as_str()).filter(|p| !p.is_empty());
+    let network = network_settings_for(state.inner(), None);
+    let connection_status = match GerritClient::for_instance(&gerrit_instance, secret) {
+        Ok(client) => match client.with_network(network).test_connection().await {
+            Ok(_) => {
+                info!("Successfully connected to Gerrit server: {}", params.url);
+                ConnectionStatus::Connected
+            }
+            Err(e) if e.is_authentication_required() => {
+                warn!("Gerrit server {} rejected credentials: {}", params.url, e);
+                ConnectionStatus::AuthenticationFailed
+            }
+            Err(e) => {
+                warn!("Failed to connect to Gerrit server {}: {}", params.url, e);
+                ConnectionStatus::Disconnected
+            }
+        },
+        Err(e) => {
+            warn!("Failed to resolve credentials for {}: {}", params.url, e);
+            ConnectionStatus::AuthenticationFailed
+        }
+    };
+    if connection_status == ConnectionStatus::Connected {
+        gerrit_instance.last_connected = Some(Utc::now().to_rfc3339());
+    }
+    gerrit_instance.connection_status = connection_status.clone();
+    
     // Store in database
     let database = state.database.lock().unwrap();
+
+    // Store credentials securely; without them the instance could not authenticate
+    if secret.is_some() {
+        let mut credential_store = state.credential_store.lock().unwrap();
+        if let Err(e) = credential_store.store(
+            &GerritAuth::credential_service(&instance_id),
+            &params.username,
+            &params.password,
+        ) {
+            error!("Failed to store credentials: {}", e);
+            return Err(format!("Failed to store credentials: {}", e));
+        }
+    }
     
     // Initialize Gerrit schema if needed
     if let Err(e) = database.init_gerrit_schema() {
@@ -248,7 +294,7 @@ pub async fn gerrit_import_change_simple(
     info!("Importing Gerrit change: {}", change_id);
     
     // Get active Gerrit instance (release lock before async operations)
-    let (gerrit_url, username, password, instance_id) = {
+    let (gerrit_url, active_instance, instance_id) = {
         let database = state.database.lock().unwrap();
         
         // Initialize Gerrit schema if needed
@@ -266,21 +312,24 @@ pub async fn gerrit_import_change_simple(
         };
         
         if let Some(instance) = active_instance {
-            (instance.url.clone(), instance.username.clone(), instance.password_encrypted.clone(), instance.id.clone())
+            (instance.url.clone(), Some(instance.clone()), instance.id.clone())
         } else {
             // Use test server as fallback
             (
                 "http://edce7739774c:8080".to_string(),
-                "admin".to_string(),
-                "8EWK0RrulrdN8d7vTFVpbQTAjQFU2lFQpRhTZpISBw".to_string(),
+                None,
                 "test-instance-1".to_string(),
             )
         }
     }; // Database lock is released here
     
     // Try to connect to the Gerrit server
-    let client = GerritClient::new(&gerrit_url)
-        .with_auth(username, password);
+    let client = match &active_instance {
+        Some(instance) => client_for_instance(state.inner(), instance)?,
+        None => GerritClient::new(&gerrit_url)
+            .with_auth("admin".to_string(), "8EWK0RrulrdN8d7vTFVpbQTAjQFU2lFQpRhTZpISBw".to_string())
+            .with_network(network_settings_for(state.inner(), None)),
+    };
     
     // Parse change ID (remove # if present)
     let clean_change_id = change_id.trim_start_matches('#');
@@ -361,6 +410,11 @@ pub async fn gerrit_import_change_simple(
             
             Ok(simple_change)
         }
+        Err(e) if e.is_authentication_required() => {
+            warn!("Gerrit rejected credentials while importing change: {}", e);
+            mark_auth_failed(state.inner(), &instance_id);
+            Err(e.to_string())
+        }
         Err(e) => {
             warn!("Failed to import change from Gerrit: {}", e);
             
@@ -445,7 +499,7 @@ pub async fn gerrit_search_changes_simple(
     info!("Using search query: {}", search_query);
     
     // Get active Gerrit instance (release lock before async operations)
-    let (gerrit_url, username, password) = {
+    let (gerrit_url, active_instance) = {
         let database = state.database.lock().unwrap();
         
         // Initialize Gerrit schema if needed
@@ -463,20 +517,20 @@ pub async fn gerrit_search_changes_simple(
         };
         
         if let Some(instance) = active_instance {
-            (instance.url, instance.username, instance.password_encrypted)
+            (instance.url.clone(), Some(instance))
         } else {
             // Use test server as fallback
-            (
-                "http://edce7739774c:8080".to_string(),
-                "admin".to_string(),
-                "8EWK0RrulrdN8d7vTFVpbQTAjQFU2lFQpRhTZpISBw".to_string(),
-            )
+            ("http://edce7739774c:8080".to_string(), None)
         }
     }; // Database lock is released here
     
     // Try to connect to the Gerrit server
-    let client = GerritClient::new(&gerrit_url)
-        .with_auth(username, password);
+    let client = match &active_instance {
+        Some(instance) => client_for_instance(state.inner(), instance)?,
+        None => GerritClient::new(&gerrit_url)
+            .with_auth("admin".to_string(), "8EWK0RrulrdN8d7vTFVpbQTAjQFU2lFQpRhTZpISBw".to_string())
+            .with_network(network_settings_for(state.inner(), None)),
+    };
     
     match client.search_changes(&search_query).await {
         Ok(gerrit_changes) => {
@@ -549,6 +603,13 @@ pub async fn gerrit_search_changes_simple(
             info!("Found and stored {} changes from Gerrit", simple_changes.len());
             Ok(simple_changes)
         }
+        Err(e) if e.is_authentication_required() => {
+            warn!("Gerrit rejected credentials while searching changes: {}", e);
+            if let Some(instance) = &active_instance {
+                mark_auth_failed(state.inner(), &instance.id);
+            }
+            Err(e.to_string())
+        }
         Err(e) => {
             warn!("Failed to search changes in Gerrit: {}", e);
             
@@ -0,0 +1,268 @@
+// Issue tracker commands
+// Configure Jira/generic trackers and show the issues a change or task implements
+
+use tauri::State;
+use log::{info, warn};
+
+use crate::AppState;
+use crate::models::issue::{IssueFieldPaths, IssueSubject, IssueTrackerConfig, LinkedIssue, TrackerKind};
+use crate::remote::issue_tracker::{compile_patterns, IssueResolver, IssueTrackerClient};
+use crate::storage::task_store::TaskStore;
+
+/// Build clients for every configured tracker. The state locks are released before returning.
+fn issue_resolver(state: &AppState) -> Result<IssueResolver, String> {
+    let database = state.database.lock().unwrap();
+    let credential_store = state.credential_store.lock().unwrap();
+    let configs = database.list_issue_trackers()
+        .map_err(|e| format!("Failed to load issue trackers: {}", e))?;
+    let network = database.get_effective_network_settings(None)
+        .map_err(|e| e.to_string())?;
+
+    let mut clients = Vec::new();
+    for config in configs {
+        let token = credential_store
+            .retrieve(&config.credential_service(), config.credential_username())
+            .ok()
+            .flatten()
+            .map(|credential| credential.password);
+        match IssueTrackerClient::new(config.clone()) {
+            Ok(client) => {
+                let client = client.with_network(network.clone());
+                clients.push(match token {
+                    Some(token) => client.with_token(token),
+                    None => client,
+                });
+            }
+            Err(e) => warn!("Skipping issue tracker {}: {}", config.name, e),
+        }
+    }
+    Ok(IssueResolver::new(clients))
+}
+
+/// Find issue keys in `text`, resolve them and record them as the subject's links
+async fn link_issues(
+    state: &AppState,
+    subject: IssueSubject,
+    subject_id: &str,
+    text: &str,
+    refresh: bool,
+) -> Result<Vec<LinkedIssue>, String> {
+    let resolver = issue_resolver(state)?;
+    let keys = resolver.extract_keys(text);
+
+    let cached: Vec<Option<LinkedIssue>> = {
+        let database = state.database.lock().unwrap();
+        keys.iter()
+            .map(|key| database.get_cached_issue(key).ok().flatten())
+            .collect()
+    };
+
+    let mut issues = Vec::with_capacity(keys.len());
+    for (key, cached) in keys.iter().zip(cached) {
+        issues.push(resolver.resolve(key, cached, refresh).await);
+    }
+
+    let database = state.database.lock().unwrap();
+    for issue in issues.iter().filter(|issue| issue.tracker_id.is_some()) {
+        if let Err(e) = database.store_cached_issue(issue) {
+            warn!("Failed to cache issue {}: {}", issue.key, e);
+        }
+    }
+    database.set_issue_links(subject, subject_id, &keys)
+        .map_err(|e| format!("Failed to save issue links: {}", e))?;
+
+    Ok(issues)
+}
+
+/// Commit messages between `base_ref` and HEAD; empty when the repository cannot be read
+fn commit_messages_since(repo_path: &str, base_ref: &str) -> Vec<String> {
+    let messages = || -> Result<Vec<String>, git2::Error> {
+        let repo = git2::Repository::open(repo_path)?;
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push_head()?;
+        if let Ok(base) = repo.revparse_single(base_ref) {
+            revwalk.hide(base.id())?;
+        }
+        let mut messages = Vec::new();
+        for oid in revwalk.take(500) {
+            if let Some(message) = repo.find_commit(oid?)?.message() {
+                messages.push(message.to_string());
+            }
+        }
+        Ok(messages)
+    };
+
+    messages().unwrap_or_else(|e| {
+        warn!("Could not read commits of {} since {}: {}", repo_path, base_ref, e);
+        Vec::new()
+    })
+}
+
+/// List configured issue trackers
+#[tauri::command]
+pub async fn list_issue_trackers(
+    state: State<'_, AppState>,
+) -> Result<Vec<IssueTrackerConfig>, String> {
+    let database = state.database.lock().unwrap();
+    database.list_issue_trackers()
+        .map_err(|e| format!("Failed to list issue trackers: {}", e))
+}
+
+/// Add an issue tracker and store its API token.
+/// Jira trackers default to matching `PROJ-123` style keys.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_issue_tracker(
+    name: String,
+    kind: TrackerKind,
+    base_url: String,
+    patterns: Vec<String>,
+    username: Option<String>,
+    field_paths: Option<IssueFieldPaths>,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<IssueTrackerConfig, String> {
+    info!("Creating {} issue tracker: {}", kind, name);
+
+    if name.trim().is_empty() {
+        return Err("Tracker name cannot be empty".to_string());
+    }
+    if kind == TrackerKind::Generic && !base_url.contains("{key}") {
+        return Err("Generic tracker URL must contain {key}".to_string());
+    }
+    reqwest::Url::parse(&base_url.replace("{key}", "KEY"))
+        .map_err(|e| format!("Invalid tracker URL '{}': {}", base_url, e))?;
+
+    let mut tracker = IssueTrackerConfig::new(name, kind, base_url, patterns);
+    if tracker.patterns.is_empty() {
+        return Err("At least one issue key pattern is required".to_string());
+    }
+    compile_patterns(&tracker.patterns).map_err(|e| e.to_string())?;
+    tracker.username = username.filter(|u| !u.is_empty());
+    if let Some(field_paths) = field_paths {
+        tracker.field_paths = field_paths;
+    }
+
+    {
+        let database = state.database.lock().unwrap();
+        database.store_issue_tracker(&tracker)
+            .map_err(|e| format!("Failed to save issue tracker: {}", e))?;
+    }
+
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        let mut credential_store = state.credential_store.lock().unwrap();
+        if let Err(e) = credential_store.store(&tracker.credential_service(), tracker.credential_username(), &token) {
+            warn!("Failed to store token for issue tracker {}: {}", tracker.id, e);
+        }
+    }
+
+    Ok(tracker)
+}
+
+/// Replace the API token of an issue tracker; an empty token removes it
+#[tauri::command]
+pub async fn update_issue_tracker_token(
+    tracker_id: String,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Updating token of issue tracker: {}", tracker_id);
+
+    let tracker = state.database.lock().unwrap()
+        .list_issue_trackers()
+        .map_err(|e| format!("Failed to load issue trackers: {}", e))?
+        .into_iter()
+        .find(|t| t.id == tracker_id)
+        .ok_or_else(|| format!("Issue tracker not found: {}", tracker_id))?;
+
+    let mut credential_store = state.credential_store.lock().unwrap();
+    let stored = match token.filter(|t| !t.is_empty()) {
+        Some(token) => credential_store.store(&tracker.credential_service(), tracker.credential_username(), &token),
+        None => credential_store.delete(&tracker.credential_service(), tracker.credential_username()),
+    };
+    stored.map_err(|e| format!("Failed to store token: {}", e))
+}
+
+/// Remove an issue tracker, its token and its cached issues
+#[tauri::command]
+pub async fn delete_issue_tracker(
+    tracker_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    info!("Deleting issue tracker: {}", tracker_id);
+
+    let database = state.database.lock().unwrap();
+    let Some(tracker) = database.list_issue_trackers()
+        .map_err(|e| format!("Failed to load issue trackers: {}", e))?
+        .into_iter()
+        .find(|t| t.id == tracker_id) else {
+        return Ok(false);
+    };
+
+    let _ = state.credential_store.lock().unwrap()
+        .delete(&tracker.credential_service(), tracker.credential_username());
+    database.delete_issue_tracker(&tracker_id)
+        .map_err(|e| format!("Failed to delete issue tracker: {}", e))
+}
+
+/// Issues referenced by a Gerrit change's subject and commit messages.
+/// The keys are also kept in the change's `issue_keys` metadata.
+#[tauri::command]
+pub async fn get_change_issues(
+    change_id: String,
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LinkedIssue>, String> {
+    let mut change = state.database.lock().unwrap()
+        .get_gerrit_change(&change_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Change not found: {}", change_id))?;
+
+    let mut text = change.subject.clone();
+    for patch_set in &change.patch_sets {
+        text.push('\n');
+        text.push_str(&patch_set.commit_message);
+    }
+    if let Some(message) = change.metadata.get("commit_message") {
+        text.push('\n');
+        text.push_str(message);
+    }
+
+    let issues = link_issues(&state, IssueSubject::Change, &change.id, &text, refresh.unwrap_or(false)).await?;
+
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    if change.metadata.get("issue_keys").map(String::as_str) != Some(keys.join(",").as_str()) {
+        change.metadata.insert("issue_keys".to_string(), keys.join(","));
+        if let Err(e) = state.database.lock().unwrap().update_gerrit_change(&change) {
+            warn!("Failed to store issue keys on change {}: {}", change.id, e);
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Issues referenced by a local task: its name, comments and the commits since its base ref
+#[tauri::command]
+pub async fn get_task_issues(
+    task_id: String,
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<LinkedIssue>, String> {
+    let store = TaskStore::new().map_err(|e| e.to_string())?;
+    let id = uuid::Uuid::parse_str(&task_id).map_err(|e| format!("Invalid task ID '{}': {}", task_id, e))?;
+    let task = store.load_task(id).map_err(|e| e.to_string())?;
+
+    let mut text = task.name.clone();
+    for message in commit_messages_since(&task.repo_path, &task.base_ref) {
+        text.push('\n');
+        text.push_str(&message);
+    }
+    for item in &task.items {
+        for note in item.preset_comment.iter().chain(item.comments.iter().map(|c| &c.content)) {
+            text.push('\n');
+            text.push_str(note);
+        }
+    }
+
+    link_issues(&state, IssueSubject::Task, &task.id.to_string(), &text, refresh.unwrap_or(false)).await
+}
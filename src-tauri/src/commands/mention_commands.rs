@@ -0,0 +1,84 @@
+// Mention Commands
+// Autocomplete @mentions from the instance's account directory
+
+use tauri::State;
+use log::{debug, warn};
+
+use crate::AppState;
+use crate::commands::gerrit_simple::{client_for_instance, mark_auth_failed};
+use crate::models::gerrit::{CommentMention, GerritUser};
+use crate::services::mentions;
+
+/// Accounts of an instance matching what was typed after an `@`. The cached directory
+/// answers first; Gerrit's account suggestions fill in the rest and are cached for
+/// next time. Offline, the cached accounts are all there is.
+#[tauri::command]
+pub async fn suggest_mention_accounts(
+    instance_id: String,
+    query: String,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<Vec<GerritUser>, String> {
+    let limit = limit.unwrap_or(10);
+    let (mut accounts, instance) = {
+        let database = state.database.lock().unwrap();
+        let accounts = database.search_gerrit_accounts(&instance_id, &query, limit)
+            .map_err(|e| format!("Failed to search accounts: {}", e))?;
+        let instance = database.get_gerrit_instance(&instance_id)
+            .map_err(|e| format!("Failed to load instance: {}", e))?
+            .ok_or_else(|| format!("Gerrit instance not found: {}", instance_id))?;
+        (accounts, instance)
+    };
+
+    // Gerrit needs at least a character to suggest anything
+    if accounts.len() >= limit as usize || query.trim().is_empty() {
+        return Ok(accounts);
+    }
+
+    let client = match client_for_instance(state.inner(), &instance) {
+        Ok(client) => client,
+        Err(e) => {
+            debug!("Suggesting cached accounts only: {}", e);
+            return Ok(accounts);
+        }
+    };
+    let suggested = match client.suggest_accounts(query.trim(), limit).await {
+        Ok(suggested) => suggested,
+        Err(e) => {
+            if e.is_authentication_required() {
+                mark_auth_failed(state.inner(), &instance_id);
+            }
+            warn!("Failed to fetch account suggestions for {}: {}", query, e);
+            return Ok(accounts);
+        }
+    };
+
+    {
+        let database = state.database.lock().unwrap();
+        if let Err(e) = database.store_gerrit_accounts(&instance_id, &suggested) {
+            warn!("Failed to cache suggested accounts: {}", e);
+        }
+    }
+    for account in suggested {
+        if accounts.len() >= limit as usize {
+            break;
+        }
+        if account.account_id != 0 && accounts.iter().all(|known| known.account_id != account.account_id) {
+            accounts.push(account);
+        }
+    }
+
+    Ok(accounts)
+}
+
+/// Mentions a comment text would resolve to in a session, for previewing them before saving
+#[tauri::command]
+pub async fn resolve_comment_mentions(
+    session_id: String,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CommentMention>, String> {
+    let database = state.database.lock().unwrap();
+    mentions::resolve_session_mentions(&session_id, &content, &database)
+        .map_err(|e| format!("Failed to resolve mentions: {}", e))
+}
@@ -14,6 +14,17 @@ pub mod file_storage_commands;
 pub mod diff_engine_commands;
 pub mod file_tree_commands;
 pub mod comment_engine_commands;
+pub mod network_commands;
+pub mod platform_commands;
+pub mod quality_gate_commands;
+pub mod issue_commands;
+pub mod patch_import_commands;
+pub mod bundle_commands;
+pub mod operation_queue_commands;
+pub mod sync_commands;
+pub mod snippet_commands;
+pub mod report_commands;
+pub mod mention_commands;
 
 #[cfg(test)]
 pub mod test_create_task_core;
@@ -0,0 +1,69 @@
+// Network settings commands
+// Global and per-instance proxy, CA and TLS configuration
+
+use tauri::State;
+use log::info;
+
+use crate::AppState;
+use crate::models::network::{NetworkSettings, GLOBAL_NETWORK_SCOPE};
+use crate::remote::http_client::{clear_client_cache, HttpClientFactory};
+
+fn scope_of(instance_id: &Option<String>) -> &str {
+    instance_id.as_deref().unwrap_or(GLOBAL_NETWORK_SCOPE)
+}
+
+/// Get stored network settings (global when no instance is given)
+#[tauri::command]
+pub async fn get_network_settings(
+    instance_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NetworkSettings, String> {
+    let database = state.database.lock().unwrap();
+    database.get_network_settings(scope_of(&instance_id))
+        .map(|settings| settings.unwrap_or_default())
+        .map_err(|e| format!("Failed to get network settings: {}", e))
+}
+
+/// Get the settings actually used for an instance (global + instance overrides)
+#[tauri::command]
+pub async fn get_effective_network_settings(
+    instance_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<NetworkSettings, String> {
+    let database = state.database.lock().unwrap();
+    database.get_effective_network_settings(instance_id.as_deref())
+        .map_err(|e| format!("Failed to get network settings: {}", e))
+}
+
+/// Save network settings after checking that a client can be built from them
+#[tauri::command]
+pub async fn save_network_settings(
+    instance_id: Option<String>,
+    settings: NetworkSettings,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Saving network settings for scope: {}", scope_of(&instance_id));
+
+    settings.validate()?;
+    clear_client_cache();
+    HttpClientFactory::new(settings.clone())
+        .async_client()
+        .map_err(|e| e.to_string())?;
+
+    let database = state.database.lock().unwrap();
+    database.store_network_settings(scope_of(&instance_id), &settings)
+        .map_err(|e| format!("Failed to save network settings: {}", e))
+}
+
+/// Remove stored network settings; instances then fall back to the global settings
+#[tauri::command]
+pub async fn clear_network_settings(
+    instance_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    info!("Clearing network settings for scope: {}", scope_of(&instance_id));
+
+    let database = state.database.lock().unwrap();
+    database.delete_network_settings(scope_of(&instance_id))
+        .map_err(|e| format!("Failed to clear network settings: {}", e))
+}
@@ -0,0 +1,98 @@
+// Operation queue commands
+// Queue remote operations while offline and inspect or steer their replay
+
+use std::sync::Arc;
+use tauri::State;
+use log::info;
+
+use crate::AppState;
+use crate::models::gerrit::{OperationPriority, OperationType};
+use crate::services::operation_executor::{PlatformOperationHandler, OperationExecutor};
+use crate::storage::operation_queue::{QueueStatus, QueuedOperation};
+
+/// Queue an operation for a Gerrit change. It is replayed by the background executor as
+/// soon as the instance is reachable. Comment operations carry a `comment_id` in their
+/// payload so updates and deletions run after the comment was created.
+#[tauri::command]
+pub async fn queue_remote_operation(
+    instance_id: String,
+    change_id: String,
+    operation_type: OperationType,
+    payload: serde_json::Value,
+    priority: Option<OperationPriority>,
+    state: State<'_, AppState>,
+) -> Result<QueuedOperation, String> {
+    info!("Queueing {} for change {} on {}", operation_type, change_id, instance_id);
+
+    if !payload.is_object() {
+        return Err("Operation payload must be a JSON object".to_string());
+    }
+    {
+        let database = state.database.lock().unwrap();
+        database.get_gerrit_instance(&instance_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Gerrit instance not found: {}", instance_id))?;
+    }
+
+    let operation = QueuedOperation::new(
+        &instance_id,
+        &change_id,
+        operation_type,
+        payload,
+        priority.unwrap_or(OperationPriority::Normal),
+    );
+    state.operation_queue.enqueue(operation.clone())
+        .map_err(|e| format!("Failed to queue operation: {}", e))?;
+    Ok(operation)
+}
+
+/// Queue statistics and the next pending operations
+#[tauri::command]
+pub async fn get_operation_queue_status(
+    state: State<'_, AppState>,
+) -> Result<QueueStatus, String> {
+    state.operation_queue.get_queue_status()
+        .map_err(|e| e.to_string())
+}
+
+/// Operations still queued, waiting or failed for a change
+#[tauri::command]
+pub async fn list_queued_operations(
+    change_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<QueuedOperation>, String> {
+    state.operation_queue.get_operations_for_change(&change_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Drop a queued operation
+#[tauri::command]
+pub async fn cancel_queued_operation(
+    operation_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    state.operation_queue.cancel_operation(&operation_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Queue operations that ran out of retries again, for one change or all of them
+#[tauri::command]
+pub async fn retry_failed_operations(
+    change_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<u32, String> {
+    state.operation_queue.retry_failed_operations(change_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Replay due operations now instead of waiting for the next executor pass.
+/// Returns the number of operations attempted.
+#[tauri::command]
+pub async fn sync_operation_queue(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<u32, String> {
+    let handler = PlatformOperationHandler::new(state.database.clone(), state.credential_store.clone());
+    let executor = OperationExecutor::new(state.operation_queue.clone(), Arc::new(handler), Arc::new(app));
+    Ok(executor.run_once().await as u32)
+}
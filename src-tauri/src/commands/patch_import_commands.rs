@@ -0,0 +1,119 @@
+// Patch Import Commands
+// Import git format-patch / mbox series as local reviews and reply to them by mail
+
+use std::path::Path;
+use tauri::State;
+use log::info;
+
+use crate::AppState;
+use crate::models::gerrit::GerritChange;
+use crate::services::patch_import::{check_series, read_patch_series, store_series, PatchSeriesImport, PATCH_IMPORT_INSTANCE};
+use crate::services::review_email::{build_reply, write_replies, Mailbox, ReplyTrailer};
+
+/// Import an mbox file or a directory of .patch files. When `base_ref` is given, each patch
+/// is checked against it in the loaded repository. Review the imported changes with the
+/// regular review session commands.
+#[tauri::command]
+pub async fn import_patch_series(
+    path: String,
+    base_ref: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<PatchSeriesImport, String> {
+    info!("Importing patch series from {}", path);
+
+    let (title, patches) = read_patch_series(Path::new(&path)).map_err(|e| e.to_string())?;
+    let base_ref = base_ref.filter(|b| !b.trim().is_empty());
+
+    let (repo, current_path) = {
+        let git_service = state.git_service.lock().unwrap();
+        (base_ref.as_ref().and_then(|_| git_service.get_repository()), git_service.get_current_path())
+    };
+    if base_ref.is_some() && repo.is_none() {
+        return Err("No repository loaded to check the patches against".to_string());
+    }
+    let project = current_path
+        .and_then(|p| Path::new(&p).file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "patches".to_string());
+
+    let checks = match (&repo, &base_ref) {
+        (Some(repo), Some(base_ref)) => Some((repo, check_series(repo, base_ref, &patches).map_err(|e| e.to_string())?)),
+        _ => None,
+    };
+
+    let database = state.database.lock().unwrap();
+    store_series(&database, &project, base_ref.as_deref(), title, &patches, checks)
+        .map_err(|e| format!("Failed to import patch series: {}", e))
+}
+
+/// List changes imported from patch series
+#[tauri::command]
+pub async fn list_imported_patches(
+    state: State<'_, AppState>,
+) -> Result<Vec<GerritChange>, String> {
+    let database = state.database.lock().unwrap();
+    database.get_gerrit_changes_for_instance(PATCH_IMPORT_INSTANCE)
+        .map_err(|e| format!("Failed to list imported patches: {}", e))
+}
+
+/// Write the comments of review sessions as quoted-reply emails, one per reviewed patch,
+/// to an .eml file (single session) or an mbox. The sender defaults to the repository's
+/// git identity. Returns the number of messages written.
+#[tauri::command]
+pub async fn export_review_reply(
+    session_ids: Vec<String>,
+    output_path: String,
+    from: Option<String>,
+    trailers: Option<Vec<ReplyTrailer>>,
+    state: State<'_, AppState>,
+) -> Result<u32, String> {
+    info!("Exporting review replies for {} sessions to {}", session_ids.len(), output_path);
+
+    if session_ids.is_empty() {
+        return Err("No review sessions selected".to_string());
+    }
+    let from = match from {
+        Some(from) => Mailbox::parse(&from).ok_or_else(|| format!("Invalid sender address: {}", from))?,
+        None => {
+            let repo = state.git_service.lock().unwrap().get_repository();
+            git_identity(repo.as_ref())
+                .ok_or("No sender given and no user.name/user.email in the git config")?
+        }
+    };
+    let trailers = trailers.unwrap_or_else(|| vec![ReplyTrailer::ReviewedBy]);
+
+    let mut replies = Vec::with_capacity(session_ids.len());
+    {
+        let database = state.database.lock().unwrap();
+        for session_id in &session_ids {
+            let session = database.get_review_session(session_id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Review session not found: {}", session_id))?;
+            let change = database.get_gerrit_change(&session.change_id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Change not found: {}", session.change_id))?;
+            let files = database.get_change_files(&change.id, session.patch_set_number)
+                .map_err(|e| e.to_string())?;
+            let comments = database.get_review_comments_for_session(session_id)
+                .map_err(|e| e.to_string())?;
+            let authors = database.get_comment_authors_for_session(session_id)
+                .map_err(|e| e.to_string())?;
+            replies.push(build_reply(&change, &files, &comments, &authors, &from, &trailers));
+        }
+    }
+
+    write_replies(Path::new(&output_path), &replies)
+        .map_err(|e| format!("Failed to write review replies: {}", e))?;
+    Ok(replies.len() as u32)
+}
+
+/// user.name and user.email from the repository or global git config
+fn git_identity(repo: Option<&git2::Repository>) -> Option<Mailbox> {
+    let config = match repo {
+        Some(repo) => repo.config().ok()?,
+        None => git2::Config::open_default().ok()?,
+    };
+    Some(Mailbox {
+        name: config.get_string("user.name").unwrap_or_default(),
+        email: config.get_string("user.email").ok()?,
+    })
+}
@@ -0,0 +1,116 @@
+// Review platform instance commands
+// Configure GitLab, GitHub and CodeArts servers and their access tokens
+
+use tauri::State;
+use log::{info, warn};
+
+use crate::AppState;
+use crate::models::platform::{PlatformInstance, PlatformKind};
+use crate::remote::platform::PlatformRegistry;
+
+fn parse_platform(platform: &str) -> Result<PlatformKind, String> {
+    PlatformKind::from_string(platform).ok_or_else(|| format!("Unsupported review platform: {}", platform))
+}
+
+/// Build the registry of every configured review backend. The state locks are
+/// released before returning, so the result can be used across awaits.
+pub(crate) fn platform_registry(state: &AppState) -> Result<PlatformRegistry, String> {
+    let database = state.database.lock().unwrap();
+    let credential_store = state.credential_store.lock().unwrap();
+    PlatformRegistry::load(&database, &credential_store)
+        .map_err(|e| format!("Failed to load review platforms: {}", e))
+}
+
+/// List configured review platform instances
+#[tauri::command]
+pub async fn list_platform_instances(
+    platform: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PlatformInstance>, String> {
+    let platform = platform.as_deref().map(parse_platform).transpose()?;
+    let database = state.database.lock().unwrap();
+    database.list_platform_instances(platform)
+        .map_err(|e| format!("Failed to list platform instances: {}", e))
+}
+
+/// Add a review platform instance and store its access token
+#[tauri::command]
+pub async fn create_platform_instance(
+    platform: String,
+    name: String,
+    base_url: String,
+    username: Option<String>,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<PlatformInstance, String> {
+    info!("Creating {} instance: {}", platform, name);
+
+    let platform = parse_platform(&platform)?;
+    if platform == PlatformKind::Gerrit {
+        return Err("Gerrit servers are managed with the Gerrit instance commands".to_string());
+    }
+    if name.trim().is_empty() {
+        return Err("Instance name cannot be empty".to_string());
+    }
+    reqwest::Url::parse(&base_url).map_err(|e| format!("Invalid base URL '{}': {}", base_url, e))?;
+
+    let mut instance = PlatformInstance::new(platform, name, base_url);
+    instance.username = username.filter(|u| !u.is_empty());
+
+    {
+        let database = state.database.lock().unwrap();
+        database.store_platform_instance(&instance)
+            .map_err(|e| format!("Failed to save instance: {}", e))?;
+    }
+
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        let mut credential_store = state.credential_store.lock().unwrap();
+        if let Err(e) = credential_store.store(&instance.credential_service(), instance.credential_username(), &token) {
+            warn!("Failed to store token for instance {}: {}", instance.id, e);
+        }
+    }
+
+    Ok(instance)
+}
+
+/// Replace the access token of a review platform instance; an empty token removes it
+#[tauri::command]
+pub async fn update_platform_instance_token(
+    instance_id: String,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Updating token of platform instance: {}", instance_id);
+
+    let instance = state.database.lock().unwrap()
+        .get_platform_instance(&instance_id)
+        .map_err(|e| format!("Failed to load instance: {}", e))?
+        .ok_or_else(|| format!("Platform instance not found: {}", instance_id))?;
+
+    let mut credential_store = state.credential_store.lock().unwrap();
+    let stored = match token.filter(|t| !t.is_empty()) {
+        Some(token) => credential_store.store(&instance.credential_service(), instance.credential_username(), &token),
+        None => credential_store.delete(&instance.credential_service(), instance.credential_username()),
+    };
+    stored.map_err(|e| format!("Failed to store token: {}", e))
+}
+
+/// Remove a review platform instance and its token
+#[tauri::command]
+pub async fn delete_platform_instance(
+    instance_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    info!("Deleting platform instance: {}", instance_id);
+
+    let database = state.database.lock().unwrap();
+    let Some(instance) = database.get_platform_instance(&instance_id)
+        .map_err(|e| format!("Failed to load instance: {}", e))? else {
+        return Ok(false);
+    };
+
+    let _ = state.credential_store.lock().unwrap()
+        .delete(&instance.credential_service(), instance.credential_username());
+    database.delete_platform_instance(&instance_id)
+        .map_err(|e| format!("Failed to delete instance: {}", e))
+}
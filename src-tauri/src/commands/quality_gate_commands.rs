@@ -0,0 +1,120 @@
+// Quality gate commands
+// Configure Jenkins, GitLab pipeline and Gerrit Verified gates
+
+use tauri::State;
+use log::{info, warn};
+
+use crate::AppState;
+use crate::models::quality_gate::{GateProviderConfig, GateSource};
+use crate::remote::quality_gates::{self, QualityGateChecker};
+
+/// Build a checker for the providers that apply to any of `scopes`.
+/// The state locks are released before returning.
+pub(crate) fn quality_gate_checker(state: &AppState, scopes: &[&str]) -> Result<QualityGateChecker, String> {
+    let database = state.database.lock().unwrap();
+    let credential_store = state.credential_store.lock().unwrap();
+    let configs = database.list_gate_providers()
+        .map_err(|e| format!("Failed to load quality gate providers: {}", e))?;
+    Ok(QualityGateChecker::load(&configs, scopes, &database, &credential_store))
+}
+
+/// List configured quality gate providers
+#[tauri::command]
+pub async fn list_quality_gate_providers(
+    state: State<'_, AppState>,
+) -> Result<Vec<GateProviderConfig>, String> {
+    let database = state.database.lock().unwrap();
+    database.list_gate_providers()
+        .map_err(|e| format!("Failed to list quality gate providers: {}", e))
+}
+
+/// Add a quality gate provider. `token` is the Jenkins API token; GitLab and
+/// Gerrit gates use the credentials of their instance.
+#[tauri::command]
+pub async fn create_quality_gate_provider(
+    name: String,
+    scope: Option<String>,
+    source: GateSource,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<GateProviderConfig, String> {
+    info!("Creating {} quality gate: {}", source.kind(), name);
+
+    if name.trim().is_empty() {
+        return Err("Gate name cannot be empty".to_string());
+    }
+    if let GateSource::Jenkins { base_url, job, .. } = &source {
+        reqwest::Url::parse(base_url).map_err(|e| format!("Invalid Jenkins URL '{}': {}", base_url, e))?;
+        if job.trim().is_empty() {
+            return Err("Jenkins job cannot be empty".to_string());
+        }
+    }
+
+    let provider = GateProviderConfig::new(name, scope, source);
+    {
+        let database = state.database.lock().unwrap();
+        database.store_gate_provider(&provider)
+            .map_err(|e| format!("Failed to save quality gate provider: {}", e))?;
+    }
+
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        let mut credential_store = state.credential_store.lock().unwrap();
+        if let Err(e) = credential_store.store(&provider.credential_service(), provider.credential_username(), &token) {
+            warn!("Failed to store token for quality gate {}: {}", provider.id, e);
+        }
+    }
+
+    Ok(provider)
+}
+
+/// Replace the Jenkins API token of a quality gate provider; an empty token removes it
+#[tauri::command]
+pub async fn update_quality_gate_token(
+    provider_id: String,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    info!("Updating token of quality gate provider: {}", provider_id);
+
+    let provider = state.database.lock().unwrap()
+        .list_gate_providers()
+        .map_err(|e| format!("Failed to load quality gate providers: {}", e))?
+        .into_iter()
+        .find(|p| p.id == provider_id)
+        .ok_or_else(|| format!("Quality gate provider not found: {}", provider_id))?;
+
+    {
+        let mut credential_store = state.credential_store.lock().unwrap();
+        let stored = match token.filter(|t| !t.is_empty()) {
+            Some(token) => credential_store.store(&provider.credential_service(), provider.credential_username(), &token),
+            None => credential_store.delete(&provider.credential_service(), provider.credential_username()),
+        };
+        stored.map_err(|e| format!("Failed to store token: {}", e))?;
+    }
+    // Results fetched with the old token may have been failures
+    quality_gates::clear_cache();
+    Ok(())
+}
+
+/// Remove a quality gate provider and its token
+#[tauri::command]
+pub async fn delete_quality_gate_provider(
+    provider_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    info!("Deleting quality gate provider: {}", provider_id);
+
+    let database = state.database.lock().unwrap();
+    let Some(provider) = database.list_gate_providers()
+        .map_err(|e| format!("Failed to load quality gate providers: {}", e))?
+        .into_iter()
+        .find(|p| p.id == provider_id) else {
+        return Ok(false);
+    };
+
+    let _ = state.credential_store.lock().unwrap()
+        .delete(&provider.credential_service(), provider.credential_username());
+    quality_gates::clear_cache();
+    database.delete_gate_provider(&provider_id)
+        .map_err(|e| format!("Failed to delete quality gate provider: {}", e))
+}
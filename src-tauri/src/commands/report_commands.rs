@@ -0,0 +1,71 @@
+// Report Commands
+// Export Markdown or HTML summaries of a review session or local task
+
+use std::path::Path;
+use tauri::State;
+use log::info;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::services::review_report::{ReportExtras, ReportFormat, ReviewReport};
+use crate::storage::task_store::TaskStore;
+
+/// Render the report of a review session, and write it to `output_path` when given
+#[tauri::command]
+pub async fn export_session_report(
+    session_id: String,
+    format: ReportFormat,
+    extras: Option<ReportExtras>,
+    output_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    info!("Exporting {:?} report for session {}", format, session_id);
+
+    let report = {
+        let database = state.database.lock().unwrap();
+        let session = database.get_review_session(&session_id)
+            .map_err(|e| format!("Failed to load session: {}", e))?
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        let change = database.get_gerrit_change(&session.change_id)
+            .map_err(|e| format!("Failed to load change: {}", e))?;
+        let file_reviews = database.get_file_reviews_for_session(&session_id)
+            .map_err(|e| format!("Failed to load file reviews: {}", e))?;
+        let files = database.get_change_files(&session.change_id, session.patch_set_number)
+            .map_err(|e| format!("Failed to load change files: {}", e))?;
+        let comments = database.get_review_comments_for_session(&session_id)
+            .map_err(|e| format!("Failed to load comments: {}", e))?;
+        let authors = database.get_comment_authors_for_session(&session_id)
+            .map_err(|e| format!("Failed to load comment authors: {}", e))?;
+
+        ReviewReport::for_session(&session, change.as_ref(), &file_reviews, &files, &comments, &authors, extras.unwrap_or_default())
+    };
+
+    write_report(&report, format, output_path)
+}
+
+/// Render the report of a local task, and write it to `output_path` when given
+#[tauri::command]
+pub async fn export_task_report(
+    task_id: Uuid,
+    format: ReportFormat,
+    extras: Option<ReportExtras>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    info!("Exporting {:?} report for task {}", format, task_id);
+
+    let store = TaskStore::new().map_err(|e| e.to_string())?;
+    let task = store.load_task(task_id).map_err(|e| e.to_string())?;
+    let report = ReviewReport::for_task(&task, extras.unwrap_or_default());
+
+    write_report(&report, format, output_path)
+}
+
+fn write_report(report: &ReviewReport, format: ReportFormat, output_path: Option<String>) -> Result<String, String> {
+    let rendered = report.render(format);
+    if let Some(output_path) = output_path {
+        std::fs::write(Path::new(&output_path), &rendered)
+            .map_err(|e| format!("Failed to write report: {}", e))?;
+        info!("Wrote review report to {}", output_path);
+    }
+    Ok(rendered)
+}
@@ -287,32 +287,29 @@ pub async fn gerrit_abandon_session(
 #[tauri::command]
 pub async fn gerrit_mark_ready_for_submission(
     session_id: String,
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
 ) -> Result<ReviewSession, String> {
     info!("Marking session {} as ready for submission", session_id);
 
-    tokio::task::spawn_blocking(move || {
-        let database = crate::storage::sqlite::Database::new("hyper_review.db")
-            .map_err(|e| format!("Database error: {}", e))?;
+    // Gates are checked for the reviewed revision; failing gates block submission
+    let (commit, project) = crate::services::review_session::session_commit(&state.database.lock().unwrap(), &session_id)
+        .map_err(|e| format!("Failed to resolve session commit: {}", e))?;
+    let checker = crate::commands::quality_gate_commands::quality_gate_checker(&state, &[project.as_str()])?;
+    let gates = checker.check(&commit, false).await;
 
-        let db_ref = Arc::new(database);
-        let session_manager = ReviewSessionManager::new(db_ref);
-
-        let rt = tokio::runtime::Runtime::new()
-            .map_err(|e| format!("Failed to create runtime: {}", e))?;
-
-        rt.block_on(async {
-            match session_manager.mark_ready_for_submission(&session_id).await {
-                Ok(session) => {
-                    info!("Session {} is now ready for submission", session_id);
-                    Ok(session)
-                }
-                Err(e) => {
-                    error!("Failed to mark session ready for submission: {}", e);
-                    Err(format!("Failed to mark session ready for submission: {}", e))
-                }
+    let database = state.database.clone();
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        match crate::services::review_session::mark_ready_for_submission(&database, &session_id, &gates) {
+            Ok(session) => {
+                info!("Session {} is now ready for submission", session_id);
+                Ok(session)
             }
-        })
+            Err(e) => {
+                error!("Failed to mark session ready for submission: {}", e);
+                Err(format!("Failed to mark session ready for submission: {}", e))
+            }
+        }
     }).await.map_err(|e| format!("Task join error: {}", e))?
 }
 
@@ -320,16 +317,17 @@ pub async fn gerrit_mark_ready_for_submission(
 #[tauri::command]
 pub async fn gerrit_recover_session(
     session_id: String,
-    _state: State<'_, AppState>,
+    state: State<'_, AppState>,
 ) -> Result<SessionRecoveryInfo, String> {
     info!("Recovering session state: {}", session_id);
 
+    let draft_journal = state.draft_journal.clone();
     tokio::task::spawn_blocking(move || {
         let database = crate::storage::sqlite::Database::new("hyper_review.db")
             .map_err(|e| format!("Database error: {}", e))?;
 
         let db_ref = Arc::new(database);
-        let session_manager = ReviewSessionManager::new(db_ref);
+        let session_manager = ReviewSessionManager::new(db_ref).with_draft_journal(draft_journal);
 
         let rt = tokio::runtime::Runtime::new()
             .map_err(|e| format!("Failed to create runtime: {}", e))?;
@@ -0,0 +1,74 @@
+// Snippet Commands
+// Insert review templates with placeholders and share them as team packs
+
+use std::path::Path;
+use tauri::State;
+use log::info;
+
+use crate::AppState;
+use crate::models::ReviewTemplate;
+use crate::services::snippets::{self, SnippetContext, SnippetPack};
+
+/// Snippets offered for the file and line being commented on
+#[tauri::command]
+pub async fn get_applicable_snippets(
+    context: SnippetContext,
+    state: State<'_, AppState>,
+) -> Result<Vec<ReviewTemplate>, String> {
+    let context = context.resolve(None);
+    let database = state.database.lock().unwrap();
+    snippets::applicable_snippets(&database, &context)
+        .map_err(|e| format!("Failed to get snippets: {}", e))
+}
+
+/// Fill in a snippet from the diff context; `file_content` is used to find the enclosing symbol
+#[tauri::command]
+pub async fn insert_snippet(
+    template_id: String,
+    context: SnippetContext,
+    file_content: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let context = context.resolve(file_content.as_deref());
+    let database = state.database.lock().unwrap();
+    snippets::insert_snippet(&database, &template_id, &context)
+        .map_err(|e| format!("Failed to insert snippet: {}", e))
+}
+
+/// Write all snippets to a team pack file
+#[tauri::command]
+pub async fn export_snippet_pack(
+    name: String,
+    output_path: String,
+    state: State<'_, AppState>,
+) -> Result<SnippetPack, String> {
+    info!("Exporting snippet pack {} to {}", name, output_path);
+
+    let pack = {
+        let database = state.database.lock().unwrap();
+        snippets::export_pack(&database, &name).map_err(|e| format!("Failed to export snippets: {}", e))?
+    };
+    let json = serde_json::to_string_pretty(&pack).map_err(|e| e.to_string())?;
+    std::fs::write(Path::new(&output_path), json)
+        .map_err(|e| format!("Failed to write snippet pack: {}", e))?;
+
+    Ok(pack)
+}
+
+/// Add the snippets of a team pack file
+#[tauri::command]
+pub async fn import_snippet_pack(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    info!("Importing snippet pack {}", path);
+
+    let json = std::fs::read_to_string(Path::new(&path))
+        .map_err(|e| format!("Failed to read snippet pack: {}", e))?;
+    let pack: SnippetPack = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid snippet pack: {}", e))?;
+
+    let database = state.database.lock().unwrap();
+    snippets::import_pack(&database, &pack)
+        .map_err(|e| format!("Failed to import snippets: {}", e))
+}
@@ -0,0 +1,94 @@
+// Sync conflict commands
+// Settle conflicts between offline comment edits and Gerrit, show what was decided,
+// and import the comments already on the review platform
+
+use tauri::State;
+use log::{info, warn, error};
+
+use crate::AppState;
+use crate::commands::gerrit_simple::mark_auth_failed;
+use crate::commands::platform_commands::platform_registry;
+use crate::models::gerrit::SyncHistoryEntry;
+use crate::models::platform::PlatformKind;
+use crate::services::comment_import::{self, CommentImportSummary};
+use crate::services::sync_manager::{CommentResolution, ConflictInfo, ResolutionChoice, SyncManager};
+
+/// Resolve a comment conflict reported by a comment import. The resulting comment is
+/// saved (or dropped) locally and the choice is recorded in the change's sync history.
+#[tauri::command]
+pub async fn resolve_sync_conflict(
+    conflict: ConflictInfo,
+    choice: ResolutionChoice,
+    state: State<'_, AppState>,
+) -> Result<CommentResolution, String> {
+    info!("Resolving {} conflict on {} with {}", conflict.conflict_type, conflict.entity_id, choice);
+
+    SyncManager::new(state.database.clone())
+        .resolve_conflict(&conflict, choice)
+        .map_err(|e| e.to_string())
+}
+
+/// Conflict resolutions recorded for a change, oldest first
+#[tauri::command]
+pub async fn get_sync_history(
+    change_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SyncHistoryEntry>, String> {
+    let database = state.database.lock().unwrap();
+    database.get_sync_history(&change_id)
+        .map_err(|e| format!("Failed to load sync history: {}", e))
+}
+
+/// Import the comments on the session's change as comment threads: published, draft
+/// and robot comments from Gerrit, or the discussion of a merge or pull request.
+/// Comments imported before are updated in place rather than duplicated, and dropped
+/// once they were deleted remotely.
+#[tauri::command]
+pub async fn import_gerrit_comments(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<CommentImportSummary, String> {
+    info!("Importing remote comments into session {}", session_id);
+
+    let (session, change) = {
+        let database = state.database.lock().unwrap();
+        let session = database.get_review_session(&session_id)
+            .map_err(|e| format!("Failed to load session: {}", e))?
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        let change = database.get_gerrit_change(&session.change_id)
+            .map_err(|e| format!("Failed to load change: {}", e))?
+            .ok_or_else(|| format!("Change not found: {}", session.change_id))?;
+        (session, change)
+    };
+
+    let registry = platform_registry(&state)?;
+    let target = registry.get(&change.instance_id)
+        .ok_or_else(|| format!("Review platform instance not found: {}", change.instance_id))?;
+    let remote_comments = match target.platform.list_remote_comments(&target.change_ref(&change.project, &change.change_id)).await {
+        Ok(comments) => comments,
+        Err(e) => {
+            if e.is_authentication_required() && target.kind == PlatformKind::Gerrit {
+                mark_auth_failed(state.inner(), &target.instance_id);
+            }
+            error!("Failed to fetch comments of {}: {}", change.change_id, e);
+            return Err(format!("Failed to fetch comments: {}", e));
+        }
+    };
+
+    let complete = target.platform.complete_comment_sources();
+    let database = state.database.clone();
+    let is_gerrit = target.kind == PlatformKind::Gerrit;
+    let instance_id = target.instance_id.clone();
+    tokio::task::spawn_blocking(move || {
+        let database = database.lock().unwrap();
+        // Commenters are the people a reviewer is most likely to mention
+        if is_gerrit {
+            let authors: Vec<_> = remote_comments.iter().map(|remote| remote.comment.author.clone()).collect();
+            if let Err(e) = database.store_gerrit_accounts(&instance_id, &authors) {
+                warn!("Failed to store comment authors of {}: {}", change.change_id, e);
+            }
+        }
+        comment_import::import_gerrit_comments(&session, &remote_comments, &complete, &database)
+    }).await.map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Failed to import comments: {}", e))
+}
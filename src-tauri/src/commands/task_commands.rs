@@ -2,6 +2,8 @@ use crate::models::task::{LocalTask, CreateTaskRequest, TaskSummary, TaskStatus,
 use crate::storage::task_store::TaskStore;
 use crate::commands::text_parser;
 use crate::git::repo_manager::RepoManager;
+use crate::models::webhook::{DeadLetter, WebhookOptions, WebhookRequest};
+use crate::remote::custom_client::CustomApiClient;
 use tauri::State;
 use uuid::Uuid;
 use chrono::Utc;
@@ -199,66 +201,98 @@ pub async fn export_all_tasks() -> Result<String, String> {
     Ok(json)
 }
 
+/// Publish a task's comments on a Gerrit change through a configured instance: the
+/// named one, the one at `gerrit_url`, or the active instance
 #[tauri::command]
 pub async fn submit_task_to_gerrit(
-    _state: State<'_, crate::AppState>,
-    _task_id: String,
-    _gerrit_url: String,
-    _username: String,
-    _change_id: String,
-    _revision_id: String,
-    _score: Option<i32>,
+    state: State<'_, crate::AppState>,
+    task_id: String,
+    change_id: String,
+    score: Option<i32>,
+    gerrit_url: Option<String>,
+    instance_id: Option<String>,
 ) -> Result<crate::models::SubmitResult, String> {
-    // Gerrit client temporarily disabled
-    Err("Gerrit integration temporarily disabled".to_string())
+    use crate::commands::platform_commands::platform_registry;
+    use crate::models::platform::PlatformKind;
+    use crate::remote::platform::{ChangeRef, PlatformReview, ReviewVote};
+
+    let task = load_task_for_submission(&task_id)?;
+    let api_comments: Vec<crate::models::Comment> = task.items
+        .into_iter()
+        .flat_map(|item| {
+            let file = item.file;
+            item.comments.into_iter()
+                .map(move |task_comment| convert_task_comment_to_api_comment(task_comment, file.clone()))
+        })
+        .collect();
+    let review = PlatformReview {
+        message: String::new(),
+        comments: api_comments,
+        vote: score.map(ReviewVote::from_score),
+    };
+
+    let registry = platform_registry(&state)?;
+    let gerrit = match (instance_id.as_deref(), gerrit_url.as_deref().filter(|url| !url.trim().is_empty())) {
+        (None, Some(url)) => registry.find_by_url(PlatformKind::Gerrit, url)
+            .ok_or_else(|| format!("No Gerrit instance is configured for {}", url))?,
+        (instance_id, _) => registry.resolve(PlatformKind::Gerrit, instance_id).map_err(|e| e.to_string())?,
+    };
+    let result = gerrit.platform.post_review(&ChangeRef::new("", &change_id), &review)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(result)
 }
 
 #[tauri::command]
 pub async fn submit_task_to_codearts(
+    state: State<'_, crate::AppState>,
     task_id: String,
     project_id: String,
     mr_id: u64,
-    _approval: Option<String>,
+    approval: Option<String>,
+    instance_id: Option<String>,
 ) -> Result<crate::models::SubmitResult, String> {
-    use crate::remote::codearts_client::CodeArtsClient;
+    use crate::commands::platform_commands::platform_registry;
+    use crate::models::platform::PlatformKind;
+    use crate::remote::platform::{ChangeRef, PlatformReview, ReviewVote};
     
     let store = TaskStore::new().map_err(|e| e.to_string())?;
-    let task = store.load_task(uuid::Uuid::parse_str(&task_id).unwrap())
+    let task = store.load_task(uuid::Uuid::parse_str(&task_id).map_err(|e| e.to_string())?)
         .map_err(|e| e.to_string())?;
 
     let api_comments: Vec<crate::models::Comment> = task.items
         .into_iter()
-        .filter_map(|item| {
-            if item.comments.is_empty() {
-                return None;
-            }
-            let task_comment = item.comments.into_iter().next()?;
-            Some(convert_task_comment_to_api_comment(task_comment, item.file))
+        .flat_map(|item| {
+            let file = item.file;
+            item.comments.into_iter()
+                .map(move |task_comment| convert_task_comment_to_api_comment(task_comment, file.clone()))
         })
         .collect();
 
-    let codearts_client = CodeArtsClient::new("https://codearts.example.com");
-    let result = codearts_client.submit_review(&project_id, mr_id, api_comments, None)
+    let vote = approval
+        .map(|a| ReviewVote::from_string(&a).ok_or_else(|| format!("Unknown approval: {}", a)))
+        .transpose()?;
+    let review = PlatformReview {
+        message: String::new(),
+        comments: api_comments,
+        vote,
+    };
+
+    let registry = platform_registry(&state)?;
+    let codearts = registry.resolve(PlatformKind::CodeArts, instance_id.as_deref())
+        .map_err(|e| e.to_string())?;
+    let result = codearts.platform.post_review(&ChangeRef::new(&project_id, mr_id), &review)
+        .await
         .map_err(|e| e.to_string())?;
 
     Ok(result)
 }
 
-#[tauri::command]
-pub async fn submit_task_to_custom_api(
-    task_id: String,
-    endpoint: String,
-    method: String,
-    api_url: String,
-) -> Result<crate::models::SubmitResult, String> {
-    use crate::remote::custom_client::CustomApiClient;
-    use serde_json::json;
-    
-    let store = TaskStore::new().map_err(|e| e.to_string())?;
-    let task = store.load_task(uuid::Uuid::parse_str(&task_id).unwrap())
-        .map_err(|e| e.to_string())?;
-
-    let payload = json!({
+/// Payload context for custom APIs: the task with every item's comments.
+/// Sent as-is without a template, otherwise rendered through it.
+fn custom_api_payload(task: &LocalTask) -> serde_json::Value {
+    json!({
         "task_id": task.id.to_string(),
         "task_name": task.name,
         "repo_path": task.repo_path,
@@ -275,15 +309,108 @@ pub async fn submit_task_to_custom_api(
         },
         "items": task.items.iter().map(|item| json!({
             "file": item.file,
+            "line_range": item.line_range,
+            "severity": item.severity,
+            "tags": item.tags,
             "reviewed": item.reviewed,
-            "comments": []
+            "comments": item.comments.iter().map(|comment| json!({
+                "id": comment.id.to_string(),
+                "author": comment.author,
+                "content": comment.content,
+                "line_number": comment.line_number,
+                "created_at": comment.created_at.to_rfc3339(),
+            })).collect::<Vec<_>>(),
         })).collect::<Vec<_>>(),
         "submitted_at": task.update_time.to_rfc3339()
-    });
+    })
+}
 
-    let custom_client = CustomApiClient::new(&api_url);
-    let result = custom_client.submit_review(&endpoint, payload, &method)
+fn load_task_for_submission(task_id: &str) -> Result<LocalTask, String> {
+    let store = TaskStore::new().map_err(|e| e.to_string())?;
+    let id = uuid::Uuid::parse_str(task_id).map_err(|e| format!("Invalid task ID '{}': {}", task_id, e))?;
+    store.load_task(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn submit_task_to_custom_api(
+    state: State<'_, crate::AppState>,
+    task_id: String,
+    endpoint: String,
+    method: String,
+    api_url: String,
+    options: Option<WebhookOptions>,
+) -> Result<crate::models::SubmitResult, String> {
+    let task = load_task_for_submission(&task_id)?;
+    let network = crate::commands::gerrit_simple::network_settings_for(state.inner(), None);
+
+    let custom_client = CustomApiClient::new(&api_url)
+        .with_options(options.unwrap_or_default())
+        .with_network(network)
+        .with_dead_letter_log(state.database.clone());
+    let result = custom_client.submit_review(&endpoint, custom_api_payload(&task), &method)
+        .await
         .map_err(|e| e.to_string())?;
 
     Ok(result)
 }
+
+/// Render the custom API request for a task without sending it
+#[tauri::command]
+pub async fn preview_custom_api_request(
+    task_id: String,
+    endpoint: String,
+    method: String,
+    api_url: String,
+    options: Option<WebhookOptions>,
+) -> Result<WebhookRequest, String> {
+    let task = load_task_for_submission(&task_id)?;
+
+    CustomApiClient::new(&api_url)
+        .with_options(options.unwrap_or_default())
+        .dry_run(&endpoint, &method, &custom_api_payload(&task))
+        .map_err(|e| e.to_string())
+}
+
+/// List custom API deliveries that failed after all retries
+#[tauri::command]
+pub async fn list_dead_letters(
+    state: State<'_, crate::AppState>,
+) -> Result<Vec<DeadLetter>, String> {
+    let database = state.database.lock().unwrap();
+    database.list_dead_letters()
+        .map_err(|e| format!("Failed to list failed deliveries: {}", e))
+}
+
+/// Resend a failed delivery. Headers, auth and signature come from `options`,
+/// since credentials are not kept with the failed request.
+#[tauri::command]
+pub async fn retry_dead_letter(
+    state: State<'_, crate::AppState>,
+    delivery_id: String,
+    options: Option<WebhookOptions>,
+) -> Result<crate::models::SubmitResult, String> {
+    let letter = state.database.lock().unwrap()
+        .get_dead_letter(&delivery_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Failed delivery not found: {}", delivery_id))?;
+    let network = crate::commands::gerrit_simple::network_settings_for(state.inner(), None);
+
+    CustomApiClient::new(&letter.url)
+        .with_options(options.unwrap_or_default())
+        .with_network(network)
+        .with_dead_letter_log(state.database.clone())
+        .resend(&letter)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Discard a failed delivery
+#[tauri::command]
+pub async fn delete_dead_letter(
+    state: State<'_, crate::AppState>,
+    delivery_id: String,
+) -> Result<bool, String> {
+    let database = state.database.lock().unwrap();
+    database.delete_dead_letter(&delivery_id)
+        .map_err(|e| format!("Failed to delete failed delivery: {}", e))
+}
@@ -47,6 +47,10 @@ pub enum HyperReviewError {
         status_code: Option<u16>,
     },
 
+    /// Remote server rejected our credentials; the user must re-authenticate
+    #[error("Authentication required for {url}: {message}. Please re-enter your credentials for this instance")]
+    AuthenticationRequired { url: String, message: String },
+
     /// Reqwest HTTP errors
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
@@ -135,6 +139,16 @@ impl HyperReviewError {
         }
     }
 
+    /// Create an authentication required error
+    pub fn authentication_required(url: String, message: String) -> Self {
+        Self::AuthenticationRequired { url, message }
+    }
+
+    /// Whether the error asks the user to re-authenticate
+    pub fn is_authentication_required(&self) -> bool {
+        matches!(self, Self::AuthenticationRequired { .. })
+    }
+
     /// Create a cache error
     pub fn cache(message: String) -> Self {
         Self::Cache { message }
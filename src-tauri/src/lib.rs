@@ -31,11 +31,13 @@ pub mod storage {
     pub mod migrations;
     pub mod offline_cache;
     pub mod operation_queue;
+    pub mod draft_journal;
 }
 
 pub mod search {
     pub mod service;
     pub mod index;
+    pub mod review_history;
 }
 
 pub mod services {
@@ -47,14 +49,35 @@ pub mod services {
     pub mod diff_engine;
     pub mod file_tree;
     pub mod comment_engine;
+    pub mod comment_import;
+    pub mod mentions;
+    pub mod patch_import;
+    pub mod review_email;
+    pub mod review_report;
+    pub mod review_bundle;
+    pub mod operation_executor;
+    pub mod sync_manager;
+    pub mod suggested_changes;
+    pub mod snippets;
 }
 
 pub mod remote {
     pub mod client;
     pub mod gitlab_client;
+    pub mod github_client;
     pub mod gerrit_client;
+    pub mod gerrit_auth;
+    pub mod platform;
+    pub mod http_client;
+    pub mod traffic_control;
+    #[cfg(test)]
+    pub mod mock_server;
+    #[cfg(test)]
+    pub mod fixtures;
     pub mod codearts_client;
     pub mod custom_client;
+    pub mod quality_gates;
+    pub mod issue_tracker;
 }
 
 pub mod utils {
@@ -76,6 +99,10 @@ pub struct AppState {
     pub background_indexer: Arc<Mutex<()>>,
     /// Credential store for external systems
     pub credential_store: Arc<Mutex<storage::credentials::CredentialStore>>,
+    /// Persistent queue of remote operations made while offline
+    pub operation_queue: Arc<storage::operation_queue::OperationQueue>,
+    /// Write-ahead journal of comments being typed
+    pub draft_journal: Arc<storage::draft_journal::DraftJournal>,
 }
 
 impl AppState {
@@ -100,13 +127,37 @@ impl AppState {
             })?;
         
         log::info!("Database and Gerrit schema initialized successfully");
+
+        let database = Arc::new(Mutex::new(database));
+        let operation_queue = storage::operation_queue::OperationQueue::with_database(database.clone())?;
+
+        // Drop saved and discarded drafts; the rest are offered for recovery
+        let comment_config = services::comment_engine::CommentEngineConfig::default();
+        let draft_journal = storage::draft_journal::DraftJournal::new(
+            "hyper_review.drafts.jsonl",
+            std::time::Duration::from_millis(comment_config.draft_autosave_debounce_ms),
+        );
+        match draft_journal.compact() {
+            Ok(0) => {}
+            Ok(unsaved) => log::info!("Found {} unsaved comment drafts from the last run", unsaved),
+            Err(e) => log::warn!("Failed to compact draft journal: {}", e),
+        }
+
+        // Tokens of platform instances, gates and trackers must outlive the process
+        let credential_store = storage::credentials::CredentialStore::open(db_path, "hyper_review.key")
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to open credential store, secrets will not be saved: {}", e);
+                storage::credentials::CredentialStore::new()
+            });
         
         Ok(Self {
             git_service: Arc::new(Mutex::new(git::service::GitService::new())),
             cache_manager: Arc::new(storage::cache::CacheManager::new()),
-            database: Arc::new(Mutex::new(database)),
+            database,
             background_indexer: Arc::new(Mutex::new(())),
-            credential_store: Arc::new(Mutex::new(storage::credentials::CredentialStore::new())),
+            credential_store: Arc::new(Mutex::new(credential_store)),
+            operation_queue: Arc::new(operation_queue),
+            draft_journal: Arc::new(draft_journal),
         })
     }
 }
@@ -139,6 +190,24 @@ pub fn run() {
 
     tauri::Builder::default()
         .manage(app_state)
+        .setup(|app| {
+            use tauri::Manager;
+
+            // Replay operations queued while offline once their instance is reachable
+            let state = app.state::<AppState>();
+            let handler = services::operation_executor::PlatformOperationHandler::new(
+                state.database.clone(),
+                state.credential_store.clone(),
+            );
+            let executor = services::operation_executor::OperationExecutor::new(
+                state.operation_queue.clone(),
+                Arc::new(handler),
+                Arc::new(app.handle()),
+            );
+            tauri::async_runtime::spawn(executor.run());
+            tauri::async_runtime::spawn(state.draft_journal.clone().run_flusher());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Repository management commands
             commands::general::open_repo_dialog,
@@ -158,6 +227,14 @@ pub fn run() {
             // Template management commands
             commands::general::get_review_templates,
             commands::general::create_template,
+            commands::snippet_commands::get_applicable_snippets,
+            commands::snippet_commands::insert_snippet,
+            commands::snippet_commands::export_snippet_pack,
+            commands::snippet_commands::import_snippet_pack,
+
+            // Review report commands
+            commands::report_commands::export_session_report,
+            commands::report_commands::export_task_report,
 
             // Insights and analysis commands
             commands::general::get_heatmap,
@@ -186,6 +263,10 @@ pub fn run() {
             commands::task_commands::submit_task_to_gerrit,
             commands::task_commands::submit_task_to_codearts,
             commands::task_commands::submit_task_to_custom_api,
+            commands::task_commands::preview_custom_api_request,
+            commands::task_commands::list_dead_letters,
+            commands::task_commands::retry_dead_letter,
+            commands::task_commands::delete_dead_letter,
 
             commands::general::get_tags,
             commands::general::create_tag,
@@ -213,6 +294,55 @@ pub fn run() {
             commands::gerrit_commands::gerrit_get_comments_simple,
             commands::gerrit_commands::gerrit_submit_review_simple,
 
+            // Network settings commands
+            commands::network_commands::get_network_settings,
+            commands::network_commands::get_effective_network_settings,
+            commands::network_commands::save_network_settings,
+            commands::network_commands::clear_network_settings,
+
+            // Review platform instance commands
+            commands::platform_commands::list_platform_instances,
+            commands::platform_commands::create_platform_instance,
+            commands::platform_commands::update_platform_instance_token,
+            commands::platform_commands::delete_platform_instance,
+
+            // Quality gate commands
+            commands::quality_gate_commands::list_quality_gate_providers,
+            commands::quality_gate_commands::create_quality_gate_provider,
+            commands::quality_gate_commands::update_quality_gate_token,
+            commands::quality_gate_commands::delete_quality_gate_provider,
+
+            // Issue tracker commands
+            commands::issue_commands::list_issue_trackers,
+            commands::issue_commands::create_issue_tracker,
+            commands::issue_commands::update_issue_tracker_token,
+            commands::issue_commands::delete_issue_tracker,
+            commands::issue_commands::get_change_issues,
+            commands::issue_commands::get_task_issues,
+
+            // Patch import commands
+            commands::patch_import_commands::import_patch_series,
+            commands::patch_import_commands::list_imported_patches,
+            commands::patch_import_commands::export_review_reply,
+
+            // Review bundle commands
+            commands::bundle_commands::export_review_bundle,
+            commands::bundle_commands::export_return_bundle,
+            commands::bundle_commands::import_review_bundle,
+
+            // Operation queue commands
+            commands::operation_queue_commands::queue_remote_operation,
+            commands::operation_queue_commands::get_operation_queue_status,
+            commands::operation_queue_commands::list_queued_operations,
+            commands::operation_queue_commands::cancel_queued_operation,
+            commands::operation_queue_commands::retry_failed_operations,
+            commands::operation_queue_commands::sync_operation_queue,
+
+            // Sync conflict commands
+            commands::sync_commands::resolve_sync_conflict,
+            commands::sync_commands::get_sync_history,
+            commands::sync_commands::import_gerrit_comments,
+
             // Change download commands
             commands::change_download_commands::gerrit_download_change,
             commands::change_download_commands::gerrit_get_download_status,
@@ -271,8 +401,17 @@ pub fn run() {
             commands::comment_engine_commands::comment_get_session_comments,
             commands::comment_engine_commands::comment_get_file_comments,
             commands::comment_engine_commands::comment_get_thread,
+            commands::comment_engine_commands::comment_get_session_threads,
+            commands::comment_engine_commands::comment_get_blocking_threads,
+            commands::comment_engine_commands::comment_set_thread_state,
+            commands::comment_engine_commands::comment_sync_gerrit_unresolved,
+            commands::comment_engine_commands::comment_get_thread_history,
             commands::comment_engine_commands::comment_search,
+            commands::comment_engine_commands::search_review_history,
             commands::comment_engine_commands::comment_get_stats,
+            commands::comment_engine_commands::comment_get_change_stats,
+            commands::comment_engine_commands::comment_get_severity_taxonomy,
+            commands::comment_engine_commands::comment_set_severity_taxonomy,
             commands::comment_engine_commands::comment_publish_all_drafts,
             commands::comment_engine_commands::comment_update_status,
             commands::comment_engine_commands::comment_get_config,
@@ -282,8 +421,22 @@ pub fn run() {
             commands::comment_engine_commands::comment_get_file_inline_comments,
             commands::comment_engine_commands::comment_get_comments_by_line,
             commands::comment_engine_commands::comment_update_inline_position,
+            commands::comment_engine_commands::comment_reanchor_session,
+            commands::comment_engine_commands::comment_get_anchors,
+            commands::comment_engine_commands::comment_get_suggestion,
+            commands::comment_engine_commands::comment_queue_suggestion,
+            commands::comment_engine_commands::comment_get_suggestion_diff,
+            commands::comment_engine_commands::comment_apply_suggestions,
+            commands::comment_engine_commands::comment_record_draft,
+            commands::comment_engine_commands::comment_get_unsaved_drafts,
+            commands::comment_engine_commands::comment_restore_draft,
+            commands::comment_engine_commands::comment_discard_draft,
             commands::comment_engine_commands::comment_highlight_range,
 
+            // Mention commands
+            commands::mention_commands::suggest_mention_accounts,
+            commands::mention_commands::resolve_comment_mentions,
+
             // Search and configuration commands
             commands::general::search,
             commands::general::get_commands,
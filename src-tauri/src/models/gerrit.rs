@@ -12,6 +12,8 @@ pub struct GerritInstance {
     pub url: String,                   // Base URL
     pub username: String,              // Authentication username
     pub password_encrypted: String,    // AES-encrypted password/token
+    #[serde(default)]
+    pub auth_method: GerritAuthMethod, // How requests are authenticated
     pub version: String,               // Gerrit version
     pub is_active: bool,               // Currently selected instance
     pub last_connected: Option<String>, // ISO 8601 timestamp
@@ -56,6 +58,39 @@ impl ConnectionStatus {
     }
 }
 
+/// Authentication scheme used when talking to a Gerrit instance
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub enum GerritAuthMethod {
+    #[default]
+    Basic,      // Username + HTTP password
+    Bearer,     // OAuth access token
+    Cookie,     // Cookie jar (.gitcookies)
+    Netrc,      // Machine entry in .netrc
+}
+
+impl std::fmt::Display for GerritAuthMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GerritAuthMethod::Basic => write!(f, "basic"),
+            GerritAuthMethod::Bearer => write!(f, "bearer"),
+            GerritAuthMethod::Cookie => write!(f, "cookie"),
+            GerritAuthMethod::Netrc => write!(f, "netrc"),
+        }
+    }
+}
+
+impl GerritAuthMethod {
+    pub fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "basic" => GerritAuthMethod::Basic,
+            "bearer" | "oauth" => GerritAuthMethod::Bearer,
+            "cookie" | "gitcookies" => GerritAuthMethod::Cookie,
+            "netrc" => GerritAuthMethod::Netrc,
+            _ => GerritAuthMethod::Basic, // Default fallback
+        }
+    }
+}
+
 /// Gerrit Change Entity
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GerritChange {
@@ -227,6 +262,57 @@ pub enum CommentSyncStatus {
     ModifiedLocally,   // Modified after sync
 }
 
+/// Which of a change's comment lists a Gerrit comment was read from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GerritCommentSource {
+    Published,
+    Draft,             // Unpublished drafts of the authenticated user
+    Robot,             // Comments posted by analyzers and bots
+}
+
+impl std::fmt::Display for GerritCommentSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GerritCommentSource::Published => write!(f, "published"),
+            GerritCommentSource::Draft => write!(f, "draft"),
+            GerritCommentSource::Robot => write!(f, "robot"),
+        }
+    }
+}
+
+impl GerritCommentSource {
+    pub fn from_string(s: &str) -> Self {
+        match s {
+            "draft" => GerritCommentSource::Draft,
+            "robot" => GerritCommentSource::Robot,
+            _ => GerritCommentSource::Published,
+        }
+    }
+}
+
+/// A comment fetched from Gerrit, with the list it came from
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteGerritComment {
+    pub source: GerritCommentSource,
+    pub comment: GerritComment,
+}
+
+/// Link between a Gerrit comment and the review comment it was imported as
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedGerritComment {
+    pub gerrit_comment_id: String,     // Gerrit comment ID
+    pub comment_id: String,            // Local review comment ID
+    pub change_id: String,
+    pub source: GerritCommentSource,
+    pub author: GerritUser,            // Review comments have no author of their own
+    pub robot_id: Option<String>,
+    pub unresolved: bool,
+    pub remote_updated: String,        // Gerrit's `updated` when last imported; empty if never imported
+    pub imported_at: String,
+    pub session_id: String,            // Session the comment was imported into or pushed from
+}
+
 /// Gerrit Review Entity
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GerritReview {
@@ -407,6 +493,47 @@ pub struct SyncError {
     pub timestamp: String,             // ISO 8601 timestamp
 }
 
+/// The parts of a comment that are merged during sync
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommentVersion {
+    pub file_path: String,             // File path within change
+    pub line: u32,                     // Line number
+    pub message: String,               // Comment content
+}
+
+impl From<&GerritComment> for CommentVersion {
+    fn from(comment: &GerritComment) -> Self {
+        Self {
+            // Patch set level comments have no file locally
+            file_path: if comment.file_path == "/PATCHSET_LEVEL" { String::new() } else { comment.file_path.clone() },
+            line: comment.line,
+            message: comment.message.clone(),
+        }
+    }
+}
+
+impl From<&ReviewComment> for CommentVersion {
+    fn from(comment: &ReviewComment) -> Self {
+        Self {
+            file_path: comment.file_path.clone(),
+            line: comment.line_number.unwrap_or(0),
+            message: comment.content.clone(),
+        }
+    }
+}
+
+/// A conflict resolution chosen during sync
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncHistoryEntry {
+    pub id: String,                    // UUID v4
+    pub change_id: String,             // Change the entity belongs to
+    pub entity_id: String,             // Local comment ID
+    pub conflict_type: String,         // concurrent_edit, line_modified, comment_deleted, ...
+    pub resolution: String,            // auto_merge, keep_mine, keep_theirs, re_anchor
+    pub details: Option<String>,       // JSON of the resulting version
+    pub resolved_at: String,           // ISO 8601 timestamp
+}
+
 /// Operation Queue for Offline/Sync Operations
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OperationQueue {
@@ -425,7 +552,7 @@ pub struct OperationQueue {
     pub error_message: Option<String>, // Last error
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum OperationType {
     AddComment,
     UpdateComment,
@@ -443,7 +570,7 @@ pub enum OperationPriority {
     Critical,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum OperationStatus {
     Queued,
     Processing,
@@ -453,6 +580,81 @@ pub enum OperationStatus {
     WaitingForDependency,
 }
 
+impl std::fmt::Display for OperationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationType::AddComment => write!(f, "add_comment"),
+            OperationType::UpdateComment => write!(f, "update_comment"),
+            OperationType::DeleteComment => write!(f, "delete_comment"),
+            OperationType::SubmitReview => write!(f, "submit_review"),
+            OperationType::UpdateLabels => write!(f, "update_labels"),
+            OperationType::PushPatchSet => write!(f, "push_patch_set"),
+        }
+    }
+}
+
+impl OperationType {
+    pub fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "add_comment" => OperationType::AddComment,
+            "update_comment" => OperationType::UpdateComment,
+            "delete_comment" => OperationType::DeleteComment,
+            "submit_review" => OperationType::SubmitReview,
+            "update_labels" => OperationType::UpdateLabels,
+            "push_patch_set" => OperationType::PushPatchSet,
+            _ => OperationType::AddComment,
+        }
+    }
+}
+
+impl std::fmt::Display for OperationPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationPriority::Low => write!(f, "low"),
+            OperationPriority::Normal => write!(f, "normal"),
+            OperationPriority::High => write!(f, "high"),
+            OperationPriority::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl OperationPriority {
+    pub fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "low" => OperationPriority::Low,
+            "high" => OperationPriority::High,
+            "critical" => OperationPriority::Critical,
+            _ => OperationPriority::Normal,
+        }
+    }
+}
+
+impl std::fmt::Display for OperationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationStatus::Queued => write!(f, "queued"),
+            OperationStatus::Processing => write!(f, "processing"),
+            OperationStatus::Completed => write!(f, "completed"),
+            OperationStatus::Failed => write!(f, "failed"),
+            OperationStatus::Cancelled => write!(f, "cancelled"),
+            OperationStatus::WaitingForDependency => write!(f, "waiting_for_dependency"),
+        }
+    }
+}
+
+impl OperationStatus {
+    pub fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "processing" => OperationStatus::Processing,
+            "completed" => OperationStatus::Completed,
+            "failed" => OperationStatus::Failed,
+            "cancelled" => OperationStatus::Cancelled,
+            "waiting_for_dependency" => OperationStatus::WaitingForDependency,
+            _ => OperationStatus::Queued,
+        }
+    }
+}
+
 /// Search Query and Results
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SearchQuery {
@@ -743,10 +945,23 @@ pub struct ReviewComment {
     pub line_number: Option<u32>,
     pub content: String,
     pub comment_type: CommentType,
+    #[serde(default)]
+    pub severity: CommentSeverity,
     pub status: CommentStatus,
     pub parent_comment_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub mentions: Vec<CommentMention>,
+}
+
+/// An `@handle` in a comment that matched an account of the change's instance
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommentMention {
+    pub handle: String,                // Username or email as written after the @
+    pub account_id: u32,
+    pub name: String,
+    pub email: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
@@ -786,6 +1001,73 @@ impl CommentType {
     }
 }
 
+/// How important a comment is to the author of the change
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CommentSeverity {
+    Blocking,
+    Major,
+    #[default]
+    Minor,
+    Nit,
+    Question,
+    Praise,
+}
+
+impl std::fmt::Display for CommentSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommentSeverity::Blocking => write!(f, "blocking"),
+            CommentSeverity::Major => write!(f, "major"),
+            CommentSeverity::Minor => write!(f, "minor"),
+            CommentSeverity::Nit => write!(f, "nit"),
+            CommentSeverity::Question => write!(f, "question"),
+            CommentSeverity::Praise => write!(f, "praise"),
+        }
+    }
+}
+
+impl CommentSeverity {
+    pub fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "blocking" => CommentSeverity::Blocking,
+            "major" => CommentSeverity::Major,
+            "nit" => CommentSeverity::Nit,
+            "question" => CommentSeverity::Question,
+            "praise" => CommentSeverity::Praise,
+            _ => CommentSeverity::Minor,
+        }
+    }
+}
+
+/// A category of the team's comment severity taxonomy
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SeverityCategory {
+    pub severity: CommentSeverity,
+    pub label: String,                 // Name shown to reviewers, e.g. "Must fix"
+    pub description: Option<String>,
+    pub blocks_submission: bool,       // Unresolved comments of this category block submission
+}
+
+impl SeverityCategory {
+    /// The built-in taxonomy, used until a team configures its own
+    pub fn default_taxonomy() -> Vec<SeverityCategory> {
+        let category = |severity, label: &str, description: &str, blocks_submission| SeverityCategory {
+            severity,
+            label: label.to_string(),
+            description: Some(description.to_string()),
+            blocks_submission,
+        };
+        vec![
+            category(CommentSeverity::Blocking, "Blocking", "Must be fixed before the change is submitted", true),
+            category(CommentSeverity::Major, "Major", "Should be fixed in this change", false),
+            category(CommentSeverity::Minor, "Minor", "Worth fixing, at the author's discretion", false),
+            category(CommentSeverity::Nit, "Nit", "Style or naming nitpick", false),
+            category(CommentSeverity::Question, "Question", "Needs an answer, not necessarily a change", false),
+            category(CommentSeverity::Praise, "Praise", "Something done well", false),
+        ]
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum CommentStatus {
     Draft,
@@ -817,6 +1099,105 @@ impl CommentStatus {
     }
 }
 
+/// Resolution state of a comment thread
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThreadState {
+    Open,
+    Acknowledged, // The author has seen it but not addressed it yet
+    WontFix,
+    Done,
+    Resolved,
+}
+
+impl std::fmt::Display for ThreadState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThreadState::Open => write!(f, "open"),
+            ThreadState::Acknowledged => write!(f, "acknowledged"),
+            ThreadState::WontFix => write!(f, "wont_fix"),
+            ThreadState::Done => write!(f, "done"),
+            ThreadState::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+impl ThreadState {
+    pub fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "acknowledged" => ThreadState::Acknowledged,
+            "wont_fix" => ThreadState::WontFix,
+            "done" => ThreadState::Done,
+            "resolved" => ThreadState::Resolved,
+            _ => ThreadState::Open,
+        }
+    }
+
+    /// Whether the thread still needs attention, i.e. Gerrit's `unresolved` flag
+    pub fn is_unresolved(&self) -> bool {
+        matches!(self, ThreadState::Open | ThreadState::Acknowledged)
+    }
+
+    /// The state a thread moves to when Gerrit reports its `unresolved` flag,
+    /// keeping the local state when it already agrees
+    pub fn from_gerrit_unresolved(unresolved: bool, current: ThreadState) -> Self {
+        match (unresolved, current.is_unresolved()) {
+            (true, false) => ThreadState::Open,
+            (false, true) => ThreadState::Resolved,
+            _ => current,
+        }
+    }
+}
+
+/// A recorded change of a thread's state
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadStateChange {
+    pub id: String,                    // UUID v4
+    pub root_comment_id: String,       // Root comment of the thread
+    pub from_state: ThreadState,
+    pub to_state: ThreadState,
+    pub changed_by: String,            // User, or the Gerrit account that changed it remotely
+    pub source: String,                // local, gerrit
+    pub changed_at: String,
+}
+
+/// Replacement text a reviewer suggests for a range of lines
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SuggestedChange {
+    pub comment_id: String,            // Foreign key to ReviewComment
+    pub file_path: String,
+    pub start_line: u32,               // First replaced line (1-based)
+    pub end_line: u32,                 // Last replaced line, inclusive
+    pub original_text: Option<String>, // The replaced lines when the suggestion was made, if known
+    pub replacement: String,           // New text for the range, without a trailing newline
+    pub applied_commit: Option<String>, // Commit that applied the suggestion locally
+    pub created_at: String,
+}
+
+impl SuggestedChange {
+    /// Comment text with the replacement appended as a ```suggestion block,
+    /// which Gerrit and GitHub render as an applicable change
+    pub fn to_markdown(&self, message: &str) -> String {
+        let mut markdown = message.trim_end().to_string();
+        if !markdown.is_empty() {
+            markdown.push_str("\n\n");
+        }
+        markdown.push_str("```suggestion\n");
+        markdown.push_str(&self.replacement);
+        markdown.push_str("\n```");
+        markdown
+    }
+}
+
+/// Where an inline review comment is anchored in the code
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommentAnchor {
+    pub comment_id: String,            // Foreign key to ReviewComment
+    pub patch_set_number: u32,         // Patch set the line number refers to
+    pub line_content: String,          // Text of the line the comment was written against
+    pub outdated: bool,                // The commented code no longer exists
+    pub updated_at: String,
+}
+
 /// Review Template for reusable review patterns
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReviewTemplate {
@@ -873,6 +1254,7 @@ impl GerritInstance {
             url,
             username,
             password_encrypted,
+            auth_method: GerritAuthMethod::Basic,
             version: String::new(),
             is_active: false,
             last_connected: None,
@@ -1069,10 +1451,12 @@ mod tests {
             line_number: Some(42),
             content: "This needs improvement".to_string(),
             comment_type: CommentType::Inline,
+            severity: CommentSeverity::Minor,
             status: CommentStatus::Draft,
             parent_comment_id: None,
             created_at: "2025-01-05 12:00:00".to_string(),
             updated_at: "2025-01-05 12:00:00".to_string(),
+            mentions: Vec::new(),
         };
 
         assert_eq!(comment.comment_type, CommentType::Inline);
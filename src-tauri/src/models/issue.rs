@@ -0,0 +1,134 @@
+// Issue tracker linking
+// Trackers whose issue keys appear in commit messages, and the issues resolved from them
+
+use serde::{Deserialize, Serialize};
+
+/// Default pattern for Jira keys such as `PROJ-123`
+pub const JIRA_KEY_PATTERN: &str = r"\b[A-Z][A-Z0-9]+-\d+\b";
+
+/// API an issue tracker speaks
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackerKind {
+    /// Jira REST API v2
+    Jira,
+    /// Any JSON endpoint; `base_url` contains `{key}` and fields are read by path
+    Generic,
+}
+
+impl std::fmt::Display for TrackerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrackerKind::Jira => write!(f, "jira"),
+            TrackerKind::Generic => write!(f, "generic"),
+        }
+    }
+}
+
+/// Dotted paths to issue fields in a generic tracker's JSON response
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct IssueFieldPaths {
+    pub title: String,
+    pub status: String,
+    pub assignee: String,
+    pub url: Option<String>,
+}
+
+impl Default for IssueFieldPaths {
+    fn default() -> Self {
+        Self {
+            title: "title".to_string(),
+            status: "status".to_string(),
+            assignee: "assignee".to_string(),
+            url: None,
+        }
+    }
+}
+
+/// A configured issue tracker. The API token lives in the credential store
+/// under `credential_service()`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IssueTrackerConfig {
+    pub id: String,                    // UUID v4
+    pub name: String,                  // Display name
+    pub kind: TrackerKind,
+    pub base_url: String,              // Jira server, or generic endpoint template with {key}
+    pub patterns: Vec<String>,         // Regexes finding keys; capture group 1 is the key if present
+    pub username: Option<String>,      // Jira account the token belongs to
+    pub field_paths: IssueFieldPaths,  // Generic trackers only
+    pub created_at: String,            // ISO 8601 timestamp
+}
+
+impl IssueTrackerConfig {
+    pub fn new(name: String, kind: TrackerKind, base_url: String, patterns: Vec<String>) -> Self {
+        let patterns = if patterns.is_empty() && kind == TrackerKind::Jira {
+            vec![JIRA_KEY_PATTERN.to_string()]
+        } else {
+            patterns
+        };
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            kind,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            patterns,
+            username: None,
+            field_paths: IssueFieldPaths::default(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Credential store service key for the tracker's token
+    pub fn credential_service(&self) -> String {
+        format!("{}:{}", self.kind, self.id)
+    }
+
+    /// Credential store username key for the tracker's token
+    pub fn credential_username(&self) -> &str {
+        self.username.as_deref().unwrap_or("token")
+    }
+}
+
+/// What issue links hang off
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSubject {
+    Change,
+    Task,
+}
+
+impl std::fmt::Display for IssueSubject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IssueSubject::Change => write!(f, "change"),
+            IssueSubject::Task => write!(f, "task"),
+        }
+    }
+}
+
+/// An issue referenced by a change or task. Details are empty when no tracker could resolve the key.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LinkedIssue {
+    pub key: String,
+    pub tracker_id: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub assignee: Option<String>,
+    pub url: Option<String>,
+    pub fetched_at: Option<String>,    // ISO 8601 timestamp of the cached details
+}
+
+impl LinkedIssue {
+    pub fn unresolved(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            tracker_id: None,
+            title: None,
+            status: None,
+            assignee: None,
+            url: None,
+            fetched_at: None,
+        }
+    }
+}
@@ -6,6 +6,12 @@ use std::collections::HashMap;
 
 pub mod task;
 pub mod gerrit;
+pub mod network;
+pub mod platform;
+pub mod webhook;
+pub mod quality_gate;
+pub mod issue;
+pub mod search;
 
 /// Repository Entity
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -327,7 +333,7 @@ pub struct BlameInfo {
 }
 
 /// Quality Gate Status
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum QualityGateStatus {
     Passing,
     Failing,
@@ -375,6 +381,10 @@ pub struct ReviewTemplate {
     pub placeholders: Vec<String>,
     pub category: Option<String>,
     pub usage_count: u32,
+    #[serde(default)]
+    pub languages: Vec<String>,  // Languages the snippet is offered for; empty for all
+    #[serde(default)]
+    pub file_globs: Vec<String>, // Paths the snippet is offered for, e.g. "src/**/*.rs"; empty for all
     pub created_at: String,
     pub updated_at: String,
 }
@@ -0,0 +1,82 @@
+// Network settings for remote clients
+// Proxy, trust store and TLS options shared by all HTTP integrations
+
+use serde::{Deserialize, Serialize};
+
+/// Scope key for settings that apply to every instance
+pub const GLOBAL_NETWORK_SCOPE: &str = "global";
+
+/// Proxy and TLS configuration, stored globally and optionally per instance
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub proxy_url: Option<String>,          // http://, https:// or socks5:// proxy
+    pub proxy_username: Option<String>,     // Proxy basic auth user
+    pub proxy_password: Option<String>,     // Proxy basic auth password
+    pub no_proxy: Vec<String>,              // Hosts/domains that bypass the proxy
+    pub ca_certificates: Vec<String>,       // Paths to extra trusted PEM CA bundles
+    pub client_certificate: Option<String>, // Path to PEM client certificate (mTLS)
+    pub client_key: Option<String>,         // Path to PKCS#8 PEM private key (mTLS)
+    pub accept_invalid_certs: Option<bool>, // Skip TLS verification (insecure)
+    pub timeout_secs: Option<u64>,          // Request timeout
+    pub connect_timeout_secs: Option<u64>,  // Connect timeout
+}
+
+impl NetworkSettings {
+    /// Layer instance-specific settings on top of these (global) settings.
+    /// Scalar values from the instance win; lists are combined.
+    pub fn overlay(&self, instance: &NetworkSettings) -> NetworkSettings {
+        let mut no_proxy = self.no_proxy.clone();
+        for host in &instance.no_proxy {
+            if !no_proxy.contains(host) {
+                no_proxy.push(host.clone());
+            }
+        }
+
+        let mut ca_certificates = self.ca_certificates.clone();
+        for path in &instance.ca_certificates {
+            if !ca_certificates.contains(path) {
+                ca_certificates.push(path.clone());
+            }
+        }
+
+        let (client_certificate, client_key) = if instance.client_certificate.is_some() {
+            (instance.client_certificate.clone(), instance.client_key.clone())
+        } else {
+            (self.client_certificate.clone(), self.client_key.clone())
+        };
+
+        let (proxy_url, proxy_username, proxy_password) = if instance.proxy_url.is_some() {
+            (instance.proxy_url.clone(), instance.proxy_username.clone(), instance.proxy_password.clone())
+        } else {
+            (self.proxy_url.clone(), self.proxy_username.clone(), self.proxy_password.clone())
+        };
+
+        NetworkSettings {
+            proxy_url,
+            proxy_username,
+            proxy_password,
+            no_proxy,
+            ca_certificates,
+            client_certificate,
+            client_key,
+            accept_invalid_certs: instance.accept_invalid_certs.or(self.accept_invalid_certs),
+            timeout_secs: instance.timeout_secs.or(self.timeout_secs),
+            connect_timeout_secs: instance.connect_timeout_secs.or(self.connect_timeout_secs),
+        }
+    }
+
+    /// Check settings for obvious mistakes before they are saved
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(proxy) = &self.proxy_url {
+            let scheme = proxy.split("://").next().unwrap_or_default().to_lowercase();
+            if !proxy.contains("://") || !matches!(scheme.as_str(), "http" | "https" | "socks5" | "socks5h") {
+                return Err(format!("Unsupported proxy URL: {}", proxy));
+            }
+        }
+        if self.client_certificate.is_some() != self.client_key.is_some() {
+            return Err("Client certificate and key must be configured together".to_string());
+        }
+        Ok(())
+    }
+}
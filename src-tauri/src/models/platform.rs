@@ -0,0 +1,79 @@
+// Review platform instances
+// Configured GitLab, GitHub, CodeArts and custom API servers that reviews can be submitted to
+
+use serde::{Deserialize, Serialize};
+
+/// Code review platform an instance belongs to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PlatformKind {
+    Gerrit,
+    GitLab,
+    GitHub,
+    CodeArts,
+    Custom,
+}
+
+impl std::fmt::Display for PlatformKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlatformKind::Gerrit => write!(f, "gerrit"),
+            PlatformKind::GitLab => write!(f, "gitlab"),
+            PlatformKind::GitHub => write!(f, "github"),
+            PlatformKind::CodeArts => write!(f, "codearts"),
+            PlatformKind::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+impl PlatformKind {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "gerrit" => Some(PlatformKind::Gerrit),
+            "gitlab" => Some(PlatformKind::GitLab),
+            "github" => Some(PlatformKind::GitHub),
+            "codearts" | "codehub" => Some(PlatformKind::CodeArts),
+            "custom" | "custom_api" => Some(PlatformKind::Custom),
+            _ => None,
+        }
+    }
+}
+
+/// A configured server of a review platform. The access token lives in the
+/// credential store under `credential_service()`. Gerrit servers are kept
+/// separately as `GerritInstance`s.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PlatformInstance {
+    pub id: String,                    // UUID v4
+    pub platform: PlatformKind,
+    pub name: String,                  // Display name
+    pub base_url: String,              // Server or API base URL
+    pub username: Option<String>,      // Account the token belongs to
+    pub created_at: String,            // ISO 8601 timestamp
+    pub updated_at: String,            // ISO 8601 timestamp
+}
+
+impl PlatformInstance {
+    pub fn new(platform: PlatformKind, name: String, base_url: String) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            platform,
+            name,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            username: None,
+            created_at: now.clone(),
+            updated_at: now,
+        }
+    }
+
+    /// Credential store service key for the instance's token
+    pub fn credential_service(&self) -> String {
+        format!("{}:{}", self.platform, self.id)
+    }
+
+    /// Credential store username key for the instance's token
+    pub fn credential_username(&self) -> &str {
+        self.username.as_deref().unwrap_or("token")
+    }
+}
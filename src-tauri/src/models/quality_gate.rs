@@ -0,0 +1,79 @@
+// Quality gate provider configuration
+// CI systems and votes that decide whether a commit may be submitted
+
+use serde::{Deserialize, Serialize};
+
+/// Where a provider reads its results from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GateSource {
+    /// Builds of a Jenkins job; `job` may name a folder path such as `team/app`
+    Jenkins {
+        base_url: String,
+        job: String,
+        username: Option<String>,
+    },
+    /// Pipelines of a GitLab project, on a configured GitLab instance
+    GitlabPipeline {
+        instance_id: Option<String>,
+        project: String,
+    },
+    /// The `Verified` label of the Gerrit change for the commit
+    GerritVerified {
+        instance_id: Option<String>,
+    },
+}
+
+impl GateSource {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GateSource::Jenkins { .. } => "jenkins",
+            GateSource::GitlabPipeline { .. } => "gitlab_pipeline",
+            GateSource::GerritVerified { .. } => "gerrit_verified",
+        }
+    }
+}
+
+/// A configured quality gate provider. Jenkins API tokens live in the
+/// credential store under `credential_service()`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GateProviderConfig {
+    pub id: String,                    // UUID v4
+    pub name: String,                  // Gate name shown in the UI
+    pub scope: Option<String>,         // Repository path or Gerrit project; None applies everywhere
+    pub source: GateSource,
+    pub created_at: String,            // ISO 8601 timestamp
+}
+
+impl GateProviderConfig {
+    pub fn new(name: String, scope: Option<String>, source: GateSource) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            scope: scope.filter(|s| !s.is_empty()),
+            source,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Whether the provider applies to any of the given repository paths or projects
+    pub fn applies_to(&self, scopes: &[&str]) -> bool {
+        match &self.scope {
+            Some(scope) => scopes.contains(&scope.as_str()),
+            None => true,
+        }
+    }
+
+    /// Credential store service key for the provider's API token
+    pub fn credential_service(&self) -> String {
+        format!("{}:{}", self.source.kind(), self.id)
+    }
+
+    /// Credential store username key for the provider's API token
+    pub fn credential_username(&self) -> &str {
+        match &self.source {
+            GateSource::Jenkins { username: Some(username), .. } => username,
+            _ => "token",
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// Review history search
+// Full-text index entries over comments, task comments and changes
+
+use serde::{Deserialize, Serialize};
+
+/// What an indexed text belongs to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSourceKind {
+    /// Comment of a Gerrit review session; scoped by session ID
+    ReviewComment,
+    /// Review comment on a file of a local task; scoped by task ID
+    TaskComment,
+    /// Subject and commit message of a Gerrit change; scoped by Change-Id
+    Change,
+}
+
+impl SearchSourceKind {
+    pub fn all() -> Vec<SearchSourceKind> {
+        vec![SearchSourceKind::ReviewComment, SearchSourceKind::TaskComment, SearchSourceKind::Change]
+    }
+
+    pub fn from_string(s: &str) -> Self {
+        match s {
+            "task_comment" => SearchSourceKind::TaskComment,
+            "change" => SearchSourceKind::Change,
+            _ => SearchSourceKind::ReviewComment,
+        }
+    }
+}
+
+impl std::fmt::Display for SearchSourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchSourceKind::ReviewComment => write!(f, "review_comment"),
+            SearchSourceKind::TaskComment => write!(f, "task_comment"),
+            SearchSourceKind::Change => write!(f, "change"),
+        }
+    }
+}
+
+/// A ranked search match. `title` and `snippet` are HTML with the matched terms in `<mark>`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReviewSearchHit {
+    pub kind: SearchSourceKind,
+    pub source_id: String,             // Comment ID or gerrit_changes row ID
+    pub scope_id: String,
+    pub title: String,                 // File path, or the change subject
+    pub snippet: String,               // Best matching fragment of the text
+    pub score: f64,                    // Higher is more relevant
+}
@@ -0,0 +1,95 @@
+// Webhook delivery settings for custom review APIs
+// Payload templates, signing, auth, and the log of deliveries that gave up
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Header carrying the HMAC-SHA256 signature, `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-HyperReview-Signature";
+/// Header carrying the Unix timestamp that is signed along with the body
+pub const TIMESTAMP_HEADER: &str = "X-HyperReview-Timestamp";
+/// Header carrying a per-delivery ID, stable across retries
+pub const DELIVERY_HEADER: &str = "X-HyperReview-Delivery";
+
+/// How requests authenticate against the receiving API
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebhookAuth {
+    #[default]
+    None,
+    Bearer { token: String },
+    Basic { username: String, password: String },
+    ApiKey { header: String, value: String },
+}
+
+impl WebhookAuth {
+    /// The header this auth adds, if any
+    pub fn header(&self) -> Option<(String, String)> {
+        use base64::Engine;
+
+        match self {
+            WebhookAuth::None => None,
+            WebhookAuth::Bearer { token } => Some(("Authorization".to_string(), format!("Bearer {}", token))),
+            WebhookAuth::Basic { username, password } => {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+                Some(("Authorization".to_string(), format!("Basic {}", encoded)))
+            }
+            WebhookAuth::ApiKey { header, value } => Some((header.clone(), value.clone())),
+        }
+    }
+}
+
+/// Per-request delivery options chosen by the user
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct WebhookOptions {
+    pub headers: HashMap<String, String>,   // Extra request headers
+    pub auth: WebhookAuth,                  // Authentication header
+    pub signing_secret: Option<String>,     // Shared secret for HMAC-SHA256 signatures
+    pub payload_template: Option<String>,   // Handlebars template; the raw context is sent when unset
+    pub max_retries: Option<u32>,           // Overrides the default retry count
+}
+
+/// A fully rendered request, as sent or as previewed by a dry run
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WebhookRequest {
+    pub delivery_id: String,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl WebhookRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Copy with credentials masked, for showing to the user
+    pub fn redacted(&self, auth: &WebhookAuth) -> Self {
+        let secret_header = auth.header().map(|(name, _)| name);
+        let mut copy = self.clone();
+        for (name, value) in copy.headers.iter_mut() {
+            if secret_header.as_deref().is_some_and(|secret| secret.eq_ignore_ascii_case(name)) {
+                *value = "********".to_string();
+            }
+        }
+        copy
+    }
+}
+
+/// A delivery that failed after all retries, kept so it can be inspected and resent
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeadLetter {
+    pub id: String,                    // Delivery ID
+    pub method: String,
+    pub url: String,
+    pub body: String,                  // Rendered payload; headers are rebuilt on resend
+    pub error: String,                 // Last failure
+    pub status_code: Option<u16>,      // Last HTTP status, if a response arrived
+    pub attempts: u32,                 // Attempts made so far, across resends
+    pub created_at: String,            // ISO 8601 timestamp
+    pub updated_at: String,            // ISO 8601 timestamp
+}
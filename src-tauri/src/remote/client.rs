@@ -1,9 +1,6 @@
 // External system integration client
 // Network operations and API communication
 
-use crate::models::{QualityGate, QualityGateStatus};
-use std::collections::HashMap;
-
 /// Remote client for external system integration
 pub struct RemoteClient {
     base_url: Option<String>,
@@ -24,47 +21,6 @@ impl RemoteClient {
         self.auth_token = auth_token;
     }
 
-    /// Check quality gates from CI/CD systems
-    pub fn check_quality_gates(&self, repo_path: &str) -> Result<Vec<QualityGate>, Box<dyn std::error::Error>> {
-        log::info!("Checking quality gates for repository: {}", repo_path);
-
-        // For now, return mock quality gates
-        // In production, this would make HTTP requests to GitLab/Gerrit/CodeArts
-        let mut gates = Vec::new();
-
-        // Mock CI pipeline status
-        gates.push(QualityGate {
-            name: "CI Pipeline".to_string(),
-            status: QualityGateStatus::Passing,
-            details: Some("All jobs passed".to_string()),
-            last_checked: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            url: None,
-            metadata: HashMap::new(),
-        });
-
-        // Mock code coverage gate
-        gates.push(QualityGate {
-            name: "Code Coverage".to_string(),
-            status: QualityGateStatus::Passing,
-            details: Some("Coverage: 85%".to_string()),
-            last_checked: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            url: None,
-            metadata: HashMap::new(),
-        });
-
-        // Mock security scan gate
-        gates.push(QualityGate {
-            name: "Security Scan".to_string(),
-            status: QualityGateStatus::Pending,
-            details: Some("Scan in progress".to_string()),
-            last_checked: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            url: None,
-            metadata: HashMap::new(),
-        });
-
-        Ok(gates)
-    }
-
     /// Handle network errors with retry logic
     pub fn handle_network_error(&self, error: &str) -> String {
         log::error!("Network error occurred: {}", error);
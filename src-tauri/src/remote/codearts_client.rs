@@ -1,18 +1,66 @@
 // CodeArts API client for review submission
-// External system integration
+// Merge request changes, line comments and approvals over the CodeArts Repo v4 API
 
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::errors::HyperReviewError;
+use crate::models::network::NetworkSettings;
+use crate::models::gerrit::{GerritChange, GerritFile};
+use crate::models::platform::PlatformKind;
 use crate::models::{SubmitResult, Comment};
+use crate::remote::platform::{unsupported, ChangeRef, PlatformReview, ReviewPlatform, ReviewVote};
+use crate::remote::gerrit_client::RetryConfig;
+use crate::remote::gitlab_client::{
+    change_from_merge_request, comments_from_discussions, file_from_diff, parse_discussion,
+    parse_merge_request, DiffPosition, DiffRefs, Discussion, MergeRequestDiff, MergeRequestInfo,
+};
+use crate::remote::http_client::HttpClientFactory;
+use crate::remote::traffic_control::{send_with_retries, CircuitBreakerConfig, RequestPolicy};
 
 pub struct CodeArtsClient {
     base_url: String,
     access_token: Option<String>,
+    http: HttpClientFactory,
+    retry_config: RetryConfig,
+    circuit_breaker: CircuitBreakerConfig,
+}
+
+/// Reviewer verdict sent along with the comments
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeArtsApproval {
+    Approve,
+    Reject,
+}
+
+impl CodeArtsApproval {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "approve" | "approved" | "pass" => Some(CodeArtsApproval::Approve),
+            "reject" | "rejected" | "request_changes" => Some(CodeArtsApproval::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// File changes of a merge request with the SHAs comments are anchored to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequestChanges {
+    pub diff_refs: DiffRefs,
+    pub changes: Vec<MergeRequestDiff>,
 }
 
 impl CodeArtsClient {
     pub fn new(base_url: &str) -> Self {
         Self {
-            base_url: base_url.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
             access_token: None,
+            http: HttpClientFactory::default(),
+            retry_config: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
         }
     }
 
@@ -21,23 +69,166 @@ impl CodeArtsClient {
         self
     }
 
-    /// Submit review comments to CodeArts merge request
-    pub fn submit_review(
+    pub fn with_network(mut self, settings: NetworkSettings) -> Self {
+        self.http = HttpClientFactory::new(settings);
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Check if client is configured
+    pub fn is_configured(&self) -> bool {
+        self.access_token.is_some()
+    }
+
+    /// Get the changed files of a merge request
+    pub async fn get_merge_request_changes(&self, repository_id: &str, mr_iid: u64) -> Result<MergeRequestChanges, HyperReviewError> {
+        info!("Fetching changes of CodeArts repository {} MR {}", repository_id, mr_iid);
+        let path = format!("/v4/repositories/{}/merge_requests/{}/changes", encode(repository_id), mr_iid);
+        let raw: serde_json::Value = self.send_json(reqwest::Method::GET, &path, None).await?;
+
+        let diff_refs = raw.get("diff_refs")
+            .filter(|v| !v.is_null())
+            .map(|v| serde_json::from_value::<DiffRefs>(v.clone()))
+            .transpose()?
+            .unwrap_or_default();
+        let changes = match raw.get("changes") {
+            Some(changes) => serde_json::from_value(changes.clone())?,
+            None => Vec::new(),
+        };
+
+        Ok(MergeRequestChanges { diff_refs, changes })
+    }
+
+    /// Get merge request details
+    pub async fn get_merge_request(&self, repository_id: &str, mr_iid: u64) -> Result<MergeRequestInfo, HyperReviewError> {
+        let path = format!("/v4/repositories/{}/merge_requests/{}", encode(repository_id), mr_iid);
+        let raw: serde_json::Value = self.send_json(reqwest::Method::GET, &path, None).await?;
+        parse_merge_request(&raw)
+    }
+
+    pub async fn list_discussions(&self, repository_id: &str, mr_iid: u64) -> Result<Vec<Discussion>, HyperReviewError> {
+        let path = format!("/v4/repositories/{}/merge_requests/{}/discussions", encode(repository_id), mr_iid);
+        let raw: Vec<serde_json::Value> = self.send_json(reqwest::Method::GET, &path, None).await?;
+        raw.iter().map(parse_discussion).collect()
+    }
+
+    /// Post a comment, anchored to a line when a position is given
+    pub async fn create_comment(
+        &self,
+        repository_id: &str,
+        mr_iid: u64,
+        body: &str,
+        position: Option<&DiffPosition>,
+    ) -> Result<Discussion, HyperReviewError> {
+        let path = format!("/v4/repositories/{}/merge_requests/{}/discussions", encode(repository_id), mr_iid);
+        let mut payload = json!({ "body": body });
+        if let Some(position) = position {
+            payload["position"] = serde_json::to_value(position)?;
+        }
+        let raw: serde_json::Value = self.send_json(reqwest::Method::POST, &path, Some(payload)).await?;
+        parse_discussion(&raw)
+    }
+
+    pub async fn delete_comment(
+        &self,
+        repository_id: &str,
+        mr_iid: u64,
+        discussion_id: &str,
+        note_id: u64,
+    ) -> Result<(), HyperReviewError> {
+        let path = format!(
+            "/v4/repositories/{}/merge_requests/{}/discussions/{}/notes/{}",
+            encode(repository_id), mr_iid, discussion_id, note_id
+        );
+        self.send(reqwest::Method::DELETE, &path, None).await?;
+        Ok(())
+    }
+
+    /// Approve or reject a merge request
+    pub async fn set_approval(&self, repository_id: &str, mr_iid: u64, approval: CodeArtsApproval) -> Result<(), HyperReviewError> {
+        let path = format!("/v4/repositories/{}/merge_requests/{}/approval", encode(repository_id), mr_iid);
+        self.send(reqwest::Method::PUT, &path, Some(json!({ "action": approval }))).await?;
+        Ok(())
+    }
+
+    /// Submit review comments to CodeArts merge request.
+    ///
+    /// `approval` is `approve` or `reject`; it is applied after all comments are posted.
+    /// When a step fails, the comments already posted are deleted again so that
+    /// retrying the review does not duplicate them.
+    pub async fn submit_review(
         &self,
         project_id: &str,
         mr_id: u64,
         comments: Vec<Comment>,
-        _approval: Option<String>,
-    ) -> Result<SubmitResult, Box<dyn std::error::Error>> {
-        log::info!("Submitting review to CodeArts project {} MR {}", project_id, mr_id);
+        approval: Option<String>,
+    ) -> Result<SubmitResult, HyperReviewError> {
+        let approval = approval
+            .map(|a| CodeArtsApproval::from_string(&a).ok_or_else(|| HyperReviewError::validation(
+                format!("Unknown approval '{}', expected approve or reject", a),
+                Some("approval".to_string()),
+            )))
+            .transpose()?;
+        self.publish_review(project_id, mr_id, &comments, "", approval).await
+    }
+
+    /// Post the line comments, then the summary, then the approval, removing what
+    /// was posted when a later step fails
+    async fn publish_review(
+        &self,
+        project_id: &str,
+        mr_id: u64,
+        comments: &[Comment],
+        message: &str,
+        approval: Option<CodeArtsApproval>,
+    ) -> Result<SubmitResult, HyperReviewError> {
+        info!("Submitting review to CodeArts project {} MR {}", project_id, mr_id);
 
         // Check if we have authentication
-        if self.access_token.is_none() {
-            return Err("CodeArts access token not configured".into());
+        if !self.is_configured() {
+            return Err(HyperReviewError::config("CodeArts access token not configured".to_string()));
         }
 
-        // In production, this would make actual HTTP requests to CodeArts REST API
-        log::info!("Would submit {} comments to CodeArts MR {}", comments.len(), mr_id);
+        let needs_position = comments.iter().any(|c| !c.file_path.is_empty() && c.line_number > 0);
+        let changes = if needs_position {
+            Some(self.get_merge_request_changes(project_id, mr_id).await?)
+        } else {
+            None
+        };
+
+        let mut bodies: Vec<(&str, Option<DiffPosition>)> = comments.iter()
+            .map(|comment| {
+                let position = changes.as_ref()
+                    .filter(|_| !comment.file_path.is_empty() && comment.line_number > 0)
+                    .map(|changes| DiffPosition::for_line(
+                        changes.diff_refs.clone(),
+                        &changes.changes,
+                        &comment.file_path,
+                        comment.line_number,
+                    ));
+                (comment.content.as_str(), position)
+            })
+            .collect();
+        if !message.trim().is_empty() {
+            bodies.push((message, None));
+        }
+
+        let mut posted = Vec::new();
+        for (body, position) in &bodies {
+            match self.create_comment(project_id, mr_id, body, position.as_ref()).await {
+                Ok(discussion) => posted.push(discussion),
+                Err(e) => return Err(self.roll_back_comments(project_id, mr_id, &posted, bodies.len(), e).await),
+            }
+        }
+        if let Some(approval) = approval {
+            if let Err(e) = self.set_approval(project_id, mr_id, approval).await {
+                return Err(self.roll_back_comments(project_id, mr_id, &posted, bodies.len(), e).await);
+            }
+        }
 
         Ok(SubmitResult {
             success: true,
@@ -47,8 +238,245 @@ impl CodeArtsClient {
         })
     }
 
-    /// Check if client is configured
-    pub fn is_configured(&self) -> bool {
-        self.access_token.is_some()
+    /// Delete the comments of a review that failed partway. Returns `cause` when all of
+    /// them were removed, otherwise an error naming the comments left on the merge request.
+    async fn roll_back_comments(
+        &self,
+        repository_id: &str,
+        mr_iid: u64,
+        posted: &[Discussion],
+        total: usize,
+        cause: HyperReviewError,
+    ) -> HyperReviewError {
+        let mut remaining = Vec::new();
+        for discussion in posted {
+            let removed = match discussion.notes.first() {
+                Some(note) => match self.delete_comment(repository_id, mr_iid, &discussion.id, note.id).await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("Failed to remove comment {} from MR {}: {}", discussion.id, mr_iid, e);
+                        false
+                    }
+                },
+                None => false,
+            };
+            if !removed {
+                remaining.push(discussion.id.clone());
+            }
+        }
+
+        if remaining.is_empty() {
+            return cause;
+        }
+        HyperReviewError::other(format!(
+            "Review submission to MR {} failed after posting {} of {} comments: {}. \
+             Comments {} could not be removed and are still on the merge request",
+            mr_iid, posted.len(), total, cause, remaining.join(", ")
+        ))
+    }
+
+    async fn send_json<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, HyperReviewError> {
+        let text = self.send(method, path, body).await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Send a request, retrying throttled and transient failures
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<String, HyperReviewError> {
+        let url = format!("{}{}", self.base_url, path);
+        let client = self.http.async_client()?;
+        let policy = RequestPolicy::new(&self.retry_config, &self.circuit_breaker);
+
+        let response = send_with_retries(&policy, &method, &url, || {
+            let mut request = client.request(method.clone(), &url);
+            if let Some(token) = &self.access_token {
+                request = request.header("X-Auth-Token", token.as_str());
+            }
+            if let Some(body) = &body {
+                request = request.json(body);
+            }
+            debug!("CodeArts {} {}", method, url);
+            request
+        })
+        .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(HyperReviewError::authentication_required(
+                self.base_url.clone(),
+                "CodeArts rejected the access token".to_string(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(HyperReviewError::network_with_status(
+                format!("CodeArts {} {} failed: HTTP {}: {}", method, path, status, text),
+                status.as_u16(),
+            ));
+        }
+        Ok(text)
+    }
+}
+
+fn encode(value: &str) -> String {
+    urlencoding::encode(value).into_owned()
+}
+
+#[async_trait]
+impl ReviewPlatform for CodeArtsClient {
+    fn kind(&self) -> PlatformKind {
+        PlatformKind::CodeArts
+    }
+
+    async fn fetch_change(&self, change: &ChangeRef) -> Result<GerritChange, HyperReviewError> {
+        let mr = self.get_merge_request(&change.project, change.number()?).await?;
+        let files = self.list_files(change).await?;
+        Ok(change_from_merge_request(&change.project, &mr, files))
+    }
+
+    async fn list_files(&self, change: &ChangeRef) -> Result<Vec<GerritFile>, HyperReviewError> {
+        let iid = change.number()?;
+        let change_id = format!("{}!{}", change.project, iid);
+        let changes = self.get_merge_request_changes(&change.project, iid).await?;
+        Ok(changes.changes.iter().map(|diff| file_from_diff(&change_id, "", diff)).collect())
+    }
+
+    async fn list_comments(&self, change: &ChangeRef) -> Result<Vec<Comment>, HyperReviewError> {
+        let discussions = self.list_discussions(&change.project, change.number()?).await?;
+        Ok(comments_from_discussions(&discussions))
+    }
+
+    async fn post_review(&self, change: &ChangeRef, review: &PlatformReview) -> Result<SubmitResult, HyperReviewError> {
+        let approval = match review.vote.map(ReviewVote::verdict) {
+            Some(ReviewVote::Approve) => Some(CodeArtsApproval::Approve),
+            Some(ReviewVote::Reject) => Some(CodeArtsApproval::Reject),
+            _ => None,
+        };
+        self.publish_review(&change.project, change.number()?, &review.comments, &review.message, approval).await
+    }
+
+    async fn vote(&self, change: &ChangeRef, vote: ReviewVote) -> Result<(), HyperReviewError> {
+        let approval = match vote.verdict() {
+            ReviewVote::Approve => CodeArtsApproval::Approve,
+            ReviewVote::Reject => CodeArtsApproval::Reject,
+            _ => return Err(unsupported(PlatformKind::CodeArts, "neutral votes")),
+        };
+        self.set_approval(&change.project, change.number()?, approval).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::fixtures::comment;
+    use crate::remote::mock_server::{MockResponse, MockServer};
+
+    const DISCUSSION_JSON: &str = r#"{"id":"d1","notes":[{"id":11,"body":"","author":{"username":"me"},"created_at":""}]}"#;
+
+    #[tokio::test]
+    async fn test_submit_review_with_line_comments_and_approval() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => MockResponse::json(r#"{
+                "diff_refs":{"base_sha":"b1","start_sha":"s1","head_sha":"h1"},
+                "changes":[{"old_path":"app.py","new_path":"app.py","diff":"@@ -1 +1 @@"}]}"#),
+            "POST" => MockResponse::json(DISCUSSION_JSON),
+            _ => MockResponse::json("{}"),
+        });
+        let client = CodeArtsClient::new(&server.base_url).with_auth("iam-token".to_string());
+
+        let result = client.submit_review(
+            "repo-1",
+            3,
+            vec![comment("app.py", 8, "Handle None"), comment("", 0, "Thanks")],
+            Some("reject".to_string()),
+        ).await.unwrap();
+        assert!(result.success);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].path, "/v4/repositories/repo-1/merge_requests/3/changes");
+        assert_eq!(requests[0].header("x-auth-token"), Some("iam-token"));
+
+        let line: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(line["position"]["head_sha"], "h1");
+        assert_eq!(line["position"]["new_line"], 8);
+        let general: serde_json::Value = serde_json::from_str(&requests[2].body).unwrap();
+        assert!(general.get("position").is_none());
+
+        assert_eq!(requests[3].method, "PUT");
+        assert_eq!(requests[3].path, "/v4/repositories/repo-1/merge_requests/3/approval");
+        assert_eq!(requests[3].body, r#"{"action":"reject"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_post_review_removes_comments_when_approval_fails() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "POST" => MockResponse::json(DISCUSSION_JSON),
+            "PUT" => MockResponse::new(403, r#"{"error_code":"DEV.00000403"}"#),
+            _ => MockResponse::json("{}"),
+        });
+        let client = CodeArtsClient::new(&server.base_url).with_auth("t".to_string());
+
+        let review = PlatformReview {
+            message: "Summary".to_string(),
+            comments: vec![comment("", 0, "first")],
+            vote: Some(ReviewVote::Approve),
+        };
+        assert!(client.post_review(&ChangeRef::new("repo-1", 3), &review).await.is_err());
+
+        let deletes: Vec<_> = server.requests().into_iter().filter(|r| r.method == "DELETE").collect();
+        assert_eq!(deletes.len(), 2);
+        assert_eq!(deletes[0].path, "/v4/repositories/repo-1/merge_requests/3/discussions/d1/notes/11");
+    }
+
+    #[tokio::test]
+    async fn test_submit_review_requires_token_and_valid_approval() {
+        let client = CodeArtsClient::new("http://127.0.0.1:9");
+        assert!(client.submit_review("r", 1, Vec::new(), None).await.is_err());
+
+        let client = client.with_auth("t".to_string());
+        let err = client.submit_review("r", 1, Vec::new(), Some("maybe".to_string())).await.unwrap_err();
+        assert!(err.to_string().contains("maybe"));
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_token() {
+        let server = MockServer::start(|_| MockResponse::new(401, r#"{"error_code":"DEV.00000003"}"#));
+        let client = CodeArtsClient::new(&server.base_url).with_auth("expired".to_string());
+        let err = client.get_merge_request_changes("repo-1", 3).await.unwrap_err();
+        assert!(err.is_authentication_required());
+    }
+
+    #[tokio::test]
+    async fn test_review_platform_lists_files_and_votes() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => MockResponse::json(r#"{
+                "diff_refs":{"base_sha":"b1","start_sha":"s1","head_sha":"h1"},
+                "changes":[{"old_path":"a.py","new_path":"b.py","diff":"@@ -1 +1,2 @@","renamed_file":true}]}"#),
+            _ => MockResponse::json("{}"),
+        });
+        let client = CodeArtsClient::new(&server.base_url).with_auth("t".to_string());
+        let change = ChangeRef::new("repo-1", 3);
+
+        let files = client.list_files(&change).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].file_path, "b.py");
+
+        client.vote(&change, ReviewVote::Approve).await.unwrap();
+        assert!(client.vote(&change, ReviewVote::Neutral).await.is_err());
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body, r#"{"action":"approve"}"#);
     }
 }
@@ -1,96 +1,499 @@
 // Generic custom API client for external review systems
-// Allows integration with custom REST APIs for review submission
+// Delivers review payloads to custom REST APIs and webhooks: templated bodies,
+// HMAC-SHA256 signatures, configurable auth, retries and a dead-letter log
 
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use handlebars::{handlebars_helper, Handlebars};
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::errors::HyperReviewError;
+use crate::models::network::NetworkSettings;
+use crate::models::platform::PlatformKind;
+use crate::models::webhook::{
+    DeadLetter, WebhookAuth, WebhookOptions, WebhookRequest, DELIVERY_HEADER, SIGNATURE_HEADER,
+    TIMESTAMP_HEADER,
+};
 use crate::models::SubmitResult;
-use serde_json::Value;
+use crate::remote::gerrit_client::RetryConfig;
+use crate::remote::http_client::HttpClientFactory;
+use crate::remote::platform::{ChangeRef, PlatformReview, ReviewPlatform, ReviewVote};
+use crate::remote::traffic_control::{send_with_retries, CircuitBreakerConfig, RequestPolicy};
+use crate::storage::sqlite::Database;
+
+type HmacSha256 = Hmac<Sha256>;
 
 pub struct CustomApiClient {
     base_url: String,
-    api_key: Option<String>,
-    headers: Option<Vec<(String, String)>>,
+    options: WebhookOptions,
+    http: HttpClientFactory,
+    retry_config: RetryConfig,
+    circuit_breaker: CircuitBreakerConfig,
+    dead_letters: Option<Arc<Mutex<Database>>>,
 }
 
 impl CustomApiClient {
     pub fn new(base_url: &str) -> Self {
         Self {
-            base_url: base_url.to_string(),
-            api_key: None,
-            headers: None,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            options: WebhookOptions::default(),
+            http: HttpClientFactory::default(),
+            retry_config: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            dead_letters: None,
         }
     }
 
+    /// Authenticate with `Authorization: Bearer <api_key>`
     pub fn with_api_key(mut self, api_key: String) -> Self {
-        self.api_key = Some(api_key);
+        self.options.auth = WebhookAuth::Bearer { token: api_key };
         self
     }
 
     pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
-        self.headers = Some(headers);
+        self.options.headers.extend(headers);
+        self
+    }
+
+    /// Apply user delivery options; their headers are added to any set before
+    pub fn with_options(mut self, options: WebhookOptions) -> Self {
+        let mut headers = std::mem::take(&mut self.options.headers);
+        headers.extend(options.headers.clone());
+        if let Some(max_retries) = options.max_retries {
+            self.retry_config.max_retries = max_retries;
+        }
+        self.options = WebhookOptions { headers, ..options };
+        self
+    }
+
+    pub fn with_network(mut self, settings: NetworkSettings) -> Self {
+        self.http = HttpClientFactory::new(settings);
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
         self
     }
 
+    /// Record deliveries that fail after all retries
+    pub fn with_dead_letter_log(mut self, database: Arc<Mutex<Database>>) -> Self {
+        self.dead_letters = Some(database);
+        self
+    }
+
+    /// Check if client is configured
+    pub fn is_configured(&self) -> bool {
+        !self.base_url.is_empty()
+    }
+
+    /// Render the request body. Without a template the context is sent as JSON.
+    ///
+    /// Templates use Handlebars syntax over the context. Interpolated values are
+    /// escaped for use inside JSON strings; `{{{json value}}}` inserts a value as JSON.
+    pub fn render_payload(&self, context: &Value) -> Result<String, HyperReviewError> {
+        let Some(template) = &self.options.payload_template else {
+            return Ok(serde_json::to_string(context)?);
+        };
+
+        handlebars_helper!(json_helper: |value: Json| serde_json::to_string(value).unwrap_or_default());
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(escape_json_string);
+        handlebars.register_helper("json", Box::new(json_helper));
+
+        let body = handlebars.render_template(template, context)
+            .map_err(|e| HyperReviewError::validation(
+                format!("Invalid payload template: {}", e),
+                Some("payload_template".to_string()),
+            ))?;
+
+        if self.sends_json() {
+            serde_json::from_str::<Value>(&body).map_err(|e| HyperReviewError::validation(
+                format!("Payload template did not produce valid JSON: {}", e),
+                Some("payload_template".to_string()),
+            ))?;
+        }
+        Ok(body)
+    }
+
+    /// Render the request that would be sent, without sending it. Credentials are masked.
+    pub fn dry_run(&self, endpoint: &str, method: &str, context: &Value) -> Result<WebhookRequest, HyperReviewError> {
+        Ok(self.build_request(endpoint, method, context)?.redacted(&self.options.auth))
+    }
+
+    /// Render and sign a request for `endpoint`, a path below the base URL or an absolute URL
+    pub fn build_request(&self, endpoint: &str, method: &str, context: &Value) -> Result<WebhookRequest, HyperReviewError> {
+        let method = parse_method(method)?;
+        let body = self.render_payload(context)?;
+        Ok(self.signed_request(
+            uuid::Uuid::new_v4().to_string(),
+            method.as_str(),
+            &self.url_for(endpoint),
+            body,
+        ))
+    }
+
     /// Submit review to custom API endpoint
     /// Supports multiple integration patterns:
     /// - REST POST to /api/reviews
     /// - Webhook submission
-    /// - Custom format via JSON payload
-    pub fn submit_review(
+    /// - Custom format via payload templates
+    pub async fn submit_review(
         &self,
         endpoint: &str,
         payload: Value,
         method: &str,
-    ) -> Result<SubmitResult, Box<dyn std::error::Error>> {
-        log::info!("Submitting review to custom API: {}", endpoint);
+    ) -> Result<SubmitResult, HyperReviewError> {
+        info!("Submitting review to custom API: {}", endpoint);
+        let request = self.build_request(endpoint, method, &payload)?;
+        self.deliver(&request, None).await
+    }
 
-        // Check if we have configuration
-        if self.api_key.is_none() && self.headers.is_none() {
-            return Err("Custom API credentials not configured".into());
+    /// Submit with webhook pattern
+    pub async fn submit_webhook(
+        &self,
+        webhook_url: &str,
+        payload: Value,
+    ) -> Result<SubmitResult, HyperReviewError> {
+        info!("Submitting review via webhook: {}", webhook_url);
+        let request = self.build_request(webhook_url, "POST", &payload)?;
+        self.deliver(&request, None).await
+    }
+
+    /// Send a dead-lettered delivery again, re-signed with the current options.
+    /// The record is removed on success and updated on failure.
+    pub async fn resend(&self, letter: &DeadLetter) -> Result<SubmitResult, HyperReviewError> {
+        info!("Resending delivery {} to {}", letter.id, letter.url);
+        let request = self.signed_request(letter.id.clone(), &letter.method, &letter.url, letter.body.clone());
+        let result = self.deliver(&request, Some(letter)).await?;
+
+        if let Some(database) = &self.dead_letters {
+            if let Err(e) = database.lock().unwrap().delete_dead_letter(&letter.id) {
+                warn!("Failed to remove dead letter {}: {}", letter.id, e);
+            }
         }
+        Ok(result)
+    }
 
-        // In production, this would make actual HTTP requests
-        // Example: POST {base_url}/{endpoint} with payload and headers
-        log::info!("Would POST {} to {}{}", 
-            method, 
-            self.base_url, 
-            endpoint
-        );
-        log::info!("Payload: {}", serde_json::to_string(&payload).unwrap_or_default());
+    fn url_for(&self, endpoint: &str) -> String {
+        if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+            endpoint.to_string()
+        } else if endpoint.is_empty() {
+            self.base_url.clone()
+        } else {
+            format!("{}/{}", self.base_url, endpoint.trim_start_matches('/'))
+        }
+    }
 
-        // Parse response for external ID and URL
-        let external_id = payload.get("review_id")
-            .and_then(|v| v.as_str())
-            .map(|s| format!("custom-{}", s))
-            .unwrap_or_else(|| format!("custom-{}", chrono::Utc::now().timestamp()));
+    fn sends_json(&self) -> bool {
+        self.options.headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .is_none_or(|(_, value)| value.contains("json"))
+    }
 
-        Ok(SubmitResult {
-            success: true,
-            message: "Submitted review to custom API".to_string(),
-            external_id: Some(external_id),
-            url: Some(format!("{}/{}", self.base_url, endpoint)),
-        })
+    fn signed_request(&self, delivery_id: String, method: &str, url: &str, body: String) -> WebhookRequest {
+        let mut headers: Vec<(String, String)> = self.options.headers.iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        headers.sort();
+        if !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("content-type")) {
+            headers.insert(0, ("Content-Type".to_string(), "application/json".to_string()));
+        }
+        if let Some(auth) = self.options.auth.header() {
+            headers.push(auth);
+        }
+        headers.push((DELIVERY_HEADER.to_string(), delivery_id.clone()));
+
+        if let Some(secret) = &self.options.signing_secret {
+            let timestamp = chrono::Utc::now().timestamp().to_string();
+            headers.push((SIGNATURE_HEADER.to_string(), sign_payload(secret, &timestamp, &body)));
+            headers.push((TIMESTAMP_HEADER.to_string(), timestamp));
+        }
+
+        WebhookRequest {
+            delivery_id,
+            method: method.to_uppercase(),
+            url: url.to_string(),
+            headers,
+            body,
+        }
     }
 
-    /// Submit with webhook pattern
-    pub fn submit_webhook(
+    /// Send with retries; a delivery that still fails is written to the dead-letter log
+    async fn deliver(&self, request: &WebhookRequest, previous: Option<&DeadLetter>) -> Result<SubmitResult, HyperReviewError> {
+        let mut attempts = 0;
+        let mut status_code = None;
+        let outcome = self.send_delivery(request, &mut attempts, &mut status_code).await;
+
+        if let Err(e) = &outcome {
+            warn!("Delivery {} to {} failed after {} attempts: {}", request.delivery_id, request.url, attempts, e);
+            if let Some(database) = &self.dead_letters {
+                let now = chrono::Utc::now().to_rfc3339();
+                let letter = DeadLetter {
+                    id: request.delivery_id.clone(),
+                    method: request.method.clone(),
+                    url: request.url.clone(),
+                    body: request.body.clone(),
+                    error: e.to_string(),
+                    status_code,
+                    attempts: previous.map_or(0, |p| p.attempts) + attempts,
+                    created_at: previous.map_or_else(|| now.clone(), |p| p.created_at.clone()),
+                    updated_at: now,
+                };
+                if let Err(e) = database.lock().unwrap().store_dead_letter(&letter) {
+                    warn!("Failed to record dead letter {}: {}", letter.id, e);
+                }
+            }
+        }
+        outcome
+    }
+
+    async fn send_delivery(
         &self,
-        webhook_url: &str,
-        payload: Value,
-    ) -> Result<SubmitResult, Box<dyn std::error::Error>> {
-        log::info!("Submitting review via webhook: {}", webhook_url);
+        request: &WebhookRequest,
+        attempts: &mut u32,
+        status_code: &mut Option<u16>,
+    ) -> Result<SubmitResult, HyperReviewError> {
+        let method = parse_method(&request.method)?;
+        let client = self.http.async_client()?;
+        // Receivers deduplicate on the delivery header, so any method may be resent
+        let policy = RequestPolicy::new(&self.retry_config, &self.circuit_breaker).idempotent();
+
+        let sent = AtomicU32::new(0);
+        let outcome = send_with_retries(&policy, &method, &request.url, || {
+            sent.fetch_add(1, Ordering::Relaxed);
+            let mut builder = client.request(method.clone(), &request.url).body(request.body.clone());
+            for (name, value) in &request.headers {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+            builder
+        })
+        .await;
+        *attempts += sent.into_inner();
+
+        let response = match outcome {
+            Ok(response) => response,
+            Err(e) => {
+                if let HyperReviewError::Network { status_code: Some(code), .. } = &e {
+                    *status_code = Some(*code);
+                }
+                return Err(e);
+            }
+        };
+        let status = response.status();
+        *status_code = Some(status.as_u16());
+
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(HyperReviewError::network_with_status(
+                format!("Custom API {} {} failed: HTTP {}: {}", method, request.url, status, text),
+                status.as_u16(),
+            ));
+        }
 
-        // In production, this would POST to webhook_url
-        log::info!("Webhook payload: {}", serde_json::to_string(&payload).unwrap_or_default());
+        // Pick up the receiver's ID and link when it returns them
+        let response_json: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+        let external_id = ["id", "review_id"].iter()
+            .find_map(|key| match response_json.get(*key) {
+                Some(Value::String(s)) => Some(s.clone()),
+                Some(Value::Number(n)) => Some(n.to_string()),
+                _ => None,
+            })
+            .unwrap_or_else(|| request.delivery_id.clone());
+        let url = ["url", "html_url"].iter()
+            .find_map(|key| response_json.get(*key).and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .or_else(|| Some(request.url.clone()));
 
         Ok(SubmitResult {
             success: true,
-            message: "Submitted review via webhook".to_string(),
-            external_id: Some(format!("webhook-{}", chrono::Utc::now().timestamp())),
-            url: Some(webhook_url.to_string()),
+            message: format!("Submitted review to custom API (HTTP {})", status.as_u16()),
+            external_id: Some(format!("custom-{}", external_id)),
+            url,
         })
     }
+}
 
-    /// Check if client is configured
-    pub fn is_configured(&self) -> bool {
-        self.api_key.is_some() || self.headers.is_some()
+/// `sha256=<hex>` HMAC of `"{timestamp}.{body}"`; receivers recompute it with the shared secret
+pub fn sign_payload(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let digest: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", digest)
+}
+
+fn parse_method(method: &str) -> Result<reqwest::Method, HyperReviewError> {
+    reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+        .map_err(|_| HyperReviewError::validation(format!("Invalid HTTP method: {}", method), Some("method".to_string())))
+}
+
+/// Escape interpolated text so templates can place it inside JSON strings
+fn escape_json_string(text: &str) -> String {
+    let quoted = serde_json::to_string(text).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Custom APIs only accept reviews; the payload carries the change and everything reviewed
+#[async_trait]
+impl ReviewPlatform for CustomApiClient {
+    fn kind(&self) -> PlatformKind {
+        PlatformKind::Custom
+    }
+
+    async fn post_review(&self, change: &ChangeRef, review: &PlatformReview) -> Result<SubmitResult, HyperReviewError> {
+        let payload = json!({
+            "project": change.project,
+            "change_id": change.id,
+            "message": review.message,
+            "vote": review.vote.map(ReviewVote::verdict),
+            "comments": review.comments,
+        });
+        self.submit_review("reviews", payload, "POST").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::fixtures::fast_retries;
+    use crate::remote::mock_server::{MockResponse, MockServer};
+
+    fn dead_letter_log() -> Arc<Mutex<Database>> {
+        let database = Database::new(":memory:").unwrap();
+        database.init_schema().unwrap();
+        database.init_gerrit_schema().unwrap();
+        Arc::new(Mutex::new(database))
+    }
+
+    #[test]
+    fn test_template_renders_escaped_json() {
+        let client = CustomApiClient::new("https://reviews.example.com").with_options(WebhookOptions {
+            payload_template: Some(
+                r#"{"title": "{{task_name}}", "notes": [{{#each items}}{{#if @index}},{{/if}}"{{file}}: {{comments.0.content}}"{{/each}}], "raw": {{{json progress}}}}"#.to_string(),
+            ),
+            ..Default::default()
+        });
+        let context = json!({
+            "task_name": "Fix \"quotes\"",
+            "items": [
+                {"file": "a.rs", "comments": [{"content": "line1\nline2"}]},
+                {"file": "b.rs", "comments": [{"content": "<ok>"}]},
+            ],
+            "progress": {"total": 2, "completed": 1},
+        });
+
+        let body: Value = serde_json::from_str(&client.render_payload(&context).unwrap()).unwrap();
+        assert_eq!(body["title"], "Fix \"quotes\"");
+        assert_eq!(body["notes"][0], "a.rs: line1\nline2");
+        assert_eq!(body["notes"][1], "b.rs: <ok>");
+        assert_eq!(body["raw"]["completed"], 1);
+
+        let broken = CustomApiClient::new("https://reviews.example.com").with_options(WebhookOptions {
+            payload_template: Some(r#"{"title": {{task_name}}}"#.to_string()),
+            ..Default::default()
+        });
+        let err = broken.render_payload(&context).unwrap_err();
+        assert!(err.to_string().contains("valid JSON"));
+    }
+
+    #[test]
+    fn test_dry_run_signs_and_masks_credentials() {
+        let client = CustomApiClient::new("https://reviews.example.com/api/").with_options(WebhookOptions {
+            headers: [("X-Team".to_string(), "core".to_string())].into_iter().collect(),
+            auth: WebhookAuth::Basic { username: "bot".to_string(), password: "pw".to_string() },
+            signing_secret: Some("s3cret".to_string()),
+            ..Default::default()
+        });
+
+        let preview = client.dry_run("/reviews", "post", &json!({"a": 1})).unwrap();
+        assert_eq!(preview.method, "POST");
+        assert_eq!(preview.url, "https://reviews.example.com/api/reviews");
+        assert_eq!(preview.body, r#"{"a":1}"#);
+        assert_eq!(preview.header("content-type"), Some("application/json"));
+        assert_eq!(preview.header("x-team"), Some("core"));
+        assert_eq!(preview.header("authorization"), Some("********"));
+
+        let timestamp = preview.header(TIMESTAMP_HEADER).unwrap();
+        assert_eq!(preview.header(SIGNATURE_HEADER), Some(sign_payload("s3cret", timestamp, &preview.body).as_str()));
+        assert!(client.dry_run("reviews", "NOT A METHOD", &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_sign_payload_known_value() {
+        // printf '1700000000.{}' | openssl dgst -sha256 -hmac key
+        assert_eq!(
+            sign_payload("key", "1700000000", "{}"),
+            "sha256=9d713ed406bb7076d4123f0dc2c39d2df5c654ed4b0cd56b52c8b4c940bd63ae"
+        );
+        assert_ne!(sign_payload("key", "1700000000", "{}"), sign_payload("key", "1700000001", "{}"));
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors_then_succeeds() {
+        let server = MockServer::start(|request| {
+            if request.path == "/hooks/review" && request.header("x-hyperreview-delivery").is_some() {
+                static CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+                if CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                    return MockResponse::new(503, "busy");
+                }
+                return MockResponse::json(r#"{"id": 42, "url": "https://reviews.example.com/42"}"#);
+            }
+            MockResponse::new(404, "")
+        });
+        let client = CustomApiClient::new(&server.base_url)
+            .with_api_key("k".to_string())
+            .with_retry_config(fast_retries());
+
+        let result = client.submit_review("hooks/review", json!({"ok": true}), "POST").await.unwrap();
+        assert_eq!(result.external_id.as_deref(), Some("custom-42"));
+        assert_eq!(result.url.as_deref(), Some("https://reviews.example.com/42"));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("authorization"), Some("Bearer k"));
+        assert_eq!(
+            requests[0].header("x-hyperreview-delivery"),
+            requests[1].header("x-hyperreview-delivery"),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_dead_lettered_and_resent() {
+        let server = MockServer::start(|request| match request.header("authorization") {
+            Some("Bearer good") => MockResponse::json("{}"),
+            _ => MockResponse::new(403, "forbidden"),
+        });
+        let log = dead_letter_log();
+        let client = CustomApiClient::new(&server.base_url)
+            .with_api_key("bad".to_string())
+            .with_retry_config(fast_retries())
+            .with_dead_letter_log(log.clone());
+
+        let err = client.submit_webhook(&format!("{}/hook", server.base_url), json!({"n": 1})).await.unwrap_err();
+        assert!(err.to_string().contains("403"));
+        assert_eq!(server.requests().len(), 1, "client errors are not retried");
+
+        let letter = log.lock().unwrap().list_dead_letters().unwrap().remove(0);
+        assert_eq!(letter.status_code, Some(403));
+        assert_eq!(letter.attempts, 1);
+        assert_eq!(letter.body, r#"{"n":1}"#);
+
+        assert!(client.resend(&letter).await.is_err());
+        let updated = log.lock().unwrap().get_dead_letter(&letter.id).unwrap().unwrap();
+        assert_eq!(updated.attempts, 2);
+        assert_eq!(updated.created_at, letter.created_at);
+
+        let fixed = CustomApiClient::new(&server.base_url)
+            .with_api_key("good".to_string())
+            .with_dead_letter_log(log.clone());
+        assert!(fixed.resend(&updated).await.unwrap().success);
+        assert!(log.lock().unwrap().list_dead_letters().unwrap().is_empty());
     }
 }
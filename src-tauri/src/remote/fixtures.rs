@@ -0,0 +1,32 @@
+// Shared fixtures for remote client tests
+// Fast retry settings and review comments for exercising clients against the mock server
+
+use crate::models::{Comment, CommentStatus};
+use crate::remote::gerrit_client::RetryConfig;
+
+/// Retries quickly and without jitter, so tests can count attempts
+pub fn fast_retries() -> RetryConfig {
+    RetryConfig {
+        max_retries: 2,
+        base_delay_ms: 10,
+        max_delay_ms: 1000,
+        backoff_multiplier: 2.0,
+        jitter_factor: 0.0,
+    }
+}
+
+/// A draft comment; an empty `file_path` or line 0 makes it a general comment
+pub fn comment(file_path: &str, line_number: u32, content: &str) -> Comment {
+    Comment {
+        id: "c1".to_string(),
+        file_path: file_path.to_string(),
+        line_number,
+        content: content.to_string(),
+        author: "me".to_string(),
+        created_at: String::new(),
+        updated_at: String::new(),
+        status: CommentStatus::Draft,
+        parent_id: None,
+        tags: Vec::new(),
+    }
+}
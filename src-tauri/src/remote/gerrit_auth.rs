@@ -0,0 +1,327 @@
+// Gerrit authentication providers
+// Resolves the configured auth method of an instance into request credentials
+
+use std::path::PathBuf;
+
+use log::{debug, warn};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::{GerritAuthMethod, GerritInstance};
+use crate::storage::credentials::CredentialStore;
+
+/// Credentials applied to every request sent to a Gerrit server
+#[derive(Debug, Clone, PartialEq)]
+pub enum GerritAuth {
+    /// No credentials; only anonymous endpoints will work
+    Anonymous,
+    /// HTTP Basic with the Gerrit HTTP password
+    Basic { username: String, password: String },
+    /// OAuth access token sent as `Authorization: Bearer`
+    Bearer { token: String },
+    /// Raw `Cookie` header value, e.g. `o=git-user.example.com=1//abc`
+    Cookie { cookie: String },
+}
+
+/// A single `machine` entry from a `.netrc` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetrcEntry {
+    pub login: String,
+    pub password: String,
+}
+
+impl GerritAuth {
+    /// Build the credentials for an instance.
+    ///
+    /// `secret` is the value held in the credential store for the instance
+    /// (HTTP password, token or cookie). Cookie and netrc auth fall back to
+    /// `~/.gitcookies` and `~/.netrc` when no secret is stored.
+    pub fn for_instance(instance: &GerritInstance, secret: Option<&str>) -> Result<Self, HyperReviewError> {
+        let secret = secret.filter(|s| !s.is_empty());
+        let host = host_of(&instance.url);
+
+        match instance.auth_method {
+            GerritAuthMethod::Basic => match secret {
+                Some(password) if !instance.username.is_empty() => Ok(GerritAuth::Basic {
+                    username: instance.username.clone(),
+                    password: password.to_string(),
+                }),
+                _ => Ok(GerritAuth::Anonymous),
+            },
+            GerritAuthMethod::Bearer => secret
+                .map(|token| GerritAuth::Bearer { token: token.to_string() })
+                .ok_or_else(|| HyperReviewError::authentication_required(
+                    instance.url.clone(),
+                    "No access token stored for this instance".to_string(),
+                )),
+            GerritAuthMethod::Cookie => {
+                if let Some(cookie) = secret {
+                    return Ok(GerritAuth::Cookie { cookie: cookie.to_string() });
+                }
+                let path = default_gitcookies_path()
+                    .ok_or_else(|| HyperReviewError::config("Cannot locate home directory for .gitcookies".to_string()))?;
+                let content = std::fs::read_to_string(&path).map_err(|e| HyperReviewError::config(
+                    format!("Failed to read {}: {}", path.display(), e),
+                ))?;
+                parse_gitcookies(&content, &host)
+                    .map(|cookie| GerritAuth::Cookie { cookie })
+                    .ok_or_else(|| HyperReviewError::authentication_required(
+                        instance.url.clone(),
+                        format!("No cookie for {} found in {}", host, path.display()),
+                    ))
+            }
+            GerritAuthMethod::Netrc => {
+                let path = default_netrc_path()
+                    .ok_or_else(|| HyperReviewError::config("Cannot locate .netrc file".to_string()))?;
+                let content = std::fs::read_to_string(&path).map_err(|e| HyperReviewError::config(
+                    format!("Failed to read {}: {}", path.display(), e),
+                ))?;
+                parse_netrc(&content, &host)
+                    .map(|entry| GerritAuth::Basic { username: entry.login, password: entry.password })
+                    .ok_or_else(|| HyperReviewError::authentication_required(
+                        instance.url.clone(),
+                        format!("No machine entry for {} found in {}", host, path.display()),
+                    ))
+            }
+        }
+    }
+
+    /// Attach the credentials to an outgoing request
+    pub fn apply(&self, request: reqwest::blocking::RequestBuilder) -> reqwest::blocking::RequestBuilder {
+        match self {
+            GerritAuth::Anonymous => request,
+            GerritAuth::Basic { username, password } => request.basic_auth(username, Some(password)),
+            GerritAuth::Bearer { token } => request.bearer_auth(token),
+            GerritAuth::Cookie { cookie } => request.header(reqwest::header::COOKIE, cookie.as_str()),
+        }
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, GerritAuth::Anonymous)
+    }
+
+    /// Identity used to keep cached responses of different credentials apart
+    pub fn cache_scope(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let secret_digest = |secret: &str| {
+            Sha256::digest(secret.as_bytes()).iter().take(8).map(|b| format!("{:02x}", b)).collect::<String>()
+        };
+        match self {
+            GerritAuth::Anonymous => "anonymous".to_string(),
+            GerritAuth::Basic { username, .. } => format!("basic:{}", username),
+            GerritAuth::Bearer { token } => format!("bearer:{}", secret_digest(token)),
+            GerritAuth::Cookie { cookie } => format!("cookie:{}", secret_digest(cookie)),
+        }
+    }
+
+    /// Credential store service key for an instance's secret
+    pub fn credential_service(instance_id: &str) -> String {
+        format!("gerrit:{}", instance_id)
+    }
+
+    /// Secret stored for an instance, preferring the credential store over the legacy column
+    pub fn stored_secret(credentials: &CredentialStore, instance: &GerritInstance) -> Option<String> {
+        match credentials.retrieve(&Self::credential_service(&instance.id), &instance.username) {
+            Ok(Some(credential)) => Some(credential.password),
+            _ => Some(instance.password_encrypted.clone()).filter(|p| !p.is_empty()),
+        }
+    }
+}
+
+/// Find the cookie for `host` in a Netscape-format cookie jar such as `.gitcookies`.
+/// Returns a `name=value` pair suitable for a `Cookie` header.
+pub fn parse_gitcookies(content: &str, host: &str) -> Option<String> {
+    let host = host.to_lowercase();
+    let mut best: Option<(usize, String)> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 7 {
+            debug!("Skipping malformed cookie line");
+            continue;
+        }
+
+        let domain = fields[0].to_lowercase();
+        let include_subdomains = fields[1].eq_ignore_ascii_case("TRUE");
+        let bare = domain.trim_start_matches('.');
+        let matches = host == bare
+            || ((include_subdomains || domain.starts_with('.')) && host.ends_with(&format!(".{}", bare)));
+        if !matches {
+            continue;
+        }
+
+        // Prefer the most specific domain
+        let cookie = format!("{}={}", fields[5], fields[6]);
+        if best.as_ref().is_none_or(|(len, _)| bare.len() > *len) {
+            best = Some((bare.len(), cookie));
+        }
+    }
+
+    best.map(|(_, cookie)| cookie)
+}
+
+/// Find the login for `host` in `.netrc` content, falling back to a `default` entry
+pub fn parse_netrc(content: &str, host: &str) -> Option<NetrcEntry> {
+    let mut tokens = content.split_whitespace();
+    let mut current: Option<String> = None;
+    let mut login: Option<String> = None;
+    let mut password: Option<String> = None;
+    let mut default_entry: Option<NetrcEntry> = None;
+
+    let finish = |machine: &Option<String>, login: &Option<String>, password: &Option<String>| {
+        match (machine, login, password) {
+            (Some(m), Some(l), Some(p)) => Some((m.clone(), NetrcEntry { login: l.clone(), password: p.clone() })),
+            _ => None,
+        }
+    };
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" | "default" => {
+                if let Some((machine, entry)) = finish(&current, &login, &password) {
+                    if machine.eq_ignore_ascii_case(host) {
+                        return Some(entry);
+                    }
+                    if machine.is_empty() {
+                        default_entry = Some(entry);
+                    }
+                }
+                current = if token == "machine" {
+                    tokens.next().map(|m| m.to_string())
+                } else {
+                    Some(String::new())
+                };
+                login = None;
+                password = None;
+            }
+            "login" => login = tokens.next().map(|s| s.to_string()),
+            "password" => password = tokens.next().map(|s| s.to_string()),
+            "account" => {
+                tokens.next();
+            }
+            "macdef" => {
+                warn!("Ignoring macdef entry in .netrc");
+                tokens.next();
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((machine, entry)) = finish(&current, &login, &password) {
+        if machine.eq_ignore_ascii_case(host) {
+            return Some(entry);
+        }
+        if machine.is_empty() {
+            default_entry = Some(entry);
+        }
+    }
+
+    default_entry
+}
+
+/// Host part of a Gerrit base URL
+pub fn host_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+        .unwrap_or_else(|| url.to_lowercase())
+}
+
+fn default_gitcookies_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".gitcookies"))
+}
+
+fn default_netrc_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("NETRC") {
+        return Some(PathBuf::from(path));
+    }
+    let home = dirs::home_dir()?;
+    let netrc = home.join(".netrc");
+    if netrc.exists() {
+        return Some(netrc);
+    }
+    Some(home.join("_netrc"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gitcookies_matches_host() {
+        let content = "# Netscape HTTP Cookie File\n\
+            .googlesource.com\tTRUE\t/\tTRUE\t2147483647\to\tgit-user.example.com=1//abc\n\
+            gerrit.example.com\tFALSE\t/\tTRUE\t2147483647\tGerritAccount\txyz\n";
+
+        assert_eq!(
+            parse_gitcookies(content, "chromium-review.googlesource.com"),
+            Some("o=git-user.example.com=1//abc".to_string())
+        );
+        assert_eq!(
+            parse_gitcookies(content, "gerrit.example.com"),
+            Some("GerritAccount=xyz".to_string())
+        );
+        assert_eq!(parse_gitcookies(content, "other.example.com"), None);
+    }
+
+    #[test]
+    fn test_parse_gitcookies_http_only_prefix() {
+        let content = "#HttpOnly_gerrit.example.com\tFALSE\t/\tTRUE\t0\tGerritAccount\tsecret\n";
+        assert_eq!(
+            parse_gitcookies(content, "gerrit.example.com"),
+            Some("GerritAccount=secret".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_netrc() {
+        let content = "machine gerrit.example.com\n  login alice\n  password s3cret\n\
+            machine other.example.com login bob password hunter2\n\
+            default login anon password guest\n";
+
+        assert_eq!(
+            parse_netrc(content, "gerrit.example.com"),
+            Some(NetrcEntry { login: "alice".to_string(), password: "s3cret".to_string() })
+        );
+        assert_eq!(
+            parse_netrc(content, "other.example.com"),
+            Some(NetrcEntry { login: "bob".to_string(), password: "hunter2".to_string() })
+        );
+        assert_eq!(
+            parse_netrc(content, "unknown.example.com"),
+            Some(NetrcEntry { login: "anon".to_string(), password: "guest".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_for_instance_basic_and_bearer() {
+        let mut instance = GerritInstance::new(
+            "Test".to_string(),
+            "https://gerrit.example.com".to_string(),
+            "alice".to_string(),
+            String::new(),
+        );
+
+        let auth = GerritAuth::for_instance(&instance, Some("pw")).unwrap();
+        assert_eq!(auth, GerritAuth::Basic { username: "alice".to_string(), password: "pw".to_string() });
+        assert!(GerritAuth::for_instance(&instance, None).unwrap().is_anonymous());
+
+        instance.auth_method = GerritAuthMethod::Bearer;
+        let auth = GerritAuth::for_instance(&instance, Some("token")).unwrap();
+        assert_eq!(auth, GerritAuth::Bearer { token: "token".to_string() });
+        assert!(matches!(
+            GerritAuth::for_instance(&instance, None),
+            Err(HyperReviewError::AuthenticationRequired { .. })
+        ));
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://Gerrit.Example.com:8443/r/"), "gerrit.example.com");
+    }
+}
@@ -2,16 +2,29 @@
 // Fully async using tokio spawn_blocking for HTTP calls
 
 use tokio::time::Duration;
-use reqwest::ClientBuilder;
-use reqwest::Client;
 use serde_json::Value;
 use serde::{Serialize, Deserialize};
-use log::{info, error};
+use log::{info, warn, error, debug};
 use rand::Rng;
-use std::collections::HashMap;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
 
 use crate::models::{SubmitResult, Comment};
 use crate::errors::HyperReviewError;
+use crate::models::gerrit::{
+    CommentSide, CommentSyncStatus, FileChangeType, FileStatus, GerritChange, GerritComment,
+    GerritCommentSource, GerritFile, GerritInstance, GerritUser, RemoteGerritComment, ReviewProgress,
+    SuggestedChange,
+};
+use crate::models::platform::PlatformKind;
+use crate::remote::platform::{ChangeRef, PlatformReview, ReviewPlatform, ReviewVote};
+use crate::services::change_downloader::ChangeDownloader;
+use crate::remote::gerrit_auth::GerritAuth;
+use crate::remote::http_client::HttpClientFactory;
+use crate::models::network::NetworkSettings;
+use crate::remote::traffic_control::{
+    send_with_retries_blocking, CachedResponse, CircuitBreakerConfig, RateLimitConfig, RequestPolicy, ResponseCache,
+};
 
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -34,29 +47,55 @@ impl Default for RetryConfig {
     }
 }
 
+impl RetryConfig {
+    /// Exponential backoff with jitter for the given (zero-based) attempt
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponential_delay = (self.base_delay_ms as f64) * self.backoff_multiplier.powi(attempt as i32);
+        let capped_delay = (exponential_delay as u64).min(self.max_delay_ms);
+
+        let jitter_range = (capped_delay as f64) * self.jitter_factor;
+        let jitter = if jitter_range > 0.0 {
+            rand::thread_rng().gen_range(-jitter_range..=jitter_range)
+        } else {
+            0.0
+        };
+
+        let final_delay = (capped_delay as f64) + jitter;
+        Duration::from_millis(final_delay.max(0.0) as u64)
+    }
+}
+
+/// Everything a blocking request needs, cloned out of the client before `spawn_blocking`
+#[derive(Clone)]
+struct RequestContext {
+    http: HttpClientFactory,
+    auth: GerritAuth,
+    retry_config: RetryConfig,
+    rate_limit: RateLimitConfig,
+    circuit_breaker: CircuitBreakerConfig,
+    cache: Option<ResponseCache>,
+}
+
 pub struct GerritClient {
     base_url: String,
-    client: Client,
-    username: Option<String>,
-    http_password: Option<String>,
+    http: HttpClientFactory,
+    auth: GerritAuth,
     retry_config: RetryConfig,
+    rate_limit: RateLimitConfig,
+    circuit_breaker: CircuitBreakerConfig,
+    cache: Option<ResponseCache>,
 }
 
 impl GerritClient {
     pub fn new(base_url: &str) -> Self {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .user_agent("HyperReview/1.0 GerritClient")
-            .build()
-            .unwrap_or_else(|_| Client::new());
-
         Self {
             base_url: base_url.trim_end_matches('/').to_string(),
-            client,
-            username: None,
-            http_password: None,
+            http: HttpClientFactory::default(),
+            auth: GerritAuth::Anonymous,
             retry_config: RetryConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            cache: None,
         }
     }
 
@@ -65,46 +104,150 @@ impl GerritClient {
     }
 
     pub fn with_auth(mut self, username: String, http_password: String) -> Self {
-        self.username = Some(username);
-        self.http_password = Some(http_password);
+        self.auth = GerritAuth::Basic { username, password: http_password };
         self
     }
 
-    fn calculate_backoff_delay(&self, attempt: u32) -> Duration {
-        let base_delay = self.retry_config.base_delay_ms;
-        let exponential_delay = (base_delay as f64) * self.retry_config.backoff_multiplier.powi(attempt as i32);
+    /// Route requests through the given proxy/CA/TLS settings
+    pub fn with_network(mut self, settings: NetworkSettings) -> Self {
+        self.http = HttpClientFactory::new(settings);
+        self
+    }
 
-        let max_delay = self.retry_config.max_delay_ms;
-        let capped_delay = (exponential_delay as u64).min(max_delay);
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
 
-        let jitter_range = (capped_delay as f64) * self.retry_config.jitter_factor;
-        let jitter = rand::thread_rng().gen_range(-jitter_range..=jitter_range);
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
 
-        let final_delay = (capped_delay as f64) + jitter;
-        let final_delay_ms = final_delay.max(0.0) as u64;
+    /// Revalidate GET responses against an on-disk ETag/Last-Modified cache
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 
-        Duration::from_millis(final_delay_ms)
+    /// Use an explicit auth provider (bearer token, cookie jar, netrc, ...)
+    pub fn with_auth_provider(mut self, auth: GerritAuth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Build a client for a stored instance using its configured auth method.
+    /// `secret` is the password, token or cookie held in the credential store.
+    pub fn for_instance(instance: &GerritInstance, secret: Option<&str>) -> Result<Self, HyperReviewError> {
+        let auth = GerritAuth::for_instance(instance, secret)?;
+        let mut client = Self::new(&instance.url).with_auth_provider(auth);
+        client.cache = ResponseCache::default_location();
+        Ok(client)
+    }
+
+    fn request_context(&self) -> RequestContext {
+        RequestContext {
+            http: self.http.clone(),
+            auth: self.auth.clone(),
+            retry_config: self.retry_config.clone(),
+            rate_limit: self.rate_limit.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+
+    /// Send a request with per-host rate limiting, circuit breaking and retries.
+    ///
+    /// GET responses carrying validators are cached on disk and revalidated with
+    /// conditional requests.
+    fn execute(
+        ctx: &RequestContext,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<String>,
+    ) -> Result<(reqwest::StatusCode, String), HyperReviewError> {
+        let client = ctx.http.blocking_client()?;
+        let is_get = method == reqwest::Method::GET;
+        let cache_scope = ctx.auth.cache_scope();
+        let cached = match (&ctx.cache, is_get) {
+            (Some(cache), true) => cache.get(&cache_scope, url),
+            _ => None,
+        };
+        let policy = RequestPolicy::new(&ctx.retry_config, &ctx.circuit_breaker).with_rate_limit(&ctx.rate_limit);
+
+        let response = send_with_retries_blocking(&policy, &method, url, || {
+            let mut request = ctx.auth.apply(client.request(method.clone(), url));
+            if let Some(body) = &body {
+                request = request
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.clone());
+            }
+            if let Some(entry) = &cached {
+                if let Some(etag) = &entry.etag {
+                    request = request.header(reqwest::header::IF_NONE_MATCH, etag.as_str());
+                }
+                if let Some(last_modified) = &entry.last_modified {
+                    request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified.as_str());
+                }
+            }
+            request
+        })?;
+
+        let status = response.status();
+        Self::ensure_authorized(status, url)?;
+
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                debug!("Cache hit (304) for {}", url);
+                return Ok((reqwest::StatusCode::OK, entry.body));
+            }
+        }
+
+        let header = |name: reqwest::header::HeaderName| {
+            response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string())
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        let text = response.text()?;
+
+        if let (Some(cache), true) = (&ctx.cache, is_get && status.is_success()) {
+            if etag.is_some() || last_modified.is_some() {
+                let entry = CachedResponse {
+                    url: url.to_string(),
+                    etag,
+                    last_modified,
+                    body: text.clone(),
+                    stored_at: chrono::Utc::now().to_rfc3339(),
+                };
+                if let Err(e) = cache.put(&cache_scope, &entry) {
+                    warn!("Failed to cache response for {}: {}", url, e);
+                }
+            }
+        }
+
+        Ok((status, text))
+    }
+
+    /// Map a 401 into a re-authentication prompt instead of a generic HTTP error
+    fn ensure_authorized(status: reqwest::StatusCode, url: &str) -> Result<(), HyperReviewError> {
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            error!("Gerrit rejected credentials for {}", url);
+            return Err(HyperReviewError::authentication_required(
+                url.to_string(),
+                "The server rejected the stored credentials (HTTP 401)".to_string(),
+            ));
+        }
+        Ok(())
     }
 
     pub async fn test_connection(&self) -> Result<ConnectionTestResult, HyperReviewError> {
         info!("Testing connection to Gerrit: {}", self.base_url);
 
         let url = format!("{}/config/server/info", self.base_url);
-        let username = self.username.clone();
-        let password = self.http_password.clone();
+        let ctx = self.request_context();
 
         let result = tokio::task::spawn_blocking(move || {
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.get(&url);
-            
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
-            }
-
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
 
             if status.is_success() {
                 let cleaned = Self::clean_gerrit_json(&body)?;
@@ -141,31 +284,29 @@ impl GerritClient {
         &self,
         change_number: i32,
     ) -> Result<GerritChangeInfo, HyperReviewError> {
-        info!("Getting Gerrit change #{}", change_number);
+        self.get_change_by_id(&change_number.to_string()).await
+    }
+
+    /// Get a change by any identifier Gerrit accepts: number, Change-Id or `project~branch~Change-Id`
+    pub async fn get_change_by_id(
+        &self,
+        change_id: &str,
+    ) -> Result<GerritChangeInfo, HyperReviewError> {
+        info!("Getting Gerrit change {}", change_id);
+        let change_id = change_id.to_string();
 
         let base_url = self.base_url.clone();
-        let username = self.username.clone();
-        let password = self.http_password.clone();
+        let ctx = self.request_context();
 
         let result = tokio::task::spawn_blocking(move || {
             let url = format!(
-                "{}/a/changes/{}?o=CURRENT_REVISION&o=DETAILED_ACCOUNTS&o=DETAILED_LABELS",
-                base_url, change_number
+                "{}/a/changes/{}?o=CURRENT_REVISION&o=CURRENT_COMMIT&o=DETAILED_ACCOUNTS&o=DETAILED_LABELS",
+                base_url, change_id
             );
 
             info!("GET to Gerrit: {}", url);
 
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.get(&url);
-
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
-            }
-
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
 
             info!("Response status: {}, body length: {}", status, body.len());
 
@@ -224,6 +365,8 @@ pub struct GerritChangeInfo {
     pub deletions: Option<i32>,
     pub current_revision: Option<String>,
     pub revisions: Option<serde_json::Value>,
+    #[serde(default)]
+    pub labels: Option<serde_json::Value>,
 }
 
 impl GerritClient {
@@ -238,23 +381,12 @@ impl GerritClient {
         let base_url = self.base_url.clone();
         let change_id = change_id.to_string();
         let revision_id = revision_id.to_string();
-        let username = self.username.clone();
-        let password = self.http_password.clone();
+        let ctx = self.request_context();
         
         let result = tokio::task::spawn_blocking(move || {
             let url = format!("{}/a/changes/{}/revisions/{}/files/", base_url, change_id, revision_id);
             
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.get(&url);
-            
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
-            }
-            
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
             
             if status.is_success() {
                 let cleaned = Self::clean_gerrit_json(&body)?;
@@ -284,25 +416,14 @@ impl GerritClient {
         let change_id = change_id.to_string();
         let revision_id = revision_id.to_string();
         let file_path = file_path.to_string();
-        let username = self.username.clone();
-        let password = self.http_password.clone();
+        let ctx = self.request_context();
         
         let result = tokio::task::spawn_blocking(move || {
             let encoded_path = urlencoding::encode(&file_path);
             let url = format!("{}/a/changes/{}/revisions/{}/files/{}/content", 
                              base_url, change_id, revision_id, encoded_path);
             
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.get(&url);
-            
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
-            }
-            
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
             
             if status.is_success() {
                 // Gerrit returns base64 encoded content
@@ -339,8 +460,7 @@ impl GerritClient {
         let revision_id = revision_id.to_string();
         let file_path = file_path.to_string();
         let base_revision = base_revision.map(|s| s.to_string());
-        let username = self.username.clone();
-        let password = self.http_password.clone();
+        let ctx = self.request_context();
         
         let result = tokio::task::spawn_blocking(move || {
             let encoded_path = urlencoding::encode(&file_path);
@@ -352,17 +472,7 @@ impl GerritClient {
                 url.push_str(&format!("?base={}", base));
             }
             
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.get(&url);
-            
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
-            }
-            
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
             
             if status.is_success() {
                 let cleaned = Self::clean_gerrit_json(&body)?;
@@ -416,41 +526,128 @@ impl GerritClient {
         unified_diff
     }
 
-    /// Get comments for a change
+    /// Get the published comments of a change, across all patch sets
     pub async fn get_comments(&self, change_id: &str) -> Result<Vec<Comment>, HyperReviewError> {
         info!("Getting comments for change: {}", change_id);
-        
-        let url = format!("{}/a/changes/{}/comments", self.base_url, change_id);
-        
-        let username = self.username.clone();
-        let password = self.http_password.clone();
-        
-        let result = tokio::task::spawn_blocking(move || {
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.get(&url);
-            
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
+
+        let comments = self.get_comment_list(change_id, "comments").await?;
+        Ok(comments.into_iter()
+            .flat_map(|(path, infos)| infos.into_iter().map(move |info| info.into_comment(&path)))
+            .collect())
+    }
+
+    /// Comment lists `get_change_comments` reads. Drafts belong to the calling user,
+    /// so they are only read when authenticated.
+    pub fn comment_sources(&self) -> Vec<GerritCommentSource> {
+        let mut sources = vec![GerritCommentSource::Published, GerritCommentSource::Robot];
+        if !matches!(self.auth, GerritAuth::Anonymous) {
+            sources.push(GerritCommentSource::Draft);
+        }
+        sources
+    }
+
+    /// Get the published, draft and robot comments of a change, oldest first
+    pub async fn get_change_comments(&self, change_id: &str) -> Result<Vec<RemoteGerritComment>, HyperReviewError> {
+        info!("Getting all comments for change: {}", change_id);
+
+        let mut comments = Vec::new();
+        for source in self.comment_sources() {
+            let list = match source {
+                GerritCommentSource::Published => "comments",
+                GerritCommentSource::Robot => "robotcomments",
+                GerritCommentSource::Draft => "drafts",
+            };
+            for (path, infos) in self.get_comment_list(change_id, list).await? {
+                comments.extend(infos.into_iter().map(|info| RemoteGerritComment {
+                    source,
+                    comment: info.into_gerrit_comment(&path, change_id),
+                }));
             }
-            
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
-            
+        }
+
+        comments.sort_by(|a, b| a.comment.updated.cmp(&b.comment.updated));
+        info!("Retrieved {} comments for change {}", comments.len(), change_id);
+        Ok(comments)
+    }
+
+    /// Accounts whose name, email or username starts with `query`, for completing @mentions
+    pub async fn suggest_accounts(&self, query: &str, limit: u32) -> Result<Vec<GerritUser>, HyperReviewError> {
+        debug!("Suggesting accounts for: {}", query);
+
+        let url = format!("{}/a/accounts/?suggest&q={}&n={}&o=DETAILS",
+                          self.base_url, urlencoding::encode(query), limit);
+        let ctx = self.request_context();
+
+        tokio::task::spawn_blocking(move || {
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
+
             if status.is_success() {
                 let cleaned = Self::clean_gerrit_json(&body)?;
-                // Parse Gerrit comments format and convert to our Comment model
-                // For now, return empty vec until full parsing is implemented
-                Ok(Vec::new())
+                let accounts: Vec<GerritAccountInfo> = serde_json::from_str(&cleaned)?;
+                Ok(accounts.into_iter().map(GerritAccountInfo::into_user).collect())
             } else {
-                let error_msg = format!("Failed to get comments: HTTP {}: {}", status, body);
-                error!("{}", error_msg);
-                Err(HyperReviewError::other(error_msg))
+                Err(HyperReviewError::network_with_status(
+                    format!("Failed to suggest accounts: HTTP {}: {}", status, body),
+                    status.as_u16(),
+                ))
             }
-        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?;
-        
-        result
+        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?
+    }
+
+    /// Account IDs of the change's owner, reviewers and CCs
+    pub async fn get_change_participants(&self, change_id: &str) -> Result<HashSet<u32>, HyperReviewError> {
+        let url = format!("{}/a/changes/{}?o=DETAILED_LABELS", self.base_url, change_id);
+        let ctx = self.request_context();
+
+        tokio::task::spawn_blocking(move || {
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
+
+            if status.is_success() {
+                let cleaned = Self::clean_gerrit_json(&body)?;
+                let change: Value = serde_json::from_str(&cleaned)?;
+                let reviewers = ["REVIEWER", "CC"].into_iter()
+                    .filter_map(|state| change["reviewers"][state].as_array())
+                    .flatten();
+                Ok(std::iter::once(&change["owner"])
+                    .chain(reviewers)
+                    .filter_map(|account| account["_account_id"].as_u64())
+                    .map(|id| id as u32)
+                    .collect())
+            } else {
+                Err(HyperReviewError::network_with_status(
+                    format!("Failed to get reviewers: HTTP {}: {}", status, body),
+                    status.as_u16(),
+                ))
+            }
+        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?
+    }
+
+    /// One of a change's comment lists, keyed by file path. Servers that dropped
+    /// robot comments answer 404, which is treated as an empty list.
+    async fn get_comment_list(
+        &self,
+        change_id: &str,
+        list: &str,
+    ) -> Result<HashMap<String, Vec<GerritCommentInfo>>, HyperReviewError> {
+        let url = format!("{}/a/changes/{}/{}", self.base_url, change_id, list);
+        let ctx = self.request_context();
+
+        tokio::task::spawn_blocking(move || {
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
+
+            if status.is_success() {
+                let cleaned = Self::clean_gerrit_json(&body)?;
+                Ok(serde_json::from_str(&cleaned)?)
+            } else if status == reqwest::StatusCode::NOT_FOUND {
+                debug!("No comment list at {}", url);
+                Ok(HashMap::new())
+            } else {
+                Err(HyperReviewError::network_with_status(
+                    format!("Failed to get comments: HTTP {}: {}", status, body),
+                    status.as_u16(),
+                ))
+            }
+        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?
     }
     
     /// Search for changes
@@ -458,23 +655,12 @@ impl GerritClient {
         info!("Searching changes with query: {}", query);
         
         let encoded_query = urlencoding::encode(query);
-        let url = format!("{}/a/changes/?q={}&o=CURRENT_REVISION", self.base_url, encoded_query);
+        let url = format!("{}/a/changes/?q={}&o=CURRENT_REVISION&o=CURRENT_COMMIT", self.base_url, encoded_query);
         
-        let username = self.username.clone();
-        let password = self.http_password.clone();
+        let ctx = self.request_context();
         
         let result = tokio::task::spawn_blocking(move || {
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.get(&url);
-            
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
-            }
-            
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
             
             if status.is_success() {
                 let cleaned = Self::clean_gerrit_json(&body)?;
@@ -491,6 +677,34 @@ impl GerritClient {
         result
     }
     
+    /// Find the change whose current revision is `commit`, with its label votes
+    pub async fn get_change_by_commit(&self, commit: &str) -> Result<Option<GerritChangeInfo>, HyperReviewError> {
+        info!("Looking up Gerrit change for commit {}", commit);
+
+        let url = format!(
+            "{}/a/changes/?q={}&o=CURRENT_REVISION&o=DETAILED_LABELS",
+            self.base_url,
+            urlencoding::encode(&format!("commit:{}", commit))
+        );
+        let ctx = self.request_context();
+        let commit = commit.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let (status, body) = Self::execute(&ctx, reqwest::Method::GET, &url, None)?;
+
+            if status.is_success() {
+                let cleaned = Self::clean_gerrit_json(&body)?;
+                let changes: Vec<GerritChangeInfo> = serde_json::from_str(&cleaned)?;
+                Ok(changes.into_iter().next())
+            } else {
+                Err(HyperReviewError::network_with_status(
+                    format!("Failed to look up commit {}: HTTP {}: {}", commit, status, body),
+                    status.as_u16(),
+                ))
+            }
+        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?
+    }
+
     /// Submit a review
     pub async fn submit_review(&self, change_id: &str, review: &ReviewInput) -> Result<(), HyperReviewError> {
         info!("Submitting review for change: {}", change_id);
@@ -498,23 +712,10 @@ impl GerritClient {
         let url = format!("{}/a/changes/{}/revisions/current/review", self.base_url, change_id);
         let review_json = serde_json::to_string(review)?;
         
-        let username = self.username.clone();
-        let password = self.http_password.clone();
+        let ctx = self.request_context();
         
         let result = tokio::task::spawn_blocking(move || {
-            let client = reqwest::blocking::Client::new();
-            let mut request = client.post(&url)
-                .header("Content-Type", "application/json")
-                .body(review_json);
-            
-            // Add basic auth if credentials are available
-            if let (Some(user), Some(pass)) = (username, password) {
-                request = request.basic_auth(user, Some(pass));
-            }
-            
-            let response = request.send()?;
-            let status = response.status();
-            let body = response.text()?;
+            let (status, body) = Self::execute(&ctx, reqwest::Method::POST, &url, Some(review_json))?;
             
             if status.is_success() {
                 info!("Review submitted successfully");
@@ -522,12 +723,82 @@ impl GerritClient {
             } else {
                 let error_msg = format!("Failed to submit review: HTTP {}: {}", status, body);
                 error!("{}", error_msg);
-                Err(HyperReviewError::other(error_msg))
+                Err(HyperReviewError::network_with_status(error_msg, status.as_u16()))
             }
         }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?;
         
         result
     }
+
+    /// Create a draft comment on the current revision and return its id
+    pub async fn create_draft(&self, change_id: &str, draft: &DraftInput) -> Result<String, HyperReviewError> {
+        info!("Creating draft comment on {} for change: {}", draft.path, change_id);
+
+        let url = format!("{}/a/changes/{}/revisions/current/drafts", self.base_url, change_id);
+        let draft_json = serde_json::to_string(draft)?;
+        let ctx = self.request_context();
+
+        tokio::task::spawn_blocking(move || {
+            let (status, body) = Self::execute(&ctx, reqwest::Method::PUT, &url, Some(draft_json))?;
+
+            if status.is_success() {
+                let cleaned = Self::clean_gerrit_json(&body)?;
+                let created: Value = serde_json::from_str(&cleaned)?;
+                created.get("id")
+                    .and_then(|id| id.as_str())
+                    .map(|id| id.to_string())
+                    .ok_or_else(|| HyperReviewError::other("Gerrit did not return a draft id".to_string()))
+            } else {
+                Err(HyperReviewError::network_with_status(
+                    format!("Failed to create draft: HTTP {}: {}", status, body),
+                    status.as_u16(),
+                ))
+            }
+        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?
+    }
+
+    /// Replace the text or position of a draft comment
+    pub async fn update_draft(&self, change_id: &str, draft_id: &str, draft: &DraftInput) -> Result<(), HyperReviewError> {
+        info!("Updating draft {} on change: {}", draft_id, change_id);
+
+        let url = format!("{}/a/changes/{}/revisions/current/drafts/{}", self.base_url, change_id, draft_id);
+        let draft_json = serde_json::to_string(draft)?;
+        let ctx = self.request_context();
+
+        tokio::task::spawn_blocking(move || {
+            let (status, body) = Self::execute(&ctx, reqwest::Method::PUT, &url, Some(draft_json))?;
+
+            if status.is_success() {
+                Ok(())
+            } else {
+                Err(HyperReviewError::network_with_status(
+                    format!("Failed to update draft: HTTP {}: {}", status, body),
+                    status.as_u16(),
+                ))
+            }
+        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?
+    }
+
+    /// Delete a draft comment
+    pub async fn delete_draft(&self, change_id: &str, draft_id: &str) -> Result<(), HyperReviewError> {
+        info!("Deleting draft {} on change: {}", draft_id, change_id);
+
+        let url = format!("{}/a/changes/{}/revisions/current/drafts/{}", self.base_url, change_id, draft_id);
+        let ctx = self.request_context();
+
+        tokio::task::spawn_blocking(move || {
+            let (status, body) = Self::execute(&ctx, reqwest::Method::DELETE, &url, None)?;
+
+            if status.is_success() || status == reqwest::StatusCode::NOT_FOUND {
+                Ok(())
+            } else {
+                Err(HyperReviewError::network_with_status(
+                    format!("Failed to delete draft: HTTP {}: {}", status, body),
+                    status.as_u16(),
+                ))
+            }
+        }).await.map_err(|e| HyperReviewError::other(format!("Task spawn failed: {}", e)))?
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -535,6 +806,26 @@ pub struct ReviewInput {
     pub message: String,
     pub labels: std::collections::HashMap<String, i32>,
     pub comments: std::collections::HashMap<String, Vec<CommentInput>>,
+    /// What to do with the user's drafts, e.g. `PUBLISH_ALL_REVISIONS`; Gerrit keeps them by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drafts: Option<String>,
+    /// Accounts to add as reviewers or CCs along with the review
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<ReviewerInput>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub add_to_attention_set: Vec<AttentionSetInput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewerInput {
+    pub reviewer: String,             // Account ID, username or email
+    pub state: String,                // "REVIEWER" or "CC"
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttentionSetInput {
+    pub user: String,                 // Account ID, username or email
+    pub reason: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -543,6 +834,80 @@ pub struct CommentInput {
     pub message: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct DraftInput {
+    pub path: String,
+    pub line: Option<i32>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<CommentRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_suggestions: Option<Vec<FixSuggestionInput>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unresolved: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<String>, // Gerrit comment the draft answers
+}
+
+impl DraftInput {
+    /// Attach a suggested change: the replacement is shown as a ```suggestion block
+    /// and offered as a fix the change owner can apply from the Gerrit UI
+    pub fn with_suggestion(mut self, suggestion: &SuggestedChange) -> Self {
+        self.message = suggestion.to_markdown(&self.message);
+        self.line = Some(suggestion.end_line as i32);
+        self.range = suggestion.original_text.as_ref()
+            .filter(|_| suggestion.end_line > suggestion.start_line)
+            .map(|original| CommentRange {
+                start_line: suggestion.start_line as i32,
+                start_character: 0,
+                end_line: suggestion.end_line as i32,
+                end_character: original.lines().last().map_or(0, |l| l.chars().count() as i32),
+            });
+
+        // Replace whole lines, up to the start of the line after the range
+        let mut replacement = suggestion.replacement.clone();
+        if !replacement.is_empty() {
+            replacement.push('\n');
+        }
+        self.fix_suggestions = Some(vec![FixSuggestionInput {
+            description: "Suggested edit".to_string(),
+            replacements: vec![FixReplacementInput {
+                path: suggestion.file_path.clone(),
+                range: CommentRange {
+                    start_line: suggestion.start_line as i32,
+                    start_character: 0,
+                    end_line: suggestion.end_line as i32 + 1,
+                    end_character: 0,
+                },
+                replacement,
+            }],
+        }]);
+        self
+    }
+}
+
+/// A range of characters; the end position is exclusive
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CommentRange {
+    pub start_line: i32,
+    pub start_character: i32,
+    pub end_line: i32,
+    pub end_character: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FixSuggestionInput {
+    pub description: String,
+    pub replacements: Vec<FixReplacementInput>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FixReplacementInput {
+    pub path: String,
+    pub range: CommentRange,
+    pub replacement: String,
+}
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct GerritFileInfo {
     pub status: Option<String>,
@@ -578,3 +943,454 @@ pub struct GerritDiffContent {
     pub due_to_rebase: Option<bool>,
     pub skip: Option<i32>,
 }
+
+/// Gerrit's `CommentInfo`, also used for drafts and robot comments
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GerritCommentInfo {
+    pub id: String,
+    pub patch_set: Option<u32>,
+    pub side: Option<String>,     // "PARENT" for comments on the base; absent for the revision
+    pub line: Option<u32>,        // Absent for file comments
+    pub range: Option<GerritCommentRange>,
+    pub in_reply_to: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    pub updated: String,          // "2025-01-05 12:00:00.000000000", UTC
+    pub author: Option<GerritAccountInfo>, // Absent for drafts
+    pub unresolved: Option<bool>,
+    pub robot_id: Option<String>,
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GerritCommentRange {
+    pub start_line: u32,
+    pub start_character: u32,
+    pub end_line: u32,
+    pub end_character: u32,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct GerritAccountInfo {
+    #[serde(rename = "_account_id", default)]
+    pub account_id: u32,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub username: Option<String>,
+    #[serde(default)]
+    pub avatars: Vec<GerritAvatarInfo>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct GerritAvatarInfo {
+    pub url: String,
+    pub height: Option<u32>,
+}
+
+impl GerritAccountInfo {
+    pub fn into_user(self) -> GerritUser {
+        // The largest avatar looks best when scaled down
+        let avatar_url = self.avatars.into_iter()
+            .max_by_key(|avatar| avatar.height.unwrap_or(0))
+            .map(|avatar| avatar.url);
+        GerritUser {
+            account_id: self.account_id,
+            name: self.name.unwrap_or_default(),
+            email: self.email.unwrap_or_default(),
+            username: self.username,
+            avatar_url,
+        }
+    }
+}
+
+impl GerritCommentInfo {
+    /// Gerrit timestamps carry nanoseconds; local comments use whole seconds
+    fn timestamp(&self) -> String {
+        self.updated.split('.').next().unwrap_or(&self.updated).to_string()
+    }
+
+    fn into_comment(self, path: &str) -> Comment {
+        let updated = self.timestamp();
+        Comment {
+            id: self.id,
+            file_path: path.to_string(),
+            line_number: self.line.unwrap_or(0),
+            content: self.message,
+            author: self.author.and_then(|author| author.name).unwrap_or_default(),
+            created_at: updated.clone(),
+            updated_at: updated,
+            status: crate::models::CommentStatus::Submitted,
+            parent_id: self.in_reply_to,
+            tags: self.robot_id.into_iter().collect(),
+        }
+    }
+
+    fn into_gerrit_comment(self, path: &str, change_id: &str) -> GerritComment {
+        let updated = self.timestamp();
+        GerritComment {
+            id: uuid::Uuid::new_v4().to_string(),
+            gerrit_comment_id: Some(self.id),
+            change_id: change_id.to_string(),
+            patch_set_id: self.patch_set.map(|number| number.to_string()).unwrap_or_default(),
+            file_path: path.to_string(),
+            side: if self.side.as_deref() == Some("PARENT") { CommentSide::Parent } else { CommentSide::Revision },
+            line: self.line.unwrap_or(0),
+            range: self.range.map(|range| crate::models::gerrit::CommentRange {
+                start_line: range.start_line,
+                start_character: range.start_character,
+                end_line: range.end_line,
+                end_character: range.end_character,
+            }),
+            message: self.message,
+            author: self.author.unwrap_or_default().into_user(),
+            created: updated.clone(), // Gerrit only reports when a comment was last written
+            updated,
+            status: CommentSyncStatus::Synced,
+            unresolved: self.unresolved.unwrap_or(false),
+            parent: self.in_reply_to,
+            robot_id: self.robot_id,
+            properties: self.properties,
+        }
+    }
+}
+
+/// Gerrit's `Code-Review` score for a vote
+fn code_review_score(vote: ReviewVote) -> i32 {
+    match vote {
+        ReviewVote::Approve => 1,
+        ReviewVote::Reject => -1,
+        ReviewVote::Neutral => 0,
+        ReviewVote::Score(score) => score,
+    }
+}
+
+#[async_trait]
+impl ReviewPlatform for GerritClient {
+    fn kind(&self) -> PlatformKind {
+        PlatformKind::Gerrit
+    }
+
+    async fn fetch_change(&self, change: &ChangeRef) -> Result<GerritChange, HyperReviewError> {
+        let info = self.get_change_by_id(&change.id).await?;
+        Ok(ChangeDownloader::convert_change_info(info))
+    }
+
+    async fn list_files(&self, change: &ChangeRef) -> Result<Vec<GerritFile>, HyperReviewError> {
+        let info = self.get_change_by_id(&change.id).await?;
+        let revision = info.current_revision.clone().unwrap_or_else(|| "current".to_string());
+        let files = self.get_revision_files(&change.id, &revision).await?;
+
+        let mut result: Vec<GerritFile> = files.into_iter()
+            .filter(|(path, _)| !path.starts_with('/'))
+            .map(|(path, file)| {
+                let change_type = match file.status.as_deref() {
+                    Some("A") => FileChangeType::Added,
+                    Some("D") => FileChangeType::Deleted,
+                    Some("R") => FileChangeType::Renamed,
+                    Some("C") => FileChangeType::Copied,
+                    Some("W") => FileChangeType::Rewritten,
+                    _ => FileChangeType::Modified,
+                };
+                GerritFile {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    change_id: info.change_id.clone(),
+                    patch_set_id: revision.clone(),
+                    file_path: path,
+                    old_path: None,
+                    change_type,
+                    status: FileStatus::Unreviewed,
+                    lines_inserted: file.lines_inserted.unwrap_or(0).max(0) as u32,
+                    lines_deleted: file.lines_deleted.unwrap_or(0).max(0) as u32,
+                    size_delta: file.size_delta.unwrap_or(0),
+                    size_new: file.size.unwrap_or(0).max(0) as u32,
+                    is_binary: false,
+                    content_type: "text/plain".to_string(),
+                    diff_content: None,
+                    review_progress: ReviewProgress::default(),
+                    last_reviewed: None,
+                }
+            })
+            .collect();
+        result.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        Ok(result)
+    }
+
+    async fn get_file_content(&self, change: &ChangeRef, path: &str) -> Result<String, HyperReviewError> {
+        GerritClient::get_file_content(self, &change.id, "current", path).await
+    }
+
+    async fn list_comments(&self, change: &ChangeRef) -> Result<Vec<Comment>, HyperReviewError> {
+        self.get_comments(&change.id).await
+    }
+
+    async fn list_remote_comments(&self, change: &ChangeRef) -> Result<Vec<RemoteGerritComment>, HyperReviewError> {
+        self.get_change_comments(&change.id).await
+    }
+
+    fn complete_comment_sources(&self) -> Vec<GerritCommentSource> {
+        self.comment_sources()
+    }
+
+    /// Line comments are published as inline comments; comments without a file go into the message
+    async fn post_review(&self, change: &ChangeRef, review: &PlatformReview) -> Result<SubmitResult, HyperReviewError> {
+        let mut message = review.message.clone();
+        let mut comments: HashMap<String, Vec<CommentInput>> = HashMap::new();
+        for comment in &review.comments {
+            if comment.file_path.is_empty() {
+                if !message.is_empty() {
+                    message.push_str("\n\n");
+                }
+                message.push_str(&comment.content);
+                continue;
+            }
+            comments.entry(comment.file_path.clone()).or_default().push(CommentInput {
+                line: Some(comment.line_number as i32).filter(|line| *line > 0),
+                message: comment.content.clone(),
+            });
+        }
+
+        let mut labels = HashMap::new();
+        if let Some(vote) = review.vote {
+            labels.insert("Code-Review".to_string(), code_review_score(vote));
+        }
+
+        self.submit_review(&change.id, &ReviewInput {
+            message,
+            labels,
+            comments,
+            drafts: None,
+            reviewers: Vec::new(),
+            add_to_attention_set: Vec::new(),
+        }).await?;
+
+        Ok(SubmitResult {
+            success: true,
+            message: format!("Submitted {} comments to Gerrit change {}", review.comments.len(), change.id),
+            external_id: Some(change.id.clone()),
+            url: Some(format!("{}/c/{}", self.base_url, change.id)),
+        })
+    }
+
+    async fn vote(&self, change: &ChangeRef, vote: ReviewVote) -> Result<(), HyperReviewError> {
+        let mut labels = HashMap::new();
+        labels.insert("Code-Review".to_string(), code_review_score(vote));
+        self.submit_review(&change.id, &ReviewInput {
+            message: String::new(),
+            labels,
+            comments: HashMap::new(),
+            drafts: None,
+            reviewers: Vec::new(),
+            add_to_attention_set: Vec::new(),
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::fixtures::fast_retries;
+    use crate::remote::mock_server::{MockResponse, MockServer};
+    use crate::remote::platform::RegisteredPlatform;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    const CHANGE_JSON: &str = r#")]}'
+{"id":"p~main~I1","change_id":"I1","_number":1,"subject":"Fix","status":"NEW","project":"p","branch":"main","owner":{},"updated":"2025-01-01","created":"2025-01-01"}"#;
+
+    #[tokio::test]
+    async fn test_retries_after_429_with_retry_after() {
+        let hits = Arc::new(AtomicU32::new(0));
+        let counter = hits.clone();
+        let server = MockServer::start(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                MockResponse::new(429, "slow down").with_header("Retry-After", "0")
+            } else {
+                MockResponse::json(CHANGE_JSON)
+            }
+        });
+
+        let client = GerritClient::new(&server.base_url).with_retry_config(fast_retries());
+        let change = client.get_change(1).await.unwrap();
+        assert_eq!(change.subject, "Fix");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fatal_status_is_not_retried() {
+        let server = MockServer::start(|_| MockResponse::new(400, "bad request"));
+
+        let client = GerritClient::new(&server.base_url).with_retry_config(fast_retries());
+        assert!(client.get_change(1).await.is_err());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_asks_for_reauthentication() {
+        let server = MockServer::start(|_| MockResponse::new(401, "Unauthorized"));
+
+        let client = GerritClient::new(&server.base_url)
+            .with_auth("alice".to_string(), "wrong".to_string());
+        let err = client.get_change(1).await.unwrap_err();
+        assert!(err.is_authentication_required());
+    }
+
+    #[tokio::test]
+    async fn test_conditional_get_uses_cached_body() {
+        let server = MockServer::start(|request| {
+            if request.header("if-none-match") == Some("\"v1\"") {
+                MockResponse::new(304, "")
+            } else {
+                MockResponse::json(CHANGE_JSON).with_header("ETag", "\"v1\"")
+            }
+        });
+        let dir = TempDir::new().unwrap();
+        let client = GerritClient::new(&server.base_url)
+            .with_response_cache(ResponseCache::new(dir.path().to_path_buf()));
+
+        let first = client.get_change(1).await.unwrap();
+        let second = client.get_change(1).await.unwrap();
+        assert_eq!(first.change_id, second.change_id);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("if-none-match"), Some("\"v1\""));
+    }
+
+    #[test]
+    fn test_draft_with_suggestion_adds_fix() {
+        let suggestion = SuggestedChange {
+            comment_id: "c1".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            start_line: 3,
+            end_line: 4,
+            original_text: Some("let a = 1;\nlet b = 2;".to_string()),
+            replacement: "let (a, b) = (1, 2);".to_string(),
+            applied_commit: None,
+            created_at: "2025-01-05 12:00:00".to_string(),
+        };
+        let draft = DraftInput {
+            path: "src/lib.rs".to_string(),
+            line: Some(3),
+            message: "Destructure these".to_string(),
+            range: None,
+            fix_suggestions: None,
+            unresolved: Some(true),
+            in_reply_to: None,
+        }.with_suggestion(&suggestion);
+
+        let json = serde_json::to_value(&draft).unwrap();
+        assert_eq!(json["line"], 4);
+        assert_eq!(json["message"], "Destructure these\n\n```suggestion\nlet (a, b) = (1, 2);\n```");
+        assert_eq!(json["range"]["end_character"], 10);
+        let replacement = &json["fix_suggestions"][0]["replacements"][0];
+        assert_eq!(replacement["range"]["start_line"], 3);
+        assert_eq!(replacement["range"]["end_line"], 5);
+        assert_eq!(replacement["replacement"], "let (a, b) = (1, 2);\n");
+        assert_eq!(json["unresolved"], true);
+    }
+
+    #[tokio::test]
+    async fn test_change_comments_merge_all_lists() {
+        let server = MockServer::start(|request| {
+            if request.path.ends_with("/robotcomments") {
+                MockResponse::new(404, "Not found")
+            } else if request.path.ends_with("/drafts") {
+                MockResponse::json(r#")]}'
+{"src/lib.rs":[{"id":"d1","patch_set":2,"line":4,"in_reply_to":"c1","message":"Will do","updated":"2025-01-06 09:00:00.000000000","unresolved":false}]}"#)
+            } else {
+                MockResponse::json(r#")]}'
+{"src/lib.rs":[{"id":"c1","patch_set":1,"line":4,"message":"Leaks here","updated":"2025-01-05 12:00:00.123000000","unresolved":true,"author":{"_account_id":7,"name":"Ann","email":"ann@example.com"}}]}"#)
+            }
+        });
+
+        let anonymous = GerritClient::new(&server.base_url);
+        assert_eq!(anonymous.get_change_comments("I1").await.unwrap().len(), 1);
+
+        let client = GerritClient::new(&server.base_url).with_auth("bob".to_string(), "secret".to_string());
+        let comments = client.get_change_comments("I1").await.unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].source, GerritCommentSource::Published);
+        assert_eq!(comments[0].comment.gerrit_comment_id.as_deref(), Some("c1"));
+        assert_eq!(comments[0].comment.author.name, "Ann");
+        assert_eq!(comments[0].comment.updated, "2025-01-05 12:00:00");
+        assert!(comments[0].comment.unresolved);
+        assert_eq!(comments[1].source, GerritCommentSource::Draft);
+        assert_eq!(comments[1].comment.parent.as_deref(), Some("c1"));
+
+        let published = client.get_comments("I1").await.unwrap();
+        assert_eq!((published[0].author.as_str(), published[0].line_number), ("Ann", 4));
+    }
+
+    #[tokio::test]
+    async fn test_registered_platform_uses_change_id() {
+        let server = MockServer::start(|request| {
+            if request.path.starts_with("/a/changes/I1/revisions/current/files/") {
+                MockResponse::json(r#")]}'
+{"/COMMIT_MSG":{"status":"A"},"src/lib.rs":{"lines_inserted":2,"lines_deleted":1}}"#)
+            } else if request.path.ends_with("/review") {
+                MockResponse::json(")]}'\n{}")
+            } else {
+                MockResponse::json(CHANGE_JSON)
+            }
+        });
+        let entry = RegisteredPlatform {
+            instance_id: "review".to_string(),
+            name: "Review".to_string(),
+            kind: PlatformKind::Gerrit,
+            base_url: server.base_url.clone(),
+            is_active: true,
+            platform: Arc::new(GerritClient::new(&server.base_url)),
+        };
+        let change = entry.change_ref("p", "I1");
+
+        let fetched = entry.fetch_change(&change).await.unwrap();
+        assert_eq!((fetched.subject.as_str(), fetched.instance_id.as_str()), ("Fix", "review"));
+
+        let files = entry.platform.list_files(&change).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!((files[0].file_path.as_str(), files[0].lines_inserted), ("src/lib.rs", 2));
+
+        let review = PlatformReview { message: "LGTM".to_string(), comments: Vec::new(), vote: Some(ReviewVote::Score(2)) };
+        entry.platform.post_review(&change, &review).await.unwrap();
+
+        let requests = server.requests();
+        assert!(requests.iter().all(|r| r.path.starts_with("/a/changes/I1")));
+        let posted: Value = serde_json::from_str(&requests.last().unwrap().body).unwrap();
+        assert_eq!(posted["labels"]["Code-Review"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_mention_lookups() {
+        let server = MockServer::start(|request| {
+            if request.path.starts_with("/a/accounts/?suggest&q=ann%20l&n=5") {
+                MockResponse::json(r#")]}'
+[{"_account_id":7,"name":"Ann Lee","email":"ann@example.com","username":"alee","avatars":[{"url":"https://a/16.png","height":16},{"url":"https://a/32.png","height":32}]}]"#)
+            } else {
+                MockResponse::json(r#")]}'
+{"owner":{"_account_id":1},"reviewers":{"REVIEWER":[{"_account_id":7}],"CC":[{"_account_id":9}]}}"#)
+            }
+        });
+        let client = GerritClient::new(&server.base_url);
+
+        let accounts = client.suggest_accounts("ann l", 5).await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].username.as_deref(), Some("alee"));
+        assert_eq!(accounts[0].avatar_url.as_deref(), Some("https://a/32.png"));
+
+        let participants = client.get_change_participants("I1").await.unwrap();
+        assert_eq!(participants, HashSet::from([1, 7, 9]));
+
+        // Reviews without mentions leave reviewers and the attention set alone
+        let review = serde_json::to_value(ReviewInput {
+            message: String::new(),
+            labels: HashMap::new(),
+            comments: HashMap::new(),
+            drafts: None,
+            reviewers: Vec::new(),
+            add_to_attention_set: Vec::new(),
+        }).unwrap();
+        assert!(review.get("reviewers").is_none() && review.get("add_to_attention_set").is_none());
+    }
+}
@@ -0,0 +1,792 @@
+// GitHub API client for pull request review
+// Imports pull requests into the local change model and submits reviews over the REST API
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use base64::Engine;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::{
+    ChangeStatus, ConflictStatus, FileChangeType, FileStatus, GerritChange, GerritFile,
+    GerritUser, ImportStatus, PatchSet, PatchSetKind, ReviewProgress,
+};
+use crate::models::network::NetworkSettings;
+use crate::models::platform::PlatformKind;
+use crate::models::{SubmitResult, Comment, CommentStatus};
+use crate::remote::platform::{unsupported, ChangeRef, PlatformReview, ReviewPlatform, ReviewVote};
+use crate::remote::gerrit_client::RetryConfig;
+use crate::remote::http_client::HttpClientFactory;
+use crate::remote::traffic_control::{send_with_retries, CircuitBreakerConfig, RequestPolicy};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+const PAGE_SIZE: u32 = 100;
+
+pub struct GitHubClient {
+    api_url: String,
+    token: Option<String>,
+    http: HttpClientFactory,
+    retry_config: RetryConfig,
+    circuit_breaker: CircuitBreakerConfig,
+}
+
+/// Outcome of a submitted review
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewEvent {
+    Approve,
+    RequestChanges,
+    Comment,
+}
+
+impl ReviewEvent {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "approve" => Some(ReviewEvent::Approve),
+            "request_changes" => Some(ReviewEvent::RequestChanges),
+            "comment" => Some(ReviewEvent::Comment),
+            _ => None,
+        }
+    }
+}
+
+/// Which version of the file a line number refers to
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DiffSide {
+    Left,
+    #[default]
+    Right,
+}
+
+/// A line or multi-line comment of a review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewLineComment {
+    pub path: String,
+    pub body: String,
+    /// Last line of the commented range
+    pub line: u32,
+    pub side: DiffSide,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_side: Option<DiffSide>,
+}
+
+impl ReviewLineComment {
+    pub fn line(path: &str, line: u32, body: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            body: body.to_string(),
+            line,
+            side: DiffSide::Right,
+            start_line: None,
+            start_side: None,
+        }
+    }
+}
+
+/// A pull request comment as returned by GitHub
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestComment {
+    pub id: u64,
+    pub path: String,
+    pub body: String,
+    pub author: String,
+    pub created_at: String,
+    pub line: Option<u32>,
+    pub start_line: Option<u32>,
+    pub side: Option<DiffSide>,
+    pub in_reply_to_id: Option<u64>,
+    pub commit_id: String,
+}
+
+/// A root comment and its replies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewThread {
+    pub root_id: u64,
+    pub path: String,
+    /// `None` when the commented code is no longer in the diff
+    pub line: Option<u32>,
+    pub start_line: Option<u32>,
+    pub comments: Vec<PullRequestComment>,
+}
+
+impl ReviewThread {
+    pub fn is_outdated(&self) -> bool {
+        self.line.is_none()
+    }
+}
+
+impl GitHubClient {
+    /// Client for github.com; GitHub Enterprise uses `https://<host>/api/v3`
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token: None,
+            http: HttpClientFactory::default(),
+            retry_config: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+
+    /// Derive the API URL from the web URL of a GitHub or GitHub Enterprise server
+    pub fn for_web_url(web_url: &str) -> Self {
+        let web_url = web_url.trim_end_matches('/');
+        let host = reqwest::Url::parse(web_url).ok()
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase()));
+        match host.as_deref() {
+            Some("github.com") | Some("www.github.com") | Some("api.github.com") | None => Self::new(GITHUB_API_URL),
+            Some(_) => Self::new(&format!("{}/api/v3", web_url)),
+        }
+    }
+
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn with_network(mut self, settings: NetworkSettings) -> Self {
+        self.http = HttpClientFactory::new(settings);
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Import a pull request with its files, patches and commits.
+    ///
+    /// Each commit becomes a patch set; the head commit is the current one.
+    pub async fn import_pull_request(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        instance_id: &str,
+    ) -> Result<GerritChange, HyperReviewError> {
+        info!("Importing GitHub pull request {}/{}#{}", owner, repo, number);
+        let base = format!("/repos/{}/{}/pulls/{}", owner, repo, number);
+
+        let pr: serde_json::Value = self.get_json(&base).await?;
+        let files: Vec<serde_json::Value> = self.get_paginated(&format!("{}/files", base)).await?;
+        let commits: Vec<serde_json::Value> = self.get_paginated(&format!("{}/commits", base)).await?;
+
+        Ok(convert_pull_request(&pr, &files, &commits, owner, repo, instance_id))
+    }
+
+    /// Changed files of a pull request with their patches
+    pub async fn list_pull_request_files(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<GerritFile>, HyperReviewError> {
+        let path = format!("/repos/{}/{}/pulls/{}/files", owner, repo, number);
+        let files: Vec<serde_json::Value> = self.get_paginated(&path).await?;
+        let change_id = format!("{}/{}#{}", owner, repo, number);
+        Ok(files.iter().map(|file| file_from_github(file, &change_id, "")).collect())
+    }
+
+    /// Content of a file at a commit
+    pub async fn get_file_content(&self, owner: &str, repo: &str, path: &str, git_ref: &str) -> Result<String, HyperReviewError> {
+        let encoded_path = path.split('/').map(|part| urlencoding::encode(part).into_owned()).collect::<Vec<_>>().join("/");
+        let file: serde_json::Value = self.get_json(&format!(
+            "/repos/{}/{}/contents/{}?ref={}", owner, repo, encoded_path, urlencoding::encode(git_ref)
+        )).await?;
+
+        let encoded: String = str_field(&file, "content").split_whitespace().collect();
+        let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)
+            .map_err(|e| HyperReviewError::other(format!("Failed to decode {}: {}", path, e)))?;
+        String::from_utf8(bytes).map_err(|_| HyperReviewError::other(format!("{} is not a text file", path)))
+    }
+
+    /// Create a pending review holding the comments; it stays invisible to others until submitted
+    pub async fn create_pending_review(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        commit_id: Option<&str>,
+        comments: &[ReviewLineComment],
+    ) -> Result<u64, HyperReviewError> {
+        let path = format!("/repos/{}/{}/pulls/{}/reviews", owner, repo, number);
+        let mut payload = json!({ "comments": comments });
+        if let Some(commit_id) = commit_id {
+            payload["commit_id"] = json!(commit_id);
+        }
+        let review: serde_json::Value = self.send_json(reqwest::Method::POST, &path, Some(payload)).await?;
+        review.get("id").and_then(|v| v.as_u64())
+            .ok_or_else(|| HyperReviewError::other("GitHub returned a review without id".to_string()))
+    }
+
+    /// Submit a pending review with its event and summary
+    pub async fn submit_pending_review(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        review_id: u64,
+        event: ReviewEvent,
+        body: &str,
+    ) -> Result<String, HyperReviewError> {
+        let path = format!("/repos/{}/{}/pulls/{}/reviews/{}/events", owner, repo, number, review_id);
+        let review: serde_json::Value = self.send_json(
+            reqwest::Method::POST,
+            &path,
+            Some(json!({ "event": event, "body": body })),
+        ).await?;
+        Ok(review.get("html_url").and_then(|v| v.as_str()).unwrap_or_default().to_string())
+    }
+
+    /// Submit a complete review: line comments first as a pending review, then the event
+    pub async fn submit_review(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        event: ReviewEvent,
+        body: &str,
+        comments: &[ReviewLineComment],
+    ) -> Result<SubmitResult, HyperReviewError> {
+        info!("Submitting {:?} review with {} comments to {}/{}#{}", event, comments.len(), owner, repo, number);
+
+        if !self.is_configured() {
+            return Err(HyperReviewError::config("GitHub token not configured".to_string()));
+        }
+        if event == ReviewEvent::RequestChanges && body.trim().is_empty() {
+            return Err(HyperReviewError::validation("Requesting changes needs a review summary".to_string(), Some("body".to_string())));
+        }
+        if event == ReviewEvent::Comment && body.trim().is_empty() && comments.is_empty() {
+            return Err(HyperReviewError::validation("A review without a vote needs a summary or line comments".to_string(), Some("body".to_string())));
+        }
+
+        let review_id = self.create_pending_review(owner, repo, number, None, comments).await?;
+        let url = self.submit_pending_review(owner, repo, number, review_id, event, body).await?;
+
+        Ok(SubmitResult {
+            success: true,
+            message: format!("Submitted {} comments to GitHub PR #{}", comments.len(), number),
+            external_id: Some(format!("github-review-{}", review_id)),
+            url: Some(url).filter(|u| !u.is_empty()),
+        })
+    }
+
+    /// Read review comments grouped into threads
+    pub async fn list_review_threads(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<ReviewThread>, HyperReviewError> {
+        let path = format!("/repos/{}/{}/pulls/{}/comments", owner, repo, number);
+        let raw: Vec<serde_json::Value> = self.get_paginated(&path).await?;
+        let comments = raw.iter().map(parse_comment).collect::<Result<Vec<_>, _>>()?;
+        Ok(group_threads(comments))
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, HyperReviewError> {
+        self.send_json(reqwest::Method::GET, path, None).await
+    }
+
+    async fn send_json<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, HyperReviewError> {
+        let (_, text) = self.send(method, &format!("{}{}", self.api_url, path), body).await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Follow `Link: <...>; rel="next"` until all pages are read
+    async fn get_paginated<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, HyperReviewError> {
+        let mut items = Vec::new();
+        let mut next = Some(format!("{}{}?per_page={}", self.api_url, path, PAGE_SIZE));
+        while let Some(url) = next {
+            let (headers, text) = self.send(reqwest::Method::GET, &url, None).await?;
+            let batch: Vec<T> = serde_json::from_str(&text)?;
+            items.extend(batch);
+            next = headers.get(reqwest::header::LINK)
+                .and_then(|v| v.to_str().ok())
+                .and_then(next_link);
+        }
+        Ok(items)
+    }
+
+    /// Send a request, retrying throttled and transient failures
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<(reqwest::header::HeaderMap, String), HyperReviewError> {
+        let client = self.http.async_client()?;
+        let policy = RequestPolicy::new(&self.retry_config, &self.circuit_breaker)
+            .with_throttling(secondary_rate_limited);
+
+        let response = send_with_retries(&policy, &method, url, || {
+            let mut request = client.request(method.clone(), url)
+                .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+                .header("X-GitHub-Api-Version", "2022-11-28");
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+            if let Some(body) = &body {
+                request = request.json(body);
+            }
+            debug!("GitHub {} {}", method, url);
+            request
+        })
+        .await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response.text().await?;
+
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(HyperReviewError::authentication_required(
+                self.api_url.clone(),
+                "GitHub rejected the access token".to_string(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(HyperReviewError::network_with_status(
+                format!("GitHub {} {} failed: HTTP {}: {}", method, url, status, text),
+                status.as_u16(),
+            ));
+        }
+        Ok((headers, text))
+    }
+}
+
+impl From<&Comment> for ReviewLineComment {
+    fn from(comment: &Comment) -> Self {
+        ReviewLineComment::line(&comment.file_path, comment.line_number, &comment.content)
+    }
+}
+
+/// Secondary rate limits come back as 403 with Retry-After or an exhausted quota
+fn secondary_rate_limited(status: reqwest::StatusCode, headers: &reqwest::header::HeaderMap) -> bool {
+    status == reqwest::StatusCode::FORBIDDEN
+        && (headers.contains_key(reqwest::header::RETRY_AFTER)
+            || headers.get("x-ratelimit-remaining").and_then(|v| v.to_str().ok()) == Some("0"))
+}
+
+/// Extract the `rel="next"` target of a `Link` header
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|part| {
+        let (target, params) = part.split_once(';')?;
+        params.split(';').any(|p| p.trim() == r#"rel="next""#)
+            .then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+fn str_field(value: &serde_json::Value, key: &str) -> String {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+}
+
+fn u32_field(value: &serde_json::Value, key: &str) -> u32 {
+    value.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as u32
+}
+
+fn parse_user(value: Option<&serde_json::Value>) -> GerritUser {
+    let value = value.cloned().unwrap_or_default();
+    let login = str_field(&value, "login");
+    GerritUser {
+        account_id: u32_field(&value, "id"),
+        name: if login.is_empty() { "Unknown".to_string() } else { login.clone() },
+        email: value.get("email").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        username: Some(login).filter(|l| !l.is_empty()),
+        avatar_url: value.get("avatar_url").and_then(|v| v.as_str()).map(|s| s.to_string()),
+    }
+}
+
+fn pull_request_status(pr: &serde_json::Value) -> ChangeStatus {
+    let merged = pr.get("merged").and_then(|v| v.as_bool()).unwrap_or(false)
+        || pr.get("merged_at").is_some_and(|v| !v.is_null());
+    match str_field(pr, "state").as_str() {
+        "closed" if merged => ChangeStatus::Merged,
+        "closed" => ChangeStatus::Abandoned,
+        _ if pr.get("draft").and_then(|v| v.as_bool()).unwrap_or(false) => ChangeStatus::Draft,
+        _ => ChangeStatus::New,
+    }
+}
+
+fn file_change_type(status: &str) -> FileChangeType {
+    match status {
+        "added" => FileChangeType::Added,
+        "removed" => FileChangeType::Deleted,
+        "renamed" => FileChangeType::Renamed,
+        "copied" => FileChangeType::Copied,
+        _ => FileChangeType::Modified,
+    }
+}
+
+fn file_from_github(file: &serde_json::Value, change_id: &str, patch_set_id: &str) -> GerritFile {
+    let status = str_field(file, "status");
+    let patch = file.get("patch").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let inserted = u32_field(file, "additions");
+    let deleted = u32_field(file, "deletions");
+    GerritFile {
+        id: uuid::Uuid::new_v4().to_string(),
+        change_id: change_id.to_string(),
+        patch_set_id: patch_set_id.to_string(),
+        file_path: str_field(file, "filename"),
+        old_path: file.get("previous_filename").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        change_type: file_change_type(&status),
+        status: FileStatus::Unreviewed,
+        lines_inserted: inserted,
+        lines_deleted: deleted,
+        size_delta: inserted as i32 - deleted as i32,
+        size_new: 0,
+        // GitHub omits the patch for binary files and very large diffs
+        is_binary: patch.is_none() && (inserted + deleted) == 0,
+        content_type: "text/plain".to_string(),
+        diff_content: patch,
+        review_progress: ReviewProgress::default(),
+        last_reviewed: None,
+    }
+}
+
+fn convert_pull_request(
+    pr: &serde_json::Value,
+    files: &[serde_json::Value],
+    commits: &[serde_json::Value],
+    owner: &str,
+    repo: &str,
+    instance_id: &str,
+) -> GerritChange {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let number = pr.get("number").and_then(|v| v.as_u64()).unwrap_or_default();
+    let change_id = format!("{}/{}#{}", owner, repo, number);
+    let head_sha = pr.get("head").map(|h| str_field(h, "sha")).unwrap_or_default();
+    let owner_user = parse_user(pr.get("user"));
+
+    let patch_sets: Vec<PatchSet> = commits.iter().enumerate().map(|(index, commit)| {
+        let sha = str_field(commit, "sha");
+        let details = commit.get("commit").cloned().unwrap_or_default();
+        let author = commit.get("author").filter(|a| !a.is_null())
+            .map(|a| parse_user(Some(a)))
+            .unwrap_or_else(|| owner_user.clone());
+        PatchSet {
+            id: uuid::Uuid::new_v4().to_string(),
+            gerrit_patch_set_id: sha.clone(),
+            change_id: change_id.clone(),
+            revision: sha.clone(),
+            number: index as u32 + 1,
+            author,
+            commit_message: str_field(&details, "message"),
+            created: details.get("author").map(|a| str_field(a, "date")).unwrap_or_default(),
+            kind: PatchSetKind::Rework,
+            files: Vec::new(),
+            size_insertions: 0,
+            size_deletions: 0,
+            is_current: sha == head_sha,
+        }
+    }).collect();
+
+    let current_patch_set_id = patch_sets.iter().find(|ps| ps.is_current)
+        .map(|ps| ps.id.clone())
+        .unwrap_or_default();
+
+    let files: Vec<GerritFile> = files.iter()
+        .map(|file| file_from_github(file, &change_id, &current_patch_set_id))
+        .collect();
+
+    let mut metadata = HashMap::new();
+    metadata.insert("platform".to_string(), "github".to_string());
+    metadata.insert("number".to_string(), number.to_string());
+    metadata.insert("head_sha".to_string(), head_sha.clone());
+    if let Some(base) = pr.get("base") {
+        metadata.insert("base_sha".to_string(), str_field(base, "sha"));
+    }
+    if let Some(url) = pr.get("html_url").and_then(|v| v.as_str()) {
+        metadata.insert("url".to_string(), url.to_string());
+    }
+
+    GerritChange {
+        id: uuid::Uuid::new_v4().to_string(),
+        change_id,
+        instance_id: instance_id.to_string(),
+        project: format!("{}/{}", owner, repo),
+        branch: pr.get("base").map(|b| str_field(b, "ref")).unwrap_or_default(),
+        subject: str_field(pr, "title"),
+        status: pull_request_status(pr),
+        owner: owner_user,
+        created: str_field(pr, "created_at"),
+        updated: str_field(pr, "updated_at"),
+        insertions: files.iter().map(|f| f.lines_inserted).sum(),
+        deletions: files.iter().map(|f| f.lines_deleted).sum(),
+        current_revision: head_sha,
+        current_patch_set_num: patch_sets.len().max(1) as u32,
+        patch_sets,
+        total_files: files.len() as u32,
+        files,
+        reviewed_files: 0,
+        local_comments: 0,
+        remote_comments: u32_field(pr, "review_comments"),
+        import_status: ImportStatus::Imported,
+        last_sync: Some(now),
+        conflict_status: ConflictStatus::None,
+        metadata,
+    }
+}
+
+fn parse_comment(value: &serde_json::Value) -> Result<PullRequestComment, HyperReviewError> {
+    let id = value.get("id").and_then(|v| v.as_u64())
+        .ok_or_else(|| HyperReviewError::other("Review comment without id".to_string()))?;
+    let line_of = |key: &str| value.get(key).and_then(|v| v.as_u64()).map(|l| l as u32);
+    let side = value.get("side")
+        .and_then(|v| serde_json::from_value::<DiffSide>(v.clone()).ok());
+
+    Ok(PullRequestComment {
+        id,
+        path: str_field(value, "path"),
+        body: str_field(value, "body"),
+        author: value.get("user").map(|u| str_field(u, "login")).unwrap_or_default(),
+        created_at: str_field(value, "created_at"),
+        line: line_of("line"),
+        start_line: line_of("start_line"),
+        side,
+        in_reply_to_id: value.get("in_reply_to_id").and_then(|v| v.as_u64()),
+        commit_id: str_field(value, "commit_id"),
+    })
+}
+
+/// Replies point at the thread's root comment; keep threads in the order they were started
+fn group_threads(comments: Vec<PullRequestComment>) -> Vec<ReviewThread> {
+    let mut threads: Vec<ReviewThread> = Vec::new();
+    let mut index: HashMap<u64, usize> = HashMap::new();
+
+    for comment in comments {
+        match comment.in_reply_to_id.and_then(|root| index.get(&root).copied()) {
+            Some(position) => threads[position].comments.push(comment),
+            None => {
+                index.insert(comment.id, threads.len());
+                threads.push(ReviewThread {
+                    root_id: comment.id,
+                    path: comment.path.clone(),
+                    line: comment.line,
+                    start_line: comment.start_line,
+                    comments: vec![comment],
+                });
+            }
+        }
+    }
+
+    threads
+}
+
+/// Split `owner/repo` into its parts
+fn split_repository(project: &str) -> Result<(&str, &str), HyperReviewError> {
+    project.split_once('/').ok_or_else(|| HyperReviewError::validation(
+        format!("Expected owner/repo, got '{}'", project),
+        Some("project".to_string()),
+    ))
+}
+
+fn review_event(vote: Option<ReviewVote>) -> ReviewEvent {
+    match vote.map(ReviewVote::verdict) {
+        Some(ReviewVote::Approve) => ReviewEvent::Approve,
+        Some(ReviewVote::Reject) => ReviewEvent::RequestChanges,
+        _ => ReviewEvent::Comment,
+    }
+}
+
+#[async_trait]
+impl ReviewPlatform for GitHubClient {
+    fn kind(&self) -> PlatformKind {
+        PlatformKind::GitHub
+    }
+
+    async fn fetch_change(&self, change: &ChangeRef) -> Result<GerritChange, HyperReviewError> {
+        let (owner, repo) = split_repository(&change.project)?;
+        self.import_pull_request(owner, repo, change.number()?, "").await
+    }
+
+    async fn list_files(&self, change: &ChangeRef) -> Result<Vec<GerritFile>, HyperReviewError> {
+        let (owner, repo) = split_repository(&change.project)?;
+        self.list_pull_request_files(owner, repo, change.number()?).await
+    }
+
+    async fn get_file_content(&self, change: &ChangeRef, path: &str) -> Result<String, HyperReviewError> {
+        let (owner, repo) = split_repository(&change.project)?;
+        let pr: serde_json::Value = self.get_json(&format!("/repos/{}/{}/pulls/{}", owner, repo, change.number()?)).await?;
+        let head = pr.get("head").map(|h| str_field(h, "sha")).unwrap_or_default();
+        GitHubClient::get_file_content(self, owner, repo, path, &head).await
+    }
+
+    async fn list_comments(&self, change: &ChangeRef) -> Result<Vec<Comment>, HyperReviewError> {
+        let (owner, repo) = split_repository(&change.project)?;
+        let threads = self.list_review_threads(owner, repo, change.number()?).await?;
+        Ok(threads.iter().flat_map(|thread| {
+            thread.comments.iter().map(move |comment| Comment {
+                id: comment.id.to_string(),
+                file_path: comment.path.clone(),
+                line_number: comment.line.or(thread.line).unwrap_or(0),
+                content: comment.body.clone(),
+                author: comment.author.clone(),
+                created_at: comment.created_at.clone(),
+                updated_at: comment.created_at.clone(),
+                status: CommentStatus::Submitted,
+                parent_id: comment.in_reply_to_id.map(|_| thread.root_id.to_string()),
+                tags: if thread.is_outdated() { vec!["outdated".to_string()] } else { Vec::new() },
+            })
+        }).collect())
+    }
+
+    async fn post_review(&self, change: &ChangeRef, review: &PlatformReview) -> Result<SubmitResult, HyperReviewError> {
+        let (owner, repo) = split_repository(&change.project)?;
+        let mut body = review.message.clone();
+        let mut line_comments = Vec::new();
+        for comment in &review.comments {
+            if comment.file_path.is_empty() || comment.line_number == 0 {
+                if !body.is_empty() {
+                    body.push_str("\n\n");
+                }
+                body.push_str(&comment.content);
+            } else {
+                line_comments.push(ReviewLineComment::from(comment));
+            }
+        }
+        self.submit_review(owner, repo, change.number()?, review_event(review.vote), &body, &line_comments).await
+    }
+
+    async fn vote(&self, change: &ChangeRef, vote: ReviewVote) -> Result<(), HyperReviewError> {
+        let vote = vote.verdict();
+        if vote == ReviewVote::Neutral {
+            return Err(unsupported(PlatformKind::GitHub, "neutral votes without a comment"));
+        }
+        let (owner, repo) = split_repository(&change.project)?;
+        let body = if vote == ReviewVote::Reject { "Changes requested" } else { "" };
+        self.submit_review(owner, repo, change.number()?, review_event(Some(vote)), body, &[]).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::mock_server::{MockResponse, MockServer};
+
+    const PR_JSON: &str = r#"{"number":12,"title":"Speed up diff","state":"open","draft":false,
+        "user":{"id":5,"login":"octo","avatar_url":"https://avatars/5"},
+        "base":{"ref":"main","sha":"base1"},"head":{"ref":"feature","sha":"head2"},
+        "html_url":"https://github.com/acme/app/pull/12","review_comments":1,
+        "created_at":"2025-01-01T00:00:00Z","updated_at":"2025-01-02T00:00:00Z"}"#;
+
+    #[test]
+    fn test_next_link() {
+        let header = r#"<https://api.github.com/x?page=2>; rel="next", <https://api.github.com/x?page=5>; rel="last""#;
+        assert_eq!(next_link(header).as_deref(), Some("https://api.github.com/x?page=2"));
+        assert_eq!(next_link(r#"<https://api.github.com/x?page=1>; rel="prev""#), None);
+    }
+
+    #[test]
+    fn test_for_web_url() {
+        assert_eq!(GitHubClient::for_web_url("https://github.com").api_url, "https://api.github.com");
+        assert_eq!(GitHubClient::for_web_url("https://ghe.corp/").api_url, "https://ghe.corp/api/v3");
+    }
+
+    #[tokio::test]
+    async fn test_import_pull_request() {
+        let server = MockServer::start(|request| {
+            if request.path.starts_with("/repos/acme/app/pulls/12/files") {
+                MockResponse::json(r#"[
+                    {"filename":"src/diff.rs","status":"modified","additions":3,"deletions":1,"patch":"@@ -1 +1,3 @@"},
+                    {"filename":"logo.png","status":"added","additions":0,"deletions":0},
+                    {"filename":"src/new.rs","previous_filename":"src/old.rs","status":"renamed","additions":0,"deletions":0,"patch":""}]"#)
+            } else if request.path.starts_with("/repos/acme/app/pulls/12/commits") {
+                MockResponse::json(r#"[
+                    {"sha":"c1","commit":{"message":"First","author":{"date":"2025-01-01"}},"author":null},
+                    {"sha":"head2","commit":{"message":"Second","author":{"date":"2025-01-02"}},"author":{"id":6,"login":"hub"}}]"#)
+            } else {
+                MockResponse::json(PR_JSON)
+            }
+        });
+        let client = GitHubClient::new(&server.base_url).with_token("ghp_x".to_string());
+
+        let change = client.import_pull_request("acme", "app", 12, "gh-1").await.unwrap();
+        assert_eq!(change.change_id, "acme/app#12");
+        assert_eq!(change.project, "acme/app");
+        assert_eq!(change.branch, "main");
+        assert_eq!(change.status, ChangeStatus::New);
+        assert_eq!(change.current_revision, "head2");
+        assert_eq!(change.current_patch_set_num, 2);
+        assert!(change.patch_sets[1].is_current);
+        assert_eq!(change.patch_sets[0].author.name, "octo");
+        assert_eq!(change.patch_sets[1].author.name, "hub");
+        assert_eq!(change.total_files, 3);
+        assert_eq!(change.insertions, 3);
+        assert!(change.files[1].is_binary);
+        assert_eq!(change.files[2].change_type, FileChangeType::Renamed);
+        assert_eq!(change.files[2].old_path.as_deref(), Some("src/old.rs"));
+        assert_eq!(change.metadata.get("base_sha").map(String::as_str), Some("base1"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("authorization"), Some("Bearer ghp_x"));
+        assert_eq!(requests[0].header("accept"), Some("application/vnd.github+json"));
+    }
+
+    #[tokio::test]
+    async fn test_submit_review_creates_pending_review_then_event() {
+        let server = MockServer::start(|request| {
+            if request.path.ends_with("/events") {
+                MockResponse::json(r#"{"id":99,"state":"CHANGES_REQUESTED","html_url":"https://github.com/acme/app/pull/12#review-99"}"#)
+            } else {
+                MockResponse::json(r#"{"id":99,"state":"PENDING"}"#)
+            }
+        });
+        let client = GitHubClient::new(&server.base_url).with_token("t".to_string());
+
+        let comments = vec![
+            ReviewLineComment::line("src/diff.rs", 4, "Off by one"),
+            ReviewLineComment {
+                start_line: Some(10),
+                start_side: Some(DiffSide::Right),
+                ..ReviewLineComment::line("src/diff.rs", 14, "Extract this block")
+            },
+        ];
+        let result = client.submit_review("acme", "app", 12, ReviewEvent::RequestChanges, "Needs work", &comments)
+            .await.unwrap();
+        assert_eq!(result.external_id.as_deref(), Some("github-review-99"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/repos/acme/app/pulls/12/reviews");
+        let pending: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert!(pending.get("event").is_none());
+        assert_eq!(pending["comments"][0]["side"], "RIGHT");
+        assert!(pending["comments"][0].get("start_line").is_none());
+        assert_eq!(pending["comments"][1]["start_line"], 10);
+        assert_eq!(pending["comments"][1]["line"], 14);
+
+        assert_eq!(requests[1].path, "/repos/acme/app/pulls/12/reviews/99/events");
+        let submitted: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(submitted["event"], "REQUEST_CHANGES");
+        assert_eq!(submitted["body"], "Needs work");
+    }
+
+    #[tokio::test]
+    async fn test_request_changes_needs_summary() {
+        let client = GitHubClient::new("http://127.0.0.1:9").with_token("t".to_string());
+        let err = client.submit_review("acme", "app", 1, ReviewEvent::RequestChanges, " ", &[]).await;
+        assert!(err.is_err());
+
+        // GitHub rejects a plain comment review with nothing in it
+        let empty = client.post_review(&ChangeRef::new("acme/app", 1), &PlatformReview::default()).await;
+        assert!(matches!(empty, Err(HyperReviewError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_list_review_threads() {
+        let server = MockServer::start(|_| MockResponse::json(r#"[
+            {"id":1,"path":"a.rs","body":"Why?","user":{"login":"rev"},"line":5,"side":"RIGHT","commit_id":"h"},
+            {"id":2,"path":"b.rs","body":"Stale","user":{"login":"rev"},"line":null,"commit_id":"old"},
+            {"id":3,"path":"a.rs","body":"Because","user":{"login":"octo"},"line":5,"in_reply_to_id":1,"commit_id":"h"}]"#));
+        let client = GitHubClient::new(&server.base_url);
+
+        let threads = client.list_review_threads("acme", "app", 12).await.unwrap();
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].comments.iter().map(|c| c.author.as_str()).collect::<Vec<_>>(), vec!["rev", "octo"]);
+        assert!(threads[1].is_outdated());
+    }
+}
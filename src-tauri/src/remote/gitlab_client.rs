@@ -1,18 +1,248 @@
 // GitLab API client for review submission
-// External system integration
+// Merge requests, diffs, versions, positioned discussions and approvals over the v4 REST API
 
-use crate::models::{SubmitResult, Comment};
+use async_trait::async_trait;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::errors::HyperReviewError;
+use crate::models::network::NetworkSettings;
+use crate::models::gerrit::{
+    ChangeStatus, ConflictStatus, FileChangeType, FileStatus, GerritChange, GerritFile,
+    GerritUser, ImportStatus, ReviewProgress,
+};
+use crate::models::platform::PlatformKind;
+use crate::models::{SubmitResult, Comment, CommentStatus};
+use crate::remote::platform::{unsupported, ChangeRef, PlatformReview, ReviewPlatform, ReviewVote};
+use crate::remote::gerrit_client::RetryConfig;
+use crate::remote::http_client::HttpClientFactory;
+use crate::remote::traffic_control::{send_with_retries, CircuitBreakerConfig, RequestPolicy};
+
+/// Number of items requested per page for list endpoints
+const PAGE_SIZE: u32 = 100;
 
 pub struct GitLabClient {
     base_url: String,
     token: Option<String>,
+    http: HttpClientFactory,
+    retry_config: RetryConfig,
+    circuit_breaker: CircuitBreakerConfig,
+}
+
+/// SHAs identifying the diff a position refers to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiffRefs {
+    pub base_sha: String,
+    pub start_sha: String,
+    pub head_sha: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequestInfo {
+    pub iid: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub author: String,
+    pub web_url: Option<String>,
+    pub sha: Option<String>,
+    pub diff_refs: Option<DiffRefs>,
+}
+
+/// One file of a merge request diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequestDiff {
+    pub old_path: String,
+    pub new_path: String,
+    #[serde(default)]
+    pub diff: String,
+    #[serde(default)]
+    pub new_file: bool,
+    #[serde(default)]
+    pub renamed_file: bool,
+    #[serde(default)]
+    pub deleted_file: bool,
+}
+
+/// A CI pipeline run for a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub id: u64,
+    pub status: String,
+    #[serde(rename = "ref", default)]
+    pub git_ref: Option<String>,
+    pub sha: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub web_url: Option<String>,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+/// A pushed revision of a merge request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeRequestVersion {
+    pub id: u64,
+    pub head_commit_sha: String,
+    pub base_commit_sha: String,
+    pub start_commit_sha: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub state: Option<String>,
+}
+
+impl MergeRequestVersion {
+    pub fn diff_refs(&self) -> DiffRefs {
+        DiffRefs {
+            base_sha: self.base_commit_sha.clone(),
+            start_sha: self.start_commit_sha.clone(),
+            head_sha: self.head_commit_sha.clone(),
+        }
+    }
+}
+
+/// Where a line comment is anchored in a merge request diff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffPosition {
+    #[serde(flatten)]
+    pub refs: DiffRefs,
+    pub position_type: String,
+    pub old_path: String,
+    pub new_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_line: Option<u32>,
+}
+
+impl DiffPosition {
+    /// Position on a line of the new version of a file; `old_path` differs when the file was renamed
+    pub fn new_line(refs: DiffRefs, old_path: &str, new_path: &str, line: u32) -> Self {
+        Self {
+            refs,
+            position_type: "text".to_string(),
+            old_path: old_path.to_string(),
+            new_path: new_path.to_string(),
+            old_line: None,
+            new_line: Some(line),
+        }
+    }
+
+    /// Position on a line that was removed from the old version of a file
+    pub fn old_line(refs: DiffRefs, old_path: &str, new_path: &str, line: u32) -> Self {
+        Self {
+            refs,
+            position_type: "text".to_string(),
+            old_path: old_path.to_string(),
+            new_path: new_path.to_string(),
+            old_line: Some(line),
+            new_line: None,
+        }
+    }
+
+    /// Position of `line` in a file of the merge request. Lines the diff adds sit on the
+    /// new version only and lines of a deleted file on the old version only; every other
+    /// line is unchanged and GitLab expects both line numbers for it.
+    pub fn for_line(refs: DiffRefs, diffs: &[MergeRequestDiff], new_path: &str, line: u32) -> Self {
+        let Some(diff) = diffs.iter().find(|diff| diff.new_path == new_path) else {
+            return Self::new_line(refs, new_path, new_path, line);
+        };
+        if diff.deleted_file {
+            return Self::old_line(refs, &diff.old_path, new_path, line);
+        }
+        let mut position = Self::new_line(refs, &diff.old_path, new_path, line);
+        if !diff.new_file {
+            position.old_line = unchanged_old_line(&diff.diff, line);
+        }
+        position
+    }
+}
+
+/// Old line number of line `line` of the new version, or `None` when the diff adds that line
+fn unchanged_old_line(diff: &str, line: u32) -> Option<u32> {
+    // Next old and new line inside the hunk that could contain `line`
+    let mut cursor: Option<(u32, u32)> = None;
+    for text in diff.lines() {
+        if let Some(header) = text.strip_prefix("@@ ") {
+            let Some((old_start, new_start)) = parse_hunk_header(header) else { continue };
+            if new_start > line {
+                break;
+            }
+            cursor = Some((old_start, new_start));
+            continue;
+        }
+        let Some((old, new)) = cursor.as_mut() else { continue };
+        match text.chars().next() {
+            Some('+') if *new == line => return None,
+            Some('+') => *new += 1,
+            Some('-') => *old += 1,
+            Some('\\') => {}
+            _ if *new == line => return Some(*old),
+            _ => {
+                *old += 1;
+                *new += 1;
+            }
+        }
+    }
+    // Outside the hunks, unchanged lines keep the shift of the hunks before them
+    let (old, new) = cursor.unwrap_or((line, line));
+    Some((line as i64 + old as i64 - new as i64).max(1) as u32)
+}
+
+/// First old and new line of a hunk from `-a,b +c,d @@`. An empty side names the line
+/// before the hunk, so the line after it is returned instead.
+fn parse_hunk_header(header: &str) -> Option<(u32, u32)> {
+    let mut ranges = header.split_whitespace();
+    let start = |range: &str| -> Option<u32> {
+        let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+        let start: u32 = start.parse().ok()?;
+        Some(if count == "0" { start + 1 } else { start })
+    };
+    let old = start(ranges.next()?.strip_prefix('-')?)?;
+    let new = start(ranges.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscussionNote {
+    pub id: u64,
+    pub body: String,
+    pub author: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub resolvable: bool,
+    #[serde(default)]
+    pub resolved: bool,
+    #[serde(default)]
+    pub position: Option<DiffPosition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Discussion {
+    pub id: String,
+    pub notes: Vec<DiscussionNote>,
+}
+
+impl Discussion {
+    /// A discussion is resolved once all of its resolvable notes are
+    pub fn is_resolved(&self) -> bool {
+        let mut resolvable = self.notes.iter().filter(|n| n.resolvable).peekable();
+        resolvable.peek().is_some() && resolvable.all(|n| n.resolved)
+    }
 }
 
 impl GitLabClient {
     pub fn new(base_url: &str) -> Self {
         Self {
-            base_url: base_url.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
             token: None,
+            http: HttpClientFactory::default(),
+            retry_config: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
         }
     }
 
@@ -21,60 +251,784 @@ impl GitLabClient {
         self
     }
 
-    /// Submit review comments to GitLab merge request
-    pub fn submit_review(
+    pub fn with_network(mut self, settings: NetworkSettings) -> Self {
+        self.http = HttpClientFactory::new(settings);
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Check if client is configured
+    pub fn is_configured(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// List merge requests of a project, e.g. with state `opened`
+    pub async fn list_merge_requests(
+        &self,
+        project_id: &str,
+        state: Option<&str>,
+    ) -> Result<Vec<MergeRequestInfo>, HyperReviewError> {
+        info!("Listing merge requests of GitLab project {}", project_id);
+        let mut path = format!("/projects/{}/merge_requests", encode_project(project_id));
+        if let Some(state) = state {
+            path.push_str(&format!("?state={}", urlencoding::encode(state)));
+        }
+        let raw: Vec<serde_json::Value> = self.get_paginated(&path).await?;
+        raw.iter().map(parse_merge_request).collect()
+    }
+
+    /// Pipelines run for a commit, newest first
+    pub async fn list_pipelines(&self, project_id: &str, sha: &str) -> Result<Vec<Pipeline>, HyperReviewError> {
+        debug!("Listing pipelines of GitLab project {} for {}", project_id, sha);
+        let path = format!(
+            "/projects/{}/pipelines?sha={}&order_by=id&sort=desc",
+            encode_project(project_id),
+            urlencoding::encode(sha)
+        );
+        self.get_json(&path).await
+    }
+
+    /// Get merge request details
+    pub async fn get_merge_request(&self, project_id: &str, mr_iid: u64) -> Result<MergeRequestInfo, HyperReviewError> {
+        info!("Fetching MR {} from project {}", mr_iid, project_id);
+        let path = format!("/projects/{}/merge_requests/{}", encode_project(project_id), mr_iid);
+        let raw: serde_json::Value = self.get_json(&path).await?;
+        parse_merge_request(&raw)
+    }
+
+    /// Get the per-file diffs of the latest version of a merge request
+    pub async fn get_merge_request_diffs(&self, project_id: &str, mr_iid: u64) -> Result<Vec<MergeRequestDiff>, HyperReviewError> {
+        let path = format!("/projects/{}/merge_requests/{}/diffs", encode_project(project_id), mr_iid);
+        self.get_paginated(&path).await
+    }
+
+    /// List the pushed versions of a merge request, newest first
+    pub async fn get_merge_request_versions(&self, project_id: &str, mr_iid: u64) -> Result<Vec<MergeRequestVersion>, HyperReviewError> {
+        let path = format!("/projects/{}/merge_requests/{}/versions", encode_project(project_id), mr_iid);
+        self.get_json(&path).await
+    }
+
+    /// Raw content of a file at a commit or branch
+    pub async fn get_file_raw(&self, project_id: &str, file_path: &str, git_ref: &str) -> Result<String, HyperReviewError> {
+        let path = format!(
+            "/projects/{}/repository/files/{}/raw?ref={}",
+            encode_project(project_id), urlencoding::encode(file_path), urlencoding::encode(git_ref)
+        );
+        let (_, text) = self.send(reqwest::Method::GET, &path, None).await?;
+        Ok(text)
+    }
+
+    pub async fn list_discussions(&self, project_id: &str, mr_iid: u64) -> Result<Vec<Discussion>, HyperReviewError> {
+        let path = format!("/projects/{}/merge_requests/{}/discussions", encode_project(project_id), mr_iid);
+        let raw: Vec<serde_json::Value> = self.get_paginated(&path).await?;
+        raw.iter().map(parse_discussion).collect()
+    }
+
+    /// Start a discussion, anchored to a diff line when a position is given
+    pub async fn create_discussion(
+        &self,
+        project_id: &str,
+        mr_iid: u64,
+        body: &str,
+        position: Option<&DiffPosition>,
+    ) -> Result<Discussion, HyperReviewError> {
+        let path = format!("/projects/{}/merge_requests/{}/discussions", encode_project(project_id), mr_iid);
+        let mut payload = json!({ "body": body });
+        if let Some(position) = position {
+            payload["position"] = serde_json::to_value(position)?;
+        }
+        let raw: serde_json::Value = self.send_json(reqwest::Method::POST, &path, Some(payload)).await?;
+        parse_discussion(&raw)
+    }
+
+    pub async fn reply_to_discussion(
+        &self,
+        project_id: &str,
+        mr_iid: u64,
+        discussion_id: &str,
+        body: &str,
+    ) -> Result<(), HyperReviewError> {
+        let path = format!(
+            "/projects/{}/merge_requests/{}/discussions/{}/notes",
+            encode_project(project_id), mr_iid, discussion_id
+        );
+        self.send(reqwest::Method::POST, &path, Some(json!({ "body": body }))).await?;
+        Ok(())
+    }
+
+    /// Resolve or reopen a discussion thread
+    pub async fn resolve_discussion(
+        &self,
+        project_id: &str,
+        mr_iid: u64,
+        discussion_id: &str,
+        resolved: bool,
+    ) -> Result<(), HyperReviewError> {
+        let path = format!(
+            "/projects/{}/merge_requests/{}/discussions/{}?resolved={}",
+            encode_project(project_id), mr_iid, discussion_id, resolved
+        );
+        self.send(reqwest::Method::PUT, &path, None).await?;
+        Ok(())
+    }
+
+    /// Approve a merge request; `sha` guards against approving a newer push than the one reviewed
+    pub async fn approve(&self, project_id: &str, mr_iid: u64, sha: Option<&str>) -> Result<(), HyperReviewError> {
+        let path = format!("/projects/{}/merge_requests/{}/approve", encode_project(project_id), mr_iid);
+        let payload = sha.map(|sha| json!({ "sha": sha }));
+        self.send(reqwest::Method::POST, &path, payload).await?;
+        Ok(())
+    }
+
+    pub async fn delete_discussion_note(
+        &self,
+        project_id: &str,
+        mr_iid: u64,
+        discussion_id: &str,
+        note_id: u64,
+    ) -> Result<(), HyperReviewError> {
+        let path = format!(
+            "/projects/{}/merge_requests/{}/discussions/{}/notes/{}",
+            encode_project(project_id), mr_iid, discussion_id, note_id
+        );
+        self.send(reqwest::Method::DELETE, &path, None).await?;
+        Ok(())
+    }
+
+    pub async fn unapprove(&self, project_id: &str, mr_iid: u64) -> Result<(), HyperReviewError> {
+        let path = format!("/projects/{}/merge_requests/{}/unapprove", encode_project(project_id), mr_iid);
+        self.send(reqwest::Method::POST, &path, None).await?;
+        Ok(())
+    }
+
+    /// Submit review comments to GitLab merge request.
+    ///
+    /// Comments with a file and line become diff discussions positioned by the
+    /// file's diff; the others are posted as general discussions. When a post
+    /// fails, the discussions already created are deleted again so that retrying
+    /// the review does not duplicate them.
+    pub async fn submit_review(
         &self,
         project_id: &str,
         merge_request_iid: u64,
         comments: Vec<Comment>,
-    ) -> Result<SubmitResult, Box<dyn std::error::Error>> {
-        log::info!("Submitting review to GitLab project {} MR {}", project_id, merge_request_iid);
+    ) -> Result<SubmitResult, HyperReviewError> {
+        self.publish_review(project_id, merge_request_iid, &comments, "", None).await
+    }
+
+    /// Post the line comments, then the summary, then the vote. A failure at any step
+    /// removes the discussions posted before it, so a queued retry starts clean.
+    async fn publish_review(
+        &self,
+        project_id: &str,
+        merge_request_iid: u64,
+        comments: &[Comment],
+        message: &str,
+        vote: Option<ReviewVote>,
+    ) -> Result<SubmitResult, HyperReviewError> {
+        info!("Submitting review to GitLab project {} MR {}", project_id, merge_request_iid);
+
+        if !self.is_configured() {
+            return Err(HyperReviewError::config("GitLab token not configured".to_string()));
+        }
+        if let Some(ReviewVote::Reject) = vote.map(ReviewVote::verdict) {
+            return Err(unsupported(PlatformKind::GitLab, "rejecting merge requests"));
+        }
 
-        // Check if we have authentication
-        let token = self.token.as_ref()
-            .ok_or("GitLab token not configured")?;
+        let mr = self.get_merge_request(project_id, merge_request_iid).await?;
+        let refs = mr.diff_refs.clone().ok_or_else(|| HyperReviewError::other(
+            format!("Merge request !{} has no diff refs yet", merge_request_iid),
+        ))?;
+        let is_positioned = |comment: &Comment| !comment.file_path.is_empty() && comment.line_number > 0;
+        let diffs = if comments.iter().any(is_positioned) {
+            self.get_merge_request_diffs(project_id, merge_request_iid).await?
+        } else {
+            Vec::new()
+        };
 
-        // In production, this would make actual HTTP requests
-        // For now, return a mock success response
-        log::info!("Would submit {} comments to GitLab", comments.len());
+        let mut discussions: Vec<(&str, Option<DiffPosition>)> = comments.iter()
+            .map(|comment| (
+                comment.content.as_str(),
+                is_positioned(comment).then(|| DiffPosition::for_line(
+                    refs.clone(),
+                    &diffs,
+                    &comment.file_path,
+                    comment.line_number,
+                )),
+            ))
+            .collect();
+        if !message.trim().is_empty() {
+            discussions.push((message, None));
+        }
+
+        let mut posted = Vec::new();
+        for (body, position) in &discussions {
+            match self.create_discussion(project_id, merge_request_iid, body, position.as_ref()).await {
+                Ok(discussion) => posted.push(discussion),
+                Err(e) => {
+                    return Err(self.roll_back_discussions(project_id, merge_request_iid, &posted, discussions.len(), e).await);
+                }
+            }
+        }
+        if let Some(vote) = vote {
+            if let Err(e) = self.apply_vote(project_id, merge_request_iid, vote, Some(&refs.head_sha)).await {
+                return Err(self.roll_back_discussions(project_id, merge_request_iid, &posted, discussions.len(), e).await);
+            }
+        }
 
         Ok(SubmitResult {
             success: true,
-            message: format!("Submitted {} comments to GitLab MR #{}", comments.len(), merge_request_iid),
+            message: format!("Submitted {} comments to GitLab MR !{}", comments.len(), merge_request_iid),
             external_id: Some(format!("gitlab-mr-{}", merge_request_iid)),
-            url: Some(format!("{}/merge_requests/{}", self.base_url, merge_request_iid)),
+            url: mr.web_url.or_else(|| Some(format!("{}/merge_requests/{}", self.base_url, merge_request_iid))),
         })
     }
 
-    /// Get merge request details
-    pub fn get_merge_request(&self, project_id: &str, mr_iid: u64) -> Result<MergeRequestInfo, Box<dyn std::error::Error>> {
-        log::info!("Fetching MR {} from project {}", mr_iid, project_id);
-
-        // Mock response
-        Ok(MergeRequestInfo {
-            iid: mr_iid,
-            title: "Feature branch merge".to_string(),
-            description: None,
-            state: "opened".to_string(),
-            source_branch: "feature".to_string(),
-            target_branch: "main".to_string(),
-            author: "developer".to_string(),
+    /// GitLab only knows approvals; a neutral vote withdraws one
+    async fn apply_vote(
+        &self,
+        project_id: &str,
+        mr_iid: u64,
+        vote: ReviewVote,
+        sha: Option<&str>,
+    ) -> Result<(), HyperReviewError> {
+        match vote.verdict() {
+            ReviewVote::Approve => self.approve(project_id, mr_iid, sha).await,
+            ReviewVote::Reject => Err(unsupported(PlatformKind::GitLab, "rejecting merge requests")),
+            _ => self.unapprove(project_id, mr_iid).await,
+        }
+    }
+
+    /// Delete the discussions of a review that failed partway. Returns `cause` when all of
+    /// them were removed, otherwise an error naming the discussions left on the merge request.
+    async fn roll_back_discussions(
+        &self,
+        project_id: &str,
+        mr_iid: u64,
+        posted: &[Discussion],
+        total: usize,
+        cause: HyperReviewError,
+    ) -> HyperReviewError {
+        let mut remaining = Vec::new();
+        for discussion in posted {
+            let removed = match discussion.notes.first() {
+                Some(note) => match self.delete_discussion_note(project_id, mr_iid, &discussion.id, note.id).await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("Failed to remove discussion {} from MR !{}: {}", discussion.id, mr_iid, e);
+                        false
+                    }
+                },
+                None => false,
+            };
+            if !removed {
+                remaining.push(discussion.id.clone());
+            }
+        }
+
+        if remaining.is_empty() {
+            return cause;
+        }
+        HyperReviewError::other(format!(
+            "Review submission to MR !{} failed after posting {} of {} comments: {}. \
+             Discussions {} could not be removed and are still on the merge request",
+            mr_iid, posted.len(), total, cause, remaining.join(", ")
+        ))
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/v4{}", self.base_url, path)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, HyperReviewError> {
+        self.send_json(reqwest::Method::GET, path, None).await
+    }
+
+    async fn send_json<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, HyperReviewError> {
+        let (_, text) = self.send(method, path, body).await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Follow `X-Next-Page` until all pages of a list endpoint are read
+    async fn get_paginated<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, HyperReviewError> {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        let mut page = 1u32;
+        loop {
+            let paged = format!("{}{}per_page={}&page={}", path, separator, PAGE_SIZE, page);
+            let (headers, text) = self.send(reqwest::Method::GET, &paged, None).await?;
+            let batch: Vec<T> = serde_json::from_str(&text)?;
+            items.extend(batch);
+
+            match headers.get("x-next-page").and_then(|v| v.to_str().ok()).and_then(|v| v.parse::<u32>().ok()) {
+                Some(next) if next > page => page = next,
+                _ => return Ok(items),
+            }
+        }
+    }
+
+    /// Send a request, retrying throttled and transient failures
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<(reqwest::header::HeaderMap, String), HyperReviewError> {
+        let url = self.api_url(path);
+        let client = self.http.async_client()?;
+        let policy = RequestPolicy::new(&self.retry_config, &self.circuit_breaker);
+
+        let response = send_with_retries(&policy, &method, &url, || {
+            let mut request = client.request(method.clone(), &url);
+            if let Some(token) = &self.token {
+                request = request.header("PRIVATE-TOKEN", token.as_str());
+            }
+            if let Some(body) = &body {
+                request = request.json(body);
+            }
+            debug!("GitLab {} {}", method, url);
+            request
         })
+        .await?;
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response.text().await?;
+
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(HyperReviewError::authentication_required(
+                self.base_url.clone(),
+                "GitLab rejected the access token".to_string(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(HyperReviewError::network_with_status(
+                format!("GitLab {} {} failed: HTTP {}: {}", method, path, status, text),
+                status.as_u16(),
+            ));
+        }
+        Ok((headers, text))
     }
+}
 
-    /// Check if client is configured
-    pub fn is_configured(&self) -> bool {
-        self.token.is_some()
+/// Project IDs may be numeric or a `group/project` path, which must be encoded
+fn encode_project(project_id: &str) -> String {
+    urlencoding::encode(project_id).into_owned()
+}
+
+fn str_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+pub(crate) fn parse_merge_request(value: &serde_json::Value) -> Result<MergeRequestInfo, HyperReviewError> {
+    let iid = value.get("iid").and_then(|v| v.as_u64())
+        .ok_or_else(|| HyperReviewError::other("Merge request without iid".to_string()))?;
+    let diff_refs = value.get("diff_refs")
+        .filter(|v| !v.is_null())
+        .map(|v| serde_json::from_value::<DiffRefs>(v.clone()))
+        .transpose()?;
+
+    Ok(MergeRequestInfo {
+        iid,
+        title: str_field(value, "title").unwrap_or_default(),
+        description: str_field(value, "description").filter(|d| !d.is_empty()),
+        state: str_field(value, "state").unwrap_or_default(),
+        source_branch: str_field(value, "source_branch").unwrap_or_default(),
+        target_branch: str_field(value, "target_branch").unwrap_or_default(),
+        author: value.get("author").and_then(|a| str_field(a, "username")).unwrap_or_default(),
+        web_url: str_field(value, "web_url"),
+        sha: str_field(value, "sha"),
+        diff_refs,
+    })
+}
+
+pub(crate) fn parse_discussion(value: &serde_json::Value) -> Result<Discussion, HyperReviewError> {
+    let id = str_field(value, "id")
+        .ok_or_else(|| HyperReviewError::other("Discussion without id".to_string()))?;
+    let notes = value.get("notes").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+
+    let notes = notes.iter().map(|note| {
+        let position = note.get("position")
+            .filter(|p| p.get("position_type").and_then(|t| t.as_str()) == Some("text"))
+            .and_then(|p| serde_json::from_value::<DiffPosition>(p.clone()).ok());
+        DiscussionNote {
+            id: note.get("id").and_then(|v| v.as_u64()).unwrap_or_default(),
+            body: str_field(note, "body").unwrap_or_default(),
+            author: note.get("author").and_then(|a| str_field(a, "username")).unwrap_or_default(),
+            created_at: str_field(note, "created_at").unwrap_or_default(),
+            resolvable: note.get("resolvable").and_then(|v| v.as_bool()).unwrap_or(false),
+            resolved: note.get("resolved").and_then(|v| v.as_bool()).unwrap_or(false),
+            position,
+        }
+    }).collect();
+
+    Ok(Discussion { id, notes })
+}
+
+/// Convert one file of a merge request diff into the local file model
+pub(crate) fn file_from_diff(change_id: &str, patch_set_id: &str, diff: &MergeRequestDiff) -> GerritFile {
+    let (inserted, deleted) = diff.diff.lines().fold((0u32, 0u32), |(ins, del), line| {
+        if line.starts_with('+') && !line.starts_with("+++") {
+            (ins + 1, del)
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            (ins, del + 1)
+        } else {
+            (ins, del)
+        }
+    });
+    let change_type = if diff.new_file {
+        FileChangeType::Added
+    } else if diff.deleted_file {
+        FileChangeType::Deleted
+    } else if diff.renamed_file {
+        FileChangeType::Renamed
+    } else {
+        FileChangeType::Modified
+    };
+
+    GerritFile {
+        id: uuid::Uuid::new_v4().to_string(),
+        change_id: change_id.to_string(),
+        patch_set_id: patch_set_id.to_string(),
+        file_path: diff.new_path.clone(),
+        old_path: Some(diff.old_path.clone()).filter(|old| old != &diff.new_path),
+        change_type,
+        status: FileStatus::Unreviewed,
+        lines_inserted: inserted,
+        lines_deleted: deleted,
+        size_delta: inserted as i32 - deleted as i32,
+        size_new: 0,
+        is_binary: diff.diff.starts_with("Binary files"),
+        content_type: "text/plain".to_string(),
+        diff_content: Some(diff.diff.clone()).filter(|d| !d.is_empty()),
+        review_progress: ReviewProgress::default(),
+        last_reviewed: None,
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct MergeRequestInfo {
-    pub iid: u64,
-    pub title: String,
-    pub description: Option<String>,
-    pub state: String,
-    pub source_branch: String,
-    pub target_branch: String,
-    pub author: String,
+/// Convert a merge request and its files into the local change model
+pub(crate) fn change_from_merge_request(project: &str, mr: &MergeRequestInfo, files: Vec<GerritFile>) -> GerritChange {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let status = match mr.state.as_str() {
+        "merged" => ChangeStatus::Merged,
+        "closed" => ChangeStatus::Abandoned,
+        _ => ChangeStatus::New,
+    };
+    let head_sha = mr.diff_refs.as_ref().map(|r| r.head_sha.clone())
+        .or_else(|| mr.sha.clone())
+        .unwrap_or_default();
+
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert("iid".to_string(), mr.iid.to_string());
+    metadata.insert("source_branch".to_string(), mr.source_branch.clone());
+    if let Some(refs) = &mr.diff_refs {
+        metadata.insert("base_sha".to_string(), refs.base_sha.clone());
+        metadata.insert("start_sha".to_string(), refs.start_sha.clone());
+    }
+    if let Some(url) = &mr.web_url {
+        metadata.insert("url".to_string(), url.clone());
+    }
+
+    GerritChange {
+        id: uuid::Uuid::new_v4().to_string(),
+        change_id: format!("{}!{}", project, mr.iid),
+        instance_id: String::new(),
+        project: project.to_string(),
+        branch: mr.target_branch.clone(),
+        subject: mr.title.clone(),
+        status,
+        owner: GerritUser {
+            account_id: 0,
+            name: mr.author.clone(),
+            email: String::new(),
+            username: Some(mr.author.clone()).filter(|a| !a.is_empty()),
+            avatar_url: None,
+        },
+        created: String::new(),
+        updated: now.clone(),
+        insertions: files.iter().map(|f| f.lines_inserted).sum(),
+        deletions: files.iter().map(|f| f.lines_deleted).sum(),
+        current_revision: head_sha,
+        current_patch_set_num: 1,
+        patch_sets: Vec::new(),
+        total_files: files.len() as u32,
+        files,
+        reviewed_files: 0,
+        local_comments: 0,
+        remote_comments: 0,
+        import_status: ImportStatus::Imported,
+        last_sync: Some(now),
+        conflict_status: ConflictStatus::None,
+        metadata,
+    }
+}
+
+/// Flatten discussions into comments; replies point at the discussion's first note
+pub(crate) fn comments_from_discussions(discussions: &[Discussion]) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    for discussion in discussions {
+        let root_id = discussion.notes.first().map(|n| n.id.to_string());
+        for (index, note) in discussion.notes.iter().enumerate() {
+            let position = note.position.as_ref().or_else(|| discussion.notes.first().and_then(|n| n.position.as_ref()));
+            comments.push(Comment {
+                id: note.id.to_string(),
+                file_path: position.map(|p| p.new_path.clone()).unwrap_or_default(),
+                line_number: position.and_then(|p| p.new_line.or(p.old_line)).unwrap_or(0),
+                content: note.body.clone(),
+                author: note.author.clone(),
+                created_at: note.created_at.clone(),
+                updated_at: note.created_at.clone(),
+                status: CommentStatus::Submitted,
+                parent_id: if index == 0 { None } else { root_id.clone() },
+                tags: if note.resolved { vec!["resolved".to_string()] } else { Vec::new() },
+            });
+        }
+    }
+    comments
+}
+
+#[async_trait]
+impl ReviewPlatform for GitLabClient {
+    fn kind(&self) -> PlatformKind {
+        PlatformKind::GitLab
+    }
+
+    async fn fetch_change(&self, change: &ChangeRef) -> Result<GerritChange, HyperReviewError> {
+        let mr = self.get_merge_request(&change.project, change.number()?).await?;
+        let files = self.list_files(change).await?;
+        Ok(change_from_merge_request(&change.project, &mr, files))
+    }
+
+    async fn list_files(&self, change: &ChangeRef) -> Result<Vec<GerritFile>, HyperReviewError> {
+        let iid = change.number()?;
+        let change_id = format!("{}!{}", change.project, iid);
+        let diffs = self.get_merge_request_diffs(&change.project, iid).await?;
+        Ok(diffs.iter().map(|diff| file_from_diff(&change_id, "", diff)).collect())
+    }
+
+    async fn get_file_content(&self, change: &ChangeRef, path: &str) -> Result<String, HyperReviewError> {
+        let mr = self.get_merge_request(&change.project, change.number()?).await?;
+        let head = mr.diff_refs.map(|r| r.head_sha).or(mr.sha).unwrap_or(mr.source_branch);
+        self.get_file_raw(&change.project, path, &head).await
+    }
+
+    async fn list_comments(&self, change: &ChangeRef) -> Result<Vec<Comment>, HyperReviewError> {
+        let discussions = self.list_discussions(&change.project, change.number()?).await?;
+        Ok(comments_from_discussions(&discussions))
+    }
+
+    async fn post_review(&self, change: &ChangeRef, review: &PlatformReview) -> Result<SubmitResult, HyperReviewError> {
+        self.publish_review(&change.project, change.number()?, &review.comments, &review.message, review.vote).await
+    }
+
+    async fn vote(&self, change: &ChangeRef, vote: ReviewVote) -> Result<(), HyperReviewError> {
+        self.apply_vote(&change.project, change.number()?, vote, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::fixtures::{comment, fast_retries};
+    use crate::remote::mock_server::{MockResponse, MockServer};
+
+    const MR_JSON: &str = r#"{"iid":7,"title":"Add cache","description":"","state":"opened",
+        "source_branch":"feature/cache","target_branch":"main","author":{"username":"dev"},
+        "web_url":"https://gitlab.example.com/group/app/-/merge_requests/7","sha":"ccc",
+        "diff_refs":{"base_sha":"aaa","start_sha":"bbb","head_sha":"ccc"}}"#;
+
+    #[tokio::test]
+    async fn test_get_merge_request_encodes_project_path() {
+        let server = MockServer::start(|_| MockResponse::json(MR_JSON));
+        let client = GitLabClient::new(&server.base_url).with_token("glpat-x".to_string());
+
+        let mr = client.get_merge_request("group/app", 7).await.unwrap();
+        assert_eq!(mr.author, "dev");
+        assert_eq!(mr.description, None);
+        assert_eq!(mr.diff_refs.unwrap().start_sha, "bbb");
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/v4/projects/group%2Fapp/merge_requests/7");
+        assert_eq!(requests[0].header("private-token"), Some("glpat-x"));
+    }
+
+    #[tokio::test]
+    async fn test_list_follows_pagination() {
+        let server = MockServer::start(|request| {
+            if request.path.ends_with("&page=1") {
+                MockResponse::json(&format!("[{}]", MR_JSON)).with_header("X-Next-Page", "2")
+            } else {
+                MockResponse::json(&format!("[{}]", MR_JSON.replace("\"iid\":7", "\"iid\":8")))
+                    .with_header("X-Next-Page", "")
+            }
+        });
+        let client = GitLabClient::new(&server.base_url);
+
+        let mrs = client.list_merge_requests("42", Some("opened")).await.unwrap();
+        assert_eq!(mrs.iter().map(|m| m.iid).collect::<Vec<_>>(), vec![7, 8]);
+        assert!(server.requests()[0].path.starts_with("/api/v4/projects/42/merge_requests?state=opened&per_page=100"));
+    }
+
+    #[tokio::test]
+    async fn test_submit_review_posts_positioned_discussions() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" if request.path.contains("/diffs") => MockResponse::json(
+                r#"[{"old_path":"src/old_lib.rs","new_path":"src/lib.rs","renamed_file":true,
+                     "diff":"@@ -10,3 +10,4 @@\n ctx\n+added\n ctx\n-gone\n ctx\n"}]"#,
+            ),
+            "GET" => MockResponse::json(MR_JSON),
+            _ => MockResponse::json(r#"{"id":"d1","notes":[]}"#),
+        });
+        let client = GitLabClient::new(&server.base_url).with_token("t".to_string());
+
+        let result = client.submit_review("group/app", 7, vec![
+            comment("src/lib.rs", 11, "Off by one"),
+            comment("src/lib.rs", 12, "Same here"),
+            comment("", 0, "Looks good overall"),
+        ]).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.url.as_deref(), Some("https://gitlab.example.com/group/app/-/merge_requests/7"));
+
+        let posts: Vec<serde_json::Value> = server.requests().iter()
+            .filter(|r| r.method == "POST")
+            .map(|r| serde_json::from_str(&r.body).unwrap())
+            .collect();
+        assert_eq!(posts.len(), 3);
+        let position = &posts[0]["position"];
+        assert_eq!(position["base_sha"], "aaa");
+        assert_eq!(position["start_sha"], "bbb");
+        assert_eq!(position["head_sha"], "ccc");
+        assert_eq!(position["position_type"], "text");
+        assert_eq!(position["old_path"], "src/old_lib.rs");
+        assert_eq!(position["new_path"], "src/lib.rs");
+        assert_eq!(position["new_line"], 11);
+        assert!(position.get("old_line").is_none());
+        assert_eq!((posts[1]["position"]["old_line"].as_u64(), posts[1]["position"]["new_line"].as_u64()), (Some(11), Some(12)));
+        assert!(posts[2].get("position").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_submit_review_removes_posted_discussions_on_failure() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => MockResponse::json(MR_JSON),
+            "POST" if request.body.contains("second") => MockResponse::new(422, r#"{"message":"invalid"}"#),
+            "POST" => MockResponse::json(r#"{"id":"d1","notes":[{"id":11,"body":"first","author":{"username":"me"},"created_at":""}]}"#),
+            _ => MockResponse::json("{}"),
+        });
+        let client = GitLabClient::new(&server.base_url).with_token("t".to_string());
+
+        let err = client.submit_review("group/app", 7, vec![
+            comment("", 0, "first"),
+            comment("", 0, "second"),
+        ]).await.unwrap_err();
+        assert!(err.to_string().contains("422"));
+
+        let requests = server.requests();
+        let delete = requests.iter().find(|r| r.method == "DELETE").unwrap();
+        assert_eq!(delete.path, "/api/v4/projects/group%2Fapp/merge_requests/7/discussions/d1/notes/11");
+    }
+
+    #[test]
+    fn test_positions_follow_diff_hunks() {
+        let diff = |old_path: &str, new_path: &str, text: &str, deleted_file: bool| MergeRequestDiff {
+            old_path: old_path.to_string(),
+            new_path: new_path.to_string(),
+            diff: text.to_string(),
+            new_file: false,
+            renamed_file: false,
+            deleted_file,
+        };
+        let lines = |position: DiffPosition| (position.old_line, position.new_line);
+        let diffs = vec![
+            diff("a.rs", "a.rs", "@@ -3,0 +4,2 @@\n+one\n+two\n@@ -20,2 +22 @@\n-x\n ctx\n", false),
+            diff("gone.rs", "gone.rs", "@@ -1,2 +0,0 @@\n-a\n-b\n", true),
+        ];
+        let at = |path: &str, line| lines(DiffPosition::for_line(DiffRefs::default(), &diffs, path, line));
+
+        assert_eq!(at("a.rs", 2), (Some(2), Some(2)));
+        assert_eq!(at("a.rs", 5), (None, Some(5)));
+        assert_eq!(at("a.rs", 10), (Some(8), Some(10)));
+        assert_eq!(at("a.rs", 22), (Some(21), Some(22)));
+        assert_eq!(at("a.rs", 30), (Some(29), Some(30)));
+        assert_eq!(at("gone.rs", 2), (Some(2), None));
+        assert_eq!(at("other.rs", 7), (None, Some(7)));
+    }
+
+    #[tokio::test]
+    async fn test_post_review_removes_discussions_when_vote_fails() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => MockResponse::json(MR_JSON),
+            "POST" if request.path.ends_with("/approve") => MockResponse::new(403, r#"{"message":"forbidden"}"#),
+            "POST" => MockResponse::json(r#"{"id":"d1","notes":[{"id":11,"body":"","author":{"username":"me"},"created_at":""}]}"#),
+            _ => MockResponse::json("{}"),
+        });
+        let client = GitLabClient::new(&server.base_url).with_token("t".to_string());
+        let change = ChangeRef::new("group/app", "7");
+
+        let review = PlatformReview {
+            message: "Summary".to_string(),
+            comments: vec![comment("", 0, "first")],
+            vote: Some(ReviewVote::Score(2)),
+        };
+        assert!(client.post_review(&change, &review).await.is_err());
+
+        let requests = server.requests();
+        let approve = requests.iter().find(|r| r.path.ends_with("/approve")).unwrap();
+        assert_eq!(approve.body, r#"{"sha":"ccc"}"#);
+        assert_eq!(requests.iter().filter(|r| r.method == "DELETE").count(), 2);
+
+        let rejected = PlatformReview { vote: Some(ReviewVote::Reject), ..review };
+        let before = server.requests().len();
+        assert!(client.post_review(&change, &rejected).await.is_err());
+        assert_eq!(server.requests().len(), before);
+    }
+
+    #[tokio::test]
+    async fn test_discussions_resolve_and_approval() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "GET" => MockResponse::json(r#"[{"id":"d1","notes":[
+                {"id":1,"body":"nit","author":{"username":"rev"},"created_at":"2025-01-01",
+                 "resolvable":true,"resolved":true,
+                 "position":{"position_type":"text","base_sha":"a","start_sha":"b","head_sha":"c",
+                             "old_path":"x.rs","new_path":"x.rs","old_line":null,"new_line":3}}]}]"#),
+            _ => MockResponse::json("{}"),
+        });
+        let client = GitLabClient::new(&server.base_url).with_token("t".to_string());
+
+        let discussions = client.list_discussions("1", 2).await.unwrap();
+        assert!(discussions[0].is_resolved());
+        assert_eq!(discussions[0].notes[0].position.as_ref().unwrap().new_line, Some(3));
+
+        client.resolve_discussion("1", 2, "d1", false).await.unwrap();
+        client.approve("1", 2, Some("c")).await.unwrap();
+        client.unapprove("1", 2).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[1].method, "PUT");
+        assert_eq!(requests[1].path, "/api/v4/projects/1/merge_requests/2/discussions/d1?resolved=false");
+        assert_eq!(requests[2].path, "/api/v4/projects/1/merge_requests/2/approve");
+        assert_eq!(requests[2].body, r#"{"sha":"c"}"#);
+        assert_eq!(requests[3].path, "/api/v4/projects/1/merge_requests/2/unapprove");
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_and_retry() {
+        let server = MockServer::start(|_| MockResponse::new(401, r#"{"message":"401 Unauthorized"}"#));
+        let client = GitLabClient::new(&server.base_url).with_token("bad".to_string());
+        assert!(client.get_merge_request("1", 1).await.unwrap_err().is_authentication_required());
+
+        let server = MockServer::start(|_| MockResponse::new(503, "").with_header("Retry-After", "0"));
+        let client = GitLabClient::new(&server.base_url).with_retry_config(fast_retries());
+        assert!(client.get_merge_request_versions("1", 1).await.is_err());
+        assert_eq!(server.requests().len(), 3);
+    }
 }
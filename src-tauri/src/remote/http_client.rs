@@ -0,0 +1,249 @@
+// Shared HTTP client factory
+// Builds reqwest clients that honour the configured proxy, CA and TLS settings
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use log::{debug, warn};
+
+use crate::errors::HyperReviewError;
+use crate::models::network::NetworkSettings;
+
+const USER_AGENT: &str = "HyperReview/1.0";
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Creates HTTP clients for every remote integration from one set of network settings
+#[derive(Debug, Clone, Default)]
+pub struct HttpClientFactory {
+    settings: NetworkSettings,
+}
+
+/// Apply the same configuration to async and blocking builders, which share method names
+macro_rules! configure_builder {
+    ($builder:expr, $tls:expr, $settings:expr) => {{
+        let tls: &TlsMaterial = $tls;
+        let settings: &NetworkSettings = $settings;
+        let mut builder = $builder
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(settings.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)))
+            .connect_timeout(Duration::from_secs(
+                settings.connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+            ));
+
+        for certificate in &tls.certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if let Some(identity) = &tls.identity {
+            builder = builder.identity(identity.clone());
+        }
+        if settings.accept_invalid_certs.unwrap_or(false) {
+            warn!("TLS certificate verification is disabled");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        if let Some(proxy) = &tls.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        builder
+    }};
+}
+
+/// Certificates, identity and proxy parsed from the settings
+struct TlsMaterial {
+    certificates: Vec<reqwest::Certificate>,
+    identity: Option<reqwest::Identity>,
+    proxy: Option<reqwest::Proxy>,
+}
+
+/// Clients built so far, keyed by the serialized settings they were built from
+#[derive(Default)]
+struct ClientCache {
+    async_clients: HashMap<String, reqwest::Client>,
+    blocking_clients: HashMap<String, reqwest::blocking::Client>,
+}
+
+fn client_cache() -> &'static Mutex<ClientCache> {
+    static CLIENTS: OnceLock<Mutex<ClientCache>> = OnceLock::new();
+    CLIENTS.get_or_init(|| Mutex::new(ClientCache::default()))
+}
+
+/// Forget built clients so the next request re-reads CA and identity files
+pub fn clear_client_cache() {
+    let mut cache = client_cache().lock().unwrap();
+    cache.async_clients.clear();
+    cache.blocking_clients.clear();
+}
+
+impl HttpClientFactory {
+    pub fn new(settings: NetworkSettings) -> Self {
+        Self { settings }
+    }
+
+    pub fn settings(&self) -> &NetworkSettings {
+        &self.settings
+    }
+
+    /// Async client for these settings, built on first use and shared afterwards
+    pub fn async_client(&self) -> Result<reqwest::Client, HyperReviewError> {
+        let key = self.cache_key();
+        if let Some(client) = client_cache().lock().unwrap().async_clients.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let material = self.load_material()?;
+        let client = configure_builder!(reqwest::Client::builder(), &material, &self.settings)
+            .build()
+            .map_err(HyperReviewError::Http)?;
+        Ok(client_cache().lock().unwrap().async_clients.entry(key).or_insert(client).clone())
+    }
+
+    /// Blocking client for these settings, built on first use and shared afterwards.
+    /// Must be called from a blocking context (e.g. `spawn_blocking`).
+    pub fn blocking_client(&self) -> Result<reqwest::blocking::Client, HyperReviewError> {
+        let key = self.cache_key();
+        if let Some(client) = client_cache().lock().unwrap().blocking_clients.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let material = self.load_material()?;
+        let client = configure_builder!(reqwest::blocking::Client::builder(), &material, &self.settings)
+            .build()
+            .map_err(HyperReviewError::Http)?;
+        Ok(client_cache().lock().unwrap().blocking_clients.entry(key).or_insert(client).clone())
+    }
+
+    fn cache_key(&self) -> String {
+        serde_json::to_string(&self.settings).unwrap_or_default()
+    }
+
+    fn load_material(&self) -> Result<TlsMaterial, HyperReviewError> {
+        let settings = &self.settings;
+        settings.validate().map_err(HyperReviewError::config)?;
+
+        let mut certificates = Vec::new();
+        for path in &settings.ca_certificates {
+            let pem = std::fs::read(path).map_err(|e| {
+                HyperReviewError::config(format!("Failed to read CA certificate {}: {}", path, e))
+            })?;
+            let bundle = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+                HyperReviewError::config(format!("Invalid CA certificate {}: {}", path, e))
+            })?;
+            debug!("Loaded {} CA certificate(s) from {}", bundle.len(), path);
+            certificates.extend(bundle);
+        }
+
+        let identity = match (&settings.client_certificate, &settings.client_key) {
+            (Some(cert_path), Some(key_path)) => {
+                let cert = std::fs::read(cert_path).map_err(|e| {
+                    HyperReviewError::config(format!("Failed to read client certificate {}: {}", cert_path, e))
+                })?;
+                let key = std::fs::read(key_path).map_err(|e| {
+                    HyperReviewError::config(format!("Failed to read client key {}: {}", key_path, e))
+                })?;
+                Some(reqwest::Identity::from_pkcs8_pem(&cert, &key).map_err(|e| {
+                    HyperReviewError::config(format!("Invalid client certificate or key: {}", e))
+                })?)
+            }
+            _ => None,
+        };
+
+        let proxy = match &settings.proxy_url {
+            Some(url) => {
+                let mut proxy = reqwest::Proxy::all(url).map_err(|e| {
+                    HyperReviewError::config(format!("Invalid proxy URL {}: {}", url, e))
+                })?;
+                if let (Some(user), Some(pass)) = (&settings.proxy_username, &settings.proxy_password) {
+                    proxy = proxy.basic_auth(user, pass);
+                }
+                if !settings.no_proxy.is_empty() {
+                    proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&settings.no_proxy.join(",")));
+                }
+                Some(proxy)
+            }
+            None => None,
+        };
+
+        Ok(TlsMaterial { certificates, identity, proxy })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_prefers_instance_values() {
+        let global = NetworkSettings {
+            proxy_url: Some("http://proxy.corp:3128".to_string()),
+            no_proxy: vec!["localhost".to_string()],
+            ca_certificates: vec!["/etc/corp-ca.pem".to_string()],
+            accept_invalid_certs: Some(true),
+            timeout_secs: Some(60),
+            ..Default::default()
+        };
+        let instance = NetworkSettings {
+            proxy_url: Some("socks5://jump.corp:1080".to_string()),
+            no_proxy: vec![".internal".to_string()],
+            accept_invalid_certs: Some(false),
+            ..Default::default()
+        };
+
+        let merged = global.overlay(&instance);
+        assert_eq!(merged.proxy_url.as_deref(), Some("socks5://jump.corp:1080"));
+        assert_eq!(merged.no_proxy, vec!["localhost".to_string(), ".internal".to_string()]);
+        assert_eq!(merged.ca_certificates, vec!["/etc/corp-ca.pem".to_string()]);
+        assert_eq!(merged.timeout_secs, Some(60));
+        assert_eq!(merged.accept_invalid_certs, Some(false));
+        assert_eq!(global.overlay(&NetworkSettings::default()).accept_invalid_certs, Some(true));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_settings() {
+        let bad_proxy = NetworkSettings {
+            proxy_url: Some("ftp://proxy".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_proxy.validate().is_err());
+
+        let half_identity = NetworkSettings {
+            client_certificate: Some("/tmp/client.pem".to_string()),
+            ..Default::default()
+        };
+        assert!(half_identity.validate().is_err());
+    }
+
+    #[test]
+    fn test_build_clients_with_proxy() {
+        let factory = HttpClientFactory::new(NetworkSettings {
+            proxy_url: Some("http://proxy.corp:3128".to_string()),
+            no_proxy: vec!["localhost".to_string()],
+            ..Default::default()
+        });
+        assert!(factory.async_client().is_ok());
+        assert!(factory.blocking_client().is_ok());
+    }
+
+    #[test]
+    fn test_clients_built_once_per_settings() {
+        let factory = HttpClientFactory::new(NetworkSettings {
+            timeout_secs: Some(4242),
+            ..Default::default()
+        });
+        assert!(factory.blocking_client().is_ok());
+        assert!(factory.clone().blocking_client().is_ok());
+
+        let cache = client_cache().lock().unwrap();
+        assert!(cache.blocking_clients.contains_key(&factory.cache_key()));
+        assert_eq!(cache.blocking_clients.keys().filter(|key| key.contains("4242")).count(), 1);
+    }
+
+    #[test]
+    fn test_missing_ca_file_is_config_error() {
+        let factory = HttpClientFactory::new(NetworkSettings {
+            ca_certificates: vec!["/nonexistent/ca.pem".to_string()],
+            ..Default::default()
+        });
+        assert!(matches!(factory.async_client(), Err(HyperReviewError::Config { .. })));
+    }
+}
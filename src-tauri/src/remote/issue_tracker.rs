@@ -0,0 +1,323 @@
+// Issue tracker clients
+// Find issue keys in commit messages and fetch their details from Jira or a generic JSON endpoint
+
+use std::time::Duration;
+
+use log::{debug, warn};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::errors::HyperReviewError;
+use crate::models::issue::{IssueTrackerConfig, LinkedIssue, TrackerKind};
+use crate::models::network::NetworkSettings;
+use crate::remote::http_client::HttpClientFactory;
+
+/// How long fetched issue details are reused before asking the tracker again
+const ISSUE_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// Compile key patterns, reporting the first invalid one
+pub fn compile_patterns(patterns: &[String]) -> Result<Vec<Regex>, HyperReviewError> {
+    patterns.iter()
+        .map(|pattern| Regex::new(pattern).map_err(|e| HyperReviewError::validation(
+            format!("Invalid issue key pattern '{}': {}", pattern, e),
+            Some("patterns".to_string()),
+        )))
+        .collect()
+}
+
+/// Issue keys in `text`, in order of first appearance. A pattern's first
+/// capture group is the key when it has one, otherwise the whole match.
+pub fn extract_issue_keys(text: &str, patterns: &[Regex]) -> Vec<String> {
+    let mut found: Vec<(usize, String)> = Vec::new();
+    for pattern in patterns {
+        for captures in pattern.captures_iter(text) {
+            let Some(m) = captures.get(1).or_else(|| captures.get(0)) else { continue };
+            if !found.iter().any(|(_, key)| key == m.as_str()) {
+                found.push((m.start(), m.as_str().to_string()));
+            }
+        }
+    }
+    found.sort_by_key(|(start, _)| *start);
+    found.into_iter().map(|(_, key)| key).collect()
+}
+
+/// Client for one configured tracker
+pub struct IssueTrackerClient {
+    config: IssueTrackerConfig,
+    patterns: Vec<Regex>,
+    token: Option<String>,
+    http: HttpClientFactory,
+}
+
+impl IssueTrackerClient {
+    pub fn new(config: IssueTrackerConfig) -> Result<Self, HyperReviewError> {
+        let patterns = compile_patterns(&config.patterns)?;
+        Ok(Self {
+            config,
+            patterns,
+            token: None,
+            http: HttpClientFactory::default(),
+        })
+    }
+
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn with_network(mut self, settings: NetworkSettings) -> Self {
+        self.http = HttpClientFactory::new(settings);
+        self
+    }
+
+    pub fn config(&self) -> &IssueTrackerConfig {
+        &self.config
+    }
+
+    /// Whether `key` looks like one of this tracker's issues
+    pub fn matches(&self, key: &str) -> bool {
+        !extract_issue_keys(key, &self.patterns).is_empty()
+    }
+
+    pub fn extract_keys(&self, text: &str) -> Vec<String> {
+        extract_issue_keys(text, &self.patterns)
+    }
+
+    /// Fetch title, status and assignee of an issue
+    pub async fn fetch_issue(&self, key: &str) -> Result<LinkedIssue, HyperReviewError> {
+        match self.config.kind {
+            TrackerKind::Jira => self.fetch_jira_issue(key).await,
+            TrackerKind::Generic => self.fetch_generic_issue(key).await,
+        }
+    }
+
+    async fn fetch_jira_issue(&self, key: &str) -> Result<LinkedIssue, HyperReviewError> {
+        let url = format!(
+            "{}/rest/api/2/issue/{}?fields=summary,status,assignee",
+            self.config.base_url,
+            urlencoding::encode(key)
+        );
+        let issue = self.get_json(&url, key).await?;
+        let fields = issue.get("fields").cloned().unwrap_or(Value::Null);
+
+        Ok(self.linked_issue(
+            key,
+            fields.get("summary").and_then(text_of),
+            fields.get("status").and_then(text_of),
+            fields.get("assignee").and_then(text_of),
+            Some(format!("{}/browse/{}", self.config.base_url, key)),
+        ))
+    }
+
+    async fn fetch_generic_issue(&self, key: &str) -> Result<LinkedIssue, HyperReviewError> {
+        let url = self.config.base_url.replace("{key}", &urlencoding::encode(key));
+        let issue = self.get_json(&url, key).await?;
+        let paths = &self.config.field_paths;
+
+        Ok(self.linked_issue(
+            key,
+            value_at(&issue, &paths.title).and_then(text_of),
+            value_at(&issue, &paths.status).and_then(text_of),
+            value_at(&issue, &paths.assignee).and_then(text_of),
+            paths.url.as_deref().and_then(|path| value_at(&issue, path)).and_then(text_of),
+        ))
+    }
+
+    fn linked_issue(
+        &self,
+        key: &str,
+        title: Option<String>,
+        status: Option<String>,
+        assignee: Option<String>,
+        url: Option<String>,
+    ) -> LinkedIssue {
+        LinkedIssue {
+            key: key.to_string(),
+            tracker_id: Some(self.config.id.clone()),
+            title,
+            status,
+            assignee,
+            url,
+            fetched_at: Some(chrono::Utc::now().to_rfc3339()),
+        }
+    }
+
+    async fn get_json(&self, url: &str, key: &str) -> Result<Value, HyperReviewError> {
+        debug!("Fetching issue {} from {}", key, self.config.name);
+        let mut request = self.http.async_client()?.get(url).header("Accept", "application/json");
+        request = match (&self.config.username, &self.token) {
+            (Some(username), Some(token)) => request.basic_auth(username, Some(token)),
+            (None, Some(token)) => request.bearer_auth(token),
+            _ => request,
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(HyperReviewError::authentication_required(
+                self.config.base_url.clone(),
+                format!("{} rejected the API token", self.config.name),
+            ));
+        }
+        if !status.is_success() {
+            return Err(HyperReviewError::network_with_status(
+                format!("{} could not fetch {}: HTTP {}", self.config.name, key, status),
+                status.as_u16(),
+            ));
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+/// Follow a dotted path such as `fields.status.name`; numeric segments index arrays
+fn value_at<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').filter(|s| !s.is_empty()).try_fold(value, |current, segment| match current {
+        Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => current.get(segment),
+    })
+}
+
+/// Display text of a field: strings and numbers as-is, objects by their name
+fn text_of(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Object(_) => ["displayName", "name", "title", "value"].iter()
+            .find_map(|key| value.get(*key).and_then(|v| v.as_str()))
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// All configured trackers; finds keys and resolves them against the right tracker
+#[derive(Default)]
+pub struct IssueResolver {
+    trackers: Vec<IssueTrackerClient>,
+}
+
+impl IssueResolver {
+    /// Jira trackers are asked first; generic endpoints are the fallback
+    pub fn new(mut trackers: Vec<IssueTrackerClient>) -> Self {
+        trackers.sort_by_key(|t| t.config.kind != TrackerKind::Jira);
+        Self { trackers }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trackers.is_empty()
+    }
+
+    /// Keys any tracker recognises in `text`, in order of appearance
+    pub fn extract_keys(&self, text: &str) -> Vec<String> {
+        let patterns: Vec<Regex> = self.trackers.iter().flat_map(|t| t.patterns.iter().cloned()).collect();
+        extract_issue_keys(text, &patterns)
+    }
+
+    /// Details for `key`. Fresh cached details are reused unless `refresh` is set;
+    /// when every tracker fails, stale cached details or an unresolved issue are returned.
+    pub async fn resolve(&self, key: &str, cached: Option<LinkedIssue>, refresh: bool) -> LinkedIssue {
+        if !refresh {
+            if let Some(cached) = cached.as_ref().filter(|c| is_fresh(c)) {
+                return cached.clone();
+            }
+        }
+
+        for tracker in self.trackers.iter().filter(|t| t.matches(key)) {
+            match tracker.fetch_issue(key).await {
+                Ok(issue) => return issue,
+                Err(e) => warn!("{} could not resolve {}: {}", tracker.config.name, key, e),
+            }
+        }
+        cached.unwrap_or_else(|| LinkedIssue::unresolved(key))
+    }
+}
+
+fn is_fresh(issue: &LinkedIssue) -> bool {
+    issue.fetched_at.as_deref()
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+        .and_then(|at| (chrono::Utc::now() - at.with_timezone(&chrono::Utc)).to_std().ok())
+        .is_some_and(|age| age < ISSUE_CACHE_TTL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::issue::JIRA_KEY_PATTERN;
+    use crate::remote::mock_server::{MockResponse, MockServer};
+
+    #[test]
+    fn test_extract_keys_with_capture_groups() {
+        let patterns = compile_patterns(&[
+            JIRA_KEY_PATTERN.to_string(),
+            r"(?i)workitem[:#\s]+(\d{5,})".to_string(),
+        ]).unwrap();
+
+        let keys = extract_issue_keys(
+            "PROJ-12: fix parser (see workitem #884211)\n\nAlso PROJ-7, PROJ-12 and utf-8 text",
+            &patterns,
+        );
+        assert_eq!(keys, vec!["PROJ-12", "884211", "PROJ-7"]);
+        assert!(compile_patterns(&["(".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_jira_issue() {
+        let server = MockServer::start(|request| {
+            assert_eq!(request.path, "/rest/api/2/issue/PROJ-12?fields=summary,status,assignee");
+            MockResponse::json(r#"{"key":"PROJ-12","fields":{"summary":"Parser crash",
+                "status":{"name":"In Review"},"assignee":{"displayName":"Dana"}}}"#)
+        });
+        let mut config = IssueTrackerConfig::new("Jira".to_string(), TrackerKind::Jira, server.base_url.clone(), Vec::new());
+        config.username = Some("bot@example.com".to_string());
+        let client = IssueTrackerClient::new(config).unwrap().with_token("api".to_string());
+
+        let issue = client.fetch_issue("PROJ-12").await.unwrap();
+        assert_eq!(issue.title.as_deref(), Some("Parser crash"));
+        assert_eq!(issue.status.as_deref(), Some("In Review"));
+        assert_eq!(issue.assignee.as_deref(), Some("Dana"));
+        assert_eq!(issue.url, Some(format!("{}/browse/PROJ-12", server.base_url)));
+        assert!(server.requests()[0].header("authorization").unwrap().starts_with("Basic "));
+    }
+
+    #[tokio::test]
+    async fn test_generic_tracker_is_fallback() {
+        let jira = MockServer::start(|_| MockResponse::new(404, r#"{"errorMessages":["Issue does not exist"]}"#));
+        let generic = MockServer::start(|request| {
+            assert_eq!(request.path, "/api/items/PROJ-3");
+            MockResponse::json(r#"{"result":{"name":"Login page","state":{"name":"Open"},
+                "owner":null,"links":[{"href":"https://items/PROJ-3"}]}}"#)
+        });
+
+        let jira_config = IssueTrackerConfig::new("Jira".to_string(), TrackerKind::Jira, jira.base_url.clone(), Vec::new());
+        let mut generic_config = IssueTrackerConfig::new(
+            "Items".to_string(),
+            TrackerKind::Generic,
+            format!("{}/api/items/{{key}}", generic.base_url),
+            vec![JIRA_KEY_PATTERN.to_string()],
+        );
+        generic_config.field_paths.title = "result.name".to_string();
+        generic_config.field_paths.status = "result.state".to_string();
+        generic_config.field_paths.assignee = "result.owner".to_string();
+        generic_config.field_paths.url = Some("result.links.0.href".to_string());
+        let generic_id = generic_config.id.clone();
+
+        let resolver = IssueResolver::new(vec![
+            IssueTrackerClient::new(generic_config).unwrap().with_token("t".to_string()),
+            IssueTrackerClient::new(jira_config).unwrap(),
+        ]);
+        let issue = resolver.resolve("PROJ-3", None, false).await;
+        assert_eq!(issue.tracker_id, Some(generic_id));
+        assert_eq!(issue.title.as_deref(), Some("Login page"));
+        assert_eq!(issue.status.as_deref(), Some("Open"));
+        assert_eq!(issue.assignee, None);
+        assert_eq!(issue.url.as_deref(), Some("https://items/PROJ-3"));
+        assert_eq!(jira.requests().len(), 1, "Jira is asked first");
+        assert_eq!(generic.requests()[0].header("authorization"), Some("Bearer t"));
+
+        // Fresh cached details are used without asking the trackers
+        let again = resolver.resolve("PROJ-3", Some(issue.clone()), false).await;
+        assert_eq!(again, issue);
+        assert_eq!(generic.requests().len(), 1);
+        assert_eq!(resolver.resolve("NOPE", None, false).await, LinkedIssue::unresolved("NOPE"));
+    }
+}
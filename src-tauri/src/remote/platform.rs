@@ -0,0 +1,466 @@
+// Review platform abstraction
+// One interface over Gerrit, GitLab, GitHub, CodeArts and custom APIs, plus the registry of configured instances
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::{
+    CommentSide, CommentSyncStatus, GerritChange, GerritComment, GerritCommentSource, GerritFile, GerritInstance,
+    GerritUser, RemoteGerritComment,
+};
+use crate::models::platform::PlatformKind;
+use crate::models::{Comment, SubmitResult};
+use crate::remote::codearts_client::CodeArtsClient;
+use crate::remote::custom_client::CustomApiClient;
+use crate::remote::gerrit_auth::GerritAuth;
+use crate::remote::gerrit_client::GerritClient;
+use crate::remote::github_client::GitHubClient;
+use crate::remote::gitlab_client::GitLabClient;
+use crate::storage::credentials::CredentialStore;
+use crate::storage::sqlite::Database;
+
+/// Identifies a change on its platform: Gerrit change number or ID,
+/// merge request IID or pull request number within `project`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeRef {
+    pub project: String,
+    pub id: String,
+}
+
+impl ChangeRef {
+    pub fn new(project: &str, id: impl ToString) -> Self {
+        Self {
+            project: project.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    /// The ID as a number, for platforms that address changes by number
+    pub fn number(&self) -> Result<u64, HyperReviewError> {
+        self.id.parse().map_err(|_| HyperReviewError::validation(
+            format!("'{}' is not a change number", self.id),
+            Some("id".to_string()),
+        ))
+    }
+}
+
+/// Reviewer verdict, mapped onto each platform's voting model. `Score` keeps a
+/// Gerrit-style Code-Review score for platforms that have scores.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVote {
+    Approve,
+    Reject,
+    Neutral,
+    Score(i32),
+}
+
+impl ReviewVote {
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "approve" | "approved" => Some(ReviewVote::Approve),
+            "reject" | "rejected" | "request_changes" => Some(ReviewVote::Reject),
+            "neutral" | "comment" => Some(ReviewVote::Neutral),
+            score => score.trim_start_matches('+').parse().ok().map(ReviewVote::Score),
+        }
+    }
+
+    /// Vote for a Gerrit-style score
+    pub fn from_score(score: i32) -> Self {
+        ReviewVote::Score(score)
+    }
+
+    /// Approve, reject or neutral, for platforms without scores: any positive score approves
+    pub fn verdict(self) -> Self {
+        match self {
+            ReviewVote::Score(s) if s > 0 => ReviewVote::Approve,
+            ReviewVote::Score(s) if s < 0 => ReviewVote::Reject,
+            ReviewVote::Score(_) => ReviewVote::Neutral,
+            vote => vote,
+        }
+    }
+}
+
+/// A review to publish: summary, line comments and an optional vote
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlatformReview {
+    pub message: String,
+    pub comments: Vec<Comment>,
+    pub vote: Option<ReviewVote>,
+}
+
+/// Operations every review backend offers. Backends that cannot support an
+/// operation keep the default, which reports it as unsupported.
+///
+/// Review sessions still download their changes from Gerrit through `ChangeDownloader`;
+/// `fetch_change` and `list_files` are not used for sessions yet.
+#[async_trait]
+pub trait ReviewPlatform: Send + Sync {
+    fn kind(&self) -> PlatformKind;
+
+    async fn fetch_change(&self, change: &ChangeRef) -> Result<GerritChange, HyperReviewError> {
+        let _ = change;
+        Err(unsupported(self.kind(), "fetching changes"))
+    }
+
+    async fn list_files(&self, change: &ChangeRef) -> Result<Vec<GerritFile>, HyperReviewError> {
+        let _ = change;
+        Err(unsupported(self.kind(), "listing files"))
+    }
+
+    /// Content of a file at the change's latest revision
+    async fn get_file_content(&self, change: &ChangeRef, path: &str) -> Result<String, HyperReviewError> {
+        let _ = (change, path);
+        Err(unsupported(self.kind(), "reading file content"))
+    }
+
+    async fn list_comments(&self, change: &ChangeRef) -> Result<Vec<Comment>, HyperReviewError> {
+        let _ = change;
+        Err(unsupported(self.kind(), "listing comments"))
+    }
+
+    /// Comments of a change in the shape review sessions import. Platforms without
+    /// drafts or robot comments report every comment as published.
+    async fn list_remote_comments(&self, change: &ChangeRef) -> Result<Vec<RemoteGerritComment>, HyperReviewError> {
+        let comments = self.list_comments(change).await?;
+        Ok(comments.into_iter()
+            .map(|comment| RemoteGerritComment {
+                source: GerritCommentSource::Published,
+                comment: remote_comment(&change.id, comment),
+            })
+            .collect())
+    }
+
+    /// Comment lists `list_remote_comments` returns in full. Imported comments from
+    /// these lists that it no longer returns were deleted on the platform.
+    fn complete_comment_sources(&self) -> Vec<GerritCommentSource> {
+        vec![GerritCommentSource::Published]
+    }
+
+    async fn post_review(&self, change: &ChangeRef, review: &PlatformReview) -> Result<SubmitResult, HyperReviewError>;
+
+    async fn vote(&self, change: &ChangeRef, vote: ReviewVote) -> Result<(), HyperReviewError> {
+        let _ = (change, vote);
+        Err(unsupported(self.kind(), "voting"))
+    }
+}
+
+pub(crate) fn unsupported(kind: PlatformKind, operation: &str) -> HyperReviewError {
+    HyperReviewError::other(format!("{} does not support {}", kind, operation))
+}
+
+/// A platform comment as a published Gerrit comment; file-less comments are patch set level
+fn remote_comment(change_id: &str, comment: Comment) -> GerritComment {
+    GerritComment {
+        id: uuid::Uuid::new_v4().to_string(),
+        gerrit_comment_id: Some(comment.id),
+        change_id: change_id.to_string(),
+        patch_set_id: String::new(),
+        file_path: if comment.file_path.is_empty() { "/PATCHSET_LEVEL".to_string() } else { comment.file_path },
+        side: CommentSide::Revision,
+        line: comment.line_number,
+        range: None,
+        message: comment.content,
+        author: GerritUser {
+            account_id: 0,
+            name: comment.author.clone(),
+            email: String::new(),
+            username: Some(comment.author).filter(|a| !a.is_empty()),
+            avatar_url: None,
+        },
+        created: comment.created_at,
+        updated: comment.updated_at,
+        status: CommentSyncStatus::Synced,
+        unresolved: false,
+        parent: comment.parent_id,
+        robot_id: None,
+        properties: Default::default(),
+    }
+}
+
+/// A configured instance and the client talking to it
+#[derive(Clone)]
+pub struct RegisteredPlatform {
+    pub instance_id: String,
+    pub name: String,
+    pub kind: PlatformKind,
+    pub base_url: String,
+    /// Gerrit's selected instance; the default when no instance is named
+    pub is_active: bool,
+    pub platform: Arc<dyn ReviewPlatform>,
+}
+
+impl RegisteredPlatform {
+    /// Fetch a change and record which instance it came from
+    pub async fn fetch_change(&self, change: &ChangeRef) -> Result<GerritChange, HyperReviewError> {
+        let mut fetched = self.platform.fetch_change(change).await?;
+        fetched.instance_id = self.instance_id.clone();
+        Ok(fetched)
+    }
+
+    /// Reference to a change stored from this instance. Gerrit changes keep their
+    /// Change-Id; the others store `<project>!<iid>` or `<owner>/<repo>#<number>`.
+    pub fn change_ref(&self, project: &str, change_id: &str) -> ChangeRef {
+        if self.kind != PlatformKind::Gerrit {
+            if let Some((project, number)) = change_id.rsplit_once(['!', '#']) {
+                return ChangeRef::new(project, number);
+            }
+        }
+        ChangeRef::new(project, change_id)
+    }
+}
+
+/// All configured review platform instances
+#[derive(Clone, Default)]
+pub struct PlatformRegistry {
+    entries: Vec<RegisteredPlatform>,
+}
+
+impl PlatformRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, entry: RegisteredPlatform) {
+        self.entries.retain(|e| e.instance_id != entry.instance_id);
+        self.entries.push(entry);
+    }
+
+    /// Build clients for every stored Gerrit and platform instance.
+    /// Instances whose credentials cannot be resolved are skipped.
+    pub fn load(database: &Database, credentials: &CredentialStore) -> Result<Self, HyperReviewError> {
+        let mut registry = Self::new();
+
+        for instance in database.get_all_gerrit_instances()? {
+            match Self::gerrit_client(database, credentials, &instance) {
+                Ok(client) => registry.register(RegisteredPlatform {
+                    instance_id: instance.id.clone(),
+                    name: instance.name.clone(),
+                    kind: PlatformKind::Gerrit,
+                    base_url: instance.url.clone(),
+                    is_active: instance.is_active,
+                    platform: Arc::new(client),
+                }),
+                Err(e) => warn!("Skipping Gerrit instance {}: {}", instance.name, e),
+            }
+        }
+
+        for instance in database.list_platform_instances(None)? {
+            let token = credentials
+                .retrieve(&instance.credential_service(), instance.credential_username())
+                .ok()
+                .flatten()
+                .map(|credential| credential.password);
+            let network = database.get_effective_network_settings(Some(&instance.id))?;
+
+            let platform: Arc<dyn ReviewPlatform> = match instance.platform {
+                PlatformKind::GitLab => {
+                    let client = GitLabClient::new(&instance.base_url).with_network(network);
+                    Arc::new(match token {
+                        Some(token) => client.with_token(token),
+                        None => client,
+                    })
+                }
+                PlatformKind::GitHub => {
+                    let client = GitHubClient::for_web_url(&instance.base_url).with_network(network);
+                    Arc::new(match token {
+                        Some(token) => client.with_token(token),
+                        None => client,
+                    })
+                }
+                PlatformKind::CodeArts => {
+                    let client = CodeArtsClient::new(&instance.base_url).with_network(network);
+                    Arc::new(match token {
+                        Some(token) => client.with_auth(token),
+                        None => client,
+                    })
+                }
+                PlatformKind::Custom => {
+                    let client = CustomApiClient::new(&instance.base_url).with_network(network);
+                    Arc::new(match token {
+                        Some(token) => client.with_api_key(token),
+                        None => client,
+                    })
+                }
+                PlatformKind::Gerrit => {
+                    warn!("Ignoring Gerrit platform instance {}; Gerrit servers are configured separately", instance.id);
+                    continue;
+                }
+            };
+
+            registry.register(RegisteredPlatform {
+                instance_id: instance.id.clone(),
+                name: instance.name.clone(),
+                kind: instance.platform,
+                base_url: instance.base_url.clone(),
+                is_active: false,
+                platform,
+            });
+        }
+
+        Ok(registry)
+    }
+
+    /// Client for a Gerrit instance with its stored secret and network settings. Gerrit-only
+    /// features (drafts, account suggestions) use this instead of the registered platform.
+    pub fn gerrit_client(
+        database: &Database,
+        credentials: &CredentialStore,
+        instance: &GerritInstance,
+    ) -> Result<GerritClient, HyperReviewError> {
+        let secret = GerritAuth::stored_secret(credentials, instance);
+        let network = database.get_effective_network_settings(Some(&instance.id))?;
+        Ok(GerritClient::for_instance(instance, secret.as_deref())?.with_network(network))
+    }
+
+    /// The instance whose base URL is `url`, ignoring a trailing slash
+    pub fn find_by_url(&self, kind: PlatformKind, url: &str) -> Option<&RegisteredPlatform> {
+        let url = url.trim_end_matches('/');
+        self.entries.iter().find(|e| e.kind == kind && e.base_url.trim_end_matches('/') == url)
+    }
+
+    pub fn instances(&self) -> &[RegisteredPlatform] {
+        &self.entries
+    }
+
+    pub fn get(&self, instance_id: &str) -> Option<&RegisteredPlatform> {
+        self.entries.iter().find(|e| e.instance_id == instance_id)
+    }
+
+    /// Pick the instance to use: the named one, else the active or only instance of the platform
+    pub fn resolve(&self, kind: PlatformKind, instance_id: Option<&str>) -> Result<&RegisteredPlatform, HyperReviewError> {
+        if let Some(id) = instance_id {
+            return self.get(id)
+                .filter(|e| e.kind == kind)
+                .ok_or_else(|| HyperReviewError::config(format!("{} instance not found: {}", kind, id)));
+        }
+
+        let candidates: Vec<&RegisteredPlatform> = self.entries.iter().filter(|e| e.kind == kind).collect();
+        if let Some(active) = candidates.iter().find(|e| e.is_active) {
+            return Ok(active);
+        }
+        match candidates.as_slice() {
+            [] => Err(HyperReviewError::config(format!("No {} instance configured", kind))),
+            [only] => Ok(only),
+            _ => Err(HyperReviewError::config(format!("Several {} instances are configured; choose one", kind))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakePlatform(PlatformKind);
+
+    #[async_trait]
+    impl ReviewPlatform for FakePlatform {
+        fn kind(&self) -> PlatformKind {
+            self.0
+        }
+
+        async fn post_review(&self, change: &ChangeRef, review: &PlatformReview) -> Result<SubmitResult, HyperReviewError> {
+            Ok(SubmitResult {
+                success: true,
+                message: format!("{} comments", review.comments.len()),
+                external_id: Some(change.id.clone()),
+                url: None,
+            })
+        }
+    }
+
+    fn entry(id: &str, kind: PlatformKind, is_active: bool) -> RegisteredPlatform {
+        RegisteredPlatform {
+            instance_id: id.to_string(),
+            name: id.to_string(),
+            kind,
+            base_url: format!("https://{}.example.com", id),
+            is_active,
+            platform: Arc::new(FakePlatform(kind)),
+        }
+    }
+
+    #[test]
+    fn test_resolve_instances() {
+        let mut registry = PlatformRegistry::new();
+        registry.register(entry("lab", PlatformKind::GitLab, false));
+        registry.register(entry("g1", PlatformKind::Gerrit, false));
+        registry.register(entry("g2", PlatformKind::Gerrit, true));
+
+        assert_eq!(registry.resolve(PlatformKind::GitLab, None).unwrap().instance_id, "lab");
+        assert_eq!(registry.resolve(PlatformKind::Gerrit, None).unwrap().instance_id, "g2");
+        assert_eq!(registry.resolve(PlatformKind::Gerrit, Some("g1")).unwrap().instance_id, "g1");
+        assert!(registry.resolve(PlatformKind::GitLab, Some("g1")).is_err());
+        assert!(registry.resolve(PlatformKind::CodeArts, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_operations_report_platform() {
+        let platform = FakePlatform(PlatformKind::Custom);
+        let change = ChangeRef::new("p", 1);
+
+        let err = platform.list_files(&change).await.unwrap_err();
+        assert!(err.to_string().contains("custom does not support listing files"));
+        assert!(platform.post_review(&change, &PlatformReview::default()).await.unwrap().success);
+    }
+
+    #[tokio::test]
+    async fn test_platform_comments_import_as_published() {
+        struct Commented;
+
+        #[async_trait]
+        impl ReviewPlatform for Commented {
+            fn kind(&self) -> PlatformKind {
+                PlatformKind::GitLab
+            }
+
+            async fn list_comments(&self, _change: &ChangeRef) -> Result<Vec<Comment>, HyperReviewError> {
+                Ok(vec![Comment {
+                    id: "note-7".to_string(),
+                    file_path: String::new(),
+                    line_number: 0,
+                    content: "Looks good".to_string(),
+                    author: "jane".to_string(),
+                    created_at: "2024-01-01 00:00:00".to_string(),
+                    updated_at: "2024-01-02 00:00:00".to_string(),
+                    status: crate::models::CommentStatus::Submitted,
+                    parent_id: None,
+                    tags: Vec::new(),
+                }])
+            }
+
+            async fn post_review(&self, _change: &ChangeRef, _review: &PlatformReview) -> Result<SubmitResult, HyperReviewError> {
+                unreachable!()
+            }
+        }
+
+        let imported = Commented.list_remote_comments(&ChangeRef::new("group/app", 5)).await.unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].source, GerritCommentSource::Published);
+        assert_eq!(imported[0].comment.gerrit_comment_id.as_deref(), Some("note-7"));
+        assert_eq!(imported[0].comment.file_path, "/PATCHSET_LEVEL");
+        assert_eq!(imported[0].comment.author.name, "jane");
+
+        let lab = entry("lab", PlatformKind::GitLab, false);
+        assert_eq!(lab.change_ref("group/app", "group/app!5"), ChangeRef::new("group/app", 5));
+        let hub = entry("hub", PlatformKind::GitHub, false);
+        assert_eq!(hub.change_ref("", "octo/app#12"), ChangeRef::new("octo/app", 12));
+        let gerrit = entry("g1", PlatformKind::Gerrit, false);
+        assert_eq!(gerrit.change_ref("app", "Iabc!1"), ChangeRef::new("app", "Iabc!1"));
+    }
+
+    #[test]
+    fn test_review_vote_from_string() {
+        assert_eq!(ReviewVote::from_string("+2"), Some(ReviewVote::Score(2)));
+        assert_eq!(ReviewVote::from_string("-1"), Some(ReviewVote::Score(-1)));
+        assert_eq!(ReviewVote::from_string("request_changes"), Some(ReviewVote::Reject));
+        assert_eq!(ReviewVote::from_string("maybe"), None);
+        assert_eq!(ReviewVote::from_score(2).verdict(), ReviewVote::Approve);
+        assert_eq!(ReviewVote::from_score(0).verdict(), ReviewVote::Neutral);
+        assert_eq!(ReviewVote::Reject.verdict(), ReviewVote::Reject);
+    }
+}
@@ -0,0 +1,672 @@
+// Quality gate providers
+// Jenkins builds, GitLab pipelines and Gerrit Verified votes for a commit, cached per SHA
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use log::{debug, warn};
+use serde_json::Value;
+
+use crate::errors::HyperReviewError;
+use crate::models::platform::PlatformKind;
+use crate::models::quality_gate::{GateProviderConfig, GateSource};
+use crate::models::{QualityGate, QualityGateStatus};
+use crate::remote::gerrit_auth::GerritAuth;
+use crate::remote::gerrit_client::{GerritClient, RetryConfig};
+use crate::remote::gitlab_client::GitLabClient;
+use crate::remote::http_client::HttpClientFactory;
+use crate::remote::traffic_control::{send_with_retries, CircuitBreakerConfig, RequestPolicy};
+use crate::storage::credentials::CredentialStore;
+use crate::storage::sqlite::Database;
+
+/// How long results are reused when every gate has finished
+const SETTLED_TTL: Duration = Duration::from_secs(600);
+/// How long results are reused while a gate is still running
+const PENDING_TTL: Duration = Duration::from_secs(30);
+
+/// Builds a job looks at when searching for a commit
+const JENKINS_TREE: &str =
+    "builds[number,url,result,building,timestamp,duration,actions[lastBuiltRevision[SHA1]]]{0,50}";
+
+/// A source of quality gate results for a commit
+#[async_trait]
+pub trait QualityGateProvider: Send + Sync {
+    /// Stable key; results are cached per provider and commit
+    fn id(&self) -> &str;
+
+    fn name(&self) -> &str;
+
+    async fn check(&self, commit_sha: &str) -> Result<Vec<QualityGate>, HyperReviewError>;
+}
+
+struct CachedGates {
+    gates: Vec<QualityGate>,
+    fetched_at: Instant,
+}
+
+fn cache() -> &'static Mutex<HashMap<(String, String), CachedGates>> {
+    static CACHE: OnceLock<Mutex<HashMap<(String, String), CachedGates>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Forget cached results, e.g. after a provider was reconfigured
+pub fn clear_cache() {
+    cache().lock().unwrap().clear();
+}
+
+/// Gates that block submission
+pub fn failing_gates(gates: &[QualityGate]) -> Vec<&QualityGate> {
+    gates.iter().filter(|g| g.status == QualityGateStatus::Failing).collect()
+}
+
+fn is_settled(gates: &[QualityGate]) -> bool {
+    gates.iter().all(|g| matches!(g.status, QualityGateStatus::Passing | QualityGateStatus::Failing))
+}
+
+fn gate(name: &str, status: QualityGateStatus, details: String, url: Option<String>, metadata: HashMap<String, String>) -> QualityGate {
+    QualityGate {
+        name: name.to_string(),
+        status,
+        details: Some(details),
+        last_checked: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        url,
+        metadata,
+    }
+}
+
+fn short_sha(sha: &str) -> &str {
+    &sha[..sha.len().min(8)]
+}
+
+/// Runs every configured provider for a commit
+#[derive(Clone, Default)]
+pub struct QualityGateChecker {
+    providers: Vec<Arc<dyn QualityGateProvider>>,
+}
+
+impl QualityGateChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_provider(mut self, provider: Arc<dyn QualityGateProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Build providers for the configs that apply to `scopes`.
+    /// Providers whose instance or credentials cannot be resolved are skipped.
+    pub fn load(
+        configs: &[GateProviderConfig],
+        scopes: &[&str],
+        database: &Database,
+        credentials: &CredentialStore,
+    ) -> Self {
+        let mut checker = Self::new();
+        for config in configs.iter().filter(|c| c.applies_to(scopes)) {
+            match build_provider(config, database, credentials) {
+                Ok(provider) => checker.providers.push(provider),
+                Err(e) => warn!("Skipping quality gate provider {}: {}", config.name, e),
+            }
+        }
+        checker
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Gate results for a commit. Cached results are reused unless `refresh` is set;
+    /// a provider that fails reports a single `Unknown` gate with the error.
+    pub async fn check(&self, commit_sha: &str, refresh: bool) -> Vec<QualityGate> {
+        let mut gates = Vec::new();
+
+        for provider in &self.providers {
+            let key = (provider.id().to_string(), commit_sha.to_string());
+            if !refresh {
+                if let Some(cached) = cache().lock().unwrap().get(&key) {
+                    let ttl = if is_settled(&cached.gates) { SETTLED_TTL } else { PENDING_TTL };
+                    if cached.fetched_at.elapsed() < ttl {
+                        debug!("Using cached gates of {} for {}", provider.name(), short_sha(commit_sha));
+                        gates.extend(cached.gates.iter().cloned());
+                        continue;
+                    }
+                }
+            }
+
+            match provider.check(commit_sha).await {
+                Ok(results) => {
+                    cache().lock().unwrap().insert(key, CachedGates {
+                        gates: results.clone(),
+                        fetched_at: Instant::now(),
+                    });
+                    gates.extend(results);
+                }
+                Err(e) => {
+                    warn!("Quality gate {} failed for {}: {}", provider.name(), short_sha(commit_sha), e);
+                    let metadata = HashMap::from([("error".to_string(), "true".to_string())]);
+                    gates.push(gate(provider.name(), QualityGateStatus::Unknown, e.to_string(), None, metadata));
+                }
+            }
+        }
+
+        gates
+    }
+}
+
+fn build_provider(
+    config: &GateProviderConfig,
+    database: &Database,
+    credentials: &CredentialStore,
+) -> Result<Arc<dyn QualityGateProvider>, HyperReviewError> {
+    match &config.source {
+        GateSource::Jenkins { base_url, job, username } => {
+            let token = credentials
+                .retrieve(&config.credential_service(), config.credential_username())
+                .ok()
+                .flatten()
+                .map(|credential| credential.password);
+            let network = database.get_effective_network_settings(None)?;
+            Ok(Arc::new(JenkinsGate {
+                id: config.id.clone(),
+                name: config.name.clone(),
+                base_url: base_url.trim_end_matches('/').to_string(),
+                job: job.clone(),
+                username: username.clone(),
+                token,
+                http: HttpClientFactory::new(network),
+                retry_config: RetryConfig::default(),
+                circuit_breaker: CircuitBreakerConfig::default(),
+            }))
+        }
+        GateSource::GitlabPipeline { instance_id, project } => {
+            let instance = match instance_id {
+                Some(id) => database.get_platform_instance(id)?
+                    .filter(|instance| instance.platform == PlatformKind::GitLab),
+                None => {
+                    let mut instances = database.list_platform_instances(Some(PlatformKind::GitLab))?;
+                    if instances.len() == 1 { instances.pop() } else { None }
+                }
+            }.ok_or_else(|| HyperReviewError::config("GitLab instance for pipeline gate not found".to_string()))?;
+
+            let network = database.get_effective_network_settings(Some(&instance.id))?;
+            let mut client = GitLabClient::new(&instance.base_url).with_network(network);
+            if let Some(credential) = credentials
+                .retrieve(&instance.credential_service(), instance.credential_username())
+                .ok()
+                .flatten()
+            {
+                client = client.with_token(credential.password);
+            }
+            Ok(Arc::new(GitLabPipelineGate {
+                id: config.id.clone(),
+                name: config.name.clone(),
+                project: project.clone(),
+                client,
+            }))
+        }
+        GateSource::GerritVerified { instance_id } => {
+            let instances = database.get_all_gerrit_instances()?;
+            let instance = match instance_id {
+                Some(id) => instances.into_iter().find(|i| &i.id == id),
+                None => instances.into_iter().find(|i| i.is_active),
+            }.ok_or_else(|| HyperReviewError::config("Gerrit instance for Verified gate not found".to_string()))?;
+
+            let secret = GerritAuth::stored_secret(credentials, &instance);
+            let network = database.get_effective_network_settings(Some(&instance.id))?;
+            let client = GerritClient::for_instance(&instance, secret.as_deref())?.with_network(network);
+            Ok(Arc::new(GerritVerifiedGate {
+                id: config.id.clone(),
+                name: config.name.clone(),
+                client,
+            }))
+        }
+    }
+}
+
+/// Status of the newest Jenkins build of a job that built the commit
+pub struct JenkinsGate {
+    id: String,
+    name: String,
+    base_url: String,
+    job: String,
+    username: Option<String>,
+    token: Option<String>,
+    http: HttpClientFactory,
+    retry_config: RetryConfig,
+    circuit_breaker: CircuitBreakerConfig,
+}
+
+impl JenkinsGate {
+    pub fn new(name: &str, base_url: &str, job: &str) -> Self {
+        Self {
+            id: format!("jenkins:{}/{}", base_url.trim_end_matches('/'), job),
+            name: name.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            job: job.to_string(),
+            username: None,
+            token: None,
+            http: HttpClientFactory::default(),
+            retry_config: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
+
+    pub fn with_auth(mut self, username: String, api_token: String) -> Self {
+        self.username = Some(username);
+        self.token = Some(api_token);
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// `team/app` → `job/team/job/app`
+    fn job_path(&self) -> String {
+        self.job.split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| format!("job/{}", urlencoding::encode(segment)))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn map_build(&self, build: &Value, commit_sha: &str) -> QualityGate {
+        let number = build.get("number").and_then(|v| v.as_u64()).unwrap_or(0);
+        let building = build.get("building").and_then(|v| v.as_bool()).unwrap_or(false);
+        let result = build.get("result").and_then(|v| v.as_str());
+
+        let status = match (building, result) {
+            (true, _) | (false, None) => QualityGateStatus::Pending,
+            (false, Some("SUCCESS")) => QualityGateStatus::Passing,
+            (false, Some("FAILURE")) | (false, Some("UNSTABLE")) => QualityGateStatus::Failing,
+            (false, Some(_)) => QualityGateStatus::Unknown,
+        };
+        let details = match result {
+            Some(result) if !building => format!("Build #{} {}", number, result),
+            _ => format!("Build #{} running", number),
+        };
+
+        let mut metadata = HashMap::from([
+            ("provider".to_string(), "jenkins".to_string()),
+            ("job".to_string(), self.job.clone()),
+            ("build_number".to_string(), number.to_string()),
+            ("commit".to_string(), commit_sha.to_string()),
+        ]);
+        if let Some(result) = result {
+            metadata.insert("result".to_string(), result.to_string());
+        }
+        if let Some(duration) = build.get("duration").and_then(|v| v.as_u64()).filter(|d| *d > 0) {
+            metadata.insert("duration_ms".to_string(), duration.to_string());
+        }
+
+        let url = build.get("url").and_then(|v| v.as_str()).map(str::to_string);
+        gate(&self.name, status, details, url, metadata)
+    }
+}
+
+fn built_revision(build: &Value) -> Option<&str> {
+    build.get("actions")?.as_array()?.iter()
+        .find_map(|action| action.get("lastBuiltRevision")?.get("SHA1")?.as_str())
+}
+
+#[async_trait]
+impl QualityGateProvider for JenkinsGate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self, commit_sha: &str) -> Result<Vec<QualityGate>, HyperReviewError> {
+        let url = format!("{}/{}/api/json", self.base_url, self.job_path());
+        let client = self.http.async_client()?;
+        let policy = RequestPolicy::new(&self.retry_config, &self.circuit_breaker);
+
+        let response = send_with_retries(&policy, &reqwest::Method::GET, &url, || {
+            let mut request = client.get(&url).query(&[("tree", JENKINS_TREE)]);
+            if let (Some(username), Some(token)) = (&self.username, &self.token) {
+                request = request.basic_auth(username, Some(token));
+            }
+            request
+        }).await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(HyperReviewError::authentication_required(
+                self.base_url.clone(),
+                "Jenkins rejected the API token".to_string(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(HyperReviewError::network_with_status(
+                format!("Jenkins job {} failed: HTTP {}: {}", self.job, status, text),
+                status.as_u16(),
+            ));
+        }
+
+        let job: Value = serde_json::from_str(&text)?;
+        let build = job.get("builds")
+            .and_then(|b| b.as_array())
+            .and_then(|builds| builds.iter().find(|build| {
+                built_revision(build).is_some_and(|sha| sha.starts_with(commit_sha) || commit_sha.starts_with(sha))
+            }));
+
+        Ok(vec![match build {
+            Some(build) => self.map_build(build, commit_sha),
+            None => gate(
+                &self.name,
+                QualityGateStatus::Pending,
+                format!("No build of {} for {}", self.job, short_sha(commit_sha)),
+                Some(format!("{}/{}/", self.base_url, self.job_path())),
+                HashMap::from([("provider".to_string(), "jenkins".to_string())]),
+            ),
+        }])
+    }
+}
+
+/// Status of the newest GitLab pipeline for the commit
+pub struct GitLabPipelineGate {
+    id: String,
+    name: String,
+    project: String,
+    client: GitLabClient,
+}
+
+impl GitLabPipelineGate {
+    pub fn new(name: &str, project: &str, client: GitLabClient) -> Self {
+        Self {
+            id: format!("gitlab:{}", project),
+            name: name.to_string(),
+            project: project.to_string(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl QualityGateProvider for GitLabPipelineGate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self, commit_sha: &str) -> Result<Vec<QualityGate>, HyperReviewError> {
+        let pipelines = self.client.list_pipelines(&self.project, commit_sha).await?;
+        let Some(pipeline) = pipelines.into_iter().max_by_key(|p| p.id) else {
+            return Ok(vec![gate(
+                &self.name,
+                QualityGateStatus::Pending,
+                format!("No pipeline for {}", short_sha(commit_sha)),
+                None,
+                HashMap::from([("provider".to_string(), "gitlab".to_string())]),
+            )]);
+        };
+
+        let status = match pipeline.status.as_str() {
+            "success" => QualityGateStatus::Passing,
+            "failed" => QualityGateStatus::Failing,
+            "created" | "waiting_for_resource" | "preparing" | "pending" | "running" | "scheduled" | "manual" => {
+                QualityGateStatus::Pending
+            }
+            _ => QualityGateStatus::Unknown,
+        };
+
+        let mut metadata = HashMap::from([
+            ("provider".to_string(), "gitlab".to_string()),
+            ("project".to_string(), self.project.clone()),
+            ("pipeline_id".to_string(), pipeline.id.to_string()),
+            ("status".to_string(), pipeline.status.clone()),
+            ("commit".to_string(), pipeline.sha.clone()),
+        ]);
+        if let Some(git_ref) = &pipeline.git_ref {
+            metadata.insert("ref".to_string(), git_ref.clone());
+        }
+        if let Some(source) = &pipeline.source {
+            metadata.insert("source".to_string(), source.clone());
+        }
+
+        Ok(vec![gate(
+            &self.name,
+            status,
+            format!("Pipeline #{} {}", pipeline.id, pipeline.status),
+            pipeline.web_url,
+            metadata,
+        )])
+    }
+}
+
+/// The `Verified` vote on the Gerrit change for the commit
+pub struct GerritVerifiedGate {
+    id: String,
+    name: String,
+    client: GerritClient,
+}
+
+impl GerritVerifiedGate {
+    pub fn new(name: &str, client: GerritClient) -> Self {
+        Self {
+            id: format!("gerrit:{}", client.base_url()),
+            name: name.to_string(),
+            client,
+        }
+    }
+}
+
+/// Summarize a Gerrit label: rejected/approved win, otherwise the lowest/highest vote cast
+fn verified_status(label: &Value) -> (QualityGateStatus, Option<i64>, Option<String>) {
+    let voter = |key: &str| {
+        label.get(key).map(|account| {
+            account.get("name")
+                .or_else(|| account.get("username"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown")
+                .to_string()
+        })
+    };
+    if let Some(name) = voter("rejected") {
+        return (QualityGateStatus::Failing, Some(-1), Some(name));
+    }
+    if let Some(name) = voter("approved") {
+        return (QualityGateStatus::Passing, Some(1), Some(name));
+    }
+
+    let votes: Vec<(i64, String)> = label.get("all")
+        .and_then(|v| v.as_array())
+        .map(|all| all.iter().filter_map(|vote| {
+            let value = vote.get("value")?.as_i64()?;
+            let name = vote.get("name").or_else(|| vote.get("username"))?.as_str()?.to_string();
+            Some((value, name))
+        }).filter(|(value, _)| *value != 0).collect())
+        .unwrap_or_default();
+
+    if let Some((value, name)) = votes.iter().min_by_key(|(value, _)| *value).filter(|(value, _)| *value < 0) {
+        return (QualityGateStatus::Failing, Some(*value), Some(name.clone()));
+    }
+    if let Some((value, name)) = votes.iter().max_by_key(|(value, _)| *value) {
+        return (QualityGateStatus::Passing, Some(*value), Some(name.clone()));
+    }
+    (QualityGateStatus::Pending, None, None)
+}
+
+#[async_trait]
+impl QualityGateProvider for GerritVerifiedGate {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self, commit_sha: &str) -> Result<Vec<QualityGate>, HyperReviewError> {
+        let Some(change) = self.client.get_change_by_commit(commit_sha).await? else {
+            return Ok(vec![gate(
+                &self.name,
+                QualityGateStatus::Unknown,
+                format!("No Gerrit change for {}", short_sha(commit_sha)),
+                None,
+                HashMap::from([("provider".to_string(), "gerrit".to_string())]),
+            )]);
+        };
+
+        let url = Some(format!("{}/c/{}/+/{}", self.client.base_url(), change.project, change._number));
+        let mut metadata = HashMap::from([
+            ("provider".to_string(), "gerrit".to_string()),
+            ("change_number".to_string(), change._number.to_string()),
+            ("project".to_string(), change.project.clone()),
+        ]);
+
+        let Some(label) = change.labels.as_ref().and_then(|labels| labels.get("Verified")) else {
+            return Ok(vec![gate(
+                &self.name,
+                QualityGateStatus::Unknown,
+                "Project does not use the Verified label".to_string(),
+                url,
+                metadata,
+            )]);
+        };
+
+        let (status, score, voter) = verified_status(label);
+        let details = match (&score, &voter) {
+            (Some(score), Some(voter)) => format!("Verified {:+} by {}", score, voter),
+            _ => "Waiting for Verified vote".to_string(),
+        };
+        if let Some(score) = score {
+            metadata.insert("verified".to_string(), score.to_string());
+        }
+        if let Some(voter) = voter {
+            metadata.insert("voter".to_string(), voter);
+        }
+
+        Ok(vec![gate(&self.name, status, details, url, metadata)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::fixtures::fast_retries;
+    use crate::remote::mock_server::{MockResponse, MockServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_jenkins_finds_build_for_commit() {
+        let server = MockServer::start(|request| {
+            assert!(request.path.starts_with("/job/team/job/app/api/json?tree="));
+            MockResponse::json(r#"{"builds":[
+                {"number":12,"url":"http://ci/job/team/job/app/12/","building":true,"result":null,
+                 "actions":[{},{"lastBuiltRevision":{"SHA1":"bbbbbbbb11"}}]},
+                {"number":11,"url":"http://ci/job/team/job/app/11/","building":false,"result":"UNSTABLE","duration":5000,
+                 "actions":[{"lastBuiltRevision":{"SHA1":"aaaaaaaa22"}}]}
+            ]}"#)
+        });
+        let jenkins = JenkinsGate::new("Jenkins", &server.base_url, "team/app")
+            .with_auth("ci".to_string(), "token".to_string());
+
+        let gates = jenkins.check("aaaaaaaa22").await.unwrap();
+        assert_eq!(gates[0].status, QualityGateStatus::Failing);
+        assert_eq!(gates[0].details.as_deref(), Some("Build #11 UNSTABLE"));
+        assert_eq!(gates[0].url.as_deref(), Some("http://ci/job/team/job/app/11/"));
+        assert_eq!(gates[0].metadata["duration_ms"], "5000");
+
+        assert_eq!(jenkins.check("bbbbbbbb11").await.unwrap()[0].status, QualityGateStatus::Pending);
+        assert_eq!(jenkins.check("cccccccc").await.unwrap()[0].status, QualityGateStatus::Pending);
+        assert!(server.requests()[0].header("authorization").unwrap().starts_with("Basic "));
+    }
+
+    #[tokio::test]
+    async fn test_jenkins_retries_unavailable_server() {
+        let server = MockServer::start(|_| MockResponse::new(503, "").with_header("Retry-After", "0"));
+        let jenkins = JenkinsGate::new("Jenkins", &server.base_url, "app").with_retry_config(fast_retries());
+
+        assert!(jenkins.check("aaaaaaaa").await.is_err());
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_pipeline_status() {
+        let server = MockServer::start(|request| {
+            assert!(request.path.starts_with("/api/v4/projects/group%2Fapp/pipelines?sha=abc123"));
+            MockResponse::json(r#"[
+                {"id":90,"status":"failed","ref":"main","sha":"abc123","web_url":"https://gl/p/90"},
+                {"id":91,"status":"success","ref":"main","sha":"abc123","web_url":"https://gl/p/91","source":"push"}
+            ]"#)
+        });
+        let client = GitLabClient::new(&server.base_url).with_token("t".to_string());
+        let gates = GitLabPipelineGate::new("Pipeline", "group/app", client).check("abc123").await.unwrap();
+
+        assert_eq!(gates[0].status, QualityGateStatus::Passing);
+        assert_eq!(gates[0].url.as_deref(), Some("https://gl/p/91"));
+        assert_eq!(gates[0].metadata["pipeline_id"], "91");
+        assert_eq!(gates[0].metadata["source"], "push");
+    }
+
+    #[tokio::test]
+    async fn test_gerrit_verified_vote() {
+        let server = MockServer::start(|request| {
+            assert!(request.path.starts_with("/a/changes/?q=commit%3Adeadbeef"));
+            MockResponse::json(r#")]}'
+[{"id":"p~main~I1","change_id":"I1","_number":7,"subject":"s","status":"NEW","project":"p","branch":"main",
+  "owner":{},"updated":"","created":"","labels":{"Verified":{"all":[
+    {"value":1,"name":"CI"},{"value":-1,"name":"Lint Bot"}]}}}]"#)
+        });
+        let client = GerritClient::new(&server.base_url).with_auth("u".to_string(), "p".to_string());
+        let gates = GerritVerifiedGate::new("Verified", client).check("deadbeef").await.unwrap();
+
+        assert_eq!(gates[0].status, QualityGateStatus::Failing);
+        assert_eq!(gates[0].details.as_deref(), Some("Verified -1 by Lint Bot"));
+        assert_eq!(gates[0].url, Some(format!("{}/c/p/+/7", server.base_url)));
+    }
+
+    #[test]
+    fn test_verified_status_prefers_approval_summary() {
+        let label = serde_json::json!({"approved": {"name": "CI"}, "all": [{"value": -1, "name": "x"}]});
+        assert_eq!(verified_status(&label).0, QualityGateStatus::Passing);
+        assert_eq!(verified_status(&serde_json::json!({})).0, QualityGateStatus::Pending);
+    }
+
+    struct CountingGate {
+        calls: AtomicUsize,
+        status: QualityGateStatus,
+    }
+
+    #[async_trait]
+    impl QualityGateProvider for CountingGate {
+        fn id(&self) -> &str {
+            "counting-test"
+        }
+
+        fn name(&self) -> &str {
+            "Counting"
+        }
+
+        async fn check(&self, _commit_sha: &str) -> Result<Vec<QualityGate>, HyperReviewError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![gate("Counting", self.status.clone(), String::new(), None, HashMap::new())])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_results_cached_per_commit() {
+        let provider = Arc::new(CountingGate { calls: AtomicUsize::new(0), status: QualityGateStatus::Failing });
+        let checker = QualityGateChecker::new().with_provider(provider.clone());
+
+        let gates = checker.check("sha-1", false).await;
+        assert_eq!(failing_gates(&gates).len(), 1);
+        checker.check("sha-1", false).await;
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+
+        checker.check("sha-2", false).await;
+        checker.check("sha-1", true).await;
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,578 @@
+// Traffic control for remote clients
+// Per-host rate limiting, retry classification, circuit breaking and conditional GET caching
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::HyperReviewError;
+use crate::remote::gerrit_client::RetryConfig;
+
+/// Token bucket limiting how many requests per second are sent to one host
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        Self {
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Take a token, or return how long to wait until one is available
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - self.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+}
+
+/// Rate limiter settings applied to each host
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub burst: u32,
+    pub requests_per_second: f64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            burst: 10,
+            requests_per_second: 5.0,
+        }
+    }
+}
+
+fn buckets() -> &'static Mutex<HashMap<String, TokenBucket>> {
+    static BUCKETS: OnceLock<Mutex<HashMap<String, TokenBucket>>> = OnceLock::new();
+    BUCKETS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Block until the host's bucket has a token. Call from a blocking context.
+pub fn acquire_rate_limit(host: &str, config: &RateLimitConfig) {
+    loop {
+        let wait = {
+            let mut buckets = buckets().lock().unwrap();
+            let bucket = buckets
+                .entry(host.to_string())
+                .or_insert_with(|| TokenBucket::new(config.burst, config.requests_per_second));
+            match bucket.try_acquire(Instant::now()) {
+                Ok(()) => return,
+                Err(wait) => wait,
+            }
+        };
+        debug!("Rate limit reached for {}, waiting {:?}", host, wait);
+        std::thread::sleep(wait);
+    }
+}
+
+/// Circuit breaker state for one host
+#[derive(Debug, Clone, PartialEq)]
+pub enum CircuitState {
+    Closed,
+    Open { until: Instant },
+    /// One probe is out; another is only let through if it never reports back by the deadline
+    HalfOpen { probe_deadline: Instant },
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Stops sending requests to a host after repeated failures, then probes it again after a cooldown
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Whether a request may be sent now. An expired open circuit lets one probe through
+    /// and holds back the rest until the probe succeeds or fails.
+    pub fn allow_request(&mut self, now: Instant, config: &CircuitBreakerConfig) -> bool {
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open { until } | CircuitState::HalfOpen { probe_deadline: until } if now >= until => {
+                self.state = CircuitState::HalfOpen { probe_deadline: now + config.cooldown };
+                true
+            }
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => false,
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+    }
+
+    pub fn record_failure(&mut self, now: Instant, config: &CircuitBreakerConfig) {
+        self.consecutive_failures += 1;
+        if matches!(self.state, CircuitState::HalfOpen { .. }) || self.consecutive_failures >= config.failure_threshold {
+            self.state = CircuitState::Open { until: now + config.cooldown };
+        }
+    }
+
+    pub fn state(&self) -> &CircuitState {
+        &self.state
+    }
+}
+
+fn breakers() -> &'static Mutex<HashMap<String, CircuitBreaker>> {
+    static BREAKERS: OnceLock<Mutex<HashMap<String, CircuitBreaker>>> = OnceLock::new();
+    BREAKERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Fail fast when the host's circuit is open
+pub fn check_circuit(host: &str, config: &CircuitBreakerConfig) -> Result<(), HyperReviewError> {
+    let mut breakers = breakers().lock().unwrap();
+    let breaker = breakers.entry(host.to_string()).or_default();
+    if breaker.allow_request(Instant::now(), config) {
+        Ok(())
+    } else {
+        Err(HyperReviewError::network(format!(
+            "Too many recent failures talking to {}; requests are paused briefly",
+            host
+        )))
+    }
+}
+
+pub fn record_outcome(host: &str, success: bool, config: &CircuitBreakerConfig) {
+    let mut breakers = breakers().lock().unwrap();
+    let breaker = breakers.entry(host.to_string()).or_default();
+    if success {
+        breaker.record_success();
+    } else {
+        breaker.record_failure(Instant::now(), config);
+        if let CircuitState::Open { .. } = breaker.state() {
+            warn!("Circuit opened for {} after {} failures", host, breaker.consecutive_failures);
+        }
+    }
+}
+
+/// `host:port` of a URL; limits and breakers are tracked per authority
+pub fn authority_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.host_str().map(|host| match u.port_or_known_default() {
+                Some(port) => format!("{}:{}", host.to_lowercase(), port),
+                None => host.to_lowercase(),
+            })
+        })
+        .unwrap_or_else(|| url.to_string())
+}
+
+/// Whether a response status is worth retrying
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Whether a transport error is worth retrying. Other errors, e.g. a malformed URL or
+/// a body that cannot be built, fail the same way on every attempt.
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+/// Parse a `Retry-After` header given either as seconds or as an HTTP date
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    DateTime::parse_from_rfc2822(value).ok().map(|date| {
+        let delta = date.with_timezone(&Utc) - now;
+        delta.to_std().unwrap_or(Duration::ZERO)
+    })
+}
+
+/// Retry, circuit-breaker and rate-limit settings a request loop runs under
+pub struct RequestPolicy<'a> {
+    retry: &'a RetryConfig,
+    circuit_breaker: &'a CircuitBreakerConfig,
+    rate_limit: Option<&'a RateLimitConfig>,
+    idempotent: bool,
+    throttled: fn(reqwest::StatusCode, &reqwest::header::HeaderMap) -> bool,
+}
+
+impl<'a> RequestPolicy<'a> {
+    pub fn new(retry: &'a RetryConfig, circuit_breaker: &'a CircuitBreakerConfig) -> Self {
+        Self {
+            retry,
+            circuit_breaker,
+            rate_limit: None,
+            idempotent: false,
+            throttled: |_, _| false,
+        }
+    }
+
+    /// Wait for a token from the host's bucket before each attempt. Blocks the thread,
+    /// so only use it with `send_with_retries_blocking`.
+    pub fn with_rate_limit(mut self, rate_limit: &'a RateLimitConfig) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Retry every method, not just GET, for requests the receiver deduplicates
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent = true;
+        self
+    }
+
+    fn may_resend(&self, method: &reqwest::Method) -> bool {
+        self.idempotent || *method == reqwest::Method::GET
+    }
+
+    /// Treat extra responses as throttling, e.g. GitHub's secondary rate limits on 403
+    pub fn with_throttling(mut self, throttled: fn(reqwest::StatusCode, &reqwest::header::HeaderMap) -> bool) -> Self {
+        self.throttled = throttled;
+        self
+    }
+
+    fn before_send(&self, host: &str) -> Result<(), HyperReviewError> {
+        check_circuit(host, self.circuit_breaker)?;
+        if let Some(rate_limit) = self.rate_limit {
+            acquire_rate_limit(host, rate_limit);
+        }
+        Ok(())
+    }
+
+    /// Record a transport failure; `Some(delay)` when the request should be sent again
+    fn after_error(
+        &self,
+        host: &str,
+        method: &reqwest::Method,
+        url: &str,
+        error: &reqwest::Error,
+        attempt: u32,
+    ) -> Option<Duration> {
+        record_outcome(host, false, self.circuit_breaker);
+        let may_retry = is_retryable_error(error) && (self.may_resend(method) || error.is_connect());
+        if !may_retry || attempt >= self.retry.max_retries {
+            return None;
+        }
+        let delay = self.retry.backoff_delay(attempt);
+        warn!("{} {} failed ({}), retrying in {:?}", method, url, error, delay);
+        Some(delay)
+    }
+
+    /// Record a response; `Some(delay)` when it should be retried after waiting
+    fn after_response(
+        &self,
+        host: &str,
+        method: &reqwest::Method,
+        url: &str,
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        attempt: u32,
+    ) -> Result<Option<Duration>, HyperReviewError> {
+        if status.is_server_error() {
+            record_outcome(host, false, self.circuit_breaker);
+        } else if status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            record_outcome(host, true, self.circuit_breaker);
+        }
+
+        let throttled = (self.throttled)(status, headers);
+        let may_retry = (is_retryable_status(status.as_u16()) || throttled)
+            && (self.may_resend(method) || throttled || matches!(status.as_u16(), 429 | 503));
+        if !may_retry || attempt >= self.retry.max_retries {
+            return Ok(None);
+        }
+
+        let retry_after = headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| parse_retry_after(v, Utc::now()));
+        match retry_after {
+            Some(delay) if delay > Duration::from_millis(self.retry.max_delay_ms) => {
+                Err(HyperReviewError::network_with_status(
+                    format!("{} is throttling requests; retry after {}s", host, delay.as_secs()),
+                    status.as_u16(),
+                ))
+            }
+            _ => {
+                let delay = retry_after.unwrap_or_else(|| self.retry.backoff_delay(attempt));
+                warn!("{} {} returned {}, retrying in {:?}", method, url, status, delay);
+                Ok(Some(delay))
+            }
+        }
+    }
+}
+
+/// Send the request `build` produces, rebuilding and resending it while the policy allows.
+///
+/// Retries honour `Retry-After`. Unless the policy is idempotent, non-GET requests are only
+/// retried when the server cannot have processed them (429/503, throttling or a failed connect).
+/// The final response is returned whatever its status.
+pub async fn send_with_retries<F>(
+    policy: &RequestPolicy<'_>,
+    method: &reqwest::Method,
+    url: &str,
+    build: F,
+) -> Result<reqwest::Response, HyperReviewError>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let host = authority_of(url);
+    let mut attempt = 0;
+    loop {
+        policy.before_send(&host)?;
+        let delay = match build().send().await {
+            Ok(response) => {
+                match policy.after_response(&host, method, url, response.status(), response.headers(), attempt)? {
+                    Some(delay) => delay,
+                    None => return Ok(response),
+                }
+            }
+            Err(e) => match policy.after_error(&host, method, url, &e, attempt) {
+                Some(delay) => delay,
+                None => return Err(HyperReviewError::Http(e)),
+            },
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Blocking twin of `send_with_retries` for clients that run inside `spawn_blocking`
+pub fn send_with_retries_blocking<F>(
+    policy: &RequestPolicy<'_>,
+    method: &reqwest::Method,
+    url: &str,
+    build: F,
+) -> Result<reqwest::blocking::Response, HyperReviewError>
+where
+    F: Fn() -> reqwest::blocking::RequestBuilder,
+{
+    let host = authority_of(url);
+    let mut attempt = 0;
+    loop {
+        policy.before_send(&host)?;
+        let delay = match build().send() {
+            Ok(response) => {
+                match policy.after_response(&host, method, url, response.status(), response.headers(), attempt)? {
+                    Some(delay) => delay,
+                    None => return Ok(response),
+                }
+            }
+            Err(e) => match policy.after_error(&host, method, url, &e, attempt) {
+                Some(delay) => delay,
+                None => return Err(HyperReviewError::Http(e)),
+            },
+        };
+        std::thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+/// A cached GET response with its validators
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CachedResponse {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+    pub stored_at: String,
+}
+
+/// On-disk cache of GET responses keyed by URL and credential identity
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Cache under `~/.hyperreview/http_cache`
+    pub fn default_location() -> Option<Self> {
+        dirs::home_dir().map(|home| Self::new(home.join(".hyperreview").join("http_cache")))
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        let digest = Sha256::digest(key.as_bytes());
+        let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// `scope` separates entries fetched with different credentials
+    pub fn get(&self, scope: &str, url: &str) -> Option<CachedResponse> {
+        let path = self.path_for(&format!("{}\n{}", scope, url));
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str::<CachedResponse>(&content)
+            .ok()
+            .filter(|entry| entry.url == url)
+    }
+
+    pub fn put(&self, scope: &str, entry: &CachedResponse) -> Result<(), HyperReviewError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(&format!("{}\n{}", scope, entry.url));
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(entry)?)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), HyperReviewError> {
+        if self.dir.exists() {
+            std::fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_token_bucket_limits_burst() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2, 1.0);
+        bucket.last_refill = start;
+
+        assert!(bucket.try_acquire(start).is_ok());
+        assert!(bucket.try_acquire(start).is_ok());
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+
+        // After a second one token is back
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_and_half_opens() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(10),
+        };
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::default();
+
+        breaker.record_failure(now, &config);
+        assert!(breaker.allow_request(now, &config));
+        breaker.record_failure(now, &config);
+        assert!(!breaker.allow_request(now, &config));
+
+        // One probe after the cooldown; a failed probe reopens immediately
+        let later = now + Duration::from_secs(11);
+        assert!(breaker.allow_request(later, &config));
+        assert!(matches!(breaker.state(), CircuitState::HalfOpen { .. }));
+        assert!(!breaker.allow_request(later, &config));
+        breaker.record_failure(later, &config);
+        assert!(!breaker.allow_request(later, &config));
+
+        // A probe that never reports back is replaced after another cooldown
+        let much_later = later + Duration::from_secs(11);
+        assert!(breaker.allow_request(much_later, &config));
+        assert!(!breaker.allow_request(much_later + Duration::from_secs(5), &config));
+        assert!(breaker.allow_request(much_later + Duration::from_secs(11), &config));
+        breaker.record_success();
+        assert_eq!(breaker.state(), &CircuitState::Closed);
+        assert!(breaker.allow_request(much_later + Duration::from_secs(11), &config));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 01 Jan 2025 00:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_retry_after("Tue, 31 Dec 2024 00:00:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_authority_of() {
+        assert_eq!(authority_of("https://Gerrit.example.com/a/changes"), "gerrit.example.com:443");
+        assert_eq!(authority_of("http://127.0.0.1:8080/"), "127.0.0.1:8080");
+    }
+
+    #[test]
+    fn test_retryable_error() {
+        let client = reqwest::blocking::Client::new();
+        let malformed = client.get("http://exa mple.com/").send().unwrap_err();
+        assert!(!is_retryable_error(&malformed));
+
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let refused = client.get(format!("http://127.0.0.1:{}/", closed)).send().unwrap_err();
+        assert!(is_retryable_error(&refused));
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(400));
+        assert!(!is_retryable_status(404));
+    }
+
+    #[test]
+    fn test_response_cache_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let cache = ResponseCache::new(dir.path().join("http_cache"));
+        let entry = CachedResponse {
+            url: "https://gerrit.example.com/a/changes/1".to_string(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            body: "{}".to_string(),
+            stored_at: Utc::now().to_rfc3339(),
+        };
+
+        cache.put("alice", &entry).unwrap();
+        assert_eq!(cache.get("alice", &entry.url), Some(entry.clone()));
+        assert_eq!(cache.get("bob", &entry.url), None);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.get("alice", &entry.url), None);
+    }
+}
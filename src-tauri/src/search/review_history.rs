@@ -0,0 +1,211 @@
+// Review history search
+// Ranked full-text search over everything reviewed, backed by the SQLite FTS5 index
+
+use crate::errors::HyperReviewError;
+use crate::models::search::{ReviewSearchHit, SearchSourceKind};
+use crate::storage::sqlite::Database;
+
+/// Results returned when no limit is given
+pub const DEFAULT_SEARCH_LIMIT: u32 = 50;
+
+// Private-use characters mark matches so the text can be escaped before adding <mark>
+const MATCH_START: &str = "\u{E000}";
+const MATCH_END: &str = "\u{E001}";
+
+/// Turn a search box query into an FTS5 match expression.
+///
+/// Words must all match, `"quoted text"` matches a phrase, `word*` matches a prefix
+/// and `OR` between two terms matches either. Anything else is taken literally, so
+/// user input never produces an FTS5 syntax error. Returns `None` when nothing is left to search.
+pub fn fts_query(input: &str) -> Option<String> {
+    let mut terms: Vec<String> = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        if c == '"' {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            if !phrase.trim().is_empty() {
+                terms.push(quote(phrase.trim()));
+            }
+            continue;
+        }
+
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '"' {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+
+        if word == "OR" {
+            if terms.last().is_some_and(|last| last != "OR") {
+                terms.push(word);
+            }
+            continue;
+        }
+
+        let stem = word.trim_end_matches('*');
+        if stem.chars().any(char::is_alphanumeric) {
+            let prefix = if stem.len() < word.len() { "*" } else { "" };
+            terms.push(format!("{}{}", quote(stem), prefix));
+        }
+    }
+
+    if terms.last().is_some_and(|last| last == "OR") {
+        terms.pop();
+    }
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Escape indexed text for HTML and turn the match markers into `<mark>`
+fn to_highlighted_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace(MATCH_START, "<mark>")
+        .replace(MATCH_END, "</mark>")
+}
+
+/// Search review comments, task comments and changes, best matches first.
+/// An empty `kinds` searches every source.
+pub fn search_review_history(
+    database: &Database,
+    query: &str,
+    kinds: &[SearchSourceKind],
+    limit: Option<u32>,
+) -> Result<Vec<ReviewSearchHit>, HyperReviewError> {
+    let Some(match_expr) = fts_query(query) else {
+        return Ok(Vec::new());
+    };
+
+    let hits = database.search_review_index(
+        &match_expr,
+        kinds,
+        (MATCH_START, MATCH_END),
+        limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+    )?;
+
+    log::debug!("Review history search {:?} matched {} entries", match_expr, hits.len());
+    Ok(hits.into_iter()
+        .map(|hit| ReviewSearchHit {
+            title: to_highlighted_html(&hit.title),
+            snippet: to_highlighted_html(&hit.snippet),
+            ..hit
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::gerrit::*;
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("memory leak").as_deref(), Some("\"memory\" \"leak\""));
+        assert_eq!(fts_query("\"null check\" refact*").as_deref(), Some("\"null check\" \"refact\"*"));
+        assert_eq!(fts_query("OR unwrap OR expect OR").as_deref(), Some("\"unwrap\" OR \"expect\""));
+        assert_eq!(fts_query("NEAR( -foo:bar").as_deref(), Some("\"NEAR(\" \"-foo:bar\""));
+        assert_eq!(fts_query(" * \"\" ** "), None);
+    }
+
+    fn comment(id: &str, content: &str) -> ReviewComment {
+        ReviewComment {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            file_path: "src/pool.rs".to_string(),
+            line_number: Some(4),
+            content: content.to_string(),
+            comment_type: CommentType::Inline,
+            severity: CommentSeverity::Minor,
+            status: CommentStatus::Draft,
+            parent_comment_id: None,
+            created_at: "2025-01-05 12:00:00".to_string(),
+            updated_at: "2025-01-05 12:00:00".to_string(),
+            mentions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_index_follows_comments_and_changes() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_schema().unwrap();
+        database.init_gerrit_schema().unwrap();
+
+        let change = GerritChange {
+            id: "c1".to_string(),
+            change_id: "I123".to_string(),
+            instance_id: "inst".to_string(),
+            project: "core".to_string(),
+            branch: "main".to_string(),
+            subject: "Fix connection pooling".to_string(),
+            status: ChangeStatus::New,
+            owner: GerritUser {
+                account_id: 1,
+                name: "Ann".to_string(),
+                email: "ann@example.com".to_string(),
+                username: None,
+                avatar_url: None,
+            },
+            created: "2025-01-05 12:00:00".to_string(),
+            updated: "2025-01-05 12:00:00".to_string(),
+            insertions: 3,
+            deletions: 1,
+            current_revision: "abc".to_string(),
+            current_patch_set_num: 1,
+            patch_sets: Vec::new(),
+            files: Vec::new(),
+            total_files: 1,
+            reviewed_files: 0,
+            local_comments: 0,
+            remote_comments: 0,
+            import_status: ImportStatus::Imported,
+            last_sync: None,
+            conflict_status: ConflictStatus::None,
+            metadata: [("commit_message".to_string(), "Pooled connections were never returned".to_string())].into(),
+        };
+        database.store_gerrit_change(&change).unwrap();
+        database.store_review_comment(&comment("rc1", "This <leaks> the connection handle")).unwrap();
+        database.store_review_comment(&comment("rc2", "Nice cleanup")).unwrap();
+
+        let task = database.create_local_task("Audit", "code", &["src/db.rs".to_string()]).unwrap();
+        database.update_file_review_status(&task.id, &task.files[0].id, "needs_work",
+                                           Some("Connection string is hardcoded"), None).unwrap();
+
+        let hits = search_review_history(&database, "connect*", &[], None).unwrap();
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].kind, SearchSourceKind::Change); // Subject matches weigh more
+        assert_eq!(hits[0].title, "Fix <mark>connection</mark> pooling");
+
+        let comments = search_review_history(&database, "\"connection handle\"", &[SearchSourceKind::ReviewComment], None).unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].snippet, "This &lt;leaks&gt; the <mark>connection handle</mark>");
+
+        let tasks = search_review_history(&database, "hardcoded", &[], None).unwrap();
+        assert_eq!((tasks[0].kind, tasks[0].scope_id.as_str(), tasks[0].title.as_str()),
+                   (SearchSourceKind::TaskComment, task.id.as_str(), "src/db.rs"));
+
+        // Edits and deletes are picked up by the triggers
+        database.update_review_comment(&comment("rc2", "Nice cleanup, thanks")).unwrap();
+        database.delete_review_comment("rc1").unwrap();
+        assert_eq!(search_review_history(&database, "thanks", &[], None).unwrap().len(), 1);
+        assert!(search_review_history(&database, "leaks", &[], None).unwrap().is_empty());
+    }
+}
@@ -10,6 +10,7 @@ use regex::Regex;
 
 use crate::models::gerrit::*;
 use crate::remote::gerrit_client::{GerritClient, GerritChangeInfo};
+use crate::services::comment_engine::{CommentEngine, CommentEngineConfig};
 use crate::storage::sqlite::Database;
 use crate::errors::HyperReviewError;
 
@@ -37,7 +38,12 @@ impl ChangeDownloader {
         info!("Starting download for change: {}", change_id);
 
         // Step 1: Get change metadata from Gerrit
-        let change_info = self.get_change_metadata(change_id).await?;
+        let mut change_info = self.get_change_metadata(change_id).await?;
+        if let Some(local) = self.database.get_gerrit_change(&change_info.change_id)? {
+            // Keep the local record so sessions, comments and earlier patch sets stay attached
+            change_info.id = local.id;
+            change_info.instance_id = local.instance_id;
+        }
         let patch_set_num = patch_set_number.unwrap_or(change_info.current_patch_set_num);
 
         // Step 2: Get file list for the patch set
@@ -175,12 +181,28 @@ impl ChangeDownloader {
                     
                     // Download the updated change
                     let download_result = self.download_change(change_id, None).await?;
+
+                    // Carry local comments over to the new patch set
+                    let mut comments_moved = 0;
+                    let mut comments_outdated = 0;
+                    if remote_change.current_patch_set_num > local.current_patch_set_num {
+                        let engine = CommentEngine::new(CommentEngineConfig::default());
+                        match engine.reanchor_change(&local.id, remote_change.current_patch_set_num, &self.database) {
+                            Ok(summaries) => {
+                                comments_moved = summaries.iter().map(|s| s.moved_count).sum();
+                                comments_outdated = summaries.iter().map(|s| s.outdated_count).sum();
+                            }
+                            Err(e) => warn!("Failed to re-anchor comments for change {}: {}", change_id, e),
+                        }
+                    }
                     
                     Ok(UpdateResult {
                         updated: true,
                         old_patch_set: local.current_patch_set_num,
                         new_patch_set: remote_change.current_patch_set_num,
                         files_changed: download_result.files.len() as u32,
+                        comments_moved,
+                        comments_outdated,
                         message: format!("Updated from PS{} to PS{}", 
                                        local.current_patch_set_num, 
                                        remote_change.current_patch_set_num),
@@ -191,6 +213,8 @@ impl ChangeDownloader {
                         old_patch_set: local.current_patch_set_num,
                         new_patch_set: local.current_patch_set_num,
                         files_changed: 0,
+                        comments_moved: 0,
+                        comments_outdated: 0,
                         message: "Change is already up to date".to_string(),
                     })
                 }
@@ -204,6 +228,8 @@ impl ChangeDownloader {
                     old_patch_set: 0,
                     new_patch_set: remote_change.current_patch_set_num,
                     files_changed: download_result.files.len() as u32,
+                    comments_moved: 0,
+                    comments_outdated: 0,
                     message: "Change downloaded for the first time".to_string(),
                 })
             }
@@ -223,11 +249,11 @@ impl ChangeDownloader {
         };
 
         // Convert GerritChangeInfo to our GerritChange model
-        Ok(self.convert_change_info(change_info))
+        Ok(Self::convert_change_info(change_info))
     }
 
     /// Parse unified diff to extract line counts and hunks
-    fn parse_unified_diff(&self, unified_diff: &str) -> (u32, u32, Vec<DiffHunk>) {
+    pub fn parse_unified_diff(unified_diff: &str) -> (u32, u32, Vec<DiffHunk>) {
         let mut old_line_count = 0u32;
         let mut new_line_count = 0u32;
         let mut hunks = Vec::new();
@@ -306,7 +332,7 @@ impl ChangeDownloader {
     }
 
     /// Convert Gerrit API response to our internal model
-    fn convert_change_info(&self, info: GerritChangeInfo) -> GerritChange {
+    pub fn convert_change_info(info: GerritChangeInfo) -> GerritChange {
         let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
         
         // Parse owner information from JSON value
@@ -361,10 +387,7 @@ impl ChangeDownloader {
                             revision: revision_id.clone(),
                             number: patch_set_number,
                             author: owner.clone(), // Simplified - could be different
-                            commit_message: revision_obj.get("description")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("")
-                                .to_string(),
+                            commit_message: Self::revision_commit_message(revision_obj),
                             created: revision_obj.get("created")
                                 .and_then(|v| v.as_str())
                                 .unwrap_or(&now)
@@ -382,6 +405,18 @@ impl ChangeDownloader {
         
         // Sort patch sets by number
         patch_sets.sort_by_key(|ps| ps.number);
+
+        // Kept in metadata so the change search index can match on it
+        let mut metadata = HashMap::new();
+        let current_message = info.revisions.as_ref()
+            .zip(info.current_revision.as_ref())
+            .and_then(|(revisions, current)| revisions.get(current))
+            .and_then(|revision| revision.as_object())
+            .map(Self::revision_commit_message)
+            .filter(|message| !message.is_empty());
+        if let Some(message) = current_message {
+            metadata.insert("commit_message".to_string(), message);
+        }
         
         GerritChange {
             id: uuid::Uuid::new_v4().to_string(),
@@ -407,10 +442,20 @@ impl ChangeDownloader {
             import_status: ImportStatus::Importing,
             last_sync: Some(now.clone()),
             conflict_status: ConflictStatus::None,
-            metadata: HashMap::new(),
+            metadata,
         }
     }
 
+    /// Commit message of a revision (requested with `o=CURRENT_COMMIT`), else its patch set description
+    fn revision_commit_message(revision: &serde_json::Map<String, serde_json::Value>) -> String {
+        revision.get("commit")
+            .and_then(|commit| commit.get("message"))
+            .or_else(|| revision.get("description"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    }
+
     /// Get file list for a specific patch set
     async fn get_patch_set_files(
         &self,
@@ -497,7 +542,7 @@ impl ChangeDownloader {
         };
         
         // Parse diff to extract line counts and create hunks
-        let (old_line_count, new_line_count, hunks) = self.parse_unified_diff(&unified_diff);
+        let (old_line_count, new_line_count, hunks) = Self::parse_unified_diff(&unified_diff);
         
         let diff = FileDiff {
             unified_diff,
@@ -583,7 +628,14 @@ impl ChangeDownloader {
 
     /// Store change metadata in database
     async fn store_change_metadata(&self, change: &GerritChange) -> Result<(), HyperReviewError> {
-        self.database.store_gerrit_change(change)?;
+        // Replacing the row would cascade and delete the change's review sessions
+        if self.database.get_gerrit_change(&change.id)?.is_some() {
+            self.database.update_gerrit_change(change)?;
+        } else {
+            self.database.store_gerrit_change(change)?;
+        }
+        // Owners seed the account directory that comment mentions resolve against
+        self.database.store_gerrit_accounts(&change.instance_id, std::slice::from_ref(&change.owner))?;
         Ok(())
     }
 }
@@ -626,6 +678,8 @@ pub struct UpdateResult {
     pub old_patch_set: u32,
     pub new_patch_set: u32,
     pub files_changed: u32,
+    pub comments_moved: u32,    // Inline comments re-anchored to a new line
+    pub comments_outdated: u32, // Inline comments whose code was deleted
     pub message: String,
 }
 
@@ -760,6 +814,22 @@ mod tests {
         assert_eq!(status.file_count, 0);
     }
 
+    #[test]
+    fn test_convert_change_info_keeps_commit_message() {
+        let info: GerritChangeInfo = serde_json::from_value(serde_json::json!({
+            "id": "core~main~I123", "change_id": "I123", "_number": 7,
+            "subject": "Fix pooling", "status": "NEW", "project": "core", "branch": "main",
+            "owner": {"_account_id": 1, "name": "Ann"},
+            "created": "2025-01-05 12:00:00", "updated": "2025-01-05 12:00:00",
+            "current_revision": "abc",
+            "revisions": {"abc": {"_number": 1, "commit": {"message": "Fix pooling\n\nReturn connections"}}}
+        })).unwrap();
+
+        let change = ChangeDownloader::convert_change_info(info);
+        assert_eq!(change.metadata.get("commit_message").map(String::as_str), Some("Fix pooling\n\nReturn connections"));
+        assert_eq!(change.patch_sets[0].commit_message, "Fix pooling\n\nReturn connections");
+    }
+
     #[test]
     fn test_file_info_creation() {
         let file_info = FileInfo {
@@ -785,6 +855,8 @@ mod tests {
             old_patch_set: 1,
             new_patch_set: 2,
             files_changed: 3,
+            comments_moved: 0,
+            comments_outdated: 0,
             message: "Updated from PS1 to PS2".to_string(),
         };
 
@@ -799,6 +871,8 @@ mod tests {
             old_patch_set: 2,
             new_patch_set: 2,
             files_changed: 0,
+            comments_moved: 0,
+            comments_outdated: 0,
             message: "Change is already up to date".to_string(),
         };
 
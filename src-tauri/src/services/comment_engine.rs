@@ -6,9 +6,20 @@ use serde::{Serialize, Deserialize};
 use log::{info, warn, error, debug};
 
 use crate::errors::HyperReviewError;
-use crate::models::gerrit::{ReviewComment, CommentType, CommentStatus};
+use crate::models::gerrit::{
+    ReviewComment, CommentType, CommentSeverity, CommentStatus, CommentAnchor, ReviewStatus,
+    SuggestedChange, ThreadState, ThreadStateChange,
+};
+use crate::models::search::SearchSourceKind;
+use crate::search::review_history::fts_query;
+use crate::services::diff_engine::{DiffEngine, DiffConfig, LineMapping, ProcessedDiff};
+use crate::services::mentions::resolve_session_mentions;
 use crate::storage::sqlite::Database;
 
+/// Lines with fewer letters or digits than this (e.g. a lone `}`) are too
+/// generic to be found again by content
+const MIN_FUZZY_ANCHOR_CHARS: usize = 3;
+
 /// Comment engine for managing review comments
 pub struct CommentEngine {
     config: CommentEngineConfig,
@@ -18,20 +29,26 @@ pub struct CommentEngine {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommentEngineConfig {
     pub auto_save_drafts: bool,
+    pub draft_autosave_debounce_ms: u64, // Minimum time between journal writes of a draft being typed
     pub max_comment_length: u32,
     pub enable_threading: bool,
     pub enable_markdown: bool,
     pub auto_publish_threshold: u32, // Auto-publish after N characters
+    pub reanchor_search_window: u32, // Lines searched around a rewritten comment anchor
+    pub reanchor_min_similarity: f32, // Minimum content similarity (0-1) for a fuzzy re-anchor
 }
 
 impl Default for CommentEngineConfig {
     fn default() -> Self {
         Self {
             auto_save_drafts: true,
+            draft_autosave_debounce_ms: 750,
             max_comment_length: 10000,
             enable_threading: true,
             enable_markdown: true,
             auto_publish_threshold: 0, // Disabled by default
+            reanchor_search_window: 10,
+            reanchor_min_similarity: 0.8,
         }
     }
 }
@@ -45,7 +62,10 @@ pub struct CommentThread {
     pub line_number: Option<u32>,
     pub root_comment: ReviewComment,
     pub replies: Vec<ReviewComment>,
-    pub is_resolved: bool,
+    /// Remote authors of imported comments by comment ID; the others are the reviewer's
+    pub authors: HashMap<String, String>,
+    pub state: ThreadState,
+    pub is_resolved: bool, // The state no longer blocks submit
     pub created_at: String,
     pub updated_at: String,
 }
@@ -58,6 +78,8 @@ pub struct CreateCommentParams {
     pub line_number: Option<u32>,
     pub content: String,
     pub comment_type: CommentType,
+    #[serde(default)]
+    pub severity: CommentSeverity,
     pub parent_comment_id: Option<String>,
 }
 
@@ -68,6 +90,8 @@ pub struct UpdateCommentParams {
     pub content: Option<String>,
     pub status: Option<CommentStatus>,
     pub comment_type: Option<CommentType>,
+    #[serde(default)]
+    pub severity: Option<CommentSeverity>,
 }
 
 /// Comment search criteria
@@ -78,21 +102,24 @@ pub struct CommentSearchCriteria {
     pub line_number: Option<u32>,
     pub comment_types: Vec<CommentType>,
     pub statuses: Vec<CommentStatus>,
-    pub content_pattern: Option<String>,
+    pub content_pattern: Option<String>, // Full-text query: words, "phrases", prefix*, OR
     pub author_filter: Option<String>,
     pub date_range: Option<(String, String)>, // (start, end) ISO dates
 }
 
 /// Comment statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CommentStats {
     pub total_comments: u32,
     pub draft_comments: u32,
     pub published_comments: u32,
     pub resolved_comments: u32,
     pub comments_by_type: HashMap<CommentType, u32>,
+    pub comments_by_severity: HashMap<CommentSeverity, u32>,
     pub comments_by_file: HashMap<String, u32>,
+    pub comments_by_reviewer: HashMap<String, u32>,
     pub threads_count: u32,
+    pub unresolved_blocking: u32, // Comments that keep the change from being submitted
 }
 
 /// Inline comment positioning information
@@ -143,7 +170,11 @@ pub struct CreateInlineCommentParams {
     pub side: DiffSide,
     pub content: String,
     pub comment_type: CommentType,
+    #[serde(default)]
+    pub severity: CommentSeverity,
     pub parent_comment_id: Option<String>,
+    pub end_line_number: Option<u32>, // Last line of a multi-line range, inclusive
+    pub suggestion: Option<String>,   // Replacement text for the range; makes this a suggestion comment
 }
 
 /// Line-level comment aggregation
@@ -168,6 +199,36 @@ pub struct CommentOperationResult {
     pub warnings: Vec<String>,
 }
 
+/// How an inline comment was carried over to a newer version of its file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorMatch {
+    Mapped,   // The line is unchanged, possibly shifted by edits around it
+    Fuzzy,    // The line was rewritten and found again by its content
+    Outdated, // The commented code was deleted
+}
+
+/// Result of re-anchoring a single inline comment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReanchoredComment {
+    pub comment_id: String,
+    pub file_path: String,
+    pub old_line: u32,
+    pub new_line: Option<u32>, // None when the comment is outdated
+    pub anchor_match: AnchorMatch,
+}
+
+/// Result of moving a review session's inline comments to a newer patch set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReanchorSummary {
+    pub session_id: String,
+    pub from_patch_set: u32,
+    pub to_patch_set: u32,
+    pub comments: Vec<ReanchoredComment>,
+    pub moved_count: u32,
+    pub outdated_count: u32,
+}
+
 impl CommentEngine {
     /// Create a new comment engine
     pub fn new(config: CommentEngineConfig) -> Self {
@@ -197,10 +258,12 @@ impl CommentEngine {
             line_number: params.line_number,
             content: params.content.clone(),
             comment_type: params.comment_type.clone(),
+            severity: params.severity,
             status: CommentStatus::Draft, // Always start as draft
             parent_comment_id: params.parent_comment_id.clone(),
             created_at: now.clone(),
             updated_at: now,
+            mentions: resolve_session_mentions(&params.session_id, &params.content, database)?,
         };
 
         // Store comment in database
@@ -258,6 +321,7 @@ impl CommentEngine {
         if let Some(content) = &params.content {
             self.validate_comment_content(content)?;
             comment.content = content.clone();
+            comment.mentions = resolve_session_mentions(&comment.session_id, content, database)?;
             updated = true;
         }
 
@@ -273,6 +337,12 @@ impl CommentEngine {
             updated = true;
         }
 
+        // Update severity if provided
+        if let Some(severity) = params.severity {
+            comment.severity = severity;
+            updated = true;
+        }
+
         if updated {
             comment.updated_at = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
             database.update_review_comment(&comment)?;
@@ -368,27 +438,83 @@ impl CommentEngine {
             None => return Ok(None),
         };
 
-        // Get all replies
-        let replies = self.get_comment_replies(root_comment_id, database)?;
+        Ok(Some(self.build_thread(root_comment, database)?))
+    }
 
-        // Check if thread is resolved (all comments are resolved or acknowledged)
-        let is_resolved = std::iter::once(&root_comment)
-            .chain(replies.iter())
-            .all(|c| matches!(c.status, CommentStatus::Resolved | CommentStatus::Acknowledged));
+    /// Get the threads of a session, limited to the given states unless `states` is empty
+    pub fn get_session_threads(
+        &self,
+        session_id: &str,
+        states: &[ThreadState],
+        database: &Database,
+    ) -> Result<Vec<CommentThread>, HyperReviewError> {
+        debug!("Getting threads for session: {} in states {:?}", session_id, states);
 
-        let thread = CommentThread {
-            id: format!("thread_{}", root_comment_id),
-            session_id: root_comment.session_id.clone(),
-            file_path: root_comment.file_path.clone(),
-            line_number: root_comment.line_number,
-            root_comment: root_comment.clone(),
-            replies,
-            is_resolved,
-            created_at: root_comment.created_at.clone(),
-            updated_at: root_comment.updated_at.clone(),
-        };
+        let mut threads = Vec::new();
+        for root_comment in database.get_session_comments(session_id)? {
+            if root_comment.parent_comment_id.is_some() {
+                continue;
+            }
+            if !states.is_empty() && !states.contains(&database.get_thread_state(&root_comment.id)?) {
+                continue;
+            }
+            threads.push(self.build_thread(root_comment, database)?);
+        }
 
-        Ok(Some(thread))
+        Ok(threads)
+    }
+
+    /// Get the unresolved threads of all review sessions of a change, which block its submission
+    pub fn get_blocking_threads(
+        &self,
+        change_id: &str,
+        database: &Database,
+    ) -> Result<Vec<CommentThread>, HyperReviewError> {
+        let mut threads = Vec::new();
+        for session in database.get_review_sessions_for_change(change_id)? {
+            threads.extend(self.get_session_threads(
+                &session.id,
+                &[ThreadState::Open, ThreadState::Acknowledged],
+                database,
+            )?);
+        }
+
+        info!("Change {} has {} unresolved threads", change_id, threads.len());
+        Ok(threads)
+    }
+
+    /// Move a thread to a new state, recording who changed it.
+    /// Returns `None` when the thread is already in that state.
+    pub fn set_thread_state(
+        &self,
+        root_comment_id: &str,
+        state: ThreadState,
+        changed_by: &str,
+        database: &Database,
+    ) -> Result<Option<ThreadStateChange>, HyperReviewError> {
+        self.change_thread_state(root_comment_id, state, changed_by, "local", database)
+    }
+
+    /// Apply the `unresolved` flag of the latest Gerrit comment in a thread
+    pub fn sync_gerrit_unresolved(
+        &self,
+        root_comment_id: &str,
+        unresolved: bool,
+        gerrit_author: &str,
+        database: &Database,
+    ) -> Result<Option<ThreadStateChange>, HyperReviewError> {
+        let current = database.get_thread_state(root_comment_id)?;
+        let state = ThreadState::from_gerrit_unresolved(unresolved, current);
+        self.change_thread_state(root_comment_id, state, gerrit_author, "gerrit", database)
+    }
+
+    /// Get the state changes of a thread, oldest first
+    pub fn get_thread_history(
+        &self,
+        root_comment_id: &str,
+        database: &Database,
+    ) -> Result<Vec<ThreadStateChange>, HyperReviewError> {
+        database.get_thread_state_history(root_comment_id)
     }
 
     /// Search comments
@@ -399,17 +525,25 @@ impl CommentEngine {
     ) -> Result<Vec<ReviewComment>, HyperReviewError> {
         debug!("Searching comments with criteria: {:?}", criteria);
 
-        // Get all comments for the session (if specified)
-        let mut comments = if let Some(session_id) = &criteria.session_id {
+        // Content is matched by the full-text index, across every session, best matches first
+        let match_expr = criteria.content_pattern.as_deref().and_then(fts_query);
+        let mut comments = if let Some(match_expr) = &match_expr {
+            database.search_review_index(match_expr, &[SearchSourceKind::ReviewComment], ("", ""), u32::MAX)?
+                .into_iter()
+                .filter_map(|hit| database.get_review_comment(&hit.source_id).transpose())
+                .collect::<Result<Vec<_>, _>>()?
+        } else if let Some(session_id) = &criteria.session_id {
             database.get_session_comments(session_id)?
         } else {
-            // If no session specified, this would require a different database method
-            // For now, return empty as we typically search within a session
             Vec::new()
         };
 
-        // Apply filters
-        comments.retain(|comment| self.matches_search_criteria(comment, criteria));
+        // Apply the remaining filters
+        let criteria = CommentSearchCriteria {
+            content_pattern: if match_expr.is_some() { None } else { criteria.content_pattern.clone() },
+            ..criteria.clone()
+        };
+        comments.retain(|comment| self.matches_search_criteria(comment, &criteria));
 
         info!("Found {} comments matching search criteria", comments.len());
         Ok(comments)
@@ -423,42 +557,45 @@ impl CommentEngine {
     ) -> Result<CommentStats, HyperReviewError> {
         debug!("Calculating comment statistics for session: {}", session_id);
 
-        let comments = database.get_session_comments(session_id)?;
+        let reviewer = database.get_review_session(session_id)?
+            .map(|session| session.reviewer_id)
+            .unwrap_or_default();
 
-        let mut stats = CommentStats {
-            total_comments: comments.len() as u32,
-            draft_comments: 0,
-            published_comments: 0,
-            resolved_comments: 0,
-            comments_by_type: HashMap::new(),
-            comments_by_file: HashMap::new(),
-            threads_count: 0,
-        };
-
-        for comment in &comments {
-            // Count by status
-            match comment.status {
-                CommentStatus::Draft => stats.draft_comments += 1,
-                CommentStatus::Published => stats.published_comments += 1,
-                CommentStatus::Resolved | CommentStatus::Acknowledged => stats.resolved_comments += 1,
-            }
+        let mut stats = CommentStats::default();
+        self.add_session_stats(&mut stats, session_id, &reviewer, database)?;
 
-            // Count by type
-            *stats.comments_by_type.entry(comment.comment_type.clone()).or_insert(0) += 1;
+        info!("Comment statistics calculated: {} total, {} threads", stats.total_comments, stats.threads_count);
+        Ok(stats)
+    }
 
-            // Count by file
-            *stats.comments_by_file.entry(comment.file_path.clone()).or_insert(0) += 1;
+    /// Get comment statistics across all review sessions of a change
+    pub fn get_change_comment_stats(
+        &self,
+        change_id: &str,
+        database: &Database,
+    ) -> Result<CommentStats, HyperReviewError> {
+        debug!("Calculating comment statistics for change: {}", change_id);
 
-            // Count threads (root comments only)
-            if comment.parent_comment_id.is_none() {
-                stats.threads_count += 1;
-            }
+        let mut stats = CommentStats::default();
+        for session in database.get_review_sessions_for_change(change_id)? {
+            self.add_session_stats(&mut stats, &session.id, &session.reviewer_id, database)?;
         }
 
-        info!("Comment statistics calculated: {} total, {} threads", stats.total_comments, stats.threads_count);
+        info!("Comment statistics calculated for change {}: {} total, {} unresolved blocking",
+              change_id, stats.total_comments, stats.unresolved_blocking);
         Ok(stats)
     }
 
+    /// Get comments in unresolved threads whose severity blocks submission
+    pub fn get_blocking_comments(
+        &self,
+        session_id: &str,
+        database: &Database,
+    ) -> Result<Vec<ReviewComment>, HyperReviewError> {
+        let comments = database.get_session_comments(session_id)?;
+        self.unresolved_blocking_comments(&comments, database)
+    }
+
     /// Publish all draft comments
     pub fn publish_all_drafts(
         &self,
@@ -501,12 +638,32 @@ impl CommentEngine {
             file_path: params.file_path.clone(),
             line_number: Some(params.line_number),
             content: params.content.clone(),
-            comment_type: params.comment_type.clone(),
+            comment_type: if params.suggestion.is_some() {
+                CommentType::Suggestion
+            } else {
+                params.comment_type.clone()
+            },
+            severity: params.severity,
             parent_comment_id: params.parent_comment_id.clone(),
         };
 
         let result = self.create_comment(comment_params, database)?;
 
+        if let (Some(replacement), Some(comment_id)) = (&params.suggestion, &result.comment_id) {
+            let end_line = params.end_line_number.unwrap_or(params.line_number);
+            let suggestion = SuggestedChange {
+                comment_id: comment_id.clone(),
+                file_path: params.file_path.clone(),
+                start_line: params.line_number,
+                end_line,
+                original_text: self.session_file_lines(&params.session_id, &params.file_path, params.line_number, end_line, database)?,
+                replacement: replacement.trim_end_matches(['\n', '\r']).to_string(),
+                applied_commit: None,
+                created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            };
+            database.store_suggested_change(&suggestion)?;
+        }
+
         // Store additional inline positioning data if needed
         // For now, we store this in the comment's line_number field
         // In a more complex implementation, we might have a separate table
@@ -528,9 +685,10 @@ impl CommentEngine {
 
         // Get all comments for the file
         let file_comments = database.get_file_comments(session_id, file_path)?;
+        let unresolved = self.unresolved_by_comment(&file_comments, database)?;
 
         // Filter to specific line
-        let line_comments: Vec<ReviewComment> = file_comments.into_iter()
+        let line_comments: Vec<&ReviewComment> = file_comments.iter()
             .filter(|c| c.line_number == Some(line_number))
             .collect();
 
@@ -547,7 +705,7 @@ impl CommentEngine {
         // Calculate aggregation stats
         let total_count = inline_comments.len() as u32;
         let unresolved_count = inline_comments.iter()
-            .filter(|c| !matches!(c.comment.status, CommentStatus::Resolved | CommentStatus::Acknowledged))
+            .filter(|c| unresolved[c.comment.id.as_str()])
             .count() as u32;
         let has_drafts = inline_comments.iter()
             .any(|c| c.comment.status == CommentStatus::Draft);
@@ -602,10 +760,11 @@ impl CommentEngine {
         debug!("Getting comments grouped by line for file: {} in session: {}", file_path, session_id);
 
         let file_comments = database.get_file_comments(session_id, file_path)?;
-        let mut comments_by_line: HashMap<u32, Vec<ReviewComment>> = HashMap::new();
+        let unresolved = self.unresolved_by_comment(&file_comments, database)?;
+        let mut comments_by_line: HashMap<u32, Vec<&ReviewComment>> = HashMap::new();
 
         // Group comments by line number
-        for comment in file_comments {
+        for comment in &file_comments {
             if let Some(line_num) = comment.line_number {
                 comments_by_line.entry(line_num).or_insert_with(Vec::new).push(comment);
             }
@@ -625,7 +784,7 @@ impl CommentEngine {
 
             let total_count = inline_comments.len() as u32;
             let unresolved_count = inline_comments.iter()
-                .filter(|c| !matches!(c.comment.status, CommentStatus::Resolved | CommentStatus::Acknowledged))
+                .filter(|c| unresolved[c.comment.id.as_str()])
                 .count() as u32;
             let has_drafts = inline_comments.iter()
                 .any(|c| c.comment.status == CommentStatus::Draft);
@@ -664,6 +823,7 @@ impl CommentEngine {
             content: None,
             status: None,
             comment_type: None,
+            severity: None,
         };
 
         // Get the comment first to update line number
@@ -675,6 +835,9 @@ impl CommentEngine {
 
         database.update_review_comment(&comment)?;
 
+        // A hand-placed comment is no longer outdated; it is anchored afresh on the next patch set
+        database.delete_comment_anchor(comment_id)?;
+
         info!("Successfully updated inline comment position");
         Ok(CommentOperationResult {
             success: true,
@@ -685,6 +848,238 @@ impl CommentEngine {
         })
     }
 
+    /// Re-anchor the inline comments of a session onto another patch set.
+    ///
+    /// Lines are carried over with the line mapping of a diff between the two
+    /// patch sets. Rewritten lines are looked up again by their original
+    /// content; comments whose code was deleted keep their old line and are
+    /// marked outdated.
+    pub fn reanchor_session(
+        &self,
+        session_id: &str,
+        to_patch_set: u32,
+        database: &Database,
+    ) -> Result<ReanchorSummary, HyperReviewError> {
+        let session = database.get_review_session(session_id)?
+            .ok_or_else(|| HyperReviewError::other(format!("Review session not found: {}", session_id)))?;
+        let change = database.get_gerrit_change(&session.change_id)?
+            .ok_or_else(|| HyperReviewError::other(format!("Change not found: {}", session.change_id)))?;
+
+        let from_patch_set = session.patch_set_number;
+        let mut summary = ReanchorSummary {
+            session_id: session_id.to_string(),
+            from_patch_set,
+            to_patch_set,
+            comments: Vec::new(),
+            moved_count: 0,
+            outdated_count: 0,
+        };
+
+        if from_patch_set == to_patch_set {
+            return Ok(summary);
+        }
+
+        info!("Re-anchoring comments of session {} from PS{} to PS{}", session_id, from_patch_set, to_patch_set);
+
+        let old_files = database.get_change_files(&change.id, from_patch_set)?;
+        let new_files = database.get_change_files(&change.id, to_patch_set)?;
+        if new_files.is_empty() {
+            return Err(HyperReviewError::other(format!(
+                "Patch set {} has not been downloaded", to_patch_set
+            )));
+        }
+
+        let mut comments_by_file: HashMap<String, Vec<ReviewComment>> = HashMap::new();
+        for comment in database.get_review_comments_for_session(session_id)? {
+            if comment.line_number.is_some() {
+                comments_by_file.entry(comment.file_path.clone()).or_default().push(comment);
+            }
+        }
+
+        let diff_engine = DiffEngine::new(DiffConfig::default());
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        for (file_path, comments) in comments_by_file {
+            let old_content = old_files.iter()
+                .find(|f| f.file_path == file_path)
+                .and_then(|f| f.new_content.clone());
+            let new_content = new_files.iter()
+                .find(|f| f.file_path == file_path)
+                .and_then(|f| f.new_content.clone());
+
+            let diff = match (&old_content, &new_content) {
+                (Some(old), Some(new)) => {
+                    let diff = diff_engine.diff_contents(&file_path, old, new)?;
+                    let mapping = diff_engine.create_line_mapping(&diff);
+                    Some((diff, mapping))
+                }
+                _ => None,
+            };
+            let old_lines: Vec<&str> = old_content.as_deref().map(|c| c.lines().collect()).unwrap_or_default();
+            let new_lines: Vec<&str> = new_content.as_deref().map(|c| c.lines().collect()).unwrap_or_default();
+
+            for mut comment in comments {
+                let old_line = comment.line_number.unwrap_or_default();
+                let anchor = database.get_comment_anchor(&comment.id)?;
+
+                // Outdated comments stay where they were written
+                if anchor.as_ref().is_some_and(|a| a.outdated) {
+                    summary.outdated_count += 1;
+                    continue;
+                }
+
+                let original = (old_line as usize).checked_sub(1)
+                    .and_then(|index| old_lines.get(index))
+                    .map(|l| l.to_string())
+                    .or_else(|| anchor.map(|a| a.line_content))
+                    .unwrap_or_default();
+
+                let (new_line, anchor_match) = match (&new_content, &diff) {
+                    (None, _) => (None, AnchorMatch::Outdated),
+                    (Some(_), Some((diff, mapping))) => {
+                        self.locate_line(diff, mapping, &new_lines, old_line, &original)
+                    }
+                    // Without the old patch set only the original content is left to go by
+                    (Some(_), None) => self.fuzzy_locate(&new_lines, old_line, &original),
+                };
+
+                let anchor = match new_line {
+                    Some(line) => {
+                        if line != old_line {
+                            comment.line_number = Some(line);
+                            comment.updated_at = now.clone();
+                            database.update_review_comment(&comment)?;
+                            summary.moved_count += 1;
+                        }
+                        CommentAnchor {
+                            comment_id: comment.id.clone(),
+                            patch_set_number: to_patch_set,
+                            line_content: new_lines[line as usize - 1].to_string(),
+                            outdated: false,
+                            updated_at: now.clone(),
+                        }
+                    }
+                    None => {
+                        summary.outdated_count += 1;
+                        CommentAnchor {
+                            comment_id: comment.id.clone(),
+                            patch_set_number: from_patch_set,
+                            line_content: original,
+                            outdated: true,
+                            updated_at: now.clone(),
+                        }
+                    }
+                };
+                database.store_comment_anchor(&anchor)?;
+
+                debug!("Comment {} at {}:{} re-anchored as {:?}", comment.id, file_path, old_line, anchor_match);
+                summary.comments.push(ReanchoredComment {
+                    comment_id: comment.id,
+                    file_path: file_path.clone(),
+                    old_line,
+                    new_line,
+                    anchor_match,
+                });
+            }
+        }
+
+        database.update_review_session_patch_set(session_id, to_patch_set)?;
+
+        info!(
+            "Re-anchored session {}: {} comments moved, {} outdated",
+            session_id, summary.moved_count, summary.outdated_count
+        );
+        Ok(summary)
+    }
+
+    /// Where a line of one patch set is in another, located the way sessions are
+    /// re-anchored. Returns the line and its text, or `None` when the code is gone
+    /// or either patch set of the file has not been downloaded.
+    pub fn map_line(
+        &self,
+        change_id: &str,
+        file_path: &str,
+        line: u32,
+        from_patch_set: u32,
+        to_patch_set: u32,
+        database: &Database,
+    ) -> Result<Option<(u32, String)>, HyperReviewError> {
+        let content = |patch_set| -> Result<Option<String>, HyperReviewError> {
+            Ok(database.get_change_files(change_id, patch_set)?.into_iter()
+                .find(|f| f.file_path == file_path)
+                .and_then(|f| f.new_content))
+        };
+        let (Some(old_content), Some(new_content)) = (content(from_patch_set)?, content(to_patch_set)?) else {
+            return Ok(None);
+        };
+
+        let diff_engine = DiffEngine::new(DiffConfig::default());
+        let diff = diff_engine.diff_contents(file_path, &old_content, &new_content)?;
+        let mapping = diff_engine.create_line_mapping(&diff);
+        let new_lines: Vec<&str> = new_content.lines().collect();
+        let original = (line as usize).checked_sub(1)
+            .and_then(|index| old_content.lines().nth(index))
+            .unwrap_or_default();
+
+        let (new_line, _) = self.locate_line(&diff, &mapping, &new_lines, line, original);
+        Ok(new_line.map(|l| (l, new_lines[l as usize - 1].to_string())))
+    }
+
+    /// Re-anchor every open review session of a change onto a newer patch set
+    pub fn reanchor_change(
+        &self,
+        change_id: &str,
+        to_patch_set: u32,
+        database: &Database,
+    ) -> Result<Vec<ReanchorSummary>, HyperReviewError> {
+        let change = database.get_gerrit_change(change_id)?
+            .ok_or_else(|| HyperReviewError::other(format!("Change not found: {}", change_id)))?;
+
+        let mut summaries = Vec::new();
+        for session in database.get_review_sessions_for_change(&change.id)? {
+            let is_open = matches!(session.status, ReviewStatus::InProgress | ReviewStatus::ReadyForSubmission);
+            if is_open && session.patch_set_number < to_patch_set {
+                summaries.push(self.reanchor_session(&session.id, to_patch_set, database)?);
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    /// Render a suggestion comment as a mini-diff of the original lines against the replacement
+    pub fn get_suggestion_diff(
+        &self,
+        comment_id: &str,
+        database: &Database,
+    ) -> Result<ProcessedDiff, HyperReviewError> {
+        let suggestion = database.get_suggested_change(comment_id)?
+            .ok_or_else(|| HyperReviewError::other(format!("Comment {} has no suggested change", comment_id)))?;
+
+        let original = suggestion.original_text.clone().map(|text| text + "\n").unwrap_or_default();
+        let replacement = if suggestion.replacement.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n", suggestion.replacement)
+        };
+
+        let diff_engine = DiffEngine::new(DiffConfig::default());
+        let mut diff = diff_engine.diff_contents(&suggestion.file_path, &original, &replacement)?;
+
+        // Number the lines as they appear in the file rather than in the snippet
+        let offset = suggestion.start_line - 1;
+        for hunk in &mut diff.hunks {
+            hunk.old_start += offset;
+            hunk.new_start += offset;
+            hunk.header = format!("@@ -{},{} +{},{} @@", hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count);
+            for line in &mut hunk.lines {
+                line.old_line_number = line.old_line_number.map(|n| n + offset);
+                line.new_line_number = line.new_line_number.map(|n| n + offset);
+            }
+        }
+
+        Ok(diff)
+    }
+
     /// Highlight comments in a line range
     pub fn highlight_comments_in_range(
         &self,
@@ -736,6 +1131,159 @@ impl CommentEngine {
         Ok(thread_id)
     }
 
+    fn build_thread(
+        &self,
+        root_comment: ReviewComment,
+        database: &Database,
+    ) -> Result<CommentThread, HyperReviewError> {
+        let replies = self.get_comment_replies(&root_comment.id, database)?;
+        let state = database.get_thread_state(&root_comment.id)?;
+
+        let mut authors = HashMap::new();
+        for comment in std::iter::once(&root_comment).chain(&replies) {
+            if let Some(import) = database.get_comment_import(&comment.id)? {
+                if !import.author.name.is_empty() {
+                    authors.insert(comment.id.clone(), import.author.name);
+                }
+            }
+        }
+
+        Ok(CommentThread {
+            id: format!("thread_{}", root_comment.id),
+            session_id: root_comment.session_id.clone(),
+            file_path: root_comment.file_path.clone(),
+            line_number: root_comment.line_number,
+            created_at: root_comment.created_at.clone(),
+            updated_at: root_comment.updated_at.clone(),
+            root_comment,
+            replies,
+            authors,
+            state,
+            is_resolved: !state.is_unresolved(),
+        })
+    }
+
+    fn change_thread_state(
+        &self,
+        root_comment_id: &str,
+        state: ThreadState,
+        changed_by: &str,
+        source: &str,
+        database: &Database,
+    ) -> Result<Option<ThreadStateChange>, HyperReviewError> {
+        match database.get_review_comment(root_comment_id)? {
+            Some(comment) if comment.parent_comment_id.is_none() => {}
+            Some(_) => return Err(HyperReviewError::other("Comment is not a root comment".to_string())),
+            None => return Err(HyperReviewError::other(format!("Comment not found: {}", root_comment_id))),
+        }
+
+        let current = database.get_thread_state(root_comment_id)?;
+        if current == state {
+            return Ok(None);
+        }
+
+        let change = ThreadStateChange {
+            id: uuid::Uuid::new_v4().to_string(),
+            root_comment_id: root_comment_id.to_string(),
+            from_state: current,
+            to_state: state,
+            changed_by: changed_by.to_string(),
+            source: source.to_string(),
+            changed_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        };
+        database.set_thread_state(&change)?;
+
+        info!("Thread {} moved from {} to {} by {}", root_comment_id, current, state, changed_by);
+        Ok(Some(change))
+    }
+
+    fn add_session_stats(
+        &self,
+        stats: &mut CommentStats,
+        session_id: &str,
+        reviewer: &str,
+        database: &Database,
+    ) -> Result<(), HyperReviewError> {
+        let comments = database.get_session_comments(session_id)?;
+        let unresolved = self.unresolved_by_comment(&comments, database)?;
+        stats.total_comments += comments.len() as u32;
+        stats.unresolved_blocking += self.unresolved_blocking_comments(&comments, database)?.len() as u32;
+
+        for comment in &comments {
+            // Count by status; resolution comes from the thread state
+            match comment.status {
+                CommentStatus::Draft => stats.draft_comments += 1,
+                CommentStatus::Published => stats.published_comments += 1,
+                CommentStatus::Resolved | CommentStatus::Acknowledged => {}
+            }
+            if !unresolved[comment.id.as_str()] {
+                stats.resolved_comments += 1;
+            }
+
+            // Count by type, severity, file and reviewer
+            *stats.comments_by_type.entry(comment.comment_type.clone()).or_insert(0) += 1;
+            *stats.comments_by_severity.entry(comment.severity).or_insert(0) += 1;
+            *stats.comments_by_file.entry(comment.file_path.clone()).or_insert(0) += 1;
+            *stats.comments_by_reviewer.entry(reviewer.to_string()).or_insert(0) += 1;
+
+            // Count threads (root comments only)
+            if comment.parent_comment_id.is_none() {
+                stats.threads_count += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Comments with a blocking severity whose thread is still unresolved
+    fn unresolved_blocking_comments(
+        &self,
+        comments: &[ReviewComment],
+        database: &Database,
+    ) -> Result<Vec<ReviewComment>, HyperReviewError> {
+        let blocking: Vec<CommentSeverity> = database.get_severity_taxonomy()?
+            .into_iter()
+            .filter(|category| category.blocks_submission)
+            .map(|category| category.severity)
+            .collect();
+        let unresolved = self.unresolved_by_comment(comments, database)?;
+
+        Ok(comments.iter()
+            .filter(|c| blocking.contains(&c.severity) && unresolved[c.id.as_str()])
+            .cloned()
+            .collect())
+    }
+
+    /// Whether the thread of each comment is still unresolved, keyed by comment ID.
+    /// The state of a thread's root applies to all of its replies.
+    fn unresolved_by_comment<'a>(
+        &self,
+        comments: &'a [ReviewComment],
+        database: &Database,
+    ) -> Result<HashMap<&'a str, bool>, HyperReviewError> {
+        let by_id: HashMap<&str, &ReviewComment> = comments.iter().map(|c| (c.id.as_str(), c)).collect();
+        let mut by_root: HashMap<&str, bool> = HashMap::new();
+
+        let mut result = HashMap::with_capacity(comments.len());
+        for comment in comments {
+            let mut root = comment;
+            while let Some(parent) = root.parent_comment_id.as_deref().and_then(|id| by_id.get(id)) {
+                root = parent;
+            }
+            let unresolved = match by_root.get(root.id.as_str()) {
+                Some(&unresolved) => unresolved,
+                None => {
+                    let unresolved = database.get_thread_state(&root.id)?.is_unresolved();
+                    by_root.insert(root.id.as_str(), unresolved);
+                    unresolved
+                }
+            };
+            result.insert(comment.id.as_str(), unresolved);
+        }
+
+        Ok(result)
+    }
+
     fn find_thread_for_comment(
         &self,
         comment_id: &str,
@@ -794,6 +1342,7 @@ impl CommentEngine {
             content: None,
             status: Some(status),
             comment_type: None,
+            severity: None,
         };
 
         self.update_comment(params, database)?;
@@ -816,6 +1365,13 @@ impl CommentEngine {
     }
 
     fn matches_search_criteria(&self, comment: &ReviewComment, criteria: &CommentSearchCriteria) -> bool {
+        // Check session
+        if let Some(session_id) = &criteria.session_id {
+            if comment.session_id != *session_id {
+                return false;
+            }
+        }
+
         // Check file path
         if let Some(file_path) = &criteria.file_path {
             if comment.file_path != *file_path {
@@ -858,6 +1414,10 @@ impl CommentEngine {
             return Err(HyperReviewError::other("Line number must be greater than 0".to_string()));
         }
 
+        if params.end_line_number.is_some_and(|end| end < params.line_number) {
+            return Err(HyperReviewError::other("End line must not be before the start line".to_string()));
+        }
+
         if let (Some(start), Some(end)) = (params.column_start, params.column_end) {
             if start > end {
                 return Err(HyperReviewError::other("Column start must be less than or equal to column end".to_string()));
@@ -911,6 +1471,106 @@ impl CommentEngine {
         }
     }
 
+    /// Text of a line range in the file under review, if it has been downloaded
+    fn session_file_lines(
+        &self,
+        session_id: &str,
+        file_path: &str,
+        start_line: u32,
+        end_line: u32,
+        database: &Database,
+    ) -> Result<Option<String>, HyperReviewError> {
+        let Some(session) = database.get_review_session(session_id)? else {
+            return Ok(None);
+        };
+        let Some(change) = database.get_gerrit_change(&session.change_id)? else {
+            return Ok(None);
+        };
+
+        let content = database.get_change_files(&change.id, session.patch_set_number)?
+            .into_iter()
+            .find(|f| f.file_path == file_path)
+            .and_then(|f| f.new_content);
+
+        Ok(content.and_then(|content| {
+            let lines: Vec<&str> = content.lines().collect();
+            lines.get(start_line as usize - 1..end_line as usize).map(|range| range.join("\n"))
+        }))
+    }
+
+    /// Find where an old line ended up in the new version of a file
+    fn locate_line(
+        &self,
+        diff: &ProcessedDiff,
+        mapping: &LineMapping,
+        new_lines: &[&str],
+        line: u32,
+        original: &str,
+    ) -> (Option<u32>, AnchorMatch) {
+        // Context lines inside a hunk are mapped directly
+        if let Some(&new_line) = mapping.old_to_new.get(&line) {
+            return (Some(new_line), AnchorMatch::Mapped);
+        }
+
+        let mut offset = 0i64;
+        for hunk in &diff.hunks {
+            if hunk.old_count > 0 && line >= hunk.old_start && line < hunk.old_start + hunk.old_count {
+                // The line was removed or rewritten; look for it where the hunk landed
+                let expected = hunk.new_start + (line - hunk.old_start).min(hunk.new_count.saturating_sub(1));
+                return self.fuzzy_locate(new_lines, expected, original);
+            }
+
+            // A pure insertion starts after old_start, so old_start itself does not move
+            let hunk_end = if hunk.old_count == 0 { hunk.old_start } else { hunk.old_start + hunk.old_count - 1 };
+            if hunk_end < line {
+                offset += hunk.new_count as i64 - hunk.old_count as i64;
+            }
+        }
+
+        // Outside every hunk a line only moves by the size of the edits above it
+        let shifted = (line as i64 + offset).max(1) as u32;
+        let unchanged = new_lines.get(shifted as usize - 1)
+            .is_some_and(|l| normalize_line(l) == normalize_line(original));
+        if unchanged {
+            (Some(shifted), AnchorMatch::Mapped)
+        } else {
+            self.fuzzy_locate(new_lines, shifted, original)
+        }
+    }
+
+    /// Search around an expected line for the line most similar to the original
+    fn fuzzy_locate(&self, new_lines: &[&str], expected: u32, original: &str) -> (Option<u32>, AnchorMatch) {
+        let target = normalize_line(original);
+        if target.chars().filter(|c| c.is_alphanumeric()).count() < MIN_FUZZY_ANCHOR_CHARS {
+            return (None, AnchorMatch::Outdated);
+        }
+
+        let window = self.config.reanchor_search_window;
+        let first = expected.saturating_sub(window).max(1);
+        let last = expected.saturating_add(window).min(new_lines.len() as u32);
+
+        let mut best: Option<(f32, u32, u32)> = None; // (similarity, distance, line)
+        for candidate in first..=last {
+            let similarity = line_similarity(&target, &normalize_line(new_lines[candidate as usize - 1]));
+            if similarity < self.config.reanchor_min_similarity {
+                continue;
+            }
+
+            let distance = candidate.abs_diff(expected);
+            let is_better = best.is_none_or(|(best_similarity, best_distance, _)| {
+                similarity > best_similarity || (similarity == best_similarity && distance < best_distance)
+            });
+            if is_better {
+                best = Some((similarity, distance, candidate));
+            }
+        }
+
+        match best {
+            Some((_, _, line)) => (Some(line), AnchorMatch::Fuzzy),
+            None => (None, AnchorMatch::Outdated),
+        }
+    }
+
     /// Get z-index for comment layering
     fn get_z_index_for_comment(&self, comment_type: &CommentType) -> u32 {
         match comment_type {
@@ -924,6 +1584,35 @@ impl CommentEngine {
     }
 }
 
+/// Collapse runs of whitespace so indentation changes don't hide a line
+fn normalize_line(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Similarity of two lines from 0 (nothing in common) to 1 (identical),
+/// based on their edit distance
+fn line_similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -943,6 +1632,7 @@ mod tests {
             url: "https://test.gerrit.com".to_string(),
             username: "test-user".to_string(),
             password_encrypted: "encrypted-password".to_string(),
+            auth_method: crate::models::gerrit::GerritAuthMethod::Basic,
             version: "3.8.0".to_string(),
             is_active: true,
             last_connected: Some("2025-01-05 12:00:00".to_string()),
@@ -1030,6 +1720,7 @@ mod tests {
             line_number: Some(42),
             content: "This needs improvement".to_string(),
             comment_type: CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
         };
 
@@ -1051,6 +1742,7 @@ mod tests {
             line_number: Some(42),
             content: "Original content".to_string(),
             comment_type: CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
         };
 
@@ -1063,6 +1755,7 @@ mod tests {
             content: Some("Updated content".to_string()),
             status: Some(CommentStatus::Published),
             comment_type: None,
+            severity: None,
         };
 
         let update_result = engine.update_comment(update_params, &database).unwrap();
@@ -1103,6 +1796,7 @@ mod tests {
                 line_number: Some(i * 10),
                 content: format!("Comment {}", i),
                 comment_type: CommentType::Issue,
+                severity: CommentSeverity::Minor,
                 parent_comment_id: None,
             };
             engine.create_comment(params, &database).unwrap();
@@ -1128,7 +1822,10 @@ mod tests {
             side: DiffSide::Right,
             content: "This line needs improvement".to_string(),
             comment_type: CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
+            end_line_number: None,
+            suggestion: None,
         };
 
         let result = engine.create_inline_comment(params, &database).unwrap();
@@ -1152,7 +1849,10 @@ mod tests {
                 side: DiffSide::Right,
                 content: format!("Comment {} on line 42", i),
                 comment_type: CommentType::Issue,
+                severity: CommentSeverity::Minor,
                 parent_comment_id: None,
+                end_line_number: None,
+                suggestion: None,
             };
             engine.create_inline_comment(params, &database).unwrap();
         }
@@ -1188,7 +1888,10 @@ mod tests {
                 side: DiffSide::Right,
                 content: format!("Comment on line {}", line),
                 comment_type: CommentType::Issue,
+                severity: CommentSeverity::Minor,
                 parent_comment_id: None,
+                end_line_number: None,
+                suggestion: None,
             };
             engine.create_inline_comment(params, &database).unwrap();
         }
@@ -1222,7 +1925,10 @@ mod tests {
                 side: DiffSide::Right,
                 content: format!("Comment on line {}", line),
                 comment_type: CommentType::Issue,
+                severity: CommentSeverity::Minor,
                 parent_comment_id: None,
+                end_line_number: None,
+                suggestion: None,
             };
             engine.create_inline_comment(params, &database).unwrap();
         }
@@ -1256,7 +1962,10 @@ mod tests {
             side: DiffSide::Right,
             content: "Test comment".to_string(),
             comment_type: CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
+            end_line_number: None,
+            suggestion: None,
         };
 
         assert!(engine.validate_inline_position(&invalid_params).is_err());
@@ -1271,7 +1980,10 @@ mod tests {
             side: DiffSide::Right,
             content: "Test comment".to_string(),
             comment_type: CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
+            end_line_number: None,
+            suggestion: None,
         };
 
         assert!(engine.validate_inline_position(&invalid_columns).is_err());
@@ -1286,9 +1998,195 @@ mod tests {
             side: DiffSide::Right,
             content: "Test comment".to_string(),
             comment_type: CommentType::Issue,
+            severity: CommentSeverity::Minor,
             parent_comment_id: None,
+            end_line_number: None,
+            suggestion: None,
         };
 
         assert!(engine.validate_inline_position(&valid_params).is_ok());
     }
-}
\ No newline at end of file
+
+    fn store_patch_set_file(database: &Database, patch_set_number: u32, content: &str) {
+        let file = crate::models::gerrit::ChangeFile {
+            id: format!("file-ps{}", patch_set_number),
+            change_id: "test-change".to_string(),
+            patch_set_number,
+            file_path: "src/compute.rs".to_string(),
+            change_type: crate::models::gerrit::FileChangeType::Modified,
+            old_content: None,
+            new_content: Some(content.to_string()),
+            diff: crate::models::gerrit::FileDiff::default(),
+            file_size: content.len() as u64,
+            downloaded_at: "2025-01-05 12:00:00".to_string(),
+        };
+        database.store_change_file(&file).expect("Failed to store change file");
+    }
+
+    #[tokio::test]
+    async fn test_reanchor_session_to_new_patch_set() {
+        let (database, _temp_dir) = create_test_database();
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+
+        let values: Vec<String> = (1..=20).map(|i| format!("    let v{} = {};", i, i)).collect();
+        let weights: Vec<String> = (1..=20).map(|i| format!("    let w{} = {};", i, i)).collect();
+
+        // PS1: header, v1..v20 (2-21), scaled (22), println (23), log (24), w1..w20 (25-44), scaled (45), brace (46)
+        let mut ps1 = vec!["fn compute() -> i32 {".to_string()];
+        ps1.extend(values.iter().cloned());
+        ps1.push("    let scaled = total * 2;".to_string());
+        ps1.push("    println!(\"scaled {}\", scaled);".to_string());
+        ps1.push("    log_debug(\"computed total\");".to_string());
+        ps1.extend(weights.iter().cloned());
+        ps1.push("    scaled".to_string());
+        ps1.push("}".to_string());
+
+        // PS2 adds two lines on top, rewrites the scale factor and drops the log call
+        let mut ps2 = vec!["// Sum and scale".to_string(), "#[inline]".to_string(), ps1[0].clone()];
+        ps2.extend(values.iter().cloned());
+        ps2.push("    let scaled = total * 3;".to_string());
+        ps2.push(ps1[22].clone());
+        ps2.extend(weights.iter().cloned());
+        ps2.push("    scaled".to_string());
+        ps2.push("}".to_string());
+
+        store_patch_set_file(&database, 1, &(ps1.join("\n") + "\n"));
+        store_patch_set_file(&database, 2, &(ps2.join("\n") + "\n"));
+
+        let mut ids = HashMap::new();
+        for line in [11, 22, 24, 45] {
+            let params = CreateCommentParams {
+                session_id: "test-session".to_string(),
+                file_path: "src/compute.rs".to_string(),
+                line_number: Some(line),
+                content: format!("Comment on line {}", line),
+                comment_type: CommentType::Inline,
+                severity: CommentSeverity::Minor,
+                parent_comment_id: None,
+            };
+            let result = engine.create_comment(params, &database).unwrap();
+            ids.insert(line, result.comment_id.unwrap());
+        }
+
+        let summary = engine.reanchor_session("test-session", 2, &database).unwrap();
+        assert_eq!(summary.from_patch_set, 1);
+        assert_eq!(summary.moved_count, 3);
+        assert_eq!(summary.outdated_count, 1);
+
+        let line_of = |line: u32| database.get_review_comment(&ids[&line]).unwrap().unwrap().line_number;
+        assert_eq!(line_of(11), Some(13)); // shifted by the inserted lines
+        assert_eq!(line_of(22), Some(24)); // rewritten, found by content
+        assert_eq!(line_of(24), Some(24)); // deleted, left in place
+        assert_eq!(line_of(45), Some(46));
+
+        let matches: HashMap<u32, AnchorMatch> = summary.comments.iter()
+            .map(|c| (c.old_line, c.anchor_match))
+            .collect();
+        assert_eq!(matches[&11], AnchorMatch::Mapped);
+        assert_eq!(matches[&22], AnchorMatch::Fuzzy);
+        assert_eq!(matches[&24], AnchorMatch::Outdated);
+
+        let outdated = database.get_comment_anchor(&ids[&24]).unwrap().unwrap();
+        assert!(outdated.outdated);
+        assert_eq!(outdated.patch_set_number, 1);
+        assert_eq!(outdated.line_content, "    log_debug(\"computed total\");");
+
+        let session = database.get_review_session("test-session").unwrap().unwrap();
+        assert_eq!(session.patch_set_number, 2);
+    }
+
+    #[test]
+    fn test_fuzzy_locate_ignores_generic_lines() {
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+        let new_lines = vec!["fn main() {", "    run();", "}"];
+
+        assert_eq!(engine.fuzzy_locate(&new_lines, 3, "}"), (None, AnchorMatch::Outdated));
+        assert_eq!(engine.fuzzy_locate(&new_lines, 1, "    run( );"), (Some(2), AnchorMatch::Fuzzy));
+    }
+
+    #[tokio::test]
+    async fn test_suggestion_comment_renders_mini_diff() {
+        let (database, _temp_dir) = create_test_database();
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+        store_patch_set_file(&database, 1, "fn main() {\n    let x = 1;\n    let y = 2;\n}\n");
+
+        let params = CreateInlineCommentParams {
+            session_id: "test-session".to_string(),
+            file_path: "src/compute.rs".to_string(),
+            line_number: 2,
+            column_start: None,
+            column_end: None,
+            side: DiffSide::Right,
+            content: "Declare both at once".to_string(),
+            comment_type: CommentType::Inline,
+            severity: CommentSeverity::Minor,
+            parent_comment_id: None,
+            end_line_number: Some(3),
+            suggestion: Some("    let (x, y) = (1, 2);\n".to_string()),
+        };
+        let comment_id = engine.create_inline_comment(params, &database).unwrap().comment_id.unwrap();
+
+        let comment = database.get_review_comment(&comment_id).unwrap().unwrap();
+        assert_eq!(comment.comment_type, CommentType::Suggestion);
+
+        let suggestion = database.get_suggested_change(&comment_id).unwrap().unwrap();
+        assert_eq!(suggestion.original_text.as_deref(), Some("    let x = 1;\n    let y = 2;"));
+        assert_eq!(suggestion.replacement, "    let (x, y) = (1, 2);");
+
+        let diff = engine.get_suggestion_diff(&comment_id, &database).unwrap();
+        assert_eq!(diff.stats.lines_removed, 2);
+        assert_eq!(diff.stats.lines_added, 1);
+        let removed: Vec<u32> = diff.hunks[0].lines.iter().filter_map(|l| l.old_line_number).collect();
+        assert_eq!(removed, vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_thread_state_workflow_and_blocking_threads() {
+        let (database, _temp_dir) = create_test_database();
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+
+        let mut root_ids = Vec::new();
+        for line in [10, 20] {
+            let params = CreateCommentParams {
+                session_id: "test-session".to_string(),
+                file_path: "src/main.rs".to_string(),
+                line_number: Some(line),
+                content: format!("Problem on line {}", line),
+                comment_type: CommentType::Issue,
+                severity: CommentSeverity::Minor,
+                parent_comment_id: None,
+            };
+            root_ids.push(engine.create_comment(params, &database).unwrap().comment_id.unwrap());
+        }
+
+        assert_eq!(engine.get_blocking_threads("test-change", &database).unwrap().len(), 2);
+
+        let change = engine.set_thread_state(&root_ids[0], ThreadState::WontFix, "alice", &database).unwrap().unwrap();
+        assert_eq!(change.from_state, ThreadState::Open);
+        assert!(engine.set_thread_state(&root_ids[0], ThreadState::WontFix, "alice", &database).unwrap().is_none());
+
+        let blocking = engine.get_blocking_threads("test-change", &database).unwrap();
+        assert_eq!(blocking.len(), 1);
+        assert_eq!(blocking[0].root_comment.id, root_ids[1]);
+
+        let wont_fix = engine.get_session_threads("test-session", &[ThreadState::WontFix], &database).unwrap();
+        assert_eq!(wont_fix.len(), 1);
+        assert!(wont_fix[0].is_resolved);
+
+        // Gerrit reopens the thread; a resolved flag for an open thread resolves it
+        engine.sync_gerrit_unresolved(&root_ids[0], true, "bob", &database).unwrap();
+        engine.sync_gerrit_unresolved(&root_ids[1], false, "bob", &database).unwrap();
+        assert_eq!(database.get_thread_state(&root_ids[0]).unwrap(), ThreadState::Open);
+        assert_eq!(database.get_thread_state(&root_ids[1]).unwrap(), ThreadState::Resolved);
+
+        // Resolution counts follow the thread state, not the comment status
+        let stats = engine.get_comment_stats("test-session", &database).unwrap();
+        assert_eq!((stats.total_comments, stats.resolved_comments), (2, 1));
+        let by_line = engine.get_comments_by_line("test-session", "src/main.rs", &database).unwrap();
+        assert_eq!((by_line[&10].unresolved_count, by_line[&20].unresolved_count), (1, 0));
+
+        let history = engine.get_thread_history(&root_ids[0], &database).unwrap();
+        let audit: Vec<(&str, &str)> = history.iter().map(|c| (c.changed_by.as_str(), c.source.as_str())).collect();
+        assert_eq!(audit, vec![("alice", "local"), ("bob", "gerrit")]);
+    }
+}
@@ -0,0 +1,590 @@
+// Gerrit Comment Import
+// Turns the published, draft and robot comments of a change into local comment threads
+
+use std::collections::{HashMap, HashSet};
+use chrono::Utc;
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::{
+    CommentAnchor, CommentSeverity, CommentSide, CommentStatus, CommentType, CommentVersion, GerritChange, GerritComment,
+    GerritCommentSource, GerritUser, ImportedGerritComment, RemoteGerritComment, ReviewComment, ReviewSession,
+};
+use crate::services::comment_engine::{CommentEngine, CommentEngineConfig};
+use crate::services::sync_manager::{reconcile_comment, ConflictInfo, Reconciled};
+use crate::storage::sqlite::Database;
+
+/// Path Gerrit uses for comments on the patch set as a whole
+const PATCHSET_LEVEL_PATH: &str = "/PATCHSET_LEVEL";
+
+/// What an import did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommentImportSummary {
+    pub imported: u32,                 // New review comments
+    pub updated: u32,                  // Comments edited or published on Gerrit since the last import
+    pub unchanged: u32,
+    pub deleted: u32,                  // Imported comments deleted on Gerrit and dropped locally
+    pub threads: u32,                  // Threads that received new or edited comments
+    pub outdated: u32,                 // Comments from another patch set whose code is gone
+    /// Gerrit IDs of comments on the base side of the diff, which the session does not show
+    pub skipped: Vec<String>,
+    /// Local edits that clash with edits or deletions made on Gerrit, left as they are until resolved
+    pub conflicts: Vec<ConflictInfo>,
+}
+
+/// A remote comment placed on the session's patch set
+struct Placed {
+    comment: GerritComment,
+    anchor: Option<CommentAnchor>,
+}
+
+/// Import the comments of the session's change. Gerrit replies are chained through
+/// `in_reply_to`; locally every reply hangs off the root comment of its thread.
+/// Comments imported before are merged with their local edits since the last sync,
+/// so importing again is safe and does not lose what was typed offline.
+///
+/// Comments written on another patch set are re-anchored onto the session's one.
+/// `complete` names the comment lists fetched in full; earlier imports from those
+/// lists that are missing now were deleted remotely.
+pub fn import_gerrit_comments(
+    session: &ReviewSession,
+    remote_comments: &[RemoteGerritComment],
+    complete: &[GerritCommentSource],
+    database: &Database,
+) -> Result<CommentImportSummary, HyperReviewError> {
+    info!("Importing {} Gerrit comments into session {}", remote_comments.len(), session.id);
+
+    let mut remote: Vec<&RemoteGerritComment> = remote_comments.iter()
+        .filter(|remote| remote.comment.gerrit_comment_id.is_some())
+        .collect();
+    remote.sort_by(|a, b| a.comment.updated.cmp(&b.comment.updated));
+
+    let by_id: HashMap<&str, &GerritComment> = remote.iter()
+        .filter_map(|remote| Some((remote.comment.gerrit_comment_id.as_deref()?, &remote.comment)))
+        .collect();
+    let batch_root = |comment: &GerritComment| -> String {
+        let mut current = comment;
+        for _ in 0..by_id.len() { // Bounded in case the server ever reports a reply cycle
+            match current.parent.as_deref().and_then(|parent| by_id.get(parent).copied()) {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+        current.gerrit_comment_id.clone().unwrap_or_default()
+    };
+
+    // Thread roots first, so replies can point at their local IDs
+    let mut ordered: Vec<(&RemoteGerritComment, String)> = remote.iter()
+        .map(|remote| (*remote, batch_root(&remote.comment)))
+        .collect();
+    ordered.sort_by_key(|(remote, root)| remote.comment.gerrit_comment_id.as_ref() != Some(root));
+
+    let engine = CommentEngine::new(CommentEngineConfig::default());
+    let change = database.get_gerrit_change(&session.change_id)?;
+    let mut summary = CommentImportSummary::default();
+    let mut local_roots: HashMap<String, String> = HashMap::new(); // Gerrit ID -> local root comment ID
+    let mut changed_threads: HashSet<String> = HashSet::new();
+    let mut latest_published: HashMap<String, GerritComment> = HashMap::new();
+
+    for (remote, root) in ordered {
+        let gerrit_id = remote.comment.gerrit_comment_id.clone().unwrap_or_default();
+        let Some(placed) = place_comment(&remote.comment, session, change.as_ref(), &engine, database)? else {
+            summary.skipped.push(gerrit_id);
+            continue;
+        };
+        let comment = &placed.comment;
+
+        let parent_comment_id = if root == gerrit_id {
+            match comment.parent.as_deref() {
+                Some(parent) => imported_thread_root(parent, session, database)?,
+                None => None,
+            }
+        } else {
+            local_roots.get(&root).cloned()
+        };
+
+        let previous = match database.get_imported_gerrit_comment(&session.id, &gerrit_id)? {
+            Some(import) => database.get_review_comment(&import.comment_id)?.map(|local| (import, local)),
+            None => None,
+        };
+
+        let local_id = match previous {
+            Some((import, local)) if import.remote_updated == comment.updated && import.source == remote.source => {
+                summary.unchanged += 1;
+                local.id
+            }
+            Some((_, local)) => {
+                // Imports made before sync bases were kept start from the local copy
+                if database.get_comment_sync_base(&local.id)?.is_none() {
+                    database.store_comment_sync_base(&session.change_id, &local.id, &CommentVersion::from(&local))?;
+                }
+
+                let local_id = local.id.clone();
+                match reconcile_comment(&session.change_id, &local, Some(comment), database)? {
+                    Reconciled::Conflict(conflict) => {
+                        // Not marked as imported, so the next import reports it again
+                        summary.conflicts.push(conflict);
+                    }
+                    Reconciled::Merged(resolution) => {
+                        let mut local = resolution.comment.unwrap_or(local);
+                        local.status = match (remote.source, &local.status) {
+                            (GerritCommentSource::Draft, _) => CommentStatus::Draft,
+                            (_, CommentStatus::Draft) => CommentStatus::Published,
+                            (_, status) => status.clone(),
+                        };
+                        database.update_review_comment(&local)?;
+                        store_anchor(&placed, &local.id, &mut summary, database)?;
+                        store_import(remote, &local.id, session, database)?;
+                        summary.updated += 1;
+                        changed_threads.insert(local.parent_comment_id.clone().unwrap_or_else(|| local.id.clone()));
+                    }
+                }
+                local_id
+            }
+            None => {
+                let local = new_review_comment(remote, comment, session, parent_comment_id.clone());
+                database.store_review_comment(&local)?;
+                database.store_comment_sync_base(&session.change_id, &local.id, &CommentVersion::from(comment))?;
+                store_anchor(&placed, &local.id, &mut summary, database)?;
+                store_import(remote, &local.id, session, database)?;
+                summary.imported += 1;
+                changed_threads.insert(parent_comment_id.clone().unwrap_or_else(|| local.id.clone()));
+                local.id
+            }
+        };
+
+        let local_root = parent_comment_id.unwrap_or_else(|| local_id.clone());
+        if remote.source != GerritCommentSource::Draft {
+            latest_published.insert(local_root.clone(), placed.comment);
+        }
+        local_roots.insert(gerrit_id, local_root);
+    }
+
+    // Gerrit resolves a thread through the flag of its latest published comment.
+    // Untouched threads keep whatever state they were given locally.
+    for root_id in &changed_threads {
+        if let Some(latest) = latest_published.get(root_id) {
+            engine.sync_gerrit_unresolved(root_id, latest.unresolved, &author_name(&latest.author, session), database)?;
+        }
+    }
+    summary.threads = changed_threads.len() as u32;
+
+    drop_deleted_comments(session, change.as_ref(), remote_comments, complete, &mut summary, database)?;
+
+    info!("Imported {} new, {} updated and {} deleted comments ({} unchanged, {} skipped, {} in conflict) into {} threads",
+          summary.imported, summary.updated, summary.deleted, summary.unchanged, summary.skipped.len(),
+          summary.conflicts.len(), summary.threads);
+    Ok(summary)
+}
+
+/// Put a remote comment on the session's patch set. Comments on the base of the diff
+/// have no place there and are skipped; comments on another patch set are re-anchored
+/// and flagged outdated when their code is gone.
+fn place_comment(
+    comment: &GerritComment,
+    session: &ReviewSession,
+    change: Option<&GerritChange>,
+    engine: &CommentEngine,
+    database: &Database,
+) -> Result<Option<Placed>, HyperReviewError> {
+    let Some(line) = line_number(comment).filter(|_| comment.file_path != PATCHSET_LEVEL_PATH) else {
+        return Ok(Some(Placed { comment: comment.clone(), anchor: None }));
+    };
+    if matches!(comment.side, CommentSide::Parent) {
+        return Ok(None);
+    }
+    let patch_set = match comment.patch_set_id.parse::<u32>() {
+        Ok(patch_set) if patch_set != session.patch_set_number => patch_set,
+        _ => return Ok(Some(Placed { comment: comment.clone(), anchor: None })),
+    };
+
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let mapped = match change {
+        Some(change) => engine.map_line(&change.id, &comment.file_path, line, patch_set, session.patch_set_number, database)?,
+        None => None,
+    };
+    let placed = match mapped {
+        Some((new_line, line_content)) => Placed {
+            comment: GerritComment { line: new_line, ..comment.clone() },
+            anchor: Some(CommentAnchor {
+                comment_id: String::new(),
+                patch_set_number: session.patch_set_number,
+                line_content,
+                outdated: false,
+                updated_at: now,
+            }),
+        },
+        None => Placed {
+            comment: comment.clone(),
+            anchor: Some(CommentAnchor {
+                comment_id: String::new(),
+                patch_set_number: patch_set,
+                line_content: String::new(),
+                outdated: true,
+                updated_at: now,
+            }),
+        },
+    };
+    Ok(Some(placed))
+}
+
+fn store_anchor(placed: &Placed, comment_id: &str, summary: &mut CommentImportSummary, database: &Database) -> Result<(), HyperReviewError> {
+    let Some(anchor) = &placed.anchor else {
+        return Ok(());
+    };
+    if anchor.outdated {
+        summary.outdated += 1;
+    }
+    database.store_comment_anchor(&CommentAnchor { comment_id: comment_id.to_string(), ..anchor.clone() })
+}
+
+/// Reconcile the earlier imports that the server no longer returns. Untouched copies
+/// are dropped; copies edited since are reported as conflicts.
+fn drop_deleted_comments(
+    session: &ReviewSession,
+    change: Option<&GerritChange>,
+    remote_comments: &[RemoteGerritComment],
+    complete: &[GerritCommentSource],
+    summary: &mut CommentImportSummary,
+    database: &Database,
+) -> Result<(), HyperReviewError> {
+    let present: HashSet<&str> = remote_comments.iter()
+        .filter_map(|remote| remote.comment.gerrit_comment_id.as_deref())
+        .collect();
+
+    // Drafts pushed from the queue are linked under the Gerrit change ID
+    let mut imports = database.get_imported_gerrit_comments(&session.change_id)?;
+    if let Some(change) = change.filter(|change| change.change_id != session.change_id) {
+        imports.extend(database.get_imported_gerrit_comments(&change.change_id)?);
+    }
+
+    let mut handled = HashSet::new();
+    for import in imports {
+        if import.session_id != session.id || !complete.contains(&import.source)
+            || present.contains(import.gerrit_comment_id.as_str()) || !handled.insert(import.comment_id.clone()) {
+            continue;
+        }
+        let Some(local) = database.get_review_comment(&import.comment_id)? else {
+            database.delete_imported_gerrit_comments_for(&import.comment_id)?;
+            continue;
+        };
+        if database.get_comment_sync_base(&local.id)?.is_none() {
+            database.store_comment_sync_base(&session.change_id, &local.id, &CommentVersion::from(&local))?;
+        }
+
+        match reconcile_comment(&session.change_id, &local, None, database)? {
+            Reconciled::Conflict(conflict) => summary.conflicts.push(conflict),
+            Reconciled::Merged(resolution) => {
+                if resolution.comment.is_none() {
+                    summary.deleted += 1;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Local root of the thread an earlier import into the session put a Gerrit comment in
+fn imported_thread_root(gerrit_comment_id: &str, session: &ReviewSession, database: &Database) -> Result<Option<String>, HyperReviewError> {
+    let Some(import) = database.get_imported_gerrit_comment(&session.id, gerrit_comment_id)? else {
+        return Ok(None);
+    };
+    Ok(database.get_review_comment(&import.comment_id)?
+        .map(|local| local.parent_comment_id.unwrap_or(local.id)))
+}
+
+fn new_review_comment(
+    remote: &RemoteGerritComment,
+    comment: &GerritComment,
+    session: &ReviewSession,
+    parent_comment_id: Option<String>,
+) -> ReviewComment {
+    let (file_path, comment_type) = match (comment.file_path.as_str(), comment.line) {
+        (PATCHSET_LEVEL_PATH, _) => (String::new(), CommentType::General),
+        (path, 0) => (path.to_string(), CommentType::FileLevel),
+        (path, _) => (path.to_string(), CommentType::Inline),
+    };
+
+    ReviewComment {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id: session.id.clone(),
+        file_path,
+        line_number: line_number(comment),
+        content: comment.message.clone(),
+        comment_type,
+        severity: CommentSeverity::default(),
+        status: if remote.source == GerritCommentSource::Draft { CommentStatus::Draft } else { CommentStatus::Published },
+        parent_comment_id,
+        created_at: comment.created.clone(),
+        updated_at: comment.updated.clone(),
+        mentions: Vec::new(),
+    }
+}
+
+fn store_import(
+    remote: &RemoteGerritComment,
+    comment_id: &str,
+    session: &ReviewSession,
+    database: &Database,
+) -> Result<(), HyperReviewError> {
+    let comment = &remote.comment;
+    let mut author = comment.author.clone();
+    author.name = author_name(&author, session);
+
+    database.store_imported_gerrit_comment(&ImportedGerritComment {
+        gerrit_comment_id: comment.gerrit_comment_id.clone().unwrap_or_default(),
+        comment_id: comment_id.to_string(),
+        change_id: session.change_id.clone(),
+        source: remote.source,
+        author,
+        robot_id: comment.robot_id.clone(),
+        unresolved: comment.unresolved,
+        remote_updated: comment.updated.clone(),
+        imported_at: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        session_id: session.id.clone(),
+    })
+}
+
+fn line_number(comment: &GerritComment) -> Option<u32> {
+    Some(comment.line).filter(|line| *line > 0)
+}
+
+/// Drafts come without an author; they are the session reviewer's own
+fn author_name(author: &GerritUser, session: &ReviewSession) -> String {
+    if author.name.is_empty() {
+        session.reviewer_id.clone()
+    } else {
+        author.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::gerrit::{OperationPriority, ReviewMode, ReviewProgress, ReviewStatus, ThreadState};
+    use crate::services::operation_executor::thread_state_operation;
+
+    fn session() -> ReviewSession {
+        ReviewSession {
+            id: "s1".to_string(),
+            change_id: "I123".to_string(),
+            patch_set_number: 2,
+            reviewer_id: "bob".to_string(),
+            mode: ReviewMode::Online,
+            status: ReviewStatus::InProgress,
+            progress: ReviewProgress::default(),
+            created_at: "2025-01-05 09:00:00".to_string(),
+            updated_at: "2025-01-05 09:00:00".to_string(),
+        }
+    }
+
+    fn remote(source: GerritCommentSource, id: &str, parent: Option<&str>, updated: &str, unresolved: bool) -> RemoteGerritComment {
+        RemoteGerritComment {
+            source,
+            comment: GerritComment {
+                gerrit_comment_id: Some(id.to_string()),
+                change_id: "I123".to_string(),
+                file_path: "src/pool.rs".to_string(),
+                line: 12,
+                message: format!("Comment {}", id),
+                author: GerritUser {
+                    account_id: 7,
+                    name: if source == GerritCommentSource::Draft { String::new() } else { "Ann".to_string() },
+                    email: "ann@example.com".to_string(),
+                    username: None,
+                    avatar_url: None,
+                },
+                created: updated.to_string(),
+                updated: updated.to_string(),
+                unresolved,
+                parent: parent.map(|parent| parent.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_import_rebuilds_threads_without_duplicates() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let session = session();
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+
+        // c1 <- c2 <- d3 (draft reply to the reply), and a resolved robot comment
+        let mut comments = vec![
+            remote(GerritCommentSource::Published, "c1", None, "2025-01-05 12:00:00", true),
+            remote(GerritCommentSource::Published, "c2", Some("c1"), "2025-01-05 13:00:00", true),
+            remote(GerritCommentSource::Draft, "d3", Some("c2"), "2025-01-05 14:00:00", false),
+            remote(GerritCommentSource::Robot, "r1", None, "2025-01-05 11:00:00", false),
+        ];
+        let summary = import_gerrit_comments(&session, &comments, &[], &database).unwrap();
+        assert_eq!((summary.imported, summary.threads), (4, 2));
+
+        let threads = engine.get_session_threads(&session.id, &[], &database).unwrap();
+        assert_eq!(threads.len(), 2);
+        let thread = threads.iter().find(|thread| thread.root_comment.content == "Comment c1").unwrap();
+        assert_eq!(thread.replies.len(), 2);
+        assert_eq!(thread.state, ThreadState::Open);
+        assert_eq!(thread.root_comment.created_at, "2025-01-05 12:00:00");
+        let draft = thread.replies.iter().find(|reply| reply.content == "Comment d3").unwrap();
+        assert_eq!(draft.status, CommentStatus::Draft);
+
+        let c1 = database.get_imported_gerrit_comment(&session.id, "c1").unwrap().unwrap();
+        assert_eq!((c1.author.name.as_str(), c1.unresolved), ("Ann", true));
+        assert_eq!(database.get_imported_gerrit_comment(&session.id, "d3").unwrap().unwrap().author.name, "bob");
+
+        // Importing again changes nothing
+        let summary = import_gerrit_comments(&session, &comments, &[], &database).unwrap();
+        assert_eq!((summary.imported, summary.updated, summary.unchanged), (0, 0, 4));
+
+        // The draft is published and resolves the thread; a new reply arrives on its own
+        comments[2] = remote(GerritCommentSource::Published, "d3", Some("c2"), "2025-01-05 15:00:00", false);
+        let summary = import_gerrit_comments(&session, &comments[2..3], &[], &database).unwrap();
+        assert_eq!((summary.imported, summary.updated), (0, 1));
+        let reply = remote(GerritCommentSource::Published, "c4", Some("d3"), "2025-01-05 16:00:00", false);
+        import_gerrit_comments(&session, &[reply], &[], &database).unwrap();
+
+        assert_eq!(database.get_session_comments(&session.id).unwrap().len(), 5);
+        let thread = engine.get_comment_thread(&thread.root_comment.id, &database).unwrap().unwrap();
+        assert_eq!(thread.replies.len(), 3);
+        assert!(thread.replies.iter().all(|reply| reply.status == CommentStatus::Published));
+        assert_eq!(thread.state, ThreadState::Resolved);
+
+        // A local edit that clashes with an edit made on Gerrit is left for the user
+        let c1_id = database.get_imported_gerrit_comment(&session.id, "c1").unwrap().unwrap().comment_id;
+        let mut edited = database.get_review_comment(&c1_id).unwrap().unwrap();
+        edited.content = "Comment c1, see the docs".to_string();
+        database.update_review_comment(&edited).unwrap();
+        let mut changed = remote(GerritCommentSource::Published, "c1", None, "2025-01-05 17:00:00", true);
+        changed.comment.message = "Comment c1, fixed upstream".to_string();
+        let summary = import_gerrit_comments(&session, &[changed], &[], &database).unwrap();
+        assert_eq!((summary.updated, summary.conflicts.len()), (0, 1));
+        assert_eq!(database.get_review_comment(&c1_id).unwrap().unwrap().content, "Comment c1, see the docs");
+    }
+
+    #[test]
+    fn test_each_session_imports_its_own_copy() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let first = session();
+        let second = ReviewSession { id: "s2".to_string(), reviewer_id: "carol".to_string(), ..session() };
+
+        let comments = vec![
+            remote(GerritCommentSource::Published, "c1", None, "2025-01-05 12:00:00", true),
+            remote(GerritCommentSource::Published, "c2", Some("c1"), "2025-01-05 13:00:00", true),
+        ];
+        import_gerrit_comments(&first, &comments, &[], &database).unwrap();
+        let summary = import_gerrit_comments(&second, &comments, &[], &database).unwrap();
+        assert_eq!((summary.imported, summary.unchanged), (2, 0));
+
+        // Replies hang off the root of their own session
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+        for session in [&first, &second] {
+            let threads = engine.get_session_threads(&session.id, &[], &database).unwrap();
+            assert_eq!(threads.len(), 1);
+            assert_eq!(threads[0].replies.len(), 1);
+            assert_eq!(threads[0].replies[0].session_id, session.id);
+        }
+        let first_c1 = database.get_imported_gerrit_comment(&first.id, "c1").unwrap().unwrap().comment_id;
+        let second_c1 = database.get_imported_gerrit_comment(&second.id, "c1").unwrap().unwrap().comment_id;
+        assert_ne!(first_c1, second_c1);
+
+        // Re-importing and deleting stay within the session
+        let summary = import_gerrit_comments(&first, &comments, &[], &database).unwrap();
+        assert_eq!((summary.imported, summary.unchanged), (0, 2));
+        let all = [GerritCommentSource::Published];
+        let summary = import_gerrit_comments(&second, &comments[..1], &all, &database).unwrap();
+        assert_eq!(summary.deleted, 1);
+        assert_eq!(database.get_session_comments(&first.id).unwrap().len(), 2);
+        assert_eq!(database.get_session_comments(&second.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_local_resolution_round_trips_through_gerrit() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let session = session();
+        database.store_gerrit_change(&GerritChange {
+            id: "I123".to_string(),
+            change_id: "I123".to_string(),
+            instance_id: "gerrit-1".to_string(),
+            ..Default::default()
+        }).unwrap();
+        database.store_review_session(&session).unwrap();
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+
+        let c1 = remote(GerritCommentSource::Published, "c1", None, "2025-01-05 12:00:00", true);
+        let c2 = remote(GerritCommentSource::Published, "c2", Some("c1"), "2025-01-05 13:00:00", true);
+        import_gerrit_comments(&session, &[c1.clone(), c2.clone()], &[], &database).unwrap();
+        let root = database.get_imported_gerrit_comment(&session.id, "c1").unwrap().unwrap().comment_id;
+
+        // Resolving here queues a "Done" reply to the thread's latest Gerrit comment
+        let change = engine.set_thread_state(&root, ThreadState::Done, "bob", &database).unwrap().unwrap();
+        let operation = thread_state_operation(&database, &change, OperationPriority::Normal).unwrap().unwrap();
+        assert_eq!((operation.instance_id.as_str(), operation.change_id.as_str()), ("gerrit-1", "I123"));
+        assert_eq!(operation.payload["in_reply_to"], "c2");
+        assert_eq!(operation.payload["unresolved"], false);
+        assert_eq!(operation.payload["message"], "Done");
+
+        // Once published, the reply is the thread's latest comment and keeps it resolved
+        let mut done = remote(GerritCommentSource::Published, "d3", Some("c2"), "2025-01-05 14:00:00", false);
+        done.comment.message = "Done".to_string();
+        import_gerrit_comments(&session, &[c1, c2, done], &[], &database).unwrap();
+        assert_eq!(database.get_thread_state(&root).unwrap(), ThreadState::Done);
+
+        // Reopening flips the flag back; a state that keeps the thread resolved queues nothing
+        let change = engine.set_thread_state(&root, ThreadState::Open, "bob", &database).unwrap().unwrap();
+        let operation = thread_state_operation(&database, &change, OperationPriority::Normal).unwrap().unwrap();
+        assert_eq!((operation.payload["in_reply_to"].as_str(), operation.payload["unresolved"].as_bool()), (Some("d3"), Some(true)));
+        engine.set_thread_state(&root, ThreadState::Done, "bob", &database).unwrap();
+        let change = engine.set_thread_state(&root, ThreadState::WontFix, "bob", &database).unwrap().unwrap();
+        assert!(thread_state_operation(&database, &change, OperationPriority::Normal).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_import_places_comments_and_drops_deleted_ones() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let session = session();
+        let all = [GerritCommentSource::Published, GerritCommentSource::Robot, GerritCommentSource::Draft];
+
+        let mut earlier = remote(GerritCommentSource::Published, "p1", None, "2025-01-05 10:00:00", true);
+        earlier.comment.patch_set_id = "1".to_string();
+        let mut base = remote(GerritCommentSource::Published, "b1", None, "2025-01-05 10:30:00", true);
+        base.comment.side = CommentSide::Parent;
+        let kept = remote(GerritCommentSource::Published, "c1", None, "2025-01-05 11:00:00", true);
+        let edited = remote(GerritCommentSource::Published, "c2", None, "2025-01-05 11:30:00", true);
+        let robot = remote(GerritCommentSource::Robot, "r1", None, "2025-01-05 12:00:00", false);
+
+        let comments = vec![earlier, base, kept.clone(), edited, robot];
+        let summary = import_gerrit_comments(&session, &comments, &all, &database).unwrap();
+        assert_eq!((summary.imported, summary.skipped.clone()), (4, vec!["b1".to_string()]));
+
+        // Patch set 1 was never downloaded, so its comment cannot be placed on patch set 2
+        assert_eq!(summary.outdated, 1);
+        let p1 = database.get_imported_gerrit_comment(&session.id, "p1").unwrap().unwrap().comment_id;
+        let anchor = database.get_comment_anchor(&p1).unwrap().unwrap();
+        assert_eq!((anchor.patch_set_number, anchor.outdated), (1, true));
+
+        // The remote author is what threads show for imported comments
+        let c1 = database.get_imported_gerrit_comment(&session.id, "c1").unwrap().unwrap().comment_id;
+        let thread = CommentEngine::new(CommentEngineConfig::default())
+            .get_comment_thread(&c1, &database).unwrap().unwrap();
+        assert_eq!(thread.authors.get(&c1).map(String::as_str), Some("Ann"));
+
+        // c2 was edited locally before Gerrit deleted it; the others were not touched
+        let c2 = database.get_imported_gerrit_comment(&session.id, "c2").unwrap().unwrap().comment_id;
+        let mut local = database.get_review_comment(&c2).unwrap().unwrap();
+        local.content = "Still relevant".to_string();
+        database.update_review_comment(&local).unwrap();
+
+        // Without drafts fetched, nothing missing from the lists that were fetched stays
+        let summary = import_gerrit_comments(&session, &[kept], &all[..2], &database).unwrap();
+        assert_eq!((summary.deleted, summary.conflicts.len()), (2, 1));
+        assert!(database.get_review_comment(&p1).unwrap().is_none());
+        assert!(database.get_imported_gerrit_comment(&session.id, "r1").unwrap().is_none());
+        assert!(database.get_review_comment(&c1).unwrap().is_some());
+        assert_eq!(database.get_review_comment(&c2).unwrap().unwrap().content, "Still relevant");
+    }
+}
@@ -180,6 +180,81 @@ impl DiffEngine {
         })
     }
 
+    /// Generate a unified diff between two versions of a file, e.g. two patch sets
+    pub fn diff_contents(
+        &self,
+        file_path: &str,
+        old_content: &str,
+        new_content: &str,
+    ) -> Result<ProcessedDiff, HyperReviewError> {
+        debug!("Diffing two versions of file: {}", file_path);
+
+        let mut options = git2::DiffOptions::new();
+        options.context_lines(self.diff_config.context_lines);
+
+        let path = std::path::Path::new(file_path);
+        let patch = git2::Patch::from_buffers(
+            old_content.as_bytes(),
+            Some(path),
+            new_content.as_bytes(),
+            Some(path),
+            Some(&mut options),
+        )?;
+
+        let language = self.detect_language(file_path);
+        let mut processed_hunks = Vec::new();
+        let mut stats = DiffStats::default();
+
+        for hunk_index in 0..patch.num_hunks() {
+            let (header, line_count) = patch.hunk(hunk_index)?;
+            let mut lines = Vec::with_capacity(line_count);
+
+            for line_index in 0..line_count {
+                let line = patch.line_in_hunk(hunk_index, line_index)?;
+                let line_type = match line.origin() {
+                    '+' => DiffLineType::Added,
+                    '-' => DiffLineType::Removed,
+                    ' ' => DiffLineType::Context,
+                    _ => continue,
+                };
+
+                lines.push(DiffLine {
+                    line_type,
+                    old_line_number: line.old_lineno(),
+                    new_line_number: line.new_lineno(),
+                    content: String::from_utf8_lossy(line.content())
+                        .trim_end_matches(['\n', '\r'])
+                        .to_string(),
+                });
+            }
+
+            let hunk = DiffHunk {
+                old_start: header.old_start(),
+                old_count: header.old_lines(),
+                new_start: header.new_start(),
+                new_count: header.new_lines(),
+                lines,
+            };
+            let processed_hunk = self.process_hunk_unified(&hunk, &language)?;
+            self.update_stats(&mut stats, &processed_hunk);
+            processed_hunks.push(processed_hunk);
+        }
+
+        stats.total_hunks = processed_hunks.len() as u32;
+        stats.file_size_old = old_content.len() as u64;
+        stats.file_size_new = new_content.len() as u64;
+
+        Ok(ProcessedDiff {
+            view_type: DiffViewType::Unified,
+            file_path: file_path.to_string(),
+            old_file_name: None,
+            new_file_name: file_path.to_string(),
+            language,
+            hunks: processed_hunks,
+            stats,
+        })
+    }
+
     /// Create line mapping for navigation
     pub fn create_line_mapping(&self, diff: &ProcessedDiff) -> LineMapping {
         debug!("Creating line mapping for file: {}", diff.file_path);
@@ -324,7 +399,8 @@ impl DiffEngine {
         self.process_hunk_unified(hunk, language) // Simplified for now
     }
 
-    fn detect_language(&self, file_path: &str) -> Option<String> {
+    /// Language of a file, from its extension
+    pub fn detect_language(&self, file_path: &str) -> Option<String> {
         self.syntax_highlighter.detect_language(file_path)
     }
 
@@ -539,4 +615,20 @@ mod tests {
         let lines = context.unwrap();
         assert!(lines.len() >= 1); // Should have at least the target line
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_diff_contents_numbers_lines() {
+        let engine = DiffEngine::new(DiffConfig::default());
+        let old = "one\ntwo\nthree\n";
+        let new = "zero\none\ntwo\nthree changed\n";
+
+        let diff = engine.diff_contents("notes.txt", old, new).unwrap();
+        assert_eq!(diff.stats.lines_added, 2);
+        assert_eq!(diff.stats.lines_removed, 1);
+
+        let mapping = engine.create_line_mapping(&diff);
+        assert_eq!(mapping.old_to_new.get(&1), Some(&2));
+        assert_eq!(mapping.old_to_new.get(&2), Some(&3));
+        assert_eq!(mapping.old_to_new.get(&3), None);
+    }
+}
@@ -0,0 +1,151 @@
+// Comment Mentions
+// @username and @email mentions, resolved against the account directory of the change's instance
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use regex::Regex;
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::CommentMention;
+use crate::remote::gerrit_client::{AttentionSetInput, ReviewerInput};
+use crate::storage::sqlite::Database;
+
+/// Handles mentioned in a comment, in order of first appearance. Code spans and fenced
+/// blocks are skipped, so the `@Override` of a suggested snippet is not a mention.
+pub fn mention_handles(content: &str) -> Vec<String> {
+    static CODE_SPAN: OnceLock<Regex> = OnceLock::new();
+    // An @ that does not continue a word, so the middle of an email is not a mention
+    static MENTION: OnceLock<Regex> = OnceLock::new();
+    let code_span = CODE_SPAN.get_or_init(|| Regex::new(r"`[^`\n]*`").unwrap());
+    let mention = MENTION.get_or_init(|| Regex::new(r"(?:^|[^\w@.])@([\w.+-]+(?:@[\w-]+(?:\.[\w-]+)+)?)").unwrap());
+
+    let mut handles: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let prose = code_span.replace_all(line, " ");
+        for captures in mention.captures_iter(&prose) {
+            let handle = captures[1].trim_end_matches('.');
+            if !handle.is_empty() && seen.insert(handle.to_lowercase()) {
+                handles.push(handle.to_string());
+            }
+        }
+    }
+    handles
+}
+
+/// Resolve the mentions of a comment against an instance's account directory.
+/// Handles that match no known account stay plain text.
+pub fn resolve_mentions(instance_id: &str, content: &str, database: &Database) -> Result<Vec<CommentMention>, HyperReviewError> {
+    let mut mentions: Vec<CommentMention> = Vec::new();
+    for handle in mention_handles(content) {
+        if let Some(account) = database.find_gerrit_account(instance_id, &handle)? {
+            if mentions.iter().all(|mention| mention.account_id != account.account_id) {
+                mentions.push(CommentMention {
+                    handle,
+                    account_id: account.account_id,
+                    name: account.name,
+                    email: account.email,
+                });
+            }
+        }
+    }
+    Ok(mentions)
+}
+
+/// Resolve the mentions of a comment written in a review session. Sessions whose change
+/// is not stored have no instance to resolve against, and get no mentions.
+pub fn resolve_session_mentions(session_id: &str, content: &str, database: &Database) -> Result<Vec<CommentMention>, HyperReviewError> {
+    if !content.contains('@') {
+        return Ok(Vec::new());
+    }
+    let Some(session) = database.get_review_session(session_id)? else {
+        return Ok(Vec::new());
+    };
+    let Some(change) = database.get_gerrit_change(&session.change_id)? else {
+        return Ok(Vec::new());
+    };
+    resolve_mentions(&change.instance_id, content, database)
+}
+
+/// Review additions that notify each mentioned account once: accounts not yet on the
+/// change are added as CCs, and everyone mentioned is added to the attention set.
+/// `participants` are the owner, reviewers and CCs, who must not be re-added as CCs
+/// because that would turn a reviewer into a CC.
+pub fn mention_notifications(
+    mentions: &[CommentMention],
+    participants: &HashSet<u32>,
+) -> (Vec<ReviewerInput>, Vec<AttentionSetInput>) {
+    let mut seen = HashSet::new();
+    let mut reviewers = Vec::new();
+    let mut attention_set = Vec::new();
+    for mention in mentions.iter().filter(|mention| seen.insert(mention.account_id)) {
+        if !participants.contains(&mention.account_id) {
+            reviewers.push(ReviewerInput {
+                reviewer: mention.account_id.to_string(),
+                state: "CC".to_string(),
+            });
+        }
+        attention_set.push(AttentionSetInput {
+            user: mention.account_id.to_string(),
+            reason: "Mentioned in a comment".to_string(),
+        });
+    }
+    (reviewers, attention_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::gerrit::GerritUser;
+
+    #[test]
+    fn test_mention_handles() {
+        let content = "@alice please check with @Bob.smith and @carol@example.com.\n\
+                       Not mail@example.com, nor `@decorator` or @alice again.\n\
+                       ```\n@Override\n```";
+        assert_eq!(mention_handles(content), vec!["alice", "Bob.smith", "carol@example.com"]);
+    }
+
+    #[test]
+    fn test_mentions_resolve_against_directory() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+
+        let account = |account_id, name: &str, username: &str| GerritUser {
+            account_id,
+            name: name.to_string(),
+            email: format!("{}@example.com", username),
+            username: Some(username.to_string()),
+            avatar_url: None,
+        };
+        let stored = database.store_gerrit_accounts("inst", &[
+            account(7, "Ann Lee", "alee"),
+            account(8, "Bob Smith", "bob"),
+            account(0, "Unknown", "unknown"),
+        ]).unwrap();
+        assert_eq!(stored, 2);
+
+        let suggestions = database.search_gerrit_accounts("inst", "le", 10).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].account_id, 7);
+
+        let mentions = resolve_mentions("inst", "@ALee and @bob@example.com, cc @bob and @nobody", &database).unwrap();
+        assert_eq!(mentions.iter().map(|m| m.account_id).collect::<Vec<_>>(), vec![7, 8]);
+        assert_eq!(mentions[0].name, "Ann Lee");
+        assert!(resolve_mentions("other", "@alee", &database).unwrap().is_empty());
+
+        // Bob already reviews the change, so he only gets its attention
+        let (reviewers, attention) = mention_notifications(&mentions, &HashSet::from([8]));
+        assert_eq!(reviewers.iter().map(|r| (r.reviewer.as_str(), r.state.as_str())).collect::<Vec<_>>(), vec![("7", "CC")]);
+        assert_eq!(attention.iter().map(|a| a.user.as_str()).collect::<Vec<_>>(), vec!["7", "8"]);
+    }
+}
@@ -0,0 +1,752 @@
+// Operation Executor
+// Replays queued remote operations in the background once their instance is reachable
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::{
+    CommentMention, CommentVersion, GerritCommentSource, GerritUser, ImportedGerritComment, OperationPriority,
+    OperationStatus, OperationType, SuggestedChange, ThreadState, ThreadStateChange,
+};
+use crate::remote::gerrit_client::{DraftInput, GerritClient, ReviewInput};
+use crate::remote::platform::{PlatformRegistry, PlatformReview, RegisteredPlatform, ReviewVote};
+use crate::services::mentions::mention_notifications;
+use crate::services::sync_manager::SyncManager;
+use crate::storage::credentials::CredentialStore;
+use crate::storage::operation_queue::{OperationQueue, QueuedOperation};
+use crate::storage::sqlite::Database;
+
+/// Event emitted to the frontend whenever a queued operation changes state
+pub const OPERATION_STATUS_EVENT: &str = "operation-status";
+
+/// How often the background executor looks for due operations
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationStatusEvent {
+    pub operation_id: String,
+    pub instance_id: String,
+    pub change_id: String,
+    pub operation_type: OperationType,
+    pub status: OperationStatus,
+    pub retry_count: u32,
+    pub max_retries: u32,
+    pub next_retry: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+}
+
+impl From<&QueuedOperation> for OperationStatusEvent {
+    fn from(operation: &QueuedOperation) -> Self {
+        Self {
+            operation_id: operation.id.clone(),
+            instance_id: operation.instance_id.clone(),
+            change_id: operation.change_id.clone(),
+            operation_type: operation.operation_type,
+            status: operation.status,
+            retry_count: operation.retry_count,
+            max_retries: operation.max_retries,
+            next_retry: operation.next_retry,
+            error_message: operation.error_message.clone(),
+        }
+    }
+}
+
+/// Performs queued operations against a remote instance
+#[async_trait]
+pub trait OperationHandler: Send + Sync {
+    /// Whether the instance can be reached right now
+    async fn is_reachable(&self, instance_id: &str) -> bool;
+
+    /// Perform the operation. A returned object is merged into the payloads of later
+    /// operations on the same comment, e.g. the id of a created draft.
+    async fn execute(&self, operation: &QueuedOperation) -> Result<Option<serde_json::Value>, HyperReviewError>;
+}
+
+/// Receives operation status changes
+pub trait OperationEventSink: Send + Sync {
+    fn publish(&self, event: OperationStatusEvent);
+}
+
+impl OperationEventSink for tauri::AppHandle {
+    fn publish(&self, event: OperationStatusEvent) {
+        use tauri::Manager;
+
+        if let Err(e) = self.emit_all(OPERATION_STATUS_EVENT, event) {
+            warn!("Failed to emit operation status event: {}", e);
+        }
+    }
+}
+
+/// Drains the operation queue for every reachable instance
+pub struct OperationExecutor {
+    queue: Arc<OperationQueue>,
+    handler: Arc<dyn OperationHandler>,
+    events: Arc<dyn OperationEventSink>,
+    worker_id: String,
+    poll_interval: Duration,
+}
+
+impl OperationExecutor {
+    pub fn new(
+        queue: Arc<OperationQueue>,
+        handler: Arc<dyn OperationHandler>,
+        events: Arc<dyn OperationEventSink>,
+    ) -> Self {
+        Self {
+            queue,
+            handler,
+            events,
+            worker_id: format!("executor-{}", uuid::Uuid::new_v4()),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Execute every due operation whose instance is reachable, in priority and
+    /// dependency order. Returns the number of operations attempted.
+    pub async fn run_once(&self) -> usize {
+        let mut reachable = Vec::new();
+        for instance_id in self.queue.pending_instances() {
+            if self.handler.is_reachable(&instance_id).await {
+                reachable.push(instance_id);
+            } else {
+                debug!("Instance {} is unreachable, keeping its operations queued", instance_id);
+            }
+        }
+
+        let mut attempted = 0;
+        while !reachable.is_empty() {
+            let operation = match self.queue.dequeue_matching(&self.worker_id, |op| reachable.contains(&op.instance_id)) {
+                Ok(Some(operation)) => operation,
+                Ok(None) => break,
+                Err(e) => {
+                    warn!("Failed to dequeue operation: {}", e);
+                    break;
+                }
+            };
+            attempted += 1;
+            self.events.publish(OperationStatusEvent::from(&operation));
+
+            match self.handler.execute(&operation).await {
+                Ok(result) => {
+                    if let Err(e) = self.queue.complete_operation(&operation.id, true, result) {
+                        warn!("Failed to complete operation {}: {}", operation.id, e);
+                    }
+                    let mut event = OperationStatusEvent::from(&operation);
+                    event.status = OperationStatus::Completed;
+                    self.events.publish(event);
+                }
+                Err(e) => {
+                    let permanent = is_permanent_failure(&e);
+                    let failed = if permanent {
+                        self.queue.abandon_operation(&operation.id, &e.to_string())
+                    } else {
+                        // The instance went away mid-run; leave the rest for the next pass
+                        reachable.retain(|id| *id != operation.instance_id);
+                        self.queue.fail_operation(&operation.id, &e.to_string())
+                    };
+                    match failed {
+                        Ok(failed) => self.events.publish(OperationStatusEvent::from(&failed)),
+                        Err(e) => warn!("Failed to record failure of operation {}: {}", operation.id, e),
+                    }
+                }
+            }
+        }
+
+        attempted
+    }
+
+    /// Poll the queue until the application exits
+    pub async fn run(self) {
+        info!("Operation executor {} started", self.worker_id);
+        loop {
+            let attempted = self.run_once().await;
+            if attempted > 0 {
+                info!("Operation executor attempted {} operations", attempted);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// Rejected requests will not succeed on retry; network errors, 5xx and throttling might
+fn is_permanent_failure(error: &HyperReviewError) -> bool {
+    match error {
+        HyperReviewError::Validation { .. } => true,
+        HyperReviewError::Network { status_code: Some(code), .. } => {
+            (400..500).contains(code) && !matches!(code, 401 | 408 | 429)
+        }
+        _ => false,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentPayload {
+    file_path: String,
+    line: Option<i32>,
+    message: String,
+    draft_id: Option<String>,
+    #[serde(default)]
+    suggestion: Option<SuggestedChange>,
+    #[serde(default)]
+    unresolved: Option<bool>, // From the thread state, see ThreadState::is_unresolved
+    #[serde(default)]
+    in_reply_to: Option<String>, // Gerrit ID of the parent comment, once it has one
+}
+
+impl CommentPayload {
+    /// What the comment looks like on Gerrit once pushed
+    fn version(&self) -> CommentVersion {
+        CommentVersion {
+            file_path: self.file_path.clone(),
+            line: self.line.filter(|line| *line > 0).unwrap_or(0) as u32,
+            message: self.message.clone(),
+        }
+    }
+
+    fn into_draft(self) -> DraftInput {
+        let draft = DraftInput {
+            path: self.file_path,
+            line: self.line.filter(|line| *line > 0),
+            message: self.message,
+            range: None,
+            fix_suggestions: None,
+            unresolved: self.unresolved,
+            in_reply_to: self.in_reply_to,
+        };
+        match &self.suggestion {
+            Some(suggestion) => draft.with_suggestion(suggestion),
+            None => draft,
+        }
+    }
+}
+
+/// Build the operation that posts a local comment to its change as a Gerrit draft. The
+/// payload carries the comment's suggested change, if any, and whether its thread is unresolved.
+/// A reply names its parent: by Gerrit ID when the parent was imported or already pushed,
+/// otherwise the queue fills it in when the parent's own AddComment completes.
+pub fn add_comment_operation(
+    database: &Database,
+    comment_id: &str,
+    priority: OperationPriority,
+) -> Result<QueuedOperation, HyperReviewError> {
+    let comment = database.get_review_comment(comment_id)?.ok_or_else(|| HyperReviewError::validation(
+        format!("Comment not found: {}", comment_id),
+        Some("comment_id".to_string()),
+    ))?;
+    let session = database.get_review_session(&comment.session_id)?.ok_or_else(|| HyperReviewError::validation(
+        format!("Review session not found: {}", comment.session_id),
+        Some("session_id".to_string()),
+    ))?;
+    let change = database.get_gerrit_change(&session.change_id)?.ok_or_else(|| HyperReviewError::validation(
+        format!("Change not found: {}", session.change_id),
+        Some("change_id".to_string()),
+    ))?;
+
+    let in_reply_to = match &comment.parent_comment_id {
+        Some(parent_id) => database.get_comment_import(parent_id)?.map(|import| import.gerrit_comment_id),
+        None => None,
+    };
+
+    let mut root_id = comment.id.clone();
+    let mut parent_id = comment.parent_comment_id.clone();
+    while let Some(id) = parent_id {
+        parent_id = database.get_review_comment(&id)?.and_then(|parent| parent.parent_comment_id);
+        root_id = id;
+    }
+
+    let payload = serde_json::json!({
+        "comment_id": comment.id,
+        "file_path": comment.file_path,
+        "line": comment.line_number,
+        "message": comment.content,
+        "suggestion": database.get_suggested_change(&comment.id)?,
+        "unresolved": database.get_thread_state(&root_id)?.is_unresolved(),
+        "parent_comment_id": comment.parent_comment_id,
+        "in_reply_to": in_reply_to,
+    });
+    Ok(QueuedOperation::new(&change.instance_id, &change.change_id, OperationType::AddComment, payload, priority))
+}
+
+/// Build the operation that carries a local change of a thread's resolution to Gerrit: a
+/// reply draft to the thread's latest Gerrit comment with the matching `unresolved` flag.
+/// Gerrit takes a thread's state from its latest comment, so without the reply the next
+/// import would undo the change. Returns `None` when the thread stays resolved or
+/// unresolved, or has no comment on Gerrit to reply to.
+pub fn thread_state_operation(
+    database: &Database,
+    change: &ThreadStateChange,
+    priority: OperationPriority,
+) -> Result<Option<QueuedOperation>, HyperReviewError> {
+    let unresolved = change.to_state.is_unresolved();
+    if unresolved == change.from_state.is_unresolved() {
+        return Ok(None);
+    }
+    let root = database.get_review_comment(&change.root_comment_id)?.ok_or_else(|| HyperReviewError::validation(
+        format!("Comment not found: {}", change.root_comment_id),
+        Some("root_comment_id".to_string()),
+    ))?;
+
+    let mut latest: Option<(String, ImportedGerritComment)> = None;
+    for comment in database.get_session_comments(&root.session_id)? {
+        if comment.id != root.id && comment.parent_comment_id.as_deref() != Some(root.id.as_str()) {
+            continue;
+        }
+        if let Some(import) = database.get_comment_import(&comment.id)? {
+            if latest.as_ref().is_none_or(|(created_at, _)| comment.created_at >= *created_at) {
+                latest = Some((comment.created_at, import));
+            }
+        }
+    }
+    let Some((_, parent)) = latest else {
+        return Ok(None);
+    };
+
+    let session = database.get_review_session(&root.session_id)?.ok_or_else(|| HyperReviewError::validation(
+        format!("Review session not found: {}", root.session_id),
+        Some("session_id".to_string()),
+    ))?;
+    let target = database.get_gerrit_change(&session.change_id)?.ok_or_else(|| HyperReviewError::validation(
+        format!("Change not found: {}", session.change_id),
+        Some("change_id".to_string()),
+    ))?;
+
+    let message = match change.to_state {
+        ThreadState::Open => "Reopened",
+        ThreadState::Acknowledged => "Acknowledged",
+        ThreadState::WontFix => "Won't fix",
+        ThreadState::Done | ThreadState::Resolved => "Done",
+    };
+    let payload = serde_json::json!({
+        "file_path": root.file_path,
+        "line": root.line_number,
+        "message": message,
+        "unresolved": unresolved,
+        "in_reply_to": parent.gerrit_comment_id,
+    });
+    Ok(Some(QueuedOperation::new(&target.instance_id, &target.change_id, OperationType::AddComment, payload, priority)))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewPayload {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    labels: HashMap<String, i32>,
+}
+
+/// Replays operations against the review platform of their instance. On Gerrit, comments
+/// are created as drafts on the current revision and published by a queued review. The
+/// other platforms have no drafts, so only reviews and votes are replayed there.
+pub struct PlatformOperationHandler {
+    database: Arc<Mutex<Database>>,
+    credential_store: Arc<Mutex<CredentialStore>>,
+}
+
+impl PlatformOperationHandler {
+    pub fn new(database: Arc<Mutex<Database>>, credential_store: Arc<Mutex<CredentialStore>>) -> Self {
+        Self { database, credential_store }
+    }
+
+    /// Gerrit client of the instance, or `None` when it is another review platform
+    fn client(&self, instance_id: &str) -> Result<Option<GerritClient>, HyperReviewError> {
+        let database = self.database.lock().unwrap();
+        let Some(instance) = database.get_gerrit_instance(instance_id)? else {
+            return Ok(None);
+        };
+        let credential_store = self.credential_store.lock().unwrap();
+        PlatformRegistry::gerrit_client(&database, &credential_store, &instance).map(Some)
+    }
+
+    fn platform(&self, instance_id: &str) -> Result<RegisteredPlatform, HyperReviewError> {
+        let registry = {
+            let database = self.database.lock().unwrap();
+            let credential_store = self.credential_store.lock().unwrap();
+            PlatformRegistry::load(&database, &credential_store)?
+        };
+        registry.get(instance_id).cloned()
+            .ok_or_else(|| HyperReviewError::validation(format!("Review platform instance not found: {}", instance_id), None))
+    }
+
+    /// Replay a review or vote on a platform other than Gerrit
+    async fn execute_on_platform(&self, operation: &QueuedOperation) -> Result<Option<serde_json::Value>, HyperReviewError> {
+        let target = self.platform(&operation.instance_id)?;
+        let change = target.change_ref("", &operation.change_id);
+        let review: ReviewPayload = match operation.operation_type {
+            OperationType::SubmitReview | OperationType::UpdateLabels => parse_payload(operation)?,
+            _ => return Err(HyperReviewError::validation(
+                format!("{} cannot queue {}; comments are published with the review", target.kind, operation.operation_type),
+                None,
+            )),
+        };
+        let vote = review.labels.get("Code-Review").map(|score| ReviewVote::from_score(*score));
+
+        if operation.operation_type == OperationType::UpdateLabels {
+            if let Some(vote) = vote {
+                target.platform.vote(&change, vote).await?;
+            }
+            return Ok(None);
+        }
+        let result = target.platform.post_review(&change, &PlatformReview {
+            message: review.message,
+            comments: Vec::new(),
+            vote,
+        }).await?;
+        Ok(Some(serde_json::json!({ "external_id": result.external_id, "url": result.url })))
+    }
+
+    /// Remember which local comment a draft was created for, so importing the change's
+    /// comments later updates that comment instead of adding a copy. The draft exists
+    /// either way, so a failure here must not fail (and repeat) the operation.
+    fn link_draft(&self, operation: &QueuedOperation, draft_id: &str) {
+        let Some(comment_id) = operation.comment_id() else {
+            return;
+        };
+        let database = self.database.lock().unwrap();
+        let session_id = match database.get_review_comment(comment_id) {
+            Ok(Some(comment)) => comment.session_id,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to load comment {} to link draft {}: {}", comment_id, draft_id, e);
+                return;
+            }
+        };
+        let link = ImportedGerritComment {
+            gerrit_comment_id: draft_id.to_string(),
+            comment_id: comment_id.to_string(),
+            change_id: operation.change_id.clone(),
+            source: GerritCommentSource::Draft,
+            author: GerritUser {
+                account_id: 0,
+                name: String::new(),
+                email: String::new(),
+                username: None,
+                avatar_url: None,
+            },
+            robot_id: None,
+            unresolved: false,
+            remote_updated: String::new(), // Never imported, so the next import refreshes it
+            imported_at: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            session_id,
+        };
+        if let Err(e) = database.store_imported_gerrit_comment(&link) {
+            warn!("Failed to link draft {} to comment {}: {}", draft_id, comment_id, e);
+        }
+    }
+
+    /// Keys the change's comment imports are stored under: drafts linked here use the
+    /// Gerrit change ID, imports use the ID of the stored change.
+    fn import_keys(database: &Database, operation: &QueuedOperation) -> Vec<String> {
+        let mut keys = vec![operation.change_id.clone()];
+        match database.get_gerrit_change_by_change_id(&operation.instance_id, &operation.change_id) {
+            Ok(change) => keys.extend(change.map(|change| change.id)),
+            Err(e) => warn!("Failed to load change {} of instance {}: {}", operation.change_id, operation.instance_id, e),
+        }
+        keys
+    }
+
+    /// Make the pushed version the base later imports merge local and remote edits against
+    fn record_pushed(&self, operation: &QueuedOperation, pushed: &CommentVersion) {
+        let Some(comment_id) = operation.comment_id() else {
+            return;
+        };
+        if let Err(e) = SyncManager::new(self.database.clone()).mark_synced(&operation.change_id, comment_id, pushed) {
+            warn!("Failed to record synced version of comment {}: {}", comment_id, e);
+        }
+    }
+
+    /// Accounts mentioned in the change's unpublished drafts, which a review publishes
+    fn draft_mentions(&self, operation: &QueuedOperation) -> Vec<CommentMention> {
+        let database = self.database.lock().unwrap();
+        let mut drafts = Vec::new();
+        for key in Self::import_keys(&database, operation) {
+            match database.get_imported_gerrit_comments(&key) {
+                Ok(imports) => drafts.extend(imports),
+                Err(e) => warn!("Failed to load drafts of change {}: {}", key, e),
+            }
+        }
+
+        drafts.iter()
+            .filter(|import| import.source == GerritCommentSource::Draft)
+            .filter_map(|import| match database.get_review_comment(&import.comment_id) {
+                Ok(comment) => comment,
+                Err(e) => {
+                    warn!("Failed to load comment {}: {}", import.comment_id, e);
+                    None
+                }
+            })
+            .flat_map(|comment| comment.mentions)
+            .collect()
+    }
+
+    /// The review published every draft of the change
+    fn mark_drafts_published(&self, operation: &QueuedOperation) {
+        let database = self.database.lock().unwrap();
+        for key in Self::import_keys(&database, operation) {
+            if let Err(e) = database.mark_gerrit_drafts_published(&key) {
+                warn!("Failed to mark drafts of change {} as published: {}", key, e);
+            }
+        }
+    }
+}
+
+fn parse_payload<T: serde::de::DeserializeOwned>(operation: &QueuedOperation) -> Result<T, HyperReviewError> {
+    serde_json::from_value(operation.payload.clone()).map_err(|e| {
+        HyperReviewError::validation(format!("Invalid {} payload: {}", operation.operation_type, e), None)
+    })
+}
+
+fn required_draft_id(operation: &QueuedOperation) -> Result<String, HyperReviewError> {
+    operation.payload.get("draft_id")
+        .and_then(|id| id.as_str())
+        .map(|id| id.to_string())
+        .ok_or_else(|| HyperReviewError::validation(
+            format!("{} needs the draft_id of a comment created through the queue", operation.operation_type),
+            Some("draft_id".to_string()),
+        ))
+}
+
+#[async_trait]
+impl OperationHandler for PlatformOperationHandler {
+    async fn is_reachable(&self, instance_id: &str) -> bool {
+        let client = match self.client(instance_id) {
+            Ok(Some(client)) => client,
+            // Platforms without a connection check are tried directly and back off on failure
+            Ok(None) => return self.platform(instance_id).is_ok(),
+            Err(e) => {
+                warn!("Cannot build client for instance {}: {}", instance_id, e);
+                return false;
+            }
+        };
+        matches!(client.test_connection().await, Ok(result) if result.success)
+    }
+
+    async fn execute(&self, operation: &QueuedOperation) -> Result<Option<serde_json::Value>, HyperReviewError> {
+        let Some(client) = self.client(&operation.instance_id)? else {
+            return self.execute_on_platform(operation).await;
+        };
+        let change_id = &operation.change_id;
+
+        match operation.operation_type {
+            OperationType::AddComment => {
+                let comment: CommentPayload = parse_payload(operation)?;
+                let pushed = comment.version();
+                let draft_id = client.create_draft(change_id, &comment.into_draft()).await?;
+                self.link_draft(operation, &draft_id);
+                self.record_pushed(operation, &pushed);
+                Ok(Some(serde_json::json!({ "draft_id": draft_id })))
+            }
+            OperationType::UpdateComment => {
+                let comment: CommentPayload = parse_payload(operation)?;
+                let draft_id = comment.draft_id.clone().map_or_else(|| required_draft_id(operation), Ok)?;
+                let pushed = comment.version();
+                client.update_draft(change_id, &draft_id, &comment.into_draft()).await?;
+                self.record_pushed(operation, &pushed);
+                Ok(None)
+            }
+            OperationType::DeleteComment => {
+                client.delete_draft(change_id, &required_draft_id(operation)?).await?;
+                Ok(None)
+            }
+            OperationType::SubmitReview => {
+                let review: ReviewPayload = parse_payload(operation)?;
+
+                // Notify whoever the published drafts mention. Existing participants are
+                // only added to the attention set, since a CC would demote a reviewer.
+                let mentions = self.draft_mentions(operation);
+                let (reviewers, add_to_attention_set) = if mentions.is_empty() {
+                    (Vec::new(), Vec::new())
+                } else {
+                    match client.get_change_participants(change_id).await {
+                        Ok(participants) => mention_notifications(&mentions, &participants),
+                        Err(e) => {
+                            // Any CC could demote a reviewer now, so only ask for attention
+                            warn!("Failed to load participants of change {}: {}", change_id, e);
+                            (Vec::new(), mention_notifications(&mentions, &Default::default()).1)
+                        }
+                    }
+                };
+
+                client.submit_review(change_id, &ReviewInput {
+                    message: review.message,
+                    labels: review.labels,
+                    comments: HashMap::new(),
+                    drafts: Some("PUBLISH_ALL_REVISIONS".to_string()),
+                    reviewers,
+                    add_to_attention_set,
+                }).await?;
+
+                self.mark_drafts_published(operation);
+                Ok(None)
+            }
+            OperationType::UpdateLabels => {
+                let review: ReviewPayload = parse_payload(operation)?;
+                client.submit_review(change_id, &ReviewInput {
+                    message: String::new(),
+                    labels: review.labels,
+                    comments: HashMap::new(),
+                    drafts: None,
+                    reviewers: Vec::new(),
+                    add_to_attention_set: Vec::new(),
+                }).await?;
+                Ok(None)
+            }
+            OperationType::PushPatchSet => Err(HyperReviewError::validation(
+                "Patch sets are pushed with git and cannot be replayed from the queue".to_string(),
+                None,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Handler that records executed operations and fails the ones it is told to
+    struct RecordingHandler {
+        reachable: bool,
+        executed: Mutex<Vec<(OperationType, serde_json::Value)>>,
+        fail_with: Option<u16>,
+    }
+
+    #[async_trait]
+    impl OperationHandler for RecordingHandler {
+        async fn is_reachable(&self, _instance_id: &str) -> bool {
+            self.reachable
+        }
+
+        async fn execute(&self, operation: &QueuedOperation) -> Result<Option<serde_json::Value>, HyperReviewError> {
+            self.executed.lock().unwrap().push((operation.operation_type, operation.payload.clone()));
+            if let Some(status) = self.fail_with {
+                return Err(HyperReviewError::network_with_status("rejected".to_string(), status));
+            }
+            Ok(match operation.operation_type {
+                OperationType::AddComment => Some(serde_json::json!({ "draft_id": "draft-1" })),
+                _ => None,
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct CollectingSink(Mutex<Vec<OperationStatusEvent>>);
+
+    impl OperationEventSink for CollectingSink {
+        fn publish(&self, event: OperationStatusEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    fn handler(reachable: bool, fail_with: Option<u16>) -> Arc<RecordingHandler> {
+        Arc::new(RecordingHandler { reachable, executed: Mutex::new(Vec::new()), fail_with })
+    }
+
+    fn comment_operation(operation_type: OperationType, priority: OperationPriority, seconds_ago: i64) -> QueuedOperation {
+        let mut operation = QueuedOperation::new(
+            "instance-1",
+            "change-1",
+            operation_type,
+            serde_json::json!({ "comment_id": "c1", "file_path": "src/lib.rs", "line": 3, "message": "nit" }),
+            priority,
+        );
+        operation.created_at = Utc::now() - chrono::Duration::seconds(seconds_ago);
+        operation
+    }
+
+    #[tokio::test]
+    async fn test_replays_create_before_update() {
+        let queue = Arc::new(OperationQueue::new());
+        // The update is queued with a higher priority but must wait for the create
+        queue.enqueue(comment_operation(OperationType::AddComment, OperationPriority::Low, 10)).unwrap();
+        queue.enqueue(comment_operation(OperationType::UpdateComment, OperationPriority::High, 5)).unwrap();
+
+        let handler = handler(true, None);
+        let sink = Arc::new(CollectingSink::default());
+        let executor = OperationExecutor::new(queue.clone(), handler.clone(), sink.clone());
+
+        assert_eq!(executor.run_once().await, 2);
+
+        let executed = handler.executed.lock().unwrap();
+        assert_eq!(executed[0].0, OperationType::AddComment);
+        assert_eq!(executed[1].0, OperationType::UpdateComment);
+        assert_eq!(executed[1].1["draft_id"], "draft-1");
+        assert!(queue.get_operations_for_change("change-1").unwrap().is_empty());
+
+        let completed = sink.0.lock().unwrap().iter()
+            .filter(|event| event.status == OperationStatus::Completed)
+            .count();
+        assert_eq!(completed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_reply_waits_for_parent_draft() {
+        let queue = Arc::new(OperationQueue::new());
+        queue.enqueue(comment_operation(OperationType::AddComment, OperationPriority::Low, 10)).unwrap();
+        let mut reply = QueuedOperation::new(
+            "instance-1",
+            "change-1",
+            OperationType::AddComment,
+            serde_json::json!({ "comment_id": "c2", "parent_comment_id": "c1", "file_path": "src/lib.rs", "line": 3, "message": "Agreed" }),
+            OperationPriority::High,
+        );
+        reply.created_at = Utc::now() - chrono::Duration::seconds(5);
+        queue.enqueue(reply).unwrap();
+
+        let handler = handler(true, None);
+        let executor = OperationExecutor::new(queue.clone(), handler.clone(), Arc::new(CollectingSink::default()));
+        assert_eq!(executor.run_once().await, 2);
+
+        let executed = handler.executed.lock().unwrap();
+        assert_eq!(executed[0].1["comment_id"], "c1");
+        assert_eq!(executed[1].1["comment_id"], "c2");
+        assert_eq!(executed[1].1["in_reply_to"], "draft-1");
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_instance_keeps_operations() {
+        let queue = Arc::new(OperationQueue::new());
+        queue.enqueue(comment_operation(OperationType::AddComment, OperationPriority::Normal, 0)).unwrap();
+
+        let handler = handler(false, None);
+        let executor = OperationExecutor::new(queue.clone(), handler.clone(), Arc::new(CollectingSink::default()));
+
+        assert_eq!(executor.run_once().await, 0);
+        assert!(handler.executed.lock().unwrap().is_empty());
+        assert_eq!(queue.get_operations_for_change("change-1").unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failures_back_off_or_abandon() {
+        let queue = Arc::new(OperationQueue::new());
+        let id = queue.enqueue(comment_operation(OperationType::AddComment, OperationPriority::Normal, 0)).unwrap();
+
+        let executor = OperationExecutor::new(queue.clone(), handler(true, Some(503)), Arc::new(CollectingSink::default()));
+        assert_eq!(executor.run_once().await, 1);
+        let operation = queue.get_operation(&id).unwrap();
+        assert_eq!(operation.status, OperationStatus::Queued);
+        assert_eq!(operation.retry_count, 1);
+        assert!(operation.next_retry.unwrap() > Utc::now());
+        // Not due yet
+        assert_eq!(executor.run_once().await, 0);
+
+        let queue = Arc::new(OperationQueue::new());
+        let id = queue.enqueue(comment_operation(OperationType::AddComment, OperationPriority::Normal, 0)).unwrap();
+        let executor = OperationExecutor::new(queue.clone(), handler(true, Some(400)), Arc::new(CollectingSink::default()));
+        executor.run_once().await;
+        assert_eq!(queue.get_operation(&id).unwrap().status, OperationStatus::Failed);
+
+        assert_eq!(queue.retry_failed_operations(Some("change-1")).unwrap(), 1);
+        assert_eq!(queue.get_operation(&id).unwrap().status, OperationStatus::Queued);
+    }
+}
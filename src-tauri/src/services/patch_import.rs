@@ -0,0 +1,822 @@
+// Patch Series Import
+// Turns git format-patch mboxes and directories of .patch files into local reviews
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use git2::{Oid, Repository};
+use log::{info, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::*;
+use crate::services::change_downloader::ChangeDownloader;
+use crate::storage::sqlite::Database;
+
+/// Instance ID imported patch series are stored under, alongside real Gerrit instances
+pub const PATCH_IMPORT_INSTANCE: &str = "patch-import";
+
+/// One file touched by a patch
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatchFile {
+    pub old_path: Option<String>,      // Set for renames and copies
+    pub path: String,
+    pub change_type: FileChangeType,
+    pub is_binary: bool,
+    pub insertions: u32,
+    pub deletions: u32,
+    pub diff: String,                  // The file's `diff --git` section
+}
+
+/// One commit parsed from a patch email
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedPatch {
+    pub commit: Option<String>,        // SHA from the mbox `From <sha>` separator
+    pub message_id: Option<String>,
+    pub author: GerritUser,
+    pub date: String,                  // ISO 8601 timestamp
+    pub subject: String,               // Subject without the [PATCH n/m] prefix
+    pub message: String,               // Full commit message
+    pub series_index: Option<u32>,
+    pub series_total: Option<u32>,
+    pub files: Vec<PatchFile>,
+}
+
+impl ImportedPatch {
+    /// The patch as a single git diff, suitable for `git apply`
+    pub fn diff(&self) -> String {
+        self.files.iter().map(|f| f.diff.as_str()).collect()
+    }
+
+    /// Change-Id trailer of the commit message, if the author used Gerrit's hook
+    pub fn change_id_trailer(&self) -> Option<String> {
+        self.message.lines().rev()
+            .filter_map(|line| line.trim().strip_prefix("Change-Id:"))
+            .map(|id| id.trim().to_string())
+            .find(|id| !id.is_empty())
+    }
+
+    /// Revision identifying this version of the patch: the commit SHA if known,
+    /// otherwise a hash of the message and diff
+    pub fn revision(&self) -> String {
+        self.commit.clone().unwrap_or_else(|| {
+            let mut hasher = Sha256::new();
+            hasher.update(self.message.as_bytes());
+            hasher.update(self.diff().as_bytes());
+            format!("{:x}", hasher.finalize())[..40].to_string()
+        })
+    }
+}
+
+/// Whether a patch applies on top of the base and the patches before it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApplyCheck {
+    pub applies: bool,
+    pub error: Option<String>,
+    #[serde(skip)]
+    base_tree: Option<Oid>,
+    #[serde(skip)]
+    result_tree: Option<Oid>,
+}
+
+/// A patch stored as a reviewable change
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedChange {
+    pub change: GerritChange,
+    pub patch_set_number: u32,
+    pub files: u32,
+    pub apply_check: Option<ApplyCheck>,
+}
+
+/// Result of importing a patch series
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatchSeriesImport {
+    pub series_id: String,
+    pub title: Option<String>,         // Cover letter subject
+    pub changes: Vec<ImportedChange>,
+}
+
+/// Read an mbox file, or every .patch/.diff/.eml/.mbox file of a directory in name order
+pub fn read_patch_series(path: &Path) -> Result<(Option<String>, Vec<ImportedPatch>), HyperReviewError> {
+    let mut texts = Vec::new();
+    if path.is_dir() {
+        let mut entries: Vec<_> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.is_file() && matches!(
+                p.extension().and_then(|e| e.to_str()),
+                Some("patch" | "diff" | "eml" | "mbox")
+            ))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            texts.push(String::from_utf8_lossy(&std::fs::read(&entry)?).into_owned());
+        }
+    } else {
+        texts.push(String::from_utf8_lossy(&std::fs::read(path)?).into_owned());
+    }
+
+    let mut title = None;
+    let mut patches = Vec::new();
+    for text in texts {
+        let (cover, mut parsed) = parse_mbox(&text)?;
+        title = title.or(cover);
+        patches.append(&mut parsed);
+    }
+    if patches.is_empty() {
+        return Err(HyperReviewError::validation(
+            format!("No patches found in {}", path.display()),
+            Some("path".to_string()),
+        ));
+    }
+    Ok((title, patches))
+}
+
+/// Parse the messages of an mbox. Messages without a diff are skipped; the subject
+/// of a `[PATCH 0/n]` cover letter is returned as the series title.
+pub fn parse_mbox(text: &str) -> Result<(Option<String>, Vec<ImportedPatch>), HyperReviewError> {
+    let text = text.replace("\r\n", "\n");
+    // "From <sha> Mon Sep 17 00:00:00 2001" as written by git format-patch
+    static MBOX_SEPARATOR: OnceLock<Regex> = OnceLock::new();
+    let mbox_separator = MBOX_SEPARATOR.get_or_init(|| Regex::new(r"^From \S+ +\w{3} \w{3} +\d+ \d+:\d+:\d+ \d{4}").unwrap());
+    let mut messages: Vec<(Option<String>, Vec<&str>)> = Vec::new();
+    let mut previous_blank = true;
+    for line in text.lines() {
+        if previous_blank && mbox_separator.is_match(line) {
+            let commit = line.split_whitespace().nth(1)
+                .filter(|sha| sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()))
+                .map(str::to_string);
+            messages.push((commit, Vec::new()));
+        } else {
+            if messages.is_empty() {
+                messages.push((None, Vec::new()));
+            }
+            messages.last_mut().unwrap().1.push(line);
+        }
+        previous_blank = line.is_empty();
+    }
+
+    let mut title = None;
+    let mut patches = Vec::new();
+    for (commit, lines) in messages {
+        if lines.iter().all(|l| l.trim().is_empty()) {
+            continue;
+        }
+        let patch = parse_message(commit, &lines)?;
+        if patch.files.is_empty() {
+            if patch.series_index == Some(0) {
+                title = Some(patch.subject);
+            }
+            continue;
+        }
+        patches.push(patch);
+    }
+    Ok((title, patches))
+}
+
+/// Parse a single email: headers, commit message and per-file diffs
+fn parse_message(commit: Option<String>, lines: &[&str]) -> Result<ImportedPatch, HyperReviewError> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut body_start = lines.len();
+    for (i, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            body_start = i + 1;
+            break;
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    let header = |name: &str| headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str());
+
+    let body: Vec<String> = lines[body_start.min(lines.len())..].iter()
+        .map(|line| unescape_from(line).to_string())
+        .collect();
+    let body = match header("content-transfer-encoding").map(|e| e.to_lowercase()) {
+        Some(e) if e == "quoted-printable" => decode_quoted_printable(&body.join("\n")),
+        Some(e) if e == "base64" => {
+            let encoded: String = body.concat().split_whitespace().collect();
+            let decoded = base64::engine::general_purpose::STANDARD.decode(encoded)
+                .map_err(|e| HyperReviewError::other(format!("Invalid base64 patch body: {}", e)))?;
+            String::from_utf8_lossy(&decoded).into_owned()
+        }
+        _ => body.join("\n"),
+    };
+
+    let raw_subject = decode_header(header("subject").unwrap_or_default());
+    let (subject, series_index, series_total) = strip_subject_prefix(&raw_subject);
+    let author = parse_author(&decode_header(header("from").unwrap_or_default()));
+    let date = header("date")
+        .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_else(|| Utc::now().to_rfc3339());
+    let message_id = header("message-id")
+        .map(|id| id.trim_matches(|c| c == '<' || c == '>').to_string());
+
+    let body_lines: Vec<&str> = body.lines().collect();
+    let diff_start = body_lines.iter().position(|l| l.starts_with("diff --git "));
+    let message_end = body_lines.iter()
+        .position(|l| *l == "---")
+        .into_iter()
+        .chain(diff_start)
+        .min()
+        .unwrap_or(body_lines.len());
+    let description = body_lines[..message_end].join("\n");
+    let description = description.trim();
+    let message = if description.is_empty() {
+        subject.clone()
+    } else {
+        format!("{}\n\n{}", subject, description)
+    };
+
+    let files = match diff_start {
+        Some(start) => parse_diff_sections(&body_lines[start..]),
+        None => Vec::new(),
+    };
+
+    Ok(ImportedPatch {
+        commit,
+        message_id,
+        author,
+        date,
+        subject,
+        message,
+        series_index,
+        series_total,
+        files,
+    })
+}
+
+/// Undo mboxrd quoting of body lines beginning with "From "
+fn unescape_from(line: &str) -> &str {
+    let unquoted = line.trim_start_matches('>');
+    if unquoted.starts_with("From ") && unquoted.len() < line.len() {
+        &line[1..]
+    } else {
+        line
+    }
+}
+
+/// Extended header lines of a `diff --git` section
+const DIFF_HEADER_PREFIXES: &[&str] = &[
+    "diff --git ", "index ", "old mode ", "new mode ", "new file mode ", "deleted file mode ",
+    "similarity index ", "dissimilarity index ", "rename from ", "rename to ", "copy from ", "copy to ",
+    "--- ", "+++ ", "@@ ", "\\",
+];
+
+/// Split the diff part of a patch into files. Hunk line counts are followed so
+/// the `-- ` signature and blank lines mangled by mailers are handled correctly.
+fn parse_diff_sections(lines: &[&str]) -> Vec<PatchFile> {
+    static HUNK_HEADER: OnceLock<Regex> = OnceLock::new();
+    let hunk_header = HUNK_HEADER.get_or_init(|| Regex::new(r"^@@ -\d+(?:,(\d+))? \+\d+(?:,(\d+))? @@").unwrap());
+    let mut files: Vec<PatchFile> = Vec::new();
+    let (mut old_remaining, mut new_remaining) = (0u32, 0u32);
+
+    for &line in lines {
+        let in_hunk = old_remaining > 0 || new_remaining > 0;
+        if !in_hunk {
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                let (old_path, path) = split_git_paths(paths);
+                files.push(PatchFile {
+                    old_path: (old_path != path).then_some(old_path),
+                    path,
+                    change_type: FileChangeType::Modified,
+                    is_binary: false,
+                    insertions: 0,
+                    deletions: 0,
+                    diff: String::new(),
+                });
+            } else if line == "-- " || line == "--" {
+                break;
+            }
+        }
+        let Some(file) = files.last_mut() else { continue };
+
+        if in_hunk {
+            let line = if line.is_empty() { " " } else { line };
+            match line.as_bytes()[0] {
+                b'+' => { new_remaining = new_remaining.saturating_sub(1); file.insertions += 1; }
+                b'-' => { old_remaining = old_remaining.saturating_sub(1); file.deletions += 1; }
+                b'\\' => {}
+                _ => {
+                    old_remaining = old_remaining.saturating_sub(1);
+                    new_remaining = new_remaining.saturating_sub(1);
+                }
+            }
+            file.diff.push_str(line);
+            file.diff.push('\n');
+            continue;
+        }
+
+        if let Some(captures) = hunk_header.captures(line) {
+            let count = |i: usize| captures.get(i).map_or(1, |m| m.as_str().parse().unwrap_or(1));
+            old_remaining = count(1);
+            new_remaining = count(2);
+        } else if line.starts_with("new file mode") {
+            file.change_type = FileChangeType::Added;
+        } else if line.starts_with("deleted file mode") {
+            file.change_type = FileChangeType::Deleted;
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            file.change_type = FileChangeType::Renamed;
+            file.old_path = Some(from.to_string());
+        } else if let Some(from) = line.strip_prefix("copy from ") {
+            file.change_type = FileChangeType::Copied;
+            file.old_path = Some(from.to_string());
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.is_binary = true;
+        } else if !file.is_binary && !DIFF_HEADER_PREFIXES.iter().any(|p| line.starts_with(p)) {
+            // Blank lines and trailers such as "base-commit:" between files
+            continue;
+        }
+        file.diff.push_str(line);
+        file.diff.push('\n');
+    }
+    files
+}
+
+/// Split "a/old b/new" from a `diff --git` line
+fn split_git_paths(paths: &str) -> (String, String) {
+    let paths = paths.strip_prefix("a/").unwrap_or(paths);
+    // Unrenamed files have identical halves, which also copes with " b/" inside a path
+    let half = paths.len().saturating_sub(3) / 2;
+    if paths.len() > 3 && paths.is_char_boundary(half) && paths[half..].starts_with(" b/") && paths[..half] == paths[half + 3..] {
+        return (paths[..half].to_string(), paths[..half].to_string());
+    }
+    match paths.split_once(" b/") {
+        Some((old, new)) => (old.to_string(), new.to_string()),
+        None => (paths.to_string(), paths.to_string()),
+    }
+}
+
+/// Strip "[PATCH v2 3/5]" style prefixes, returning the index and total
+fn strip_subject_prefix(subject: &str) -> (String, Option<u32>, Option<u32>) {
+    static PREFIX: OnceLock<Regex> = OnceLock::new();
+    static NUMBERING: OnceLock<Regex> = OnceLock::new();
+    let prefix = PREFIX.get_or_init(|| Regex::new(r"^\s*(?:\[[^\]]*\]\s*)+").unwrap());
+    let numbering = NUMBERING.get_or_init(|| Regex::new(r"(\d+)/(\d+)").unwrap());
+    let Some(found) = prefix.find(subject) else {
+        return (subject.trim().to_string(), None, None);
+    };
+    let (index, total) = numbering.captures(found.as_str())
+        .map(|c| (c[1].parse().ok(), c[2].parse().ok()))
+        .unwrap_or((None, None));
+    (subject[found.end()..].trim().to_string(), index, total)
+}
+
+/// Parse `Name <email>` into a user
+fn parse_author(from: &str) -> GerritUser {
+    let (name, email) = match (from.rfind('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => (
+            from[..start].trim().trim_matches('"').to_string(),
+            from[start + 1..end].trim().to_string(),
+        ),
+        _ => (String::new(), from.trim().to_string()),
+    };
+    GerritUser {
+        account_id: 0,
+        name: if name.is_empty() { email.clone() } else { name },
+        email,
+        username: None,
+        avatar_url: None,
+    }
+}
+
+/// Decode RFC 2047 encoded words such as `=?UTF-8?q?J=C3=B6rg?=`
+fn decode_header(value: &str) -> String {
+    // Whitespace between adjacent encoded words is not part of the text
+    static ADJACENT: OnceLock<Regex> = OnceLock::new();
+    static ENCODED_WORD: OnceLock<Regex> = OnceLock::new();
+    let adjacent = ADJACENT.get_or_init(|| Regex::new(r"\?=\s+=\?").unwrap());
+    let encoded_word = ENCODED_WORD.get_or_init(|| Regex::new(r"=\?([^?]+)\?([bBqQ])\?([^?]*)\?=").unwrap());
+    let value = adjacent.replace_all(value, "?==?");
+    encoded_word.replace_all(&value, |captures: &regex::Captures| {
+        let text = &captures[3];
+        let bytes = if captures[2].eq_ignore_ascii_case("b") {
+            base64::engine::general_purpose::STANDARD.decode(text).unwrap_or_default()
+        } else {
+            decode_quoted_printable(&text.replace('_', " ")).into_bytes()
+        };
+        String::from_utf8_lossy(&bytes).into_owned()
+    }).into_owned()
+}
+
+/// Decode a quoted-printable body, including soft line breaks
+fn decode_quoted_printable(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            if bytes.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            if let Some(byte) = text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Check each patch of a series against `base_ref`, applying them in order in memory.
+/// A patch that fails is skipped, so later patches are checked against the last good tree.
+pub fn check_series(repo: &Repository, base_ref: &str, patches: &[ImportedPatch]) -> Result<Vec<ApplyCheck>, HyperReviewError> {
+    let base = repo.revparse_single(base_ref)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|e| HyperReviewError::validation(
+            format!("Cannot resolve base '{}': {}", base_ref, e.message()),
+            Some("base_ref".to_string()),
+        ))?;
+
+    let mut tree = base;
+    let mut checks = Vec::with_capacity(patches.len());
+    for patch in patches {
+        let applied = git2::Diff::from_buffer(patch.diff().as_bytes())
+            .and_then(|diff| repo.apply_to_tree(&tree, &diff, None))
+            .and_then(|mut index| index.write_tree_to(repo))
+            .and_then(|oid| repo.find_tree(oid));
+        match applied {
+            Ok(result) => {
+                checks.push(ApplyCheck {
+                    applies: true,
+                    error: None,
+                    base_tree: Some(tree.id()),
+                    result_tree: Some(result.id()),
+                });
+                tree = result;
+            }
+            Err(e) => {
+                warn!("Patch '{}' does not apply: {}", patch.subject, e.message());
+                checks.push(ApplyCheck {
+                    applies: false,
+                    error: Some(e.message().to_string()),
+                    base_tree: Some(tree.id()),
+                    result_tree: None,
+                });
+            }
+        }
+    }
+    Ok(checks)
+}
+
+/// Text content of `path` in a tree; None for missing or binary files
+fn tree_file_content(repo: &Repository, tree: Option<Oid>, path: &str) -> Option<String> {
+    let tree = repo.find_tree(tree?).ok()?;
+    let blob = tree.get_path(Path::new(path)).ok()?.to_object(repo).ok()?.peel_to_blob().ok()?;
+    if blob.is_binary() {
+        return None;
+    }
+    String::from_utf8(blob.content().to_vec()).ok()
+}
+
+/// Store a parsed series as changes of the patch import instance, one change per patch.
+/// The change's local ID is its Change-Id trailer (or revision), so review sessions can refer
+/// to it either way. Re-importing a changed patch with the same Change-Id adds a patch set.
+pub fn store_series(
+    database: &Database,
+    project: &str,
+    base_ref: Option<&str>,
+    title: Option<String>,
+    patches: &[ImportedPatch],
+    checks: Option<(&Repository, Vec<ApplyCheck>)>,
+) -> Result<PatchSeriesImport, HyperReviewError> {
+    database.ensure_local_gerrit_instance(PATCH_IMPORT_INSTANCE, "Imported patches")?;
+
+    let series_id = uuid::Uuid::new_v4().to_string();
+    let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let (repo, checks): (_, Vec<Option<ApplyCheck>>) = match checks {
+        Some((repo, checks)) => (Some(repo), checks.into_iter().map(Some).collect()),
+        None => (None, vec![None; patches.len()]),
+    };
+
+    let mut changes = Vec::with_capacity(patches.len());
+    for (position, (patch, check)) in patches.iter().zip(checks).enumerate() {
+        let revision = patch.revision();
+        let change_id = patch.change_id_trailer().unwrap_or_else(|| revision.clone());
+
+        let existing = database.get_gerrit_change(&change_id)?
+            .filter(|c| c.instance_id == PATCH_IMPORT_INSTANCE);
+        let patch_set_number = match &existing {
+            Some(c) if c.current_revision == revision => c.current_patch_set_num,
+            Some(c) => c.current_patch_set_num + 1,
+            None => 1,
+        };
+        let id = change_id.clone();
+
+        let mut metadata = HashMap::new();
+        metadata.insert("source".to_string(), PATCH_IMPORT_INSTANCE.to_string());
+        metadata.insert("series_id".to_string(), series_id.clone());
+        metadata.insert("series_index".to_string(), patch.series_index.unwrap_or(position as u32 + 1).to_string());
+        metadata.insert("series_total".to_string(), patch.series_total.unwrap_or(patches.len() as u32).to_string());
+        metadata.insert("commit_message".to_string(), patch.message.clone());
+        if let Some(title) = &title {
+            metadata.insert("series_title".to_string(), title.clone());
+        }
+        if let Some(message_id) = &patch.message_id {
+            metadata.insert("message_id".to_string(), message_id.clone());
+        }
+        if let Some(base_ref) = base_ref {
+            metadata.insert("base_ref".to_string(), base_ref.to_string());
+        }
+        if let Some(check) = &check {
+            metadata.insert("applies".to_string(), check.applies.to_string());
+            if let Some(error) = &check.error {
+                metadata.insert("apply_error".to_string(), error.clone());
+            }
+        }
+
+        let change = GerritChange {
+            id: id.clone(),
+            change_id,
+            instance_id: PATCH_IMPORT_INSTANCE.to_string(),
+            project: project.to_string(),
+            branch: base_ref.unwrap_or_default().to_string(),
+            subject: patch.subject.clone(),
+            status: ChangeStatus::New,
+            owner: patch.author.clone(),
+            created: existing.as_ref().map(|c| c.created.clone()).unwrap_or_else(|| patch.date.clone()),
+            updated: patch.date.clone(),
+            insertions: patch.files.iter().map(|f| f.insertions).sum(),
+            deletions: patch.files.iter().map(|f| f.deletions).sum(),
+            current_revision: revision.clone(),
+            current_patch_set_num: patch_set_number,
+            patch_sets: vec![PatchSet {
+                id: uuid::Uuid::new_v4().to_string(),
+                gerrit_patch_set_id: format!("{}/{}", id, patch_set_number),
+                change_id: id.clone(),
+                revision,
+                number: patch_set_number,
+                author: patch.author.clone(),
+                commit_message: patch.message.clone(),
+                created: patch.date.clone(),
+                kind: PatchSetKind::Rework,
+                files: patch.files.iter().map(|f| f.path.clone()).collect(),
+                size_insertions: patch.files.iter().map(|f| f.insertions).sum(),
+                size_deletions: patch.files.iter().map(|f| f.deletions).sum(),
+                is_current: true,
+            }],
+            files: Vec::new(),
+            total_files: patch.files.len() as u32,
+            reviewed_files: existing.as_ref().map_or(0, |c| c.reviewed_files),
+            local_comments: existing.as_ref().map_or(0, |c| c.local_comments),
+            remote_comments: 0,
+            import_status: ImportStatus::Imported,
+            last_sync: Some(now.clone()),
+            conflict_status: ConflictStatus::None,
+            metadata,
+        };
+        if existing.is_some() {
+            // Replacing the row would cascade to the change's sessions and files
+            database.update_gerrit_change(&change)?;
+        } else {
+            database.store_gerrit_change(&change)?;
+        }
+
+        for file in &patch.files {
+            let (old_content, new_content) = match (repo, &check) {
+                (Some(repo), Some(check)) => (
+                    tree_file_content(repo, check.base_tree, file.old_path.as_deref().unwrap_or(&file.path)),
+                    tree_file_content(repo, check.result_tree, &file.path),
+                ),
+                _ => (None, None),
+            };
+            let (old_line_count, new_line_count, hunks) = ChangeDownloader::parse_unified_diff(&file.diff);
+            database.store_change_file(&ChangeFile {
+                id: uuid::Uuid::new_v4().to_string(),
+                change_id: id.clone(),
+                patch_set_number,
+                file_path: file.path.clone(),
+                change_type: file.change_type.clone(),
+                file_size: new_content.as_ref().or(old_content.as_ref()).map_or(0, |c| c.len() as u64),
+                old_content,
+                new_content,
+                diff: FileDiff {
+                    unified_diff: file.diff.clone(),
+                    old_line_count,
+                    new_line_count,
+                    context_lines: 3,
+                    hunks,
+                },
+                downloaded_at: now.clone(),
+            })?;
+        }
+
+        changes.push(ImportedChange {
+            files: patch.files.len() as u32,
+            patch_set_number,
+            apply_check: check,
+            change,
+        });
+    }
+
+    info!("Imported patch series {} with {} changes", series_id, changes.len());
+    Ok(PatchSeriesImport { series_id, title, changes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use crate::services::review_session::ReviewSessionManager;
+
+    const SERIES: &str = "\
+From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001
+From: Jane Doe <jane@example.com>
+Date: Tue, 2 Jan 2024 10:00:00 +0100
+Subject: [PATCH 0/2] Greeting fixes
+
+Two small fixes.
+
+From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+From: =?UTF-8?q?J=C3=B6rg=20M=C3=BCller?= <joerg@example.com>
+Date: Tue, 2 Jan 2024 10:00:00 +0100
+Message-ID: <patch-1@example.com>
+Subject: [PATCH 1/2] Say hello to
+ the world
+
+Greet everyone instead of nobody.
+
+Change-Id: I1234567890abcdef1234567890abcdef12345678
+---
+ hello.txt | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+diff --git a/hello.txt b/hello.txt
+index 3b18e51..a042389 100644
+--- a/hello.txt
++++ b/hello.txt
+@@ -1,3 +1,3 @@
+ first
+
+-hello nobody
++hello world
+--
+2.43.0
+
+From 2222222222222222222222222222222222222222 Mon Sep 17 00:00:00 2001
+From: Jane Doe <jane@example.com>
+Date: Tue, 2 Jan 2024 10:05:00 +0100
+Subject: [PATCH 2/2] Add notes
+
+---
+diff --git a/notes.md b/notes.md
+new file mode 100644
+index 0000000..e69de29
+--- /dev/null
++++ b/notes.md
+@@ -0,0 +1 @@
++--
+--
+2.43.0
+";
+
+    fn init_repo() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("hello.txt"), "first\n\nhello nobody\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("hello.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "base", &tree, &[]).unwrap();
+        drop(tree);
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_parse_format_patch_series() {
+        let (title, patches) = parse_mbox(SERIES).unwrap();
+        assert_eq!(title.as_deref(), Some("Greeting fixes"));
+        assert_eq!(patches.len(), 2);
+
+        let first = &patches[0];
+        assert_eq!(first.subject, "Say hello to the world");
+        assert_eq!(first.author.name, "Jörg Müller");
+        assert_eq!(first.author.email, "joerg@example.com");
+        assert_eq!((first.series_index, first.series_total), (Some(1), Some(2)));
+        assert_eq!(first.message_id.as_deref(), Some("patch-1@example.com"));
+        assert!(first.message.starts_with("Say hello to the world\n\nGreet everyone"));
+        assert_eq!(first.change_id_trailer().as_deref(), Some("I1234567890abcdef1234567890abcdef12345678"));
+        assert_eq!(first.revision(), "1".repeat(40));
+        assert_eq!(first.files.len(), 1);
+        assert_eq!((first.files[0].insertions, first.files[0].deletions), (1, 1));
+        // The blank context line stripped by the mailer is restored, and the signature dropped
+        assert!(first.files[0].diff.contains("\n \n-hello nobody\n+hello world\n"));
+        assert!(!first.files[0].diff.contains("2.43.0"));
+
+        let second = &patches[1];
+        assert_eq!(second.files[0].path, "notes.md");
+        assert_eq!(second.files[0].change_type, FileChangeType::Added);
+        // Content resembling the signature separator is kept
+        assert!(second.files[0].diff.ends_with("+--\n"));
+    }
+
+    #[test]
+    fn test_parse_rename_and_plain_patch() {
+        let patch = "\
+From: Dev <dev@example.com>
+Subject: Move module
+Content-Transfer-Encoding: quoted-printable
+
+Rename the module=20
+so it matches.
+---
+diff --git a/src/old name.rs b/src/new name.rs
+similarity index 100%
+rename from src/old name.rs
+rename to src/new name.rs
+";
+        let (_, patches) = parse_mbox(patch).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].message, "Move module\n\nRename the module \nso it matches.");
+        let file = &patches[0].files[0];
+        assert_eq!(file.change_type, FileChangeType::Renamed);
+        assert_eq!(file.old_path.as_deref(), Some("src/old name.rs"));
+        assert_eq!(file.path, "src/new name.rs");
+        assert!(patches[0].commit.is_none());
+        assert_eq!(patches[0].revision().len(), 40);
+    }
+
+    #[test]
+    fn test_check_series_applies_in_order() {
+        let (_dir, repo) = init_repo();
+        let (_, mut patches) = parse_mbox(SERIES).unwrap();
+        let checks = check_series(&repo, "HEAD", &patches).unwrap();
+        assert!(checks.iter().all(|c| c.applies), "{:?}", checks);
+        assert_eq!(
+            tree_file_content(&repo, checks[0].result_tree, "hello.txt").as_deref(),
+            Some("first\n\nhello world\n")
+        );
+
+        // The same patch again no longer applies on top of itself
+        patches.push(patches[0].clone());
+        let checks = check_series(&repo, "HEAD", &patches).unwrap();
+        assert!(!checks[2].applies);
+        assert!(checks[2].error.is_some());
+
+        assert!(check_series(&repo, "no-such-branch", &patches).is_err());
+    }
+
+    #[test]
+    fn test_store_series_adds_patch_sets_on_reimport() {
+        let (_dir, repo) = init_repo();
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let (title, patches) = parse_mbox(SERIES).unwrap();
+
+        let checks = check_series(&repo, "HEAD", &patches).unwrap();
+        let imported = store_series(&database, "demo", Some("HEAD"), title.clone(), &patches, Some((&repo, checks))).unwrap();
+        assert_eq!(imported.changes.len(), 2);
+        let first = &imported.changes[0];
+        assert_eq!(first.change.change_id, "I1234567890abcdef1234567890abcdef12345678");
+        assert_eq!(first.change.metadata.get("applies").map(String::as_str), Some("true"));
+        assert_eq!(first.change.metadata.get("series_title").map(String::as_str), Some("Greeting fixes"));
+
+        let files = database.get_change_files_by_gerrit_id(&first.change.change_id, 1).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].old_content.as_deref(), Some("first\n\nhello nobody\n"));
+        assert_eq!(files[0].new_content.as_deref(), Some("first\n\nhello world\n"));
+        assert_eq!(files[0].diff.hunks.len(), 1);
+
+        // Same content: same patch set. New revision: next patch set on the same change.
+        let again = store_series(&database, "demo", None, None, &patches, None).unwrap();
+        assert_eq!(again.changes[0].change.id, first.change.id);
+        assert_eq!(again.changes[0].patch_set_number, 1);
+
+        let mut revised = patches.clone();
+        revised[0].commit = Some("3".repeat(40));
+        let again = store_series(&database, "demo", None, None, &revised, None).unwrap();
+        assert_eq!(again.changes[0].change.id, first.change.id);
+        assert_eq!(again.changes[0].patch_set_number, 2);
+        assert!(database.is_change_downloaded_by_gerrit_id(&first.change.change_id, 2).unwrap());
+        assert!(database.get_all_gerrit_instances().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    async fn test_imported_patch_can_be_reviewed_offline() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let (_, patches) = parse_mbox(SERIES).unwrap();
+        let imported = store_series(&database, "demo", None, None, &patches, None).unwrap();
+
+        let manager = ReviewSessionManager::new(std::sync::Arc::new(database));
+        let session = manager.create_session(&imported.changes[1].change.change_id, 1, "reviewer", ReviewMode::Offline)
+            .await
+            .unwrap();
+        assert_eq!(session.progress.total_files, 1);
+    }
+}
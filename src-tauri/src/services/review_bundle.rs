@@ -0,0 +1,633 @@
+// Review Bundle Service
+// Portable .hrbundle archives for reviewing changes on machines without server access
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path};
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::*;
+use crate::services::file_storage::FileStorage;
+use crate::storage::sqlite::Database;
+
+/// File extension of review bundles
+pub const BUNDLE_EXTENSION: &str = "hrbundle";
+
+/// Bundle format version written by this build; newer bundles are rejected
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+const CHANGE: &str = "change.json";
+const SESSIONS: &str = "sessions.json";
+const COMMENTS: &str = "comments.json";
+
+/// Change metadata key recording when a review bundle was imported
+const IMPORTED_AT: &str = "bundle_imported_at";
+
+/// What a bundle carries
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleKind {
+    /// A change with its files, diffs and comments, for offline review
+    Review,
+    /// Comments made on an imported review bundle, to merge back and publish
+    Return,
+}
+
+/// Server the bundled change came from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BundleInstance {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+}
+
+/// An archive member and its checksum
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BundleEntry {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// `manifest.json`, listing every other member of the archive
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub kind: BundleKind,
+    pub created_at: String,            // ISO 8601 timestamp
+    pub change_id: String,
+    pub subject: String,
+    pub instance: Option<BundleInstance>,
+    pub patch_sets: Vec<u32>,
+    pub entries: Vec<BundleEntry>,
+}
+
+/// Outcome of importing a bundle
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundleImport {
+    pub kind: BundleKind,
+    pub change_id: String,
+    pub files: u32,
+    pub sessions: u32,
+    pub comments: u32,
+}
+
+/// Collects archive members, then writes them with the manifest as a gzipped tar
+struct BundleWriter {
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl BundleWriter {
+    fn new() -> Self {
+        Self { entries: BTreeMap::new() }
+    }
+
+    fn add(&mut self, path: String, data: Vec<u8>) {
+        self.entries.insert(path, data);
+    }
+
+    fn add_json<T: Serialize>(&mut self, path: &str, value: &T) -> Result<(), HyperReviewError> {
+        self.add(path.to_string(), serde_json::to_vec_pretty(value)?);
+        Ok(())
+    }
+
+    fn write(self, path: &Path, mut manifest: BundleManifest) -> Result<BundleManifest, HyperReviewError> {
+        manifest.entries = self.entries.iter()
+            .map(|(path, data)| BundleEntry {
+                path: path.clone(),
+                sha256: checksum(data),
+                size: data.len() as u64,
+            })
+            .collect();
+
+        let encoder = GzEncoder::new(File::create(path)?, Compression::default());
+        let mut archive = tar::Builder::new(encoder);
+        let mtime = Utc::now().timestamp().max(0) as u64;
+        let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+        for (name, data) in std::iter::once((MANIFEST, &manifest_json)).chain(self.entries.iter().map(|(p, d)| (p.as_str(), d))) {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            archive.append_data(&mut header, name, data.as_slice())?;
+        }
+        archive.into_inner()?.finish()?;
+
+        info!("Wrote {:?} bundle for change {} to {}", manifest.kind, manifest.change_id, path.display());
+        Ok(manifest)
+    }
+}
+
+/// A bundle read into memory, with every member checked against the manifest
+struct BundleReader {
+    manifest: BundleManifest,
+    entries: HashMap<String, Vec<u8>>,
+}
+
+impl BundleReader {
+    fn open(path: &Path) -> Result<Self, HyperReviewError> {
+        let invalid = |message: String| HyperReviewError::validation(message, Some("path".to_string()));
+
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+        let mut entries = HashMap::new();
+        for entry in archive.entries().map_err(|e| invalid(format!("Not a review bundle: {}", e)))? {
+            let mut entry = entry.map_err(|e| invalid(format!("Corrupt review bundle: {}", e)))?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            entries.insert(name, data);
+        }
+
+        let manifest: BundleManifest = serde_json::from_slice(
+            &entries.remove(MANIFEST).ok_or_else(|| invalid("Review bundle has no manifest".to_string()))?
+        )?;
+        if manifest.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(invalid(format!(
+                "Bundle format {} is newer than supported ({})", manifest.format_version, BUNDLE_FORMAT_VERSION
+            )));
+        }
+        for listed in &manifest.entries {
+            let data = entries.get(&listed.path)
+                .ok_or_else(|| invalid(format!("Bundle is missing {}", listed.path)))?;
+            if checksum(data) != listed.sha256 {
+                return Err(invalid(format!("Checksum mismatch for {}", listed.path)));
+            }
+        }
+        if let Some(extra) = entries.keys().find(|name| !manifest.entries.iter().any(|e| &e.path == *name)) {
+            return Err(invalid(format!("Bundle member {} is not in the manifest", extra)));
+        }
+
+        Ok(Self { manifest, entries })
+    }
+
+    fn json<T: DeserializeOwned>(&self, path: &str) -> Result<T, HyperReviewError> {
+        let data = self.entries.get(path)
+            .ok_or_else(|| HyperReviewError::validation(format!("Bundle is missing {}", path), Some("path".to_string())))?;
+        Ok(serde_json::from_slice(data)?)
+    }
+}
+
+fn checksum(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn diffs_path(patch_set: u32) -> String {
+    format!("patchsets/{}/files.json", patch_set)
+}
+
+fn content_prefix(patch_set: u32) -> String {
+    format!("patchsets/{}/content/", patch_set)
+}
+
+fn load_change(database: &Database, change_id: &str) -> Result<GerritChange, HyperReviewError> {
+    database.get_gerrit_change(change_id)?
+        .ok_or_else(|| HyperReviewError::other(format!("Change not found: {}", change_id)))
+}
+
+/// Sessions of a change, which may refer to it by local or Gerrit ID
+fn change_sessions(database: &Database, change: &GerritChange) -> Result<Vec<ReviewSession>, HyperReviewError> {
+    let mut sessions = database.get_review_sessions_for_change(&change.id)?;
+    if change.change_id != change.id {
+        sessions.extend(database.get_review_sessions_for_change(&change.change_id)?);
+    }
+    Ok(sessions)
+}
+
+/// Patch set numbers with downloaded files, newest last
+fn downloaded_patch_sets(database: &Database, change: &GerritChange) -> Result<Vec<u32>, HyperReviewError> {
+    let mut patch_sets = Vec::new();
+    for number in 1..=change.current_patch_set_num {
+        if database.is_change_downloaded(&change.id, number)? {
+            patch_sets.push(number);
+        }
+    }
+    Ok(patch_sets)
+}
+
+/// Pack a change for offline review: metadata, per patch set diffs and the file contents
+/// cached in `storage`, and all review sessions and comments on it
+pub fn export_review_bundle(
+    database: &Database,
+    storage: &mut FileStorage,
+    change_id: &str,
+    path: &Path,
+) -> Result<BundleManifest, HyperReviewError> {
+    let change = load_change(database, change_id)?;
+    let patch_sets = downloaded_patch_sets(database, &change)?;
+    if patch_sets.is_empty() {
+        return Err(HyperReviewError::other(format!(
+            "Change {} must be downloaded before it can be bundled", change.change_id
+        )));
+    }
+
+    let mut writer = BundleWriter::new();
+    writer.add_json(CHANGE, &change)?;
+    for &number in &patch_sets {
+        writer.add_json(&diffs_path(number), &database.get_change_files(&change.id, number)?)?;
+
+        // Cached files may be keyed by either ID of the change
+        let mut cached: Vec<(String, String)> = storage.list_cached_files(&change.change_id, Some(number)).into_iter()
+            .chain(storage.list_cached_files(&change.id, Some(number)))
+            .map(|info| (info.change_id.clone(), info.file_path.clone()))
+            .collect();
+        cached.sort_by(|a, b| a.1.cmp(&b.1));
+        cached.dedup_by(|a, b| a.1 == b.1);
+        for (cache_change_id, file_path) in cached {
+            match storage.get_file(&cache_change_id, number, &file_path)? {
+                Some(content) => writer.add(format!("{}{}", content_prefix(number), file_path), content.into_bytes()),
+                None => warn!("Cached file {} of PS{} is unreadable, not bundled", file_path, number),
+            }
+        }
+    }
+
+    let sessions = change_sessions(database, &change)?;
+    let mut comments = Vec::new();
+    for session in &sessions {
+        comments.extend(database.get_review_comments_for_session(&session.id)?);
+    }
+    writer.add_json(SESSIONS, &sessions)?;
+    writer.add_json(COMMENTS, &comments)?;
+
+    let instance = database.get_gerrit_instance(&change.instance_id)?
+        .map(|i| BundleInstance { id: i.id, name: i.name, url: i.url });
+    writer.write(path, BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        kind: BundleKind::Review,
+        created_at: Utc::now().to_rfc3339(),
+        change_id: change.change_id.clone(),
+        subject: change.subject.clone(),
+        instance,
+        patch_sets,
+        entries: Vec::new(),
+    })
+}
+
+/// Pack the comments made since a review bundle was imported, with the sessions they belong to
+pub fn export_return_bundle(database: &Database, change_id: &str, path: &Path) -> Result<BundleManifest, HyperReviewError> {
+    let change = load_change(database, change_id)?;
+    let imported_at = change.metadata.get(IMPORTED_AT).cloned().ok_or_else(|| HyperReviewError::other(format!(
+        "Change {} was not imported from a review bundle", change.change_id
+    )))?;
+
+    let mut sessions = Vec::new();
+    let mut comments = Vec::new();
+    for session in change_sessions(database, &change)? {
+        let new_comments: Vec<ReviewComment> = database.get_review_comments_for_session(&session.id)?
+            .into_iter()
+            .filter(|c| c.updated_at >= imported_at)
+            .collect();
+        if !new_comments.is_empty() {
+            comments.extend(new_comments);
+            sessions.push(session);
+        }
+    }
+
+    let mut writer = BundleWriter::new();
+    writer.add_json(SESSIONS, &sessions)?;
+    writer.add_json(COMMENTS, &comments)?;
+    writer.write(path, BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        kind: BundleKind::Return,
+        created_at: Utc::now().to_rfc3339(),
+        change_id: change.change_id.clone(),
+        subject: change.subject.clone(),
+        instance: None,
+        patch_sets: Vec::new(),
+        entries: Vec::new(),
+    })
+}
+
+/// Import a review bundle for offline review, or merge a return bundle's comments into
+/// the change it was made from. Importing the same bundle again is harmless.
+pub fn import_bundle(database: &Database, storage: &mut FileStorage, path: &Path) -> Result<BundleImport, HyperReviewError> {
+    let bundle = BundleReader::open(path)?;
+    let sessions: Vec<ReviewSession> = bundle.json(SESSIONS)?;
+    let comments: Vec<ReviewComment> = bundle.json(COMMENTS)?;
+    let mut files = 0;
+
+    // The cache directory is built from the change ID and file paths, so check every
+    // one of them before anything is written
+    check_change_id(&bundle.manifest.change_id)?;
+    if bundle.manifest.kind == BundleKind::Review {
+        check_change_id(&bundle.json::<GerritChange>(CHANGE)?.change_id)?;
+        for &number in &bundle.manifest.patch_sets {
+            for file in bundle.json::<Vec<ChangeFile>>(&diffs_path(number))? {
+                check_member_path(&file.file_path)?;
+            }
+            let prefix = content_prefix(number);
+            for file_path in bundle.entries.keys().filter_map(|name| name.strip_prefix(&prefix)) {
+                check_member_path(file_path)?;
+            }
+        }
+    }
+
+    match bundle.manifest.kind {
+        BundleKind::Review => {
+            let mut change: GerritChange = bundle.json(CHANGE)?;
+            if database.get_gerrit_instance(&change.instance_id)?.is_none() {
+                let name = bundle.manifest.instance.as_ref().map_or("Review bundles", |i| i.name.as_str());
+                database.ensure_local_gerrit_instance(&change.instance_id, &format!("{} (bundle)", name))?;
+            }
+            change.metadata.insert(IMPORTED_AT.to_string(), Utc::now().format("%Y-%m-%d %H:%M:%S").to_string());
+            if database.get_gerrit_change(&change.id)?.is_some() {
+                database.update_gerrit_change(&change)?;
+            } else {
+                database.store_gerrit_change(&change)?;
+            }
+
+            for &number in &bundle.manifest.patch_sets {
+                let change_files: Vec<ChangeFile> = bundle.json(&diffs_path(number))?;
+                for file in &change_files {
+                    database.store_change_file(file)?;
+                }
+                files += change_files.len() as u32;
+
+                let prefix = content_prefix(number);
+                for (name, data) in &bundle.entries {
+                    let Some(file_path) = name.strip_prefix(&prefix) else { continue };
+                    let cached = ChangeFile {
+                        file_path: file_path.to_string(),
+                        new_content: Some(String::from_utf8_lossy(data).into_owned()),
+                        ..change_files.iter().find(|f| f.file_path == file_path).cloned()
+                            .unwrap_or_else(|| placeholder_file(&change, number, file_path))
+                    };
+                    storage.store_file(&cached, &change.change_id, number)?;
+                }
+            }
+        }
+        BundleKind::Return => {
+            load_change(database, &bundle.manifest.change_id)?;
+        }
+    }
+
+    // Replacing an existing session would cascade to the comments already on it
+    for session in &sessions {
+        if database.get_review_session(&session.id)?.is_none() {
+            database.store_review_session(session)?;
+        }
+    }
+    // Parents first, so replies never point at a comment that is not stored yet
+    let mut ordered: Vec<&ReviewComment> = comments.iter().collect();
+    ordered.sort_by_key(|c| c.parent_comment_id.is_some());
+    for comment in ordered {
+        database.store_review_comment(comment)?;
+    }
+
+    info!("Imported {:?} bundle for change {}: {} files, {} comments",
+          bundle.manifest.kind, bundle.manifest.change_id, files, comments.len());
+    Ok(BundleImport {
+        kind: bundle.manifest.kind,
+        change_id: bundle.manifest.change_id,
+        files,
+        sessions: sessions.len() as u32,
+        comments: comments.len() as u32,
+    })
+}
+
+/// A change ID names one directory of the file cache, so it must be a single plain component
+fn check_change_id(change_id: &str) -> Result<(), HyperReviewError> {
+    let mut components = Path::new(change_id).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if !change_id.contains(['/', '\\']) => Ok(()),
+        _ => Err(HyperReviewError::validation(
+            format!("Bundle has an invalid change ID: {}", change_id), Some("change_id".to_string()),
+        )),
+    }
+}
+
+/// File paths must stay inside the change's cache directory
+fn check_member_path(file_path: &str) -> Result<(), HyperReviewError> {
+    let path = Path::new(file_path);
+    if file_path.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(HyperReviewError::validation(
+            format!("Bundle has an invalid file path: {}", file_path), Some("path".to_string()),
+        ));
+    }
+    Ok(())
+}
+
+fn placeholder_file(change: &GerritChange, patch_set_number: u32, file_path: &str) -> ChangeFile {
+    ChangeFile {
+        id: uuid::Uuid::new_v4().to_string(),
+        change_id: change.id.clone(),
+        patch_set_number,
+        file_path: file_path.to_string(),
+        change_type: FileChangeType::Modified,
+        old_content: None,
+        new_content: None,
+        diff: FileDiff::default(),
+        file_size: 0,
+        downloaded_at: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file_storage::FileStorageConfig;
+    use crate::services::patch_import::{parse_mbox, store_series};
+    use tempfile::TempDir;
+
+    const PATCH: &str = "\
+From: Jane Doe <jane@example.com>
+Subject: [PATCH] Say hello
+
+Change-Id: Iabc
+---
+diff --git a/hello.txt b/hello.txt
+--- a/hello.txt
++++ b/hello.txt
+@@ -1 +1 @@
+-hello nobody
++hello world
+";
+
+    struct Machine {
+        _dir: TempDir,
+        database: Database,
+        storage: FileStorage,
+    }
+
+    fn machine() -> Machine {
+        let dir = TempDir::new().unwrap();
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let storage = FileStorage::new(FileStorageConfig {
+            base_directory: dir.path().join("cache"),
+            ..FileStorageConfig::default()
+        }).unwrap();
+        Machine { _dir: dir, database, storage }
+    }
+
+    fn comment(session_id: &str, content: &str, timestamp: &str) -> ReviewComment {
+        ReviewComment {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: session_id.to_string(),
+            file_path: "hello.txt".to_string(),
+            line_number: Some(1),
+            content: content.to_string(),
+            comment_type: CommentType::Inline,
+            severity: CommentSeverity::Minor,
+            status: CommentStatus::Draft,
+            parent_comment_id: None,
+            created_at: timestamp.to_string(),
+            updated_at: timestamp.to_string(),
+            mentions: Vec::new(),
+        }
+    }
+
+    fn session(id: &str) -> ReviewSession {
+        ReviewSession {
+            id: id.to_string(),
+            change_id: "Iabc".to_string(),
+            patch_set_number: 1,
+            reviewer_id: "reviewer".to_string(),
+            mode: ReviewMode::Offline,
+            status: ReviewStatus::InProgress,
+            progress: ReviewProgress {
+                total_files: 1,
+                reviewed_files: 0,
+                files_with_comments: 0,
+                pending_files: Vec::new(),
+            },
+            created_at: "2024-01-01 00:00:00".to_string(),
+            updated_at: "2024-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_review_and_return_bundle_roundtrip() {
+        let mut origin = machine();
+        let (_, patches) = parse_mbox(PATCH).unwrap();
+        store_series(&origin.database, "demo", None, None, &patches, None).unwrap();
+        let files = origin.database.get_change_files("Iabc", 1).unwrap();
+        let mut cached = files[0].clone();
+        cached.new_content = Some("hello world\n".to_string());
+        origin.storage.store_file(&cached, "Iabc", 1).unwrap();
+        origin.database.store_review_session(&session("s1")).unwrap();
+        let existing = comment("s1", "Existing remark", "2024-01-01 00:00:00");
+        origin.database.store_review_comment(&existing).unwrap();
+
+        let out = TempDir::new().unwrap();
+        let bundle_path = out.path().join(format!("change.{}", BUNDLE_EXTENSION));
+        let manifest = export_review_bundle(&origin.database, &mut origin.storage, "Iabc", &bundle_path).unwrap();
+        assert_eq!(manifest.patch_sets, vec![1]);
+        assert!(manifest.entries.iter().any(|e| e.path == "patchsets/1/content/hello.txt"));
+
+        // Air-gapped machine: full offline review without the server
+        let mut remote = machine();
+        let imported = import_bundle(&remote.database, &mut remote.storage, &bundle_path).unwrap();
+        assert_eq!((imported.kind, imported.files, imported.comments), (BundleKind::Review, 1, 1));
+        assert!(remote.database.is_change_downloaded_by_gerrit_id("Iabc", 1).unwrap());
+        assert_eq!(remote.storage.get_file("Iabc", 1, "hello.txt").unwrap().as_deref(), Some("hello world\n"));
+        assert!(remote.database.get_all_gerrit_instances().unwrap().is_empty());
+
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let new_comment = comment("s1", "Needs a test", &now);
+        remote.database.store_review_comment(&new_comment).unwrap();
+        let return_path = out.path().join("return.hrbundle");
+        export_return_bundle(&remote.database, "Iabc", &return_path).unwrap();
+
+        // Back at the origin, only the new comment is merged
+        let merged = import_bundle(&origin.database, &mut origin.storage, &return_path).unwrap();
+        assert_eq!((merged.kind, merged.comments), (BundleKind::Return, 1));
+        let comments = origin.database.get_review_comments_for_session("s1").unwrap();
+        assert_eq!(comments.len(), 2);
+        assert!(comments.iter().any(|c| c.content == "Needs a test"));
+
+        assert!(export_return_bundle(&origin.database, "Iabc", &return_path).is_err());
+    }
+
+    #[test]
+    fn test_tampered_bundle_is_rejected() {
+        let mut origin = machine();
+        let (_, patches) = parse_mbox(PATCH).unwrap();
+        store_series(&origin.database, "demo", None, None, &patches, None).unwrap();
+        let out = TempDir::new().unwrap();
+        let bundle_path = out.path().join("change.hrbundle");
+        let manifest = export_review_bundle(&origin.database, &mut origin.storage, "Iabc", &bundle_path).unwrap();
+
+        // Swap change.json for other content but keep the original manifest
+        let original = BundleReader::open(&bundle_path).unwrap();
+        let tampered = out.path().join("tampered.hrbundle");
+        write_archive(&tampered, &manifest, original.entries.into_iter().map(|(name, data)| {
+            if name == CHANGE { (name, b"{}".to_vec()) } else { (name, data) }
+        }));
+
+        let mut remote = machine();
+        let error = import_bundle(&remote.database, &mut remote.storage, &tampered).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch for change.json"), "{}", error);
+    }
+
+    #[test]
+    fn test_bundle_paths_cannot_escape_cache() {
+        let mut origin = machine();
+        let (_, patches) = parse_mbox(PATCH).unwrap();
+        store_series(&origin.database, "demo", None, None, &patches, None).unwrap();
+        let out = TempDir::new().unwrap();
+        let bundle_path = out.path().join("change.hrbundle");
+        let manifest = export_review_bundle(&origin.database, &mut origin.storage, "Iabc", &bundle_path).unwrap();
+        let original = BundleReader::open(&bundle_path).unwrap();
+
+        // A correctly checksummed member whose path climbs out of the cache
+        let escaping = |name: &str| {
+            let mut manifest = manifest.clone();
+            let mut entries = original.entries.clone();
+            entries.insert(name.to_string(), b"owned".to_vec());
+            manifest.entries.push(BundleEntry { path: name.to_string(), sha256: checksum(b"owned"), size: 5 });
+            let path = out.path().join("escaping.hrbundle");
+            write_archive(&path, &manifest, entries);
+            path
+        };
+        // A change ID that is itself a path
+        let renamed = {
+            let mut manifest = manifest.clone();
+            let mut entries = original.entries.clone();
+            let mut change: GerritChange = original.json(CHANGE).unwrap();
+            change.change_id = "../../outside".to_string();
+            let data = serde_json::to_vec(&change).unwrap();
+            let entry = manifest.entries.iter_mut().find(|e| e.path == CHANGE).unwrap();
+            entry.sha256 = checksum(&data);
+            entry.size = data.len() as u64;
+            entries.insert(CHANGE.to_string(), data);
+            let path = out.path().join("renamed.hrbundle");
+            write_archive(&path, &manifest, entries);
+            path
+        };
+
+        for crafted in [escaping("patchsets/1/content/../../../../escape.txt"), escaping("patchsets/1/content//tmp/escape.txt"), renamed] {
+            let mut remote = machine();
+            let error = import_bundle(&remote.database, &mut remote.storage, &crafted).unwrap_err();
+            assert!(error.to_string().contains("invalid"), "{}", error);
+            assert!(remote.database.get_gerrit_change("Iabc").unwrap().is_none());
+            assert!(!remote._dir.path().join("escape.txt").exists());
+        }
+    }
+
+    fn write_archive(path: &Path, manifest: &BundleManifest, entries: impl IntoIterator<Item = (String, Vec<u8>)>) {
+        let encoder = GzEncoder::new(File::create(path).unwrap(), Compression::default());
+        let mut archive = tar::Builder::new(encoder);
+        let members = std::iter::once((MANIFEST.to_string(), serde_json::to_vec(manifest).unwrap())).chain(entries);
+        for (name, data) in members {
+            // Written raw, since `set_path` refuses the escaping names the tests need
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_mode(0o644);
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            archive.append(&header, data.as_slice()).unwrap();
+        }
+        archive.into_inner().unwrap().finish().unwrap();
+    }
+}
@@ -0,0 +1,491 @@
+// Review Reply Email
+// Renders stored review comments as mailing-list style replies to patch emails
+
+use std::collections::HashMap;
+use std::path::Path;
+use base64::Engine;
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::*;
+
+/// Trailer added to the end of a reply
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyTrailer {
+    ReviewedBy,
+    AckedBy,
+}
+
+impl std::fmt::Display for ReplyTrailer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplyTrailer::ReviewedBy => write!(f, "Reviewed-by"),
+            ReplyTrailer::AckedBy => write!(f, "Acked-by"),
+        }
+    }
+}
+
+/// A `Name <email>` address
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Mailbox {
+    pub name: String,
+    pub email: String,
+}
+
+impl Mailbox {
+    /// Parse `Name <email>` or a bare address
+    pub fn parse(value: &str) -> Option<Self> {
+        let (name, email) = match (value.rfind('<'), value.rfind('>')) {
+            (Some(start), Some(end)) if start < end => (value[..start].trim().trim_matches('"'), value[start + 1..end].trim()),
+            _ => ("", value.trim()),
+        };
+        email.contains('@').then(|| Self { name: name.to_string(), email: email.to_string() })
+    }
+
+    /// Address for a mail header, encoding non-ASCII names
+    fn header_value(&self) -> String {
+        if self.name.is_empty() {
+            format!("<{}>", self.email)
+        } else {
+            format!("{} <{}>", encode_header(&self.name), self.email)
+        }
+    }
+}
+
+impl std::fmt::Display for Mailbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "<{}>", self.email)
+        } else {
+            write!(f, "{} <{}>", self.name, self.email)
+        }
+    }
+}
+
+/// A reply to one reviewed patch
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewReply {
+    pub message_id: String,
+    pub in_reply_to: Option<String>,
+    pub from: Mailbox,
+    pub to: Option<Mailbox>,
+    pub subject: String,
+    pub date: String,                  // RFC 2822 timestamp
+    pub body: String,
+}
+
+impl ReviewReply {
+    /// The reply as an RFC 5322 message
+    pub fn to_message(&self) -> String {
+        let mut message = String::new();
+        message.push_str(&format!("From: {}\n", self.from.header_value()));
+        if let Some(to) = &self.to {
+            message.push_str(&format!("To: {}\n", to.header_value()));
+        }
+        message.push_str(&format!("Subject: {}\n", encode_header(&self.subject)));
+        message.push_str(&format!("Date: {}\n", self.date));
+        message.push_str(&format!("Message-ID: <{}>\n", self.message_id));
+        if let Some(parent) = &self.in_reply_to {
+            message.push_str(&format!("In-Reply-To: <{}>\n", parent));
+            message.push_str(&format!("References: <{}>\n", parent));
+        }
+        message.push_str("MIME-Version: 1.0\n");
+        message.push_str("Content-Type: text/plain; charset=UTF-8\n");
+        message.push_str("Content-Transfer-Encoding: 8bit\n\n");
+        message.push_str(&self.body);
+        message
+    }
+}
+
+/// Build the reply for a reviewed change. Inline comments are interleaved with the quoted
+/// diff right after the line they refer to; hunks without comments are snipped.
+/// Resolved and acknowledged comments are left out, and comments imported from
+/// other people (named in `authors`) are attributed to them.
+pub fn build_reply(
+    change: &GerritChange,
+    files: &[ChangeFile],
+    comments: &[ReviewComment],
+    authors: &HashMap<String, String>,
+    from: &Mailbox,
+    trailers: &[ReplyTrailer],
+) -> ReviewReply {
+    let mut comments: Vec<&ReviewComment> = comments.iter()
+        .filter(|c| matches!(c.status, CommentStatus::Draft | CommentStatus::Published))
+        .collect();
+    comments.sort_by(|a, b| a.line_number.cmp(&b.line_number).then(a.created_at.cmp(&b.created_at)));
+
+    let file_paths: Vec<&str> = files.iter().map(|f| f.file_path.as_str()).collect();
+    let (general, on_files): (Vec<&ReviewComment>, Vec<&ReviewComment>) = comments.into_iter()
+        .partition(|c| !file_paths.contains(&c.file_path.as_str()));
+
+    let author = Mailbox {
+        name: change.owner.name.clone(),
+        email: change.owner.email.clone(),
+    };
+    let mut body = Body::default();
+    body.line(&format!("On {}, {} wrote:", change.updated, author));
+
+    let commit_message = change.metadata.get("commit_message")
+        .or_else(|| change.patch_sets.iter().find(|ps| ps.is_current).map(|ps| &ps.commit_message))
+        .unwrap_or(&change.subject);
+    for line in commit_message.lines() {
+        body.quote(line);
+    }
+    for comment in &general {
+        body.comment(&comment_text(comment, authors));
+    }
+
+    let mut quoted_diff = false;
+    for file in files {
+        let file_comments: Vec<&ReviewComment> = on_files.iter()
+            .filter(|c| c.file_path == file.file_path)
+            .copied()
+            .collect();
+        if file_comments.is_empty() {
+            body.snip();
+            continue;
+        }
+        if !quoted_diff {
+            body.quote("---");
+            quoted_diff = true;
+        }
+        quote_file(&mut body, &file.diff.unified_diff, &file_comments, authors);
+    }
+
+    let trailer_lines: Vec<String> = trailers.iter().map(|t| format!("{}: {}", t, from)).collect();
+    if !trailer_lines.is_empty() {
+        body.text(&trailer_lines.join("\n"));
+    }
+
+    let prefix = match (change.metadata.get("series_index"), change.metadata.get("series_total")) {
+        (Some(index), Some(total)) if total != "1" => format!("[PATCH {}/{}] ", index, total),
+        _ if change.metadata.contains_key("message_id") => "[PATCH] ".to_string(),
+        _ => String::new(),
+    };
+
+    ReviewReply {
+        message_id: format!("{}@hyperreview", uuid::Uuid::new_v4()),
+        in_reply_to: change.metadata.get("message_id").cloned(),
+        from: from.clone(),
+        to: author.email.contains('@').then_some(author),
+        subject: format!("Re: {}{}", prefix, change.subject),
+        date: Utc::now().to_rfc2822(),
+        body: body.finish(),
+    }
+}
+
+/// A comment as the reply shows it, naming its author unless that is the sender
+fn comment_text(comment: &ReviewComment, authors: &HashMap<String, String>) -> String {
+    match authors.get(&comment.id) {
+        Some(author) => format!("{} wrote:\n{}", author, comment.content),
+        None => comment.content.clone(),
+    }
+}
+
+/// Quote one file's diff with its comments
+fn quote_file(body: &mut Body, unified_diff: &str, comments: &[&ReviewComment], authors: &HashMap<String, String>) {
+    let hunk_header = Regex::new(r"^@@ -(\d+)(?:,\d+)? \+(\d+)(?:,\d+)? @@").unwrap();
+
+    // Split into the file header and hunks, numbering each line on the new side
+    let mut header: Vec<&str> = Vec::new();
+    let mut hunks: Vec<Vec<(&str, Option<u32>)>> = Vec::new();
+    let mut new_line = 0u32;
+    for line in unified_diff.lines() {
+        if let Some(captures) = hunk_header.captures(line) {
+            new_line = captures[2].parse().unwrap_or(1);
+            hunks.push(vec![(line, None)]);
+        } else if let Some(hunk) = hunks.last_mut() {
+            let number = match line.as_bytes().first() {
+                Some(b'-') | Some(b'\\') => None,
+                _ => {
+                    new_line += 1;
+                    Some(new_line - 1)
+                }
+            };
+            hunk.push((line, number));
+        } else {
+            header.push(line);
+        }
+    }
+
+    let mut by_line: HashMap<u32, Vec<&ReviewComment>> = HashMap::new();
+    let mut unanchored: Vec<&ReviewComment> = Vec::new();
+    for comment in comments {
+        let anchored = comment.line_number.filter(|line| {
+            hunks.iter().flatten().any(|(_, number)| number == &Some(*line))
+        });
+        match anchored {
+            Some(line) => by_line.entry(line).or_default().push(comment),
+            None => unanchored.push(comment),
+        }
+    }
+
+    for line in header.iter().filter(|l| l.starts_with("diff --git ")) {
+        body.quote(line);
+    }
+    for comment in unanchored {
+        match comment.line_number {
+            Some(line) => body.comment(&format!("(line {}) {}", line, comment_text(comment, authors))),
+            None => body.comment(&comment_text(comment, authors)),
+        }
+    }
+
+    for hunk in &hunks {
+        let last_commented = hunk.iter()
+            .rposition(|(_, number)| number.is_some_and(|n| by_line.contains_key(&n)));
+        let Some(last_commented) = last_commented else {
+            body.snip();
+            continue;
+        };
+        for (line, number) in &hunk[..=last_commented] {
+            body.quote(line);
+            if let Some(comments) = number.and_then(|n| by_line.get(&n)) {
+                for comment in comments {
+                    body.comment(&comment_text(comment, authors));
+                }
+            }
+        }
+        if last_commented + 1 < hunk.len() {
+            body.snip();
+        }
+    }
+}
+
+/// Reply body under construction, keeping blank lines between quotes and replies tidy
+#[derive(Default)]
+struct Body {
+    lines: Vec<String>,
+}
+
+impl Body {
+    fn line(&mut self, line: &str) {
+        self.lines.push(line.to_string());
+    }
+
+    fn quote(&mut self, line: &str) {
+        self.lines.push(if line.is_empty() { ">".to_string() } else { format!("> {}", line) });
+    }
+
+    fn comment(&mut self, text: &str) {
+        self.separate();
+        self.lines.extend(text.trim_end().lines().map(str::to_string));
+        self.lines.push(String::new());
+    }
+
+    fn text(&mut self, text: &str) {
+        self.separate();
+        self.lines.extend(text.lines().map(str::to_string));
+        self.lines.push(String::new());
+    }
+
+    fn snip(&mut self) {
+        if self.lines.iter().rev().find(|l| !l.is_empty()).map(String::as_str) != Some("[...]") {
+            self.separate();
+            self.lines.push("[...]".to_string());
+            self.lines.push(String::new());
+        }
+    }
+
+    fn separate(&mut self) {
+        if self.lines.last().is_some_and(|l| !l.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn finish(mut self) -> String {
+        while self.lines.last().is_some_and(|l| l.is_empty() || l == "[...]") {
+            self.lines.pop();
+        }
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        text
+    }
+}
+
+/// RFC 2047 encode a header value that is not plain ASCII
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", base64::engine::general_purpose::STANDARD.encode(value))
+    }
+}
+
+/// Write replies for a mail client: a single message to an `.eml` file, otherwise an mbox
+pub fn write_replies(path: &Path, replies: &[ReviewReply]) -> Result<(), HyperReviewError> {
+    let is_eml = path.extension().and_then(|e| e.to_str()) == Some("eml");
+    if is_eml && replies.len() != 1 {
+        return Err(HyperReviewError::validation(
+            format!("An .eml file holds one message, but there are {} replies", replies.len()),
+            Some("output_path".to_string()),
+        ));
+    }
+
+    let content = if is_eml {
+        replies[0].to_message()
+    } else {
+        let mut mbox = String::new();
+        for reply in replies {
+            mbox.push_str("From hyperreview Mon Sep 17 00:00:00 2001\n");
+            for line in reply.to_message().lines() {
+                // mboxrd quoting, so body lines are not taken for message separators
+                if line.trim_start_matches('>').starts_with("From ") {
+                    mbox.push('>');
+                }
+                mbox.push_str(line);
+                mbox.push('\n');
+            }
+            mbox.push('\n');
+        }
+        mbox
+    };
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::patch_import::{parse_mbox, store_series};
+    use crate::storage::sqlite::Database;
+    use tempfile::TempDir;
+
+    const PATCH: &str = "\
+From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+From: Jane Doe <jane@example.com>
+Date: Tue, 2 Jan 2024 10:00:00 +0100
+Message-ID: <patch-2@example.com>
+Subject: [PATCH 2/3] Greet the world
+
+From now on greet everyone.
+---
+diff --git a/hello.txt b/hello.txt
+index 3b18e51..a042389 100644
+--- a/hello.txt
++++ b/hello.txt
+@@ -1,4 +1,4 @@
+ first
+-hello nobody
++hello world
+ middle
+ last
+@@ -20,2 +20,3 @@
+ tail
++more
+ end
+--
+2.43.0
+";
+
+    fn comment(line: Option<u32>, file_path: &str, content: &str, status: CommentStatus) -> ReviewComment {
+        ReviewComment {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id: "session".to_string(),
+            file_path: file_path.to_string(),
+            line_number: line,
+            content: content.to_string(),
+            comment_type: CommentType::Inline,
+            severity: CommentSeverity::Minor,
+            status,
+            parent_comment_id: None,
+            created_at: "2024-01-03 00:00:00".to_string(),
+            updated_at: "2024-01-03 00:00:00".to_string(),
+            mentions: Vec::new(),
+        }
+    }
+
+    fn imported_change() -> (GerritChange, Vec<ChangeFile>) {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let (_, patches) = parse_mbox(PATCH).unwrap();
+        let imported = store_series(&database, "demo", None, None, &patches, None).unwrap();
+        let change = imported.changes[0].change.clone();
+        let files = database.get_change_files_by_gerrit_id(&change.change_id, 1).unwrap();
+        (change, files)
+    }
+
+    #[test]
+    fn test_reply_interleaves_comments_with_quoted_diff() {
+        let (change, files) = imported_change();
+        let reviewer = Mailbox::parse("Rev Iewer <rev@example.com>").unwrap();
+        let comments = vec![
+            comment(Some(2), "hello.txt", "Should this be \"Hello, world\"?", CommentStatus::Draft),
+            comment(None, "", "Nice cleanup overall.", CommentStatus::Published),
+            comment(Some(3), "hello.txt", "Already fixed.", CommentStatus::Resolved),
+        ];
+
+        let reply = build_reply(&change, &files, &comments, &HashMap::new(), &reviewer, &[ReplyTrailer::ReviewedBy]);
+        assert_eq!(reply.subject, "Re: [PATCH 2/3] Greet the world");
+        assert_eq!(reply.in_reply_to.as_deref(), Some("patch-2@example.com"));
+        assert_eq!(reply.to.as_ref().unwrap().email, "jane@example.com");
+
+        let expected = "\
+On 2024-01-02T10:00:00+01:00, Jane Doe <jane@example.com> wrote:
+> Greet the world
+>
+> From now on greet everyone.
+
+Nice cleanup overall.
+
+> ---
+> diff --git a/hello.txt b/hello.txt
+> @@ -1,4 +1,4 @@
+>  first
+> -hello nobody
+> +hello world
+
+Should this be \"Hello, world\"?
+
+[...]
+
+Reviewed-by: Rev Iewer <rev@example.com>
+";
+        assert_eq!(reply.body, expected);
+
+        let message = reply.to_message();
+        assert!(message.contains("In-Reply-To: <patch-2@example.com>\nReferences: <patch-2@example.com>\n"));
+        assert!(message.contains("\n\nOn 2024-01-02"));
+    }
+
+    #[test]
+    fn test_unanchored_comments_and_acks() {
+        let (change, files) = imported_change();
+        let reviewer = Mailbox { name: "Jörg".to_string(), email: "joerg@example.com".to_string() };
+        let comments = vec![
+            comment(Some(99), "hello.txt", "Where is this?", CommentStatus::Draft),
+            comment(Some(21), "hello.txt", "Why more?", CommentStatus::Draft),
+        ];
+
+        let authors = HashMap::from([(comments[1].id.clone(), "Ann".to_string())]);
+
+        let reply = build_reply(&change, &files, &comments, &authors, &reviewer, &[ReplyTrailer::AckedBy]);
+        assert!(reply.body.contains("> diff --git a/hello.txt b/hello.txt\n\n(line 99) Where is this?\n\n[...]\n\n> @@ -20,2 +20,3 @@\n>  tail\n> +more\n\nAnn wrote:\nWhy more?\n"));
+        assert!(reply.body.ends_with("Acked-by: Jörg <joerg@example.com>\n"));
+        assert!(reply.to_message().starts_with("From: =?UTF-8?B?SsO2cmc=?= <joerg@example.com>\n"));
+    }
+
+    #[test]
+    fn test_write_eml_and_mbox() {
+        let (change, files) = imported_change();
+        let reviewer = Mailbox::parse("rev@example.com").unwrap();
+        let comments = vec![comment(None, "", "From my side this is fine.", CommentStatus::Draft)];
+        let reply = build_reply(&change, &files, &comments, &HashMap::new(), &reviewer, &[ReplyTrailer::ReviewedBy]);
+        let dir = TempDir::new().unwrap();
+
+        let eml = dir.path().join("reply.eml");
+        write_replies(&eml, std::slice::from_ref(&reply)).unwrap();
+        assert!(std::fs::read_to_string(&eml).unwrap().starts_with("From: <rev@example.com>\n"));
+        assert!(write_replies(&eml, &[reply.clone(), reply.clone()]).is_err());
+
+        let mbox = dir.path().join("replies.mbox");
+        write_replies(&mbox, &[reply.clone(), reply]).unwrap();
+        let content = std::fs::read_to_string(&mbox).unwrap();
+        assert_eq!(content.lines().filter(|l| l.starts_with("From hyperreview ")).count(), 2);
+        // Body lines starting with "From " are escaped, quoted ones need not be
+        assert!(content.contains("\n>From my side this is fine.\n"));
+        assert!(content.contains("\n> From now on greet everyone.\n"));
+    }
+}
@@ -0,0 +1,739 @@
+// Review Reports
+// Markdown and self-contained HTML summaries of a review session or local task
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::engine::AnalysisEngine;
+use crate::models::{ChecklistItem, DiffLine as AnalyzedLine, DiffLineType as AnalyzedLineType};
+use crate::models::gerrit::*;
+use crate::models::task::{LocalTask, TaskSeverity, TaskStatus};
+use crate::services::diff_engine::{DiffConfig, DiffEngine};
+
+/// Lines of code shown above and below a commented line
+pub const CONTEXT_LINES: u32 = 2;
+
+/// Output format of a report
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// Review results that are kept by the frontend rather than stored with the review
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReportExtras {
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default)]
+    pub votes: BTreeMap<String, i32>,  // Final label votes, e.g. Code-Review
+}
+
+/// A line of code around a comment
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ContextLine {
+    pub number: u32,
+    pub text: String,
+    pub commented: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportReply {
+    pub author: String,
+    pub content: String,
+}
+
+/// A comment thread with the code it is about
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportComment {
+    pub file_path: String,
+    pub line: Option<u32>,
+    pub author: String,
+    pub label: String,                 // Severity, e.g. "blocking"
+    pub status: String,
+    pub content: String,
+    pub replies: Vec<ReportReply>,
+    pub context: Vec<ContextLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportFile {
+    pub path: String,
+    pub status: String,
+    pub comments: u32,
+}
+
+/// An issue found by static analysis in the reviewed code
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportFinding {
+    pub file_path: String,
+    pub line: u32,
+    pub severity: String,
+    pub message: String,
+}
+
+/// Everything a report shows, independent of its format
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewReport {
+    pub title: String,
+    pub metadata: Vec<(String, String)>,
+    pub description: Option<String>,   // Commit message
+    pub files: Vec<ReportFile>,
+    pub comments: Vec<ReportComment>,
+    pub checklist: Vec<ChecklistItem>,
+    pub findings: Vec<ReportFinding>,
+    pub votes: BTreeMap<String, i32>,
+    pub generated_at: String,
+}
+
+impl ReviewReport {
+    /// Report of a Gerrit review session. Findings are for the lines the change adds.
+    /// `authors` names who wrote imported comments; the rest are the reviewer's.
+    pub fn for_session(
+        session: &ReviewSession,
+        change: Option<&GerritChange>,
+        file_reviews: &[FileReview],
+        files: &[ChangeFile],
+        comments: &[ReviewComment],
+        authors: &HashMap<String, String>,
+        extras: ReportExtras,
+    ) -> Self {
+        let mut metadata = Vec::new();
+        if let Some(change) = change {
+            metadata.push(("Change".to_string(), format!("{} ({})", change.change_id, humanize(&change.status.to_string()))));
+            metadata.push(("Project".to_string(), format!("{} @ {}", change.project, change.branch)));
+            metadata.push(("Owner".to_string(), format!("{} <{}>", change.owner.name, change.owner.email)));
+            metadata.push(("Size".to_string(), format!("+{} / -{}", change.insertions, change.deletions)));
+        }
+        metadata.push(("Patch set".to_string(), session.patch_set_number.to_string()));
+        metadata.push(("Reviewer".to_string(), session.reviewer_id.clone()));
+        metadata.push(("Review status".to_string(), humanize(&session.status.to_string())));
+
+        let contents: HashMap<&str, &str> = files.iter()
+            .filter_map(|f| f.new_content.as_deref().map(|content| (f.file_path.as_str(), content)))
+            .collect();
+
+        // Threads: replies are grouped under the comment that started them
+        let by_id: HashMap<&str, &ReviewComment> = comments.iter().map(|c| (c.id.as_str(), c)).collect();
+        let root_of = |comment: &ReviewComment| {
+            let mut root = comment;
+            for _ in 0..comments.len() {
+                match root.parent_comment_id.as_deref().and_then(|id| by_id.get(id)) {
+                    Some(parent) => root = *parent,
+                    None => break,
+                }
+            }
+            root.id.clone()
+        };
+        let mut sorted: Vec<&ReviewComment> = comments.iter().collect();
+        sorted.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+        let author_of = |comment: &ReviewComment| authors.get(&comment.id).cloned()
+            .unwrap_or_else(|| session.reviewer_id.clone());
+
+        let mut report_comments: Vec<ReportComment> = Vec::new();
+        let mut thread_index: HashMap<String, usize> = HashMap::new();
+        for comment in sorted {
+            let root = root_of(comment);
+            if let Some(&index) = thread_index.get(&root) {
+                report_comments[index].replies.push(ReportReply {
+                    author: author_of(comment),
+                    content: comment.content.clone(),
+                });
+                continue;
+            }
+            thread_index.insert(root, report_comments.len());
+            report_comments.push(ReportComment {
+                file_path: comment.file_path.clone(),
+                line: comment.line_number,
+                author: author_of(comment),
+                label: comment.severity.to_string(),
+                status: humanize(&comment.status.to_string()),
+                content: comment.content.clone(),
+                replies: Vec::new(),
+                context: match (contents.get(comment.file_path.as_str()), comment.line_number) {
+                    (Some(content), Some(line)) => code_context(content, line),
+                    _ => Vec::new(),
+                },
+            });
+        }
+        sort_comments(&mut report_comments);
+
+        let mut report_files: Vec<ReportFile> = files.iter()
+            .map(|file| ReportFile {
+                path: file.file_path.clone(),
+                status: file_reviews.iter()
+                    .find(|r| r.file_path == file.file_path)
+                    .map(|r| humanize(&r.review_status.to_string()))
+                    .unwrap_or_else(|| "pending".to_string()),
+                comments: 0,
+            })
+            .collect();
+        for review in file_reviews.iter().filter(|r| !files.iter().any(|f| f.file_path == r.file_path)) {
+            report_files.push(ReportFile {
+                path: review.file_path.clone(),
+                status: humanize(&review.review_status.to_string()),
+                comments: 0,
+            });
+        }
+        count_comments(&mut report_files, &report_comments);
+
+        let engine = AnalysisEngine::new();
+        let findings = files.iter()
+            .flat_map(|file| analyze_lines(&engine, &file.file_path, added_lines(file)))
+            .collect();
+
+        Self {
+            title: change.map(|c| c.subject.clone())
+                .unwrap_or_else(|| format!("Review session {}", session.id)),
+            metadata,
+            description: change.and_then(|c| c.metadata.get("commit_message").cloned()),
+            files: report_files,
+            comments: report_comments,
+            checklist: extras.checklist,
+            findings,
+            votes: extras.votes,
+            generated_at: Utc::now().format("%Y-%m-%d %H:%M UTC").to_string(),
+        }
+    }
+
+    /// Report of a local task. Code is read from the task's working tree; findings are
+    /// for each item's line range, or the whole file when it has none.
+    pub fn for_task(task: &LocalTask, extras: ReportExtras) -> Self {
+        let status = match task.status {
+            TaskStatus::InProgress => "in progress",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Archived => "archived",
+        };
+        let metadata = vec![
+            ("Repository".to_string(), task.repo_path.clone()),
+            ("Base".to_string(), task.base_ref.clone()),
+            ("Status".to_string(), status.to_string()),
+            ("Progress".to_string(), format!("{} of {} items reviewed", task.completed_items, task.total_items)),
+            ("Updated".to_string(), task.update_time.format("%Y-%m-%d %H:%M UTC").to_string()),
+        ];
+
+        let engine = AnalysisEngine::new();
+        let mut files = Vec::new();
+        let mut comments = Vec::new();
+        let mut findings = Vec::new();
+        for item in &task.items {
+            let content = std::fs::read_to_string(Path::new(&task.repo_path).join(&item.file)).ok();
+            let range = item.line_range.as_ref();
+            let start = range.and_then(|r| r.start);
+            let label = match &item.severity {
+                Some(TaskSeverity::Error) => "error",
+                Some(TaskSeverity::Warning) => "warning",
+                Some(TaskSeverity::Question) => "question",
+                Some(TaskSeverity::Ok) => "ok",
+                None => "",
+            };
+            let context = |line: Option<u32>| match (&content, line) {
+                (Some(content), Some(line)) => code_context(content, line),
+                _ => Vec::new(),
+            };
+
+            files.push(ReportFile {
+                path: item.file.clone(),
+                status: if item.reviewed { "reviewed" } else { "pending" }.to_string(),
+                comments: 0,
+            });
+
+            if let Some(preset) = item.preset_comment.as_ref().filter(|c| !c.trim().is_empty()) {
+                comments.push(ReportComment {
+                    file_path: item.file.clone(),
+                    line: start,
+                    author: String::new(),
+                    label: label.to_string(),
+                    status: String::new(),
+                    content: preset.clone(),
+                    replies: Vec::new(),
+                    context: context(start),
+                });
+            }
+            for comment in &item.comments {
+                let line = comment.line_number.or(start);
+                comments.push(ReportComment {
+                    file_path: item.file.clone(),
+                    line,
+                    author: comment.author.clone(),
+                    label: label.to_string(),
+                    status: String::new(),
+                    content: comment.content.clone(),
+                    replies: Vec::new(),
+                    context: context(line),
+                });
+            }
+
+            if let Some(content) = &content {
+                let first = start.unwrap_or(1);
+                let last = range.and_then(|r| r.end).unwrap_or(u32::MAX);
+                let lines = numbered_lines(content)
+                    .filter(|(number, _)| (first..=last).contains(number))
+                    .collect();
+                findings.extend(analyze_lines(&engine, &item.file, lines));
+            }
+        }
+        sort_comments(&mut comments);
+        count_comments(&mut files, &comments);
+
+        Self {
+            title: task.name.clone(),
+            metadata,
+            description: None,
+            files,
+            comments,
+            checklist: extras.checklist,
+            findings,
+            votes: extras.votes,
+            generated_at: Utc::now().format("%Y-%m-%d %H:%M UTC").to_string(),
+        }
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    /// CommonMark without tables or raw HTML, so it renders on Gerrit as well as GitLab
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Review: {}\n\n", escape_markdown(&self.title));
+        for (label, value) in &self.metadata {
+            md.push_str(&format!("- **{}:** {}\n", label, escape_markdown(value)));
+        }
+
+        if let Some(description) = &self.description {
+            md.push_str("\n## Description\n\n");
+            for line in description.trim_end().lines() {
+                md.push_str(&format!("> {}\n", line).replace("> \n", ">\n"));
+            }
+        }
+
+        md.push_str(&format!("\n## Files ({})\n\n", self.files.len()));
+        for file in &self.files {
+            md.push_str(&format!("- {} — {}{}\n", code_span(&file.path), file.status, comment_count(file.comments)));
+        }
+
+        md.push_str(&format!("\n## Comments ({})\n", self.comments.len()));
+        if self.comments.is_empty() {
+            md.push_str("\nNo comments.\n");
+        }
+        for comment in &self.comments {
+            md.push_str(&format!("\n### {}\n\n", markdown_location(comment)));
+            let byline: Vec<&str> = [comment.author.as_str(), comment.status.as_str()].into_iter()
+                .filter(|s| !s.is_empty())
+                .collect();
+            if !byline.is_empty() {
+                md.push_str(&format!("*{}*\n\n", escape_markdown(&byline.join(" · "))));
+            }
+            if !comment.context.is_empty() {
+                let block: Vec<String> = comment.context.iter().map(context_text).collect();
+                let fence = code_fence(&block);
+                md.push_str(&format!("{}\n{}\n{}\n\n", fence, block.join("\n"), fence));
+            }
+            md.push_str(comment.content.trim_end());
+            md.push('\n');
+            for reply in &comment.replies {
+                md.push_str(&format!("\n> **{}:**\n", escape_markdown(&reply.author)));
+                for line in reply.content.trim_end().lines() {
+                    md.push_str(&format!("> {}\n", line).replace("> \n", ">\n"));
+                }
+            }
+        }
+
+        if !self.checklist.is_empty() {
+            md.push_str("\n## Checklist\n\n");
+            for item in &self.checklist {
+                md.push_str(&format!(
+                    "- [{}] {} ({})\n",
+                    if item.is_checked { "x" } else { " " },
+                    escape_markdown(&item.description),
+                    format!("{:?}", item.category).to_lowercase()
+                ));
+            }
+        }
+
+        if !self.findings.is_empty() {
+            md.push_str(&format!("\n## Analysis findings ({})\n\n", self.findings.len()));
+            for finding in &self.findings {
+                md.push_str(&format!(
+                    "- {} **{}:** {}\n",
+                    code_span(&format!("{}:{}", finding.file_path, finding.line)),
+                    finding.severity,
+                    escape_markdown(&finding.message)
+                ));
+            }
+        }
+
+        if !self.votes.is_empty() {
+            md.push_str("\n## Votes\n\n");
+            for (label, score) in &self.votes {
+                md.push_str(&format!("- **{}:** {}\n", escape_markdown(label), format_score(*score)));
+            }
+        }
+
+        md.push_str(&format!("\n---\n\n*Generated by HyperReview on {}*\n", self.generated_at));
+        md
+    }
+
+    /// A single HTML page with inline styles and no external resources
+    pub fn to_html(&self) -> String {
+        let mut body = format!("<h1>Review: {}</h1>\n<dl class=\"meta\">\n", escape_html(&self.title));
+        for (label, value) in &self.metadata {
+            body.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(label), escape_html(value)));
+        }
+        body.push_str("</dl>\n");
+
+        if let Some(description) = &self.description {
+            body.push_str(&format!("<h2>Description</h2>\n<pre class=\"description\">{}</pre>\n", escape_html(description.trim_end())));
+        }
+
+        body.push_str(&format!("<h2>Files ({})</h2>\n<ul class=\"files\">\n", self.files.len()));
+        for file in &self.files {
+            body.push_str(&format!(
+                "<li><code>{}</code> <span class=\"status\">{}</span>{}</li>\n",
+                escape_html(&file.path), escape_html(&file.status), escape_html(&comment_count(file.comments))
+            ));
+        }
+        body.push_str("</ul>\n");
+
+        body.push_str(&format!("<h2>Comments ({})</h2>\n", self.comments.len()));
+        if self.comments.is_empty() {
+            body.push_str("<p>No comments.</p>\n");
+        }
+        for comment in &self.comments {
+            body.push_str("<section class=\"comment\">\n");
+            body.push_str(&format!("<h3><code>{}</code>", escape_html(&comment.file_path)));
+            if let Some(line) = comment.line {
+                body.push_str(&format!(" line {}", line));
+            }
+            if !comment.label.is_empty() {
+                body.push_str(&format!(" <span class=\"label label-{0}\">{0}</span>", escape_html(&comment.label)));
+            }
+            body.push_str("</h3>\n");
+            let byline: Vec<&str> = [comment.author.as_str(), comment.status.as_str()].into_iter()
+                .filter(|s| !s.is_empty())
+                .collect();
+            if !byline.is_empty() {
+                body.push_str(&format!("<p class=\"byline\">{}</p>\n", escape_html(&byline.join(" · "))));
+            }
+            if !comment.context.is_empty() {
+                body.push_str("<pre class=\"context\">");
+                for line in &comment.context {
+                    let text = escape_html(&context_text(line));
+                    if line.commented {
+                        body.push_str(&format!("<mark>{}</mark>\n", text));
+                    } else {
+                        body.push_str(&format!("{}\n", text));
+                    }
+                }
+                body.push_str("</pre>\n");
+            }
+            body.push_str(&format!("<div class=\"body\">{}</div>\n", escape_html(comment.content.trim_end())));
+            for reply in &comment.replies {
+                body.push_str(&format!(
+                    "<blockquote><strong>{}:</strong> {}</blockquote>\n",
+                    escape_html(&reply.author), escape_html(reply.content.trim_end())
+                ));
+            }
+            body.push_str("</section>\n");
+        }
+
+        if !self.checklist.is_empty() {
+            body.push_str("<h2>Checklist</h2>\n<ul class=\"checklist\">\n");
+            for item in &self.checklist {
+                body.push_str(&format!(
+                    "<li>{} {} <span class=\"status\">{}</span></li>\n",
+                    if item.is_checked { "&#9745;" } else { "&#9744;" },
+                    escape_html(&item.description),
+                    format!("{:?}", item.category).to_lowercase()
+                ));
+            }
+            body.push_str("</ul>\n");
+        }
+
+        if !self.findings.is_empty() {
+            body.push_str(&format!("<h2>Analysis findings ({})</h2>\n<ul class=\"findings\">\n", self.findings.len()));
+            for finding in &self.findings {
+                body.push_str(&format!(
+                    "<li><code>{}:{}</code> <span class=\"label label-{2}\">{2}</span> {3}</li>\n",
+                    escape_html(&finding.file_path), finding.line, escape_html(&finding.severity), escape_html(&finding.message)
+                ));
+            }
+            body.push_str("</ul>\n");
+        }
+
+        if !self.votes.is_empty() {
+            body.push_str("<h2>Votes</h2>\n<dl class=\"votes\">\n");
+            for (label, score) in &self.votes {
+                body.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(label), format_score(*score)));
+            }
+            body.push_str("</dl>\n");
+        }
+
+        body.push_str(&format!("<footer>Generated by HyperReview on {}</footer>\n", escape_html(&self.generated_at)));
+
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Review: {}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape_html(&self.title), REPORT_CSS, body
+        )
+    }
+}
+
+const REPORT_CSS: &str = "
+body { font: 14px/1.5 -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; max-width: 960px; margin: 2em auto; color: #1f2328; }
+h1, h2 { border-bottom: 1px solid #d0d7de; padding-bottom: .3em; }
+dl.meta, dl.votes { display: grid; grid-template-columns: max-content auto; gap: .2em 1em; }
+dt { font-weight: 600; }
+dd { margin: 0; }
+pre { background: #f6f8fa; padding: .8em; overflow-x: auto; border-radius: 6px; }
+pre mark { background: #fff8c5; display: inline-block; width: 100%; }
+.comment { border: 1px solid #d0d7de; border-radius: 6px; padding: 0 1em 1em; margin: 1em 0; }
+.byline, .status, footer { color: #656d76; }
+.body { white-space: pre-wrap; }
+blockquote { border-left: 3px solid #d0d7de; margin: .5em 0; padding-left: 1em; white-space: pre-wrap; }
+.label { border-radius: 1em; padding: 0 .6em; font-size: 12px; background: #ddf4ff; }
+.label-blocking, .label-error { background: #ffebe9; }
+.label-major, .label-warning { background: #fff8c5; }
+footer { margin-top: 2em; font-size: 12px; }
+";
+
+fn humanize(value: &str) -> String {
+    value.replace('_', " ")
+}
+
+fn numbered_lines(content: &str) -> impl Iterator<Item = (u32, String)> + '_ {
+    content.lines().enumerate().map(|(i, line)| (i as u32 + 1, line.to_string()))
+}
+
+/// The commented line (1-based) with `CONTEXT_LINES` around it
+fn code_context(content: &str, line: u32) -> Vec<ContextLine> {
+    let first = line.saturating_sub(CONTEXT_LINES).max(1);
+    numbered_lines(content)
+        .filter(|(number, _)| (first..=line.saturating_add(CONTEXT_LINES)).contains(number))
+        .map(|(number, text)| ContextLine { number, text, commented: number == line })
+        .collect()
+}
+
+/// Lines a change adds to a file; all of them for a new file
+fn added_lines(file: &ChangeFile) -> Vec<(u32, String)> {
+    let Some(new_content) = &file.new_content else {
+        return Vec::new();
+    };
+    let Some(old_content) = &file.old_content else {
+        return numbered_lines(new_content).collect();
+    };
+
+    DiffEngine::new(DiffConfig::default())
+        .diff_contents(&file.file_path, old_content, new_content)
+        .map(|diff| diff.hunks.iter()
+            .flat_map(|hunk| &hunk.lines)
+            .filter(|line| matches!(line.line_type, DiffLineType::Added))
+            .filter_map(|line| Some((line.new_line_number?, line.content.trim_end_matches('\n').to_string())))
+            .collect())
+        .unwrap_or_default()
+}
+
+fn analyze_lines(engine: &AnalysisEngine, file_path: &str, lines: Vec<(u32, String)>) -> Vec<ReportFinding> {
+    let mut analyzed: Vec<AnalyzedLine> = lines.into_iter()
+        .map(|(number, content)| AnalyzedLine {
+            old_line_number: None,
+            new_line_number: Some(number),
+            content,
+            line_type: AnalyzedLineType::Added,
+            severity: None,
+            message: None,
+            hunk_header: None,
+        })
+        .collect();
+    if engine.analyze_diff_lines(&mut analyzed, file_path).is_err() {
+        return Vec::new();
+    }
+
+    analyzed.into_iter()
+        .filter_map(|line| Some(ReportFinding {
+            file_path: file_path.to_string(),
+            line: line.new_line_number?,
+            severity: format!("{:?}", line.severity?).to_lowercase(),
+            message: line.message?,
+        }))
+        .collect()
+}
+
+fn sort_comments(comments: &mut [ReportComment]) {
+    comments.sort_by(|a, b| a.file_path.cmp(&b.file_path).then(a.line.cmp(&b.line)));
+}
+
+fn count_comments(files: &mut [ReportFile], comments: &[ReportComment]) {
+    for file in files {
+        file.comments = comments.iter().filter(|c| c.file_path == file.path).count() as u32;
+    }
+}
+
+fn comment_count(count: u32) -> String {
+    match count {
+        0 => String::new(),
+        1 => ", 1 comment".to_string(),
+        n => format!(", {} comments", n),
+    }
+}
+
+fn format_score(score: i32) -> String {
+    if score > 0 { format!("+{}", score) } else { score.to_string() }
+}
+
+fn context_text(line: &ContextLine) -> String {
+    format!("{} {:>4} | {}", if line.commented { ">" } else { " " }, line.number, line.text)
+}
+
+fn markdown_location(comment: &ReportComment) -> String {
+    let mut location = code_span(&comment.file_path);
+    if let Some(line) = comment.line {
+        location.push_str(&format!(" line {}", line));
+    }
+    if !comment.label.is_empty() {
+        location.push_str(&format!(" — {}", comment.label));
+    }
+    location
+}
+
+/// Backslash-escape characters with inline Markdown meaning
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Inline code that survives backticks in the text
+fn code_span(text: &str) -> String {
+    let ticks = "`".repeat(longest_backtick_run(text) + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", ticks, text, ticks)
+    } else {
+        format!("{}{}{}", ticks, text, ticks)
+    }
+}
+
+/// A code fence longer than any backtick run in the block
+fn code_fence(lines: &[String]) -> String {
+    let longest = lines.iter().map(|l| longest_backtick_run(l)).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: &str, line: u32, content: &str, parent: Option<&str>, created_at: &str) -> ReviewComment {
+        ReviewComment {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            file_path: "src/pool.rs".to_string(),
+            line_number: Some(line),
+            content: content.to_string(),
+            comment_type: CommentType::Inline,
+            severity: if parent.is_some() { CommentSeverity::Minor } else { CommentSeverity::Blocking },
+            status: CommentStatus::Draft,
+            parent_comment_id: parent.map(str::to_string),
+            created_at: created_at.to_string(),
+            updated_at: created_at.to_string(),
+            mentions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_session_report() {
+        let session = ReviewSession {
+            id: "s1".to_string(),
+            change_id: "c1".to_string(),
+            patch_set_number: 2,
+            reviewer_id: "alice".to_string(),
+            mode: ReviewMode::Online,
+            status: ReviewStatus::InProgress,
+            progress: ReviewProgress {
+                total_files: 1,
+                reviewed_files: 1,
+                files_with_comments: 1,
+                pending_files: Vec::new(),
+            },
+            created_at: "2025-01-05 12:00:00".to_string(),
+            updated_at: "2025-01-05 12:00:00".to_string(),
+        };
+        let file = ChangeFile {
+            id: "f1".to_string(),
+            change_id: "c1".to_string(),
+            patch_set_number: 2,
+            file_path: "src/pool.rs".to_string(),
+            change_type: FileChangeType::Modified,
+            old_content: Some("fn get() {\n    pool.take()\n}\n".to_string()),
+            new_content: Some("fn get() {\n    let conn = pool.take().unwrap();\n    conn\n}\n".to_string()),
+            diff: FileDiff::default(),
+            file_size: 0,
+            downloaded_at: String::new(),
+        };
+        let review = FileReview {
+            id: "r1".to_string(),
+            session_id: "s1".to_string(),
+            file_path: "src/pool.rs".to_string(),
+            review_status: FileReviewStatus::NeedsWork,
+            last_reviewed: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let comments = vec![
+            comment("c2", 2, "Fair, but `take` can't fail here", Some("c1"), "2025-01-05 12:05:00"),
+            comment("c1", 2, "Panics when the pool is empty", None, "2025-01-05 12:00:00"),
+        ];
+        let extras = ReportExtras {
+            checklist: Vec::new(),
+            votes: BTreeMap::from([("Code-Review".to_string(), -1)]),
+        };
+
+        let authors = HashMap::from([("c2".to_string(), "bob".to_string())]);
+        let report = ReviewReport::for_session(&session, None, &[review], &[file], &comments, &authors, extras);
+        assert_eq!(report.comments.len(), 1);
+        assert_eq!(report.comments[0].replies.len(), 1);
+        assert_eq!(report.files[0].status, "needs work");
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].line, 2);
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("- `src/pool.rs` — needs work, 1 comment\n"));
+        assert!(markdown.contains("### `src/pool.rs` line 2 — blocking\n\n*alice · draft*\n\n```\n     1 | fn get() {\n>    2 |     let conn = pool.take().unwrap();\n"));
+        assert!(markdown.contains("> **bob:**\n> Fair, but `take` can't fail here\n"));
+        assert!(markdown.contains("- **Code-Review:** -1\n"));
+
+        let html = report.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<mark>&gt;    2 |     let conn = pool.take().unwrap();</mark>"));
+        assert!(!html.contains("<link") && !html.contains("<script"));
+    }
+
+    #[test]
+    fn test_markdown_escaping() {
+        assert_eq!(escape_markdown("fix *all* the [bugs]"), "fix \\*all\\* the \\[bugs\\]");
+        assert_eq!(code_span("a`b"), "``a`b``");
+        assert_eq!(code_fence(&["let s = \"```\";".to_string()]), "````");
+    }
+}
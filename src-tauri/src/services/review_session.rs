@@ -9,17 +9,28 @@ use uuid::Uuid;
 use chrono::Utc;
 
 use crate::models::gerrit::*;
+use crate::models::QualityGate;
+use crate::remote::quality_gates::failing_gates;
+use crate::services::comment_engine::{CommentEngine, CommentEngineConfig};
+use crate::storage::draft_journal::{DraftJournal, JournaledDraft};
 use crate::storage::sqlite::Database;
 use crate::errors::HyperReviewError;
 
 /// Review Session Manager
 pub struct ReviewSessionManager {
     database: Arc<Database>,
+    draft_journal: Option<Arc<DraftJournal>>,
 }
 
 impl ReviewSessionManager {
     pub fn new(database: Arc<Database>) -> Self {
-        Self { database }
+        Self { database, draft_journal: None }
+    }
+
+    /// Offer the unsaved drafts of this journal when recovering sessions
+    pub fn with_draft_journal(mut self, draft_journal: Arc<DraftJournal>) -> Self {
+        self.draft_journal = Some(draft_journal);
+        self
     }
 
     /// Create a new review session
@@ -187,27 +198,15 @@ impl ReviewSessionManager {
         Ok(())
     }
 
-    /// Mark session as ready for submission
-    pub async fn mark_ready_for_submission(&self, session_id: &str) -> Result<ReviewSession, HyperReviewError> {
-        info!("Marking session {} as ready for submission", session_id);
-
-        let mut session = self.get_session(session_id).await?
-            .ok_or_else(|| HyperReviewError::other("Session not found".to_string()))?;
-
-        // Validate that session can be submitted
-        if session.progress.reviewed_files == 0 {
-            return Err(HyperReviewError::other(
-                "Cannot submit review without reviewing any files".to_string()
-            ));
-        }
-
-        session.status = ReviewStatus::ReadyForSubmission;
-        session.updated_at = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-
-        self.database.store_review_session(&session)?;
+    /// Commit and project the session reviews, for checking quality gates
+    pub fn session_commit(&self, session_id: &str) -> Result<(String, String), HyperReviewError> {
+        session_commit(&self.database, session_id)
+    }
 
-        info!("Session {} is now ready for submission", session_id);
-        Ok(session)
+    /// Mark session as ready for submission. Blocked while any of `gates` is failing
+    /// or a comment with a blocking severity is unresolved.
+    pub async fn mark_ready_for_submission(&self, session_id: &str, gates: &[QualityGate]) -> Result<ReviewSession, HyperReviewError> {
+        mark_ready_for_submission(&self.database, session_id, gates)
     }
 
     /// Recover session state (for session persistence)
@@ -223,15 +222,23 @@ impl ReviewSessionManager {
         // Get comments for this session
         let comments = self.database.get_review_comments_for_session(session_id)?;
 
+        // Comments that were being typed when the app went down
+        let unsaved_drafts = match &self.draft_journal {
+            Some(journal) => journal.unsaved_drafts_for_session(session_id)?,
+            None => Vec::new(),
+        };
+
         let recovery_info = SessionRecoveryInfo {
             session: session.clone(),
             file_reviews,
             comments,
             last_active_file: self.get_last_active_file(&session).await?,
+            unsaved_drafts,
         };
 
-        info!("Successfully recovered session {} with {} file reviews and {} comments", 
-              session_id, recovery_info.file_reviews.len(), recovery_info.comments.len());
+        info!("Successfully recovered session {} with {} file reviews, {} comments and {} unsaved drafts",
+              session_id, recovery_info.file_reviews.len(), recovery_info.comments.len(),
+              recovery_info.unsaved_drafts.len());
 
         Ok(recovery_info)
     }
@@ -302,6 +309,65 @@ impl ReviewSessionManager {
     }
 }
 
+/// Commit and project the session reviews, for checking quality gates
+pub fn session_commit(database: &Database, session_id: &str) -> Result<(String, String), HyperReviewError> {
+    let session = database.get_review_session(session_id)?
+        .ok_or_else(|| HyperReviewError::other("Session not found".to_string()))?;
+    let change = database.get_gerrit_change(&session.change_id)?
+        .ok_or_else(|| HyperReviewError::other(format!("Change not found: {}", session.change_id)))?;
+
+    let revision = change.patch_sets.iter()
+        .find(|ps| ps.number == session.patch_set_number)
+        .map(|ps| ps.revision.clone())
+        .unwrap_or(change.current_revision);
+    Ok((revision, change.project))
+}
+
+/// Mark session as ready for submission. Blocked while any of `gates` is failing
+/// or a comment with a blocking severity is unresolved.
+pub fn mark_ready_for_submission(database: &Database, session_id: &str, gates: &[QualityGate]) -> Result<ReviewSession, HyperReviewError> {
+    info!("Marking session {} as ready for submission", session_id);
+
+    let mut session = database.get_review_session(session_id)?
+        .ok_or_else(|| HyperReviewError::other("Session not found".to_string()))?;
+
+    // Validate that session can be submitted
+    if session.progress.reviewed_files == 0 {
+        return Err(HyperReviewError::other(
+            "Cannot submit review without reviewing any files".to_string()
+        ));
+    }
+
+    let failing: Vec<&str> = failing_gates(gates).iter().map(|g| g.name.as_str()).collect();
+    if !failing.is_empty() {
+        return Err(HyperReviewError::other(format!(
+            "Quality gates failing: {}", failing.join(", ")
+        )));
+    }
+
+    let blocking = CommentEngine::new(CommentEngineConfig::default())
+        .get_blocking_comments(session_id, database)?;
+    if !blocking.is_empty() {
+        let locations: Vec<String> = blocking.iter()
+            .map(|c| match c.line_number {
+                Some(line) => format!("{}:{}", c.file_path, line),
+                None => c.file_path.clone(),
+            })
+            .collect();
+        return Err(HyperReviewError::other(format!(
+            "Unresolved blocking comments: {}", locations.join(", ")
+        )));
+    }
+
+    session.status = ReviewStatus::ReadyForSubmission;
+    session.updated_at = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    database.store_review_session(&session)?;
+
+    info!("Session {} is now ready for submission", session_id);
+    Ok(session)
+}
+
 /// Session recovery information
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SessionRecoveryInfo {
@@ -309,6 +375,7 @@ pub struct SessionRecoveryInfo {
     pub file_reviews: Vec<FileReview>,
     pub comments: Vec<ReviewComment>,
     pub last_active_file: Option<String>,
+    pub unsaved_drafts: Vec<JournaledDraft>, // To restore or discard
 }
 
 /// Session creation parameters
@@ -333,7 +400,7 @@ mod tests {
     use super::*;
     use std::sync::Arc;
     use crate::storage::sqlite::Database;
-    use crate::models::gerrit::{GerritInstance, GerritChange, GerritUser, ChangeStatus, ConnectionStatus, ImportStatus, ConflictStatus};
+    use crate::models::gerrit::{GerritInstance, GerritAuthMethod, GerritChange, GerritUser, ChangeStatus, ConnectionStatus, ImportStatus, ConflictStatus};
 
     fn setup_test_database() -> Arc<Database> {
         let db = Arc::new(Database::new(":memory:").expect("Failed to create test database"));
@@ -347,6 +414,7 @@ mod tests {
             url: "http://test.gerrit.com".to_string(),
             username: "testuser".to_string(),
             password_encrypted: "encrypted_password".to_string(),
+            auth_method: GerritAuthMethod::Basic,
             version: "3.0".to_string(),
             is_active: true,
             last_connected: None,
@@ -520,4 +588,97 @@ mod tests {
         let recovered_session = session_manager.get_session(&session.id).await.unwrap().unwrap();
         assert_eq!(recovered_session.status, ReviewStatus::Abandoned);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_failing_quality_gates_block_submission() {
+        let db = setup_test_database();
+        let session_manager = ReviewSessionManager::new(db.clone());
+
+        let mut session = session_manager.create_session("I12345", 1, "reviewer1", ReviewMode::Online).await.unwrap();
+        session.progress.reviewed_files = 1;
+        db.store_review_session(&session).unwrap();
+
+        assert_eq!(
+            session_manager.session_commit(&session.id).unwrap(),
+            ("abc123".to_string(), "test-project".to_string())
+        );
+
+        let gate = |name: &str, status: crate::models::QualityGateStatus| QualityGate {
+            name: name.to_string(),
+            status,
+            details: None,
+            last_checked: String::new(),
+            url: None,
+            metadata: HashMap::new(),
+        };
+        let gates = vec![
+            gate("Jenkins", crate::models::QualityGateStatus::Failing),
+            gate("Verified", crate::models::QualityGateStatus::Pending),
+        ];
+        let err = session_manager.mark_ready_for_submission(&session.id, &gates).await.unwrap_err();
+        assert!(err.to_string().contains("Quality gates failing: Jenkins"));
+
+        let ready = session_manager.mark_ready_for_submission(&session.id, &gates[1..]).await.unwrap();
+        assert_eq!(ready.status, ReviewStatus::ReadyForSubmission);
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_blocking_comments_block_submission() {
+        let db = setup_test_database();
+        let session_manager = ReviewSessionManager::new(db.clone());
+
+        let mut session = session_manager.create_session("I12345", 1, "reviewer1", ReviewMode::Online).await.unwrap();
+        session.progress.reviewed_files = 1;
+        db.store_review_session(&session).unwrap();
+
+        let engine = CommentEngine::new(CommentEngineConfig::default());
+        let comment = |severity| crate::services::comment_engine::CreateCommentParams {
+            session_id: session.id.clone(),
+            file_path: "src/main.rs".to_string(),
+            line_number: Some(7),
+            content: "Leaks the handle".to_string(),
+            comment_type: CommentType::Issue,
+            severity,
+            parent_comment_id: None,
+        };
+        engine.create_comment(comment(CommentSeverity::Nit), &db).unwrap();
+        let blocking_id = engine.create_comment(comment(CommentSeverity::Blocking), &db).unwrap().comment_id.unwrap();
+
+        let err = session_manager.mark_ready_for_submission(&session.id, &[]).await.unwrap_err();
+        assert!(err.to_string().contains("Unresolved blocking comments: src/main.rs:7"));
+
+        let stats = engine.get_change_comment_stats("I12345", &db).unwrap();
+        assert_eq!(stats.comments_by_severity.get(&CommentSeverity::Blocking), Some(&1));
+        assert_eq!(stats.comments_by_reviewer.get("reviewer1"), Some(&2));
+        assert_eq!(stats.unresolved_blocking, 1);
+
+        engine.set_thread_state(&blocking_id, ThreadState::Done, "author", &db).unwrap();
+        let ready = session_manager.mark_ready_for_submission(&session.id, &[]).await.unwrap();
+        assert_eq!(ready.status, ReviewStatus::ReadyForSubmission);
+    }
+
+    #[tokio::test]
+    async fn test_recover_session_offers_unsaved_drafts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let journal = Arc::new(DraftJournal::new(dir.path().join("drafts.jsonl"), std::time::Duration::ZERO));
+        let session_manager = ReviewSessionManager::new(setup_test_database()).with_draft_journal(journal.clone());
+
+        let session = session_manager.create_session("I12345", 1, "reviewer1", ReviewMode::Online).await.unwrap();
+        let draft = |key: &str, session_id: &str| JournaledDraft {
+            draft_key: key.to_string(),
+            session_id: session_id.to_string(),
+            file_path: "src/main.rs".to_string(),
+            line_number: Some(3),
+            parent_comment_id: None,
+            comment_id: None,
+            content: "Half-written thought".to_string(),
+            written_at: String::new(),
+        };
+        journal.record(draft("mine", &session.id)).unwrap();
+        journal.record(draft("other", "another-session")).unwrap();
+
+        let info = session_manager.recover_session(&session.id).await.unwrap();
+        assert_eq!(info.unsaved_drafts.len(), 1);
+        assert_eq!(info.unsaved_drafts[0].draft_key, "mine");
+    }
+}
@@ -0,0 +1,293 @@
+// Comment Snippets
+// Review templates with placeholders, filled in from the diff context they are inserted into
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HyperReviewError;
+use crate::models::ReviewTemplate;
+use crate::services::diff_engine::{DiffConfig, DiffEngine};
+use crate::storage::sqlite::Database;
+
+/// Format version of exported snippet packs
+pub const SNIPPET_PACK_VERSION: u32 = 1;
+
+/// Declarations recognised as the symbol enclosing a line, for the common languages
+const SYMBOL_PATTERN: &str = r"(?x)
+    \b(?:fn|def|func|function|class|struct|enum|trait|interface|impl|module|mod)\s+([A-Za-z_][A-Za-z0-9_]*)
+    | ^\s*(?:(?:public|private|protected|static|final|async|override|virtual|abstract)\s+)+
+      [A-Za-z_][A-Za-z0-9_<>\[\],\s]*?\s([A-Za-z_][A-Za-z0-9_]*)\s*\(";
+
+/// Where a snippet is being inserted
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SnippetContext {
+    pub file: String,
+    pub line: Option<u32>,
+    pub symbol: Option<String>,        // Function, class, ... enclosing the line
+    pub selected_code: Option<String>,
+    pub rule_id: Option<String>,       // Static analysis rule the comment is about
+    pub language: Option<String>,
+}
+
+impl SnippetContext {
+    /// Context for a line of a file, with the enclosing symbol and the language
+    /// worked out from the file when they are not given
+    pub fn resolve(mut self, content: Option<&str>) -> Self {
+        if self.language.is_none() {
+            self.language = DiffEngine::new(DiffConfig::default()).detect_language(&self.file);
+        }
+        if self.symbol.is_none() {
+            if let (Some(content), Some(line)) = (content, self.line) {
+                self.symbol = enclosing_symbol(content, line);
+            }
+        }
+        self
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        match name {
+            "file" => Some(self.file.clone()),
+            "line" => self.line.map(|line| line.to_string()),
+            "symbol" => self.symbol.clone(),
+            "selected_code" => self.selected_code.clone(),
+            "rule_id" => self.rule_id.clone(),
+            "language" => self.language.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// A team's snippets, shared as a JSON file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SnippetPack {
+    pub name: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub snippets: Vec<ReviewTemplate>,
+}
+
+fn placeholder_regex() -> &'static Regex {
+    // ${name}, and the older {{name}}
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\$\{(\w+)\}|\{\{(\w+)\}\}").unwrap())
+}
+
+/// Names of the placeholders in a snippet, in order of first use
+pub fn extract_placeholders(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for cap in placeholder_regex().captures_iter(content) {
+        let name = cap.get(1).or_else(|| cap.get(2)).unwrap().as_str();
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Fill in the placeholders of a snippet. Known placeholders without a value
+/// become empty; unknown ones are left for the reviewer to fill in.
+pub fn render_snippet(template: &ReviewTemplate, context: &SnippetContext) -> String {
+    const KNOWN: [&str; 6] = ["file", "line", "symbol", "selected_code", "rule_id", "language"];
+
+    placeholder_regex().replace_all(&template.content, |cap: &regex::Captures| {
+        let name = cap.get(1).or_else(|| cap.get(2)).unwrap().as_str();
+        match context.value(name) {
+            Some(value) => value,
+            None if KNOWN.contains(&name) => String::new(),
+            None => cap[0].to_string(),
+        }
+    }).into_owned()
+}
+
+/// Whether a snippet is offered for a file, by its language and path globs
+pub fn snippet_applies(template: &ReviewTemplate, context: &SnippetContext) -> bool {
+    let language_matches = template.languages.is_empty()
+        || context.language.as_deref().is_some_and(|language| {
+            template.languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+        });
+    let path_matches = template.file_globs.is_empty()
+        || template.file_globs.iter().any(|glob| glob_matches(glob, &context.file));
+
+    language_matches && path_matches
+}
+
+/// Match a path against a glob: `*` and `?` stay within a path segment, `**` spans segments.
+/// Globs without a `/` match the file name anywhere in the tree.
+pub fn glob_matches(glob: &str, path: &str) -> bool {
+    // Snippets are checked against every file a reviewer opens, so each glob is compiled once
+    static GLOBS: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+    let mut globs = GLOBS.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap();
+    globs.entry(glob.to_string())
+        .or_insert_with(|| glob_regex(glob))
+        .as_ref()
+        .is_some_and(|re| re.is_match(path))
+}
+
+/// Regex equivalent of a glob, `None` when it does not compile
+fn glob_regex(glob: &str) -> Option<Regex> {
+    let glob = if glob.contains('/') { glob.to_string() } else { format!("**/{}", glob) };
+
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).ok()
+}
+
+/// Name of the closest declaration at or above a line (1-based)
+pub fn enclosing_symbol(content: &str, line: u32) -> Option<String> {
+    static SYMBOL: OnceLock<Regex> = OnceLock::new();
+    let symbol = SYMBOL.get_or_init(|| Regex::new(SYMBOL_PATTERN).unwrap());
+    content.lines()
+        .take(line as usize)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .find_map(|text| symbol.captures(text))
+        .and_then(|cap| cap.get(1).or_else(|| cap.get(2)))
+        .map(|m| m.as_str().to_string())
+}
+
+/// Snippets offered where the reviewer is commenting, most used first
+pub fn applicable_snippets(
+    database: &Database,
+    context: &SnippetContext,
+) -> Result<Vec<ReviewTemplate>, HyperReviewError> {
+    let mut snippets = database.get_review_templates()?;
+    snippets.retain(|snippet| snippet_applies(snippet, context));
+    Ok(snippets)
+}
+
+/// Render a snippet for insertion and count its use
+pub fn insert_snippet(
+    database: &Database,
+    template_id: &str,
+    context: &SnippetContext,
+) -> Result<String, HyperReviewError> {
+    let template = database.get_review_template(template_id)?
+        .ok_or_else(|| HyperReviewError::other(format!("Template not found: {}", template_id)))?;
+
+    let text = render_snippet(&template, context);
+    database.increment_template_usage(template_id)?;
+    Ok(text)
+}
+
+/// Pack all snippets for sharing; usage counts stay local
+pub fn export_pack(database: &Database, name: &str) -> Result<SnippetPack, HyperReviewError> {
+    let snippets = database.get_review_templates()?
+        .into_iter()
+        .map(|snippet| ReviewTemplate { usage_count: 0, ..snippet })
+        .collect();
+
+    Ok(SnippetPack {
+        name: name.to_string(),
+        version: SNIPPET_PACK_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        snippets,
+    })
+}
+
+/// Add or update the snippets of a pack, keeping the local usage counts of known snippets.
+/// Returns the number of snippets imported.
+pub fn import_pack(database: &Database, pack: &SnippetPack) -> Result<usize, HyperReviewError> {
+    if pack.version > SNIPPET_PACK_VERSION {
+        return Err(HyperReviewError::validation(
+            format!("Snippet pack version {} is newer than supported version {}", pack.version, SNIPPET_PACK_VERSION),
+            Some("version".to_string()),
+        ));
+    }
+
+    let usage: HashMap<String, u32> = database.get_review_templates()?
+        .into_iter()
+        .map(|snippet| (snippet.id, snippet.usage_count))
+        .collect();
+
+    for snippet in &pack.snippets {
+        let snippet = ReviewTemplate {
+            usage_count: usage.get(&snippet.id).copied().unwrap_or(0),
+            placeholders: extract_placeholders(&snippet.content),
+            ..snippet.clone()
+        };
+        database.store_review_template(&snippet)?;
+    }
+
+    log::info!("Imported {} snippets from pack {}", pack.snippets.len(), pack.name);
+    Ok(pack.snippets.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(content: &str, languages: &[&str], file_globs: &[&str]) -> ReviewTemplate {
+        ReviewTemplate {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: "snippet".to_string(),
+            content: content.to_string(),
+            placeholders: extract_placeholders(content),
+            category: None,
+            usage_count: 0,
+            languages: languages.iter().map(|l| l.to_string()).collect(),
+            file_globs: file_globs.iter().map(|g| g.to_string()).collect(),
+            created_at: "2025-01-05 12:00:00".to_string(),
+            updated_at: "2025-01-05 12:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_snippet_from_context() {
+        let content = "impl Cache {\n    fn get(&self) -> u32 {\n        self.map.len()\n    }\n}\n";
+        let context = SnippetContext {
+            file: "src/cache.rs".to_string(),
+            line: Some(3),
+            selected_code: Some("self.map.len()".to_string()),
+            rule_id: Some("clippy::len_zero".to_string()),
+            ..SnippetContext::default()
+        }.resolve(Some(content));
+
+        assert_eq!(context.symbol.as_deref(), Some("get"));
+        assert_eq!(context.language.as_deref(), Some("rust"));
+
+        let template = snippet("`${selected_code}` in ${symbol} (${file}:${line}) violates ${rule_id}; see {{ticket}}", &[], &[]);
+        assert_eq!(template.placeholders, vec!["selected_code", "symbol", "file", "line", "rule_id", "ticket"]);
+        assert_eq!(
+            render_snippet(&template, &context),
+            "`self.map.len()` in get (src/cache.rs:3) violates clippy::len_zero; see {{ticket}}"
+        );
+    }
+
+    #[test]
+    fn test_snippet_scope() {
+        let context = SnippetContext {
+            file: "src/main/java/App.java".to_string(),
+            language: Some("java".to_string()),
+            ..SnippetContext::default()
+        };
+
+        assert!(snippet_applies(&snippet("x", &[], &[]), &context));
+        assert!(snippet_applies(&snippet("x", &["Java"], &["src/**/*.java"]), &context));
+        assert!(snippet_applies(&snippet("x", &[], &["*.java"]), &context));
+        assert!(!snippet_applies(&snippet("x", &["rust"], &[]), &context));
+        assert!(!snippet_applies(&snippet("x", &[], &["test/**"]), &context));
+        assert!(!glob_matches("src/*.java", "src/main/App.java"));
+    }
+}
@@ -0,0 +1,244 @@
+// Suggested Changes
+// Applies reviewers' suggestion comments to the local working tree as a single commit
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use git2::build::TreeUpdateBuilder;
+use git2::{FileMode, Repository, Signature, Status};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HyperReviewError;
+use crate::models::gerrit::SuggestedChange;
+
+/// Outcome of applying a batch of suggested changes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SuggestionApplyResult {
+    pub commit_id: String,
+    pub applied: Vec<String>,          // Comment IDs of the applied suggestions
+    pub files: Vec<String>,
+}
+
+/// Apply suggested changes to the working tree of `repo` and commit them on top of HEAD.
+///
+/// Every suggestion is checked before any file is written: the touched files must have no
+/// uncommitted changes, suggestions on the same file must not overlap, and lines that were
+/// recorded when the suggestion was made must still be there.
+pub fn apply_suggestions(
+    repo: &Repository,
+    suggestions: &[SuggestedChange],
+    message: Option<&str>,
+) -> Result<SuggestionApplyResult, HyperReviewError> {
+    if suggestions.is_empty() {
+        return Err(HyperReviewError::validation("No suggestions to apply".to_string(), None));
+    }
+    let workdir = repo.workdir()
+        .ok_or_else(|| HyperReviewError::validation("Repository has no working tree".to_string(), None))?
+        .to_path_buf();
+
+    let mut by_file: BTreeMap<&str, Vec<&SuggestedChange>> = BTreeMap::new();
+    for suggestion in suggestions {
+        if suggestion.applied_commit.is_some() {
+            return Err(HyperReviewError::validation(
+                format!("Suggestion {} has already been applied", suggestion.comment_id),
+                Some("comment_ids".to_string()),
+            ));
+        }
+        by_file.entry(suggestion.file_path.as_str()).or_default().push(suggestion);
+    }
+
+    // Build the new content of every file before touching the working tree
+    let mut updates = Vec::with_capacity(by_file.len());
+    for (file_path, mut file_suggestions) in by_file {
+        let status = repo.status_file(Path::new(file_path))?;
+        if !(status.is_empty() || status == Status::CURRENT) {
+            return Err(HyperReviewError::validation(
+                format!("{} has uncommitted changes", file_path),
+                Some("file_path".to_string()),
+            ));
+        }
+
+        let content = std::fs::read_to_string(workdir.join(file_path))?;
+        file_suggestions.sort_by_key(|s| s.start_line);
+        for pair in file_suggestions.windows(2) {
+            if pair[1].start_line <= pair[0].end_line {
+                return Err(HyperReviewError::validation(
+                    format!("Suggestions {} and {} overlap in {}", pair[0].comment_id, pair[1].comment_id, file_path),
+                    Some("comment_ids".to_string()),
+                ));
+            }
+        }
+
+        updates.push((file_path, replace_lines(file_path, &content, &file_suggestions)?));
+    }
+
+    // The commit is HEAD's tree plus the patched files only, so changes the user has staged
+    // elsewhere stay staged instead of being swept into the suggestion commit
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let base_tree = match &parent {
+        Some(commit) => commit.tree()?,
+        None => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+    };
+    let mut tree_update = TreeUpdateBuilder::new();
+    let mut index = repo.index()?;
+    for (file_path, content) in &updates {
+        std::fs::write(workdir.join(file_path), content)?;
+        let executable = base_tree.get_path(Path::new(file_path))
+            .is_ok_and(|entry| entry.filemode() == 0o100755);
+        let mode = if executable { FileMode::BlobExecutable } else { FileMode::Blob };
+        let blob = repo.blob(content.as_bytes())?;
+        tree_update.upsert(*file_path, blob, mode);
+        index.add_path(Path::new(file_path))?;
+    }
+    index.write()?;
+
+    let tree = repo.find_tree(tree_update.create_updated(repo, &base_tree)?)?;
+    let signature = repo.signature().or_else(|_| Signature::now("HyperReview", "hyperreview@localhost"))?;
+    let message = message.map(str::to_string).unwrap_or_else(|| default_message(suggestions));
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let commit_id = repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &parents)?;
+
+    info!("Applied {} suggested changes as commit {}", suggestions.len(), commit_id);
+    Ok(SuggestionApplyResult {
+        commit_id: commit_id.to_string(),
+        applied: suggestions.iter().map(|s| s.comment_id.clone()).collect(),
+        files: updates.into_iter().map(|(file_path, _)| file_path.to_string()).collect(),
+    })
+}
+
+/// Replace the line ranges of non-overlapping suggestions, sorted by start line
+fn replace_lines(file_path: &str, content: &str, suggestions: &[&SuggestedChange]) -> Result<String, HyperReviewError> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut result = String::with_capacity(content.len());
+    let mut next_line = 0usize;
+
+    for suggestion in suggestions {
+        let end = suggestion.end_line as usize;
+        if suggestion.start_line == 0 || suggestion.end_line < suggestion.start_line || end > lines.len() {
+            return Err(HyperReviewError::validation(
+                format!("Lines {}-{} are outside of {}", suggestion.start_line, suggestion.end_line, file_path),
+                Some("comment_ids".to_string()),
+            ));
+        }
+        let start = suggestion.start_line as usize - 1;
+
+        let current: String = lines[start..end].concat();
+        if let Some(original) = &suggestion.original_text {
+            if current.trim_end_matches(['\n', '\r']) != original.trim_end_matches(['\n', '\r']) {
+                return Err(HyperReviewError::validation(
+                    format!("{} changed since suggestion {} was made", file_path, suggestion.comment_id),
+                    Some("comment_ids".to_string()),
+                ));
+            }
+        }
+
+        result.extend(lines[next_line..start].iter().copied());
+        if !suggestion.replacement.is_empty() {
+            result.push_str(&suggestion.replacement);
+            // Keep the line ending of the replaced range, including a missing one at EOF
+            let ending = if current.ends_with("\r\n") { "\r\n" } else if current.ends_with('\n') { "\n" } else { "" };
+            result.push_str(ending);
+        }
+        next_line = end;
+    }
+
+    result.extend(lines[next_line..].iter().copied());
+    Ok(result)
+}
+
+fn default_message(suggestions: &[SuggestedChange]) -> String {
+    match suggestions {
+        [single] => format!("Apply suggested change to {}", single.file_path),
+        _ => format!("Apply {} suggested changes", suggestions.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn init_repo() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib.rs")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "base", &tree, &[]).unwrap();
+        drop(tree);
+        (dir, repo)
+    }
+
+    fn suggestion(id: &str, start_line: u32, end_line: u32, original: &str, replacement: &str) -> SuggestedChange {
+        SuggestedChange {
+            comment_id: id.to_string(),
+            file_path: "lib.rs".to_string(),
+            start_line,
+            end_line,
+            original_text: Some(original.to_string()),
+            replacement: replacement.to_string(),
+            applied_commit: None,
+            created_at: "2025-01-05 12:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_suggestions_as_one_commit() {
+        let (dir, repo) = init_repo();
+        let suggestions = vec![
+            suggestion("s2", 3, 4, "fn c() {}\nfn d() {}", "fn cd() {}"),
+            suggestion("s1", 1, 1, "fn a() {}", "pub fn a() {}"),
+        ];
+
+        let result = apply_suggestions(&repo, &suggestions, None).unwrap();
+        assert_eq!(result.applied, vec!["s2", "s1"]);
+        assert_eq!(result.files, vec!["lib.rs"]);
+
+        let content = std::fs::read_to_string(dir.path().join("lib.rs")).unwrap();
+        assert_eq!(content, "pub fn a() {}\nfn b() {}\nfn cd() {}\n");
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.id().to_string(), result.commit_id);
+        assert_eq!(head.message(), Some("Apply 2 suggested changes"));
+        assert_eq!(head.parent_count(), 1);
+        assert!(repo.statuses(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_leaves_other_staged_changes_out_of_the_commit() {
+        let (dir, repo) = init_repo();
+        std::fs::write(dir.path().join("notes.txt"), "work in progress\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("notes.txt")).unwrap();
+        index.write().unwrap();
+
+        apply_suggestions(&repo, &[suggestion("s1", 1, 1, "fn a() {}", "pub fn a() {}")], None).unwrap();
+
+        let head_tree = repo.head().unwrap().peel_to_tree().unwrap();
+        assert!(head_tree.get_path(Path::new("lib.rs")).is_ok());
+        assert!(head_tree.get_path(Path::new("notes.txt")).is_err());
+        assert_eq!(repo.status_file(Path::new("notes.txt")).unwrap(), Status::INDEX_NEW);
+        assert_eq!(repo.status_file(Path::new("lib.rs")).unwrap(), Status::CURRENT);
+    }
+
+    #[test]
+    fn test_apply_rejects_stale_and_overlapping_suggestions() {
+        let (dir, repo) = init_repo();
+
+        let stale = suggestion("s1", 2, 2, "fn old_b() {}", "fn b2() {}");
+        assert!(apply_suggestions(&repo, &[stale], None).is_err());
+
+        let overlapping = vec![
+            suggestion("s1", 1, 2, "fn a() {}\nfn b() {}", "fn ab() {}"),
+            suggestion("s2", 2, 3, "fn b() {}\nfn c() {}", "fn bc() {}"),
+        ];
+        assert!(apply_suggestions(&repo, &overlapping, None).is_err());
+
+        // Nothing was written or committed
+        let content = std::fs::read_to_string(dir.path().join("lib.rs")).unwrap();
+        assert_eq!(content, "fn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n");
+        assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().message(), Some("base"));
+    }
+}
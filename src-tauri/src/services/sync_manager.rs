@@ -1,18 +1,19 @@
-use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Utc};
-use log::{info, warn, error, debug};
+use log::{info, debug};
 use serde::{Deserialize, Serialize};
 
 use crate::models::gerrit::*;
-use crate::models::HyperReviewError;
+use crate::errors::HyperReviewError;
 use crate::storage::sqlite::Database;
-use crate::remote::gerrit_client::GerritClient;
 
-/// Manages synchronization between local data and Gerrit servers
+/// Reconciles locally edited comments with their Gerrit counterparts.
+///
+/// The version last in sync with the server is kept per comment and used as the base of
+/// a three-way merge, so edits made on only one side are taken over silently and only
+/// overlapping edits, moved anchors and remote deletions need a decision.
 pub struct SyncManager {
     db: Arc<Mutex<Database>>,
-    gerrit_clients: HashMap<String, GerritClient>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,7 +36,7 @@ pub struct SyncError {
     pub suggested_action: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum ConflictType {
     ConcurrentEdit,
     LineModified,
@@ -43,12 +44,27 @@ pub enum ConflictType {
     StatusChanged,
 }
 
+impl std::fmt::Display for ConflictType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictType::ConcurrentEdit => write!(f, "concurrent_edit"),
+            ConflictType::LineModified => write!(f, "line_modified"),
+            ConflictType::CommentDeleted => write!(f, "comment_deleted"),
+            ConflictType::StatusChanged => write!(f, "status_changed"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConflictInfo {
+    pub change_id: String,
+    pub entity_id: String,
     pub conflict_type: ConflictType,
     pub local_version: serde_json::Value,
     pub remote_version: serde_json::Value,
     pub base_version: Option<serde_json::Value>,
+    /// Text of the three-way merge, with conflict markers where the edits overlap
+    pub merged_text: Option<String>,
     pub resolution_options: Vec<ConflictResolutionOption>,
 }
 
@@ -60,266 +76,526 @@ pub struct ConflictResolutionOption {
     pub auto_resolvable: bool,
 }
 
-impl SyncManager {
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self {
-            db,
-            gerrit_clients: HashMap::new(),
+/// How to settle a comment conflict
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionChoice {
+    /// Combine both edits; only possible when they do not overlap
+    AutoMerge,
+    /// Keep the local version (re-posting it if the remote comment was deleted)
+    KeepMine,
+    /// Take the server's version (dropping the local comment if it was deleted)
+    KeepTheirs,
+    /// Keep the local text at the server's new position
+    ReAnchor,
+}
+
+impl std::fmt::Display for ResolutionChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolutionChoice::AutoMerge => write!(f, "auto_merge"),
+            ResolutionChoice::KeepMine => write!(f, "keep_mine"),
+            ResolutionChoice::KeepTheirs => write!(f, "keep_theirs"),
+            ResolutionChoice::ReAnchor => write!(f, "re_anchor"),
         }
     }
-    
-    /// Synchronize a specific change with Gerrit
-    pub async fn sync_change(
-        &mut self,
-        instance_id: &str,
-        change_id: &str,
-        sync_type: SyncType,
-    ) -> Result<SyncResult, HyperReviewError> {
-        let start_time = std::time::Instant::now();
-        info!("Starting sync for change {} on instance {}", change_id, instance_id);
-        
-        let mut result = SyncResult {
-            success: true,
-            changes_processed: 0,
-            comments_processed: 0,
-            conflicts_detected: 0,
-            conflicts_resolved: 0,
-            errors: Vec::new(),
-            duration_ms: 0,
-        };
-        
-        // Get or create Gerrit client for this instance
-        let client = self.get_or_create_client(instance_id).await?;
-        
-        // Fetch current change data from Gerrit
-        let remote_change = match client.get_change(change_id).await {
-            Ok(change) => change,
-            Err(e) => {
-                result.success = false;
-                result.errors.push(SyncError {
-                    change_id: Some(change_id.to_string()),
-                    error_type: "FetchError".to_string(),
-                    message: format!("Failed to fetch change from Gerrit: {}", e),
-                    timestamp: Utc::now(),
-                    suggested_action: Some("Verify change ID and permissions".to_string()),
-                });
-                return Ok(result);
-            }
-        };
-        
-        // Get local change data
-        let db = self.db.lock().map_err(|e| HyperReviewError::DatabaseError(e.to_string()))?;
-        let local_change = db.get_gerrit_change(change_id)?;
-        drop(db);
-        
-        // Compare and detect conflicts
-        let conflicts = self.detect_conflicts(&local_change, &remote_change, sync_type)?;
-        result.conflicts_detected = conflicts.len() as u32;
-        
-        // Resolve conflicts automatically where possible
-        let mut resolved_conflicts = 0;
-        for conflict in &conflicts {
-            if self.try_auto_resolve_conflict(conflict).await? {
-                resolved_conflicts += 1;
-            }
-        }
-        result.conflicts_resolved = resolved_conflicts;
-        
-        // Update local data with merged changes
-        match self.merge_changes(&local_change, &remote_change, sync_type).await {
-            Ok(_) => {
-                result.changes_processed += 1;
-                info!("Successfully synced change {}", change_id);
-            }
-            Err(e) => {
-                result.success = false;
-                result.errors.push(SyncError {
-                    change_id: Some(change_id.to_string()),
-                    error_type: "MergeError".to_string(),
-                    message: format!("Failed to merge changes: {}", e),
-                    timestamp: Utc::now(),
-                    suggested_action: Some("Manual intervention may be required".to_string()),
-                });
-            }
-        }
-        
-        // Sync comments if requested
-        if sync_type == SyncType::Full || sync_type == SyncType::CommentsOnly {
-            match self.sync_comments(instance_id, change_id).await {
-                Ok(comment_count) => {
-                    result.comments_processed = comment_count;
-                }
-                Err(e) => {
-                    warn!("Failed to sync comments for change {}: {}", change_id, e);
-                    result.errors.push(SyncError {
-                        change_id: Some(change_id.to_string()),
-                        error_type: "CommentSyncError".to_string(),
-                        message: format!("Comment sync failed: {}", e),
-                        timestamp: Utc::now(),
-                        suggested_action: Some("Comments may be out of sync".to_string()),
-                    });
-                }
-            }
+}
+
+/// Outcome of resolving a comment conflict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentResolution {
+    /// The local comment after resolution; `None` when it is dropped
+    pub comment: Option<ReviewComment>,
+    /// Whether the result differs from the server and still has to be pushed
+    pub needs_push: bool,
+}
+
+/// What reconciling a local comment with the server's copy came to
+#[derive(Debug, Clone)]
+pub enum Reconciled {
+    /// Merged and saved, possibly through an automatic resolution
+    Merged(CommentResolution),
+    /// Left untouched until the user picks a resolution
+    Conflict(ConflictInfo),
+}
+
+/// Result of a three-way text merge
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeResult {
+    Clean(String),
+    /// Merged text with `<<<<<<<`/`=======`/`>>>>>>>` markers around overlapping edits
+    Conflicted(String),
+}
+
+impl MergeResult {
+    pub fn text(&self) -> &str {
+        match self {
+            MergeResult::Clean(text) | MergeResult::Conflicted(text) => text,
         }
-        
-        result.duration_ms = start_time.elapsed().as_millis() as u64;
-        info!("Sync completed for change {} in {}ms", change_id, result.duration_ms);
-        
-        Ok(result)
-    }
-    
-    /// Detect conflicts between local and remote data
-    fn detect_conflicts(
+    }
+}
+
+impl SyncManager {
+    pub fn new(db: Arc<Mutex<Database>>) -> Self {
+        Self { db }
+    }
+
+    /// Detect change-level differences between the local copy and the server
+    pub fn detect_conflicts(
         &self,
         local: &GerritChange,
         remote: &GerritChange,
-        sync_type: SyncType,
     ) -> Result<Vec<ConflictInfo>, HyperReviewError> {
         let mut conflicts = Vec::new();
-        
+
         // Check for concurrent modifications based on timestamps
         if local.updated < remote.updated {
             conflicts.push(ConflictInfo {
+                change_id: local.id.clone(),
+                entity_id: local.id.clone(),
                 conflict_type: ConflictType::ConcurrentEdit,
                 local_version: serde_json::to_value(local)?,
                 remote_version: serde_json::to_value(remote)?,
                 base_version: None,
+                merged_text: None,
                 resolution_options: vec![
-                    ConflictResolutionOption {
-                        id: "use_remote".to_string(),
-                        description: "Use remote version".to_string(),
-                        action: "overwrite_local".to_string(),
-                        auto_resolvable: true,
-                    },
-                    ConflictResolutionOption {
-                        id: "use_local".to_string(),
-                        description: "Keep local version".to_string(),
-                        action: "keep_local".to_string(),
-                        auto_resolvable: false,
-                    },
+                    option(ResolutionChoice::KeepTheirs, "Use remote version", true),
+                    option(ResolutionChoice::KeepMine, "Keep local version", false),
                 ],
             });
         }
-        
+
         // Check for patch set updates
         if local.current_patch_set_num != remote.current_patch_set_num {
             conflicts.push(ConflictInfo {
+                change_id: local.id.clone(),
+                entity_id: local.id.clone(),
                 conflict_type: ConflictType::StatusChanged,
-                local_version: serde_json::to_value(&local.current_patch_set_num)?,
-                remote_version: serde_json::to_value(&remote.current_patch_set_num)?,
+                local_version: serde_json::to_value(local.current_patch_set_num)?,
+                remote_version: serde_json::to_value(remote.current_patch_set_num)?,
                 base_version: None,
+                merged_text: None,
                 resolution_options: vec![
-                    ConflictResolutionOption {
-                        id: "update_patch_set".to_string(),
-                        description: "Update to newer patch set".to_string(),
-                        action: "update_patch_set".to_string(),
-                        auto_resolvable: true,
-                    },
+                    option(ResolutionChoice::KeepTheirs, "Update to newer patch set", true),
                 ],
             });
         }
-        
+
         Ok(conflicts)
     }
-    
-    /// Attempt to automatically resolve a conflict
-    async fn try_auto_resolve_conflict(
+
+    /// Compare a local comment with the server's copy (`None` when it was deleted remotely)
+    /// against the last synced version
+    pub fn comment_conflict(
+        &self,
+        change_id: &str,
+        local: &ReviewComment,
+        remote: Option<&GerritComment>,
+    ) -> Result<Option<ConflictInfo>, HyperReviewError> {
+        let base = self.db.lock().unwrap().get_comment_sync_base(&local.id)?;
+        detect_comment_conflict(change_id, local, remote, base.as_ref())
+    }
+
+    /// Apply a resolution to the local comment and record it, see [`apply_resolution`]
+    pub fn resolve_conflict(
         &self,
         conflict: &ConflictInfo,
-    ) -> Result<bool, HyperReviewError> {
-        // Auto-resolve simple conflicts based on rules
-        match conflict.conflict_type {
-            ConflictType::ConcurrentEdit => {
-                // If remote is newer and no semantic conflicts, auto-resolve
-                if let Some(option) = conflict.resolution_options.iter()
-                    .find(|opt| opt.auto_resolvable) {
-                    debug!("Auto-resolving conflict: {}", option.description);
-                    return Ok(true);
-                }
-            }
-            ConflictType::StatusChanged => {
-                // Always auto-resolve patch set updates
-                debug!("Auto-resolving patch set update conflict");
-                return Ok(true);
-            }
-            _ => {}
+        choice: ResolutionChoice,
+    ) -> Result<CommentResolution, HyperReviewError> {
+        apply_resolution(conflict, choice, &self.db.lock().unwrap())
+    }
+
+    /// Record the version of a local comment that was pushed, making it the base for later merges
+    pub fn mark_synced(&self, change_id: &str, comment_id: &str, pushed: &CommentVersion) -> Result<(), HyperReviewError> {
+        self.db.lock().unwrap().store_comment_sync_base(change_id, comment_id, pushed)
+    }
+
+    /// Conflict resolutions recorded for a change
+    pub fn history(&self, change_id: &str) -> Result<Vec<SyncHistoryEntry>, HyperReviewError> {
+        self.db.lock().unwrap().get_sync_history(change_id)
+    }
+}
+
+/// Apply a resolution: save (or drop) the local comment, record the choice in the
+/// sync history and move the comment's base to the server version it now incorporates
+pub fn apply_resolution(
+    conflict: &ConflictInfo,
+    choice: ResolutionChoice,
+    db: &Database,
+) -> Result<CommentResolution, HyperReviewError> {
+    let resolution = resolve_comment_conflict(conflict, choice)?;
+    let remote: Option<GerritComment> = serde_json::from_value(conflict.remote_version.clone())?;
+
+    match &resolution.comment {
+        Some(comment) => db.update_review_comment(comment)?,
+        None => {
+            db.delete_review_comment(&conflict.entity_id)?;
         }
-        
-        Ok(false)
     }
-    
-    /// Merge local and remote changes
-    async fn merge_changes(
-        &self,
-        local: &GerritChange,
-        remote: &GerritChange,
-        sync_type: SyncType,
-    ) -> Result<(), HyperReviewError> {
-        let db = self.db.lock().map_err(|e| HyperReviewError::DatabaseError(e.to_string()))?;
-        
-        // Update local change with remote data based on sync type
-        match sync_type {
-            SyncType::Full => {
-                // Update all fields except local-only data
-                db.update_gerrit_change(&local.id,
-                    &remote.subject,
-                    &remote.status,
-                    &remote.current_revision,
-                    remote.current_patch_set_num,
-                    &remote.updated,
-                )?;
-            }
-            SyncType::StatusOnly => {
-                // Update only status-related fields
-                db.update_gerrit_change_status(&local.id, &remote.status)?;
+    match &remote {
+        Some(remote) => db.store_comment_sync_base(&conflict.change_id, &conflict.entity_id, &CommentVersion::from(remote))?,
+        None => {
+            // Whatever is kept is a local comment now, to be posted again
+            db.delete_imported_gerrit_comments_for(&conflict.entity_id)?;
+            db.delete_comment_sync_base(&conflict.entity_id)?;
+        }
+    }
+    db.add_sync_history(&SyncHistoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        change_id: conflict.change_id.clone(),
+        entity_id: conflict.entity_id.clone(),
+        conflict_type: conflict.conflict_type.to_string(),
+        resolution: choice.to_string(),
+        details: resolution.comment.as_ref()
+            .map(|comment| serde_json::to_string(&CommentVersion::from(comment)))
+            .transpose()?,
+        resolved_at: Utc::now().to_rfc3339(),
+    })?;
+
+    info!("Resolved {} conflict on comment {} with {}", conflict.conflict_type, conflict.entity_id, choice);
+    Ok(resolution)
+}
+
+/// Reconcile a local comment with the server's copy (`None` when it was deleted remotely).
+///
+/// Edits made on one side only are taken over, non-overlapping edits are merged and
+/// auto-resolvable conflicts are settled and recorded; the rest is returned for the user
+/// with the local comment left as it is.
+pub fn reconcile_comment(
+    change_id: &str,
+    local: &ReviewComment,
+    remote: Option<&GerritComment>,
+    db: &Database,
+) -> Result<Reconciled, HyperReviewError> {
+    let base = db.get_comment_sync_base(&local.id)?;
+    if let Some(conflict) = detect_comment_conflict(change_id, local, remote, base.as_ref())? {
+        let auto = conflict.resolution_options.iter()
+            .find(|option| option.auto_resolvable)
+            .and_then(|option| choice_for(&option.id));
+        return match auto {
+            Some(choice) => {
+                debug!("Auto-resolving {} conflict on comment {}", conflict.conflict_type, local.id);
+                Ok(Reconciled::Merged(apply_resolution(&conflict, choice, db)?))
             }
-            _ => {}
+            None => Ok(Reconciled::Conflict(conflict)),
+        };
+    }
+
+    let Some(remote) = remote else {
+        // Deleted remotely before it was ever synced; keep it local
+        return Ok(Reconciled::Merged(CommentResolution { comment: Some(local.clone()), needs_push: true }));
+    };
+    let resolution = fast_forward(local, remote, base.as_ref());
+    if let Some(comment) = &resolution.comment {
+        if CommentVersion::from(comment) != CommentVersion::from(local) || comment.updated_at != local.updated_at {
+            db.update_review_comment(comment)?;
         }
-        
-        Ok(())
     }
-    
-    /// Sync comments for a change
-    async fn sync_comments(
-        &self,
-        instance_id: &str,
-        change_id: &str,
-    ) -> Result<u32, HyperReviewError> {
-        let client = self.get_or_create_client(instance_id).await?;
-        
-        // Fetch remote comments
-        let remote_comments = client.get_comments(change_id).await?;
-        
-        let db = self.db.lock().map_err(|e| HyperReviewError::DatabaseError(e.to_string()))?;
-        let mut synced_count = 0;
-        
-        for comment in remote_comments {
-            // Check if comment already exists locally
-            if !db.comment_exists(&comment.id)? {
-                db.insert_comment(&comment)?;
-                synced_count += 1;
+    db.store_comment_sync_base(change_id, &local.id, &CommentVersion::from(remote))?;
+    Ok(Reconciled::Merged(resolution))
+}
+
+fn option(choice: ResolutionChoice, description: &str, auto_resolvable: bool) -> ConflictResolutionOption {
+    ConflictResolutionOption {
+        id: choice.to_string(),
+        description: description.to_string(),
+        action: choice.to_string(),
+        auto_resolvable,
+    }
+}
+
+fn choice_for(option_id: &str) -> Option<ResolutionChoice> {
+    match option_id {
+        "auto_merge" => Some(ResolutionChoice::AutoMerge),
+        "keep_mine" => Some(ResolutionChoice::KeepMine),
+        "keep_theirs" => Some(ResolutionChoice::KeepTheirs),
+        "re_anchor" => Some(ResolutionChoice::ReAnchor),
+        _ => None,
+    }
+}
+
+fn anchor(version: &CommentVersion) -> (&str, u32) {
+    (&version.file_path, version.line)
+}
+
+/// Three-way merge of the comment position: the side that moved wins
+fn merge_anchor<'a>(base: &CommentVersion, mine: &'a CommentVersion, theirs: &'a CommentVersion) -> Option<&'a CommentVersion> {
+    if anchor(mine) == anchor(base) || anchor(mine) == anchor(theirs) {
+        Some(theirs)
+    } else if anchor(theirs) == anchor(base) {
+        Some(mine)
+    } else {
+        None
+    }
+}
+
+/// Classify how a local comment diverged from the server since the last sync.
+/// Returns `None` when there is nothing to decide (no base, or at most one side changed).
+pub fn detect_comment_conflict(
+    change_id: &str,
+    local: &ReviewComment,
+    remote: Option<&GerritComment>,
+    base: Option<&CommentVersion>,
+) -> Result<Option<ConflictInfo>, HyperReviewError> {
+    let Some(base) = base else {
+        return Ok(None);
+    };
+    let mine = CommentVersion::from(local);
+
+    let conflict = |conflict_type: ConflictType,
+                    merged_text: Option<String>,
+                    resolution_options: Vec<ConflictResolutionOption>|
+        -> Result<Option<ConflictInfo>, HyperReviewError> {
+        Ok(Some(ConflictInfo {
+            change_id: change_id.to_string(),
+            entity_id: local.id.clone(),
+            conflict_type,
+            local_version: serde_json::to_value(local)?,
+            remote_version: serde_json::to_value(remote)?,
+            base_version: Some(serde_json::to_value(base)?),
+            merged_text,
+            resolution_options,
+        }))
+    };
+
+    let Some(remote) = remote else {
+        let unchanged = mine == *base;
+        return conflict(ConflictType::CommentDeleted, None, vec![
+            option(ResolutionChoice::KeepTheirs, "Delete my copy of the comment", unchanged),
+            option(ResolutionChoice::KeepMine, "Post my version again as a new comment", false),
+        ]);
+    };
+    let theirs = CommentVersion::from(remote);
+
+    let text_changed_both = mine.message != base.message
+        && theirs.message != base.message
+        && mine.message != theirs.message;
+    let anchor_moved_remotely = anchor(&theirs) != anchor(base) && anchor(&theirs) != anchor(&mine);
+    let anchor_merge = merge_anchor(base, &mine, &theirs);
+
+    if text_changed_both {
+        let merged = merge_text(&base.message, &mine.message, &theirs.message);
+        let clean = matches!(merged, MergeResult::Clean(_)) && anchor_merge.is_some();
+        let mut options = vec![
+            option(ResolutionChoice::AutoMerge, "Merge both edits", clean),
+            option(ResolutionChoice::KeepMine, "Keep my text", false),
+            option(ResolutionChoice::KeepTheirs, "Take the text from Gerrit", false),
+        ];
+        if anchor_moved_remotely {
+            options.push(option(ResolutionChoice::ReAnchor, "Keep my text at the new position", false));
+        }
+        return conflict(ConflictType::ConcurrentEdit, Some(merged.text().to_string()), options);
+    }
+
+    if anchor_moved_remotely {
+        let local_unmoved = anchor(&mine) == anchor(base);
+        let merged = merge_text(&base.message, &mine.message, &theirs.message);
+        return conflict(ConflictType::LineModified, Some(merged.text().to_string()), vec![
+            option(ResolutionChoice::ReAnchor, "Move my comment to the new position", local_unmoved),
+            option(ResolutionChoice::KeepMine, "Keep my position", false),
+            option(ResolutionChoice::KeepTheirs, "Take the comment from Gerrit", false),
+        ]);
+    }
+
+    Ok(None)
+}
+
+/// Compute the local comment that results from `choice`
+pub fn resolve_comment_conflict(
+    conflict: &ConflictInfo,
+    choice: ResolutionChoice,
+) -> Result<CommentResolution, HyperReviewError> {
+    let local: ReviewComment = serde_json::from_value(conflict.local_version.clone())?;
+    let remote: Option<GerritComment> = serde_json::from_value(conflict.remote_version.clone())?;
+    let base: Option<CommentVersion> = conflict.base_version.clone()
+        .map(serde_json::from_value)
+        .transpose()?;
+    let unsupported = || HyperReviewError::validation(
+        format!("{} cannot resolve a {} conflict", choice, conflict.conflict_type),
+        Some("choice".to_string()),
+    );
+
+    let Some(remote) = remote else {
+        return match choice {
+            ResolutionChoice::KeepTheirs => Ok(CommentResolution { comment: None, needs_push: false }),
+            ResolutionChoice::KeepMine => {
+                // Posted again as a new draft
+                let mut comment = local;
+                comment.status = CommentStatus::Draft;
+                Ok(CommentResolution { comment: Some(comment), needs_push: true })
             }
+            _ => Err(unsupported()),
+        };
+    };
+
+    let base = base.unwrap_or_else(|| CommentVersion::from(&remote));
+    let mine = CommentVersion::from(&local);
+    let theirs = CommentVersion::from(&remote);
+
+    let (message, take_remote_anchor) = match choice {
+        ResolutionChoice::KeepMine => (local.content.clone(), false),
+        ResolutionChoice::KeepTheirs => (remote.message.clone(), true),
+        ResolutionChoice::ReAnchor => {
+            let message = match merge_text(&base.message, &mine.message, &theirs.message) {
+                MergeResult::Clean(text) => text,
+                MergeResult::Conflicted(_) => local.content.clone(),
+            };
+            (message, true)
+        }
+        ResolutionChoice::AutoMerge => {
+            let MergeResult::Clean(message) = merge_text(&base.message, &mine.message, &theirs.message) else {
+                return Err(HyperReviewError::validation(
+                    "The local and remote edits overlap and cannot be merged automatically".to_string(),
+                    Some("choice".to_string()),
+                ));
+            };
+            let anchor = merge_anchor(&base, &mine, &theirs).ok_or_else(|| HyperReviewError::validation(
+                "The comment was moved to different lines locally and on Gerrit".to_string(),
+                Some("choice".to_string()),
+            ))?;
+            (message, anchor == &theirs)
+        }
+    };
+
+    Ok(build_resolution(local, &remote, message, take_remote_anchor))
+}
+
+fn build_resolution(local: ReviewComment, remote: &GerritComment, message: String, take_remote_anchor: bool) -> CommentResolution {
+    let theirs = CommentVersion::from(remote);
+    let mut comment = local;
+    comment.content = message;
+    if take_remote_anchor {
+        comment.file_path = theirs.file_path.clone();
+        comment.line_number = Some(theirs.line).filter(|line| *line > 0);
+    }
+
+    let needs_push = CommentVersion::from(&comment) != theirs;
+    if !needs_push {
+        comment.updated_at = remote.updated.clone();
+    }
+    CommentResolution { comment: Some(comment), needs_push }
+}
+
+/// Take over the side that changed when the other did not
+fn fast_forward(local: &ReviewComment, remote: &GerritComment, base: Option<&CommentVersion>) -> CommentResolution {
+    let theirs = CommentVersion::from(remote);
+    let base = base.cloned().unwrap_or_else(|| theirs.clone());
+    let mine = CommentVersion::from(local);
+
+    let message = match merge_text(&base.message, &mine.message, &theirs.message) {
+        MergeResult::Clean(text) => text,
+        MergeResult::Conflicted(_) => local.content.clone(),
+    };
+    let take_remote_anchor = merge_anchor(&base, &mine, &theirs).is_none_or(|anchor| anchor == &theirs);
+
+    build_resolution(local.clone(), remote, message, take_remote_anchor)
+}
+
+/// Split text into alternating word and whitespace tokens
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (index, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|in_space| in_space != space) {
+            tokens.push(&text[start..index]);
+            start = index;
         }
-        
-        Ok(synced_count)
-    }
-    
-    /// Get or create a Gerrit client for an instance
-    async fn get_or_create_client(
-        &mut self,
-        instance_id: &str,
-    ) -> Result<&mut GerritClient, HyperReviewError> {
-        if !self.gerrit_clients.contains_key(instance_id) {
-            let db = self.db.lock().map_err(|e| HyperReviewError::DatabaseError(e.to_string()))?;
-            let instance = db.get_gerrit_instance(instance_id)?;
-            drop(db);
-            
-            let client = GerritClient::new(&instance.url, &instance.username, &instance.password_encrypted)?;
-            self.gerrit_clients.insert(instance_id.to_string(), client);
+        in_space = Some(space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// For each token of `base`, the index of the token it matches in `other` (longest common subsequence)
+fn matching(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let (n, m) = (base.len(), other.len());
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if base[i] == other[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut matches = vec![None; n];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if base[i] == other[j] {
+            matches[i] = Some(j);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
         }
-        
-        self.gerrit_clients.get_mut(instance_id)
-            .ok_or_else(|| HyperReviewError::InternalError("Failed to get Gerrit client".to_string()))
+    }
+    matches
+}
+
+/// Word-level three-way merge of `mine` and `theirs`, both derived from `base`
+pub fn merge_text(base: &str, mine: &str, theirs: &str) -> MergeResult {
+    if mine == theirs || theirs == base {
+        return MergeResult::Clean(mine.to_string());
+    }
+    if mine == base {
+        return MergeResult::Clean(theirs.to_string());
+    }
+
+    let base_tokens = tokenize(base);
+    let mine_tokens = tokenize(mine);
+    let theirs_tokens = tokenize(theirs);
+    let to_mine = matching(&base_tokens, &mine_tokens);
+    let to_theirs = matching(&base_tokens, &theirs_tokens);
+
+    let mut merged = String::new();
+    let mut conflicted = false;
+    let (mut i, mut a, mut b) = (0, 0, 0);
+    loop {
+        // Tokens unchanged on both sides
+        while i < base_tokens.len() && to_mine[i] == Some(a) && to_theirs[i] == Some(b) {
+            merged.push_str(base_tokens[i]);
+            i += 1;
+            a += 1;
+            b += 1;
+        }
+
+        // The next base token both sides kept ends the changed region
+        let (next, next_a, next_b) = (i..base_tokens.len())
+            .find_map(|k| Some((k, to_mine[k]?, to_theirs[k]?)))
+            .unwrap_or((base_tokens.len(), mine_tokens.len(), theirs_tokens.len()));
+        if next == i && next_a == a && next_b == b {
+            break;
+        }
+
+        let original = base_tokens[i..next].concat();
+        let ours = mine_tokens[a..next_a].concat();
+        let others = theirs_tokens[b..next_b].concat();
+        if ours == original || ours == others {
+            merged.push_str(&others);
+        } else if others == original {
+            merged.push_str(&ours);
+        } else {
+            conflicted = true;
+            merged.push_str(&format!("\n<<<<<<< mine\n{}\n=======\n{}\n>>>>>>> theirs\n", ours, others));
+        }
+
+        i = next;
+        a = next_a;
+        b = next_b;
+    }
+
+    if conflicted {
+        MergeResult::Conflicted(merged)
+    } else {
+        MergeResult::Clean(merged)
     }
 }
 
@@ -327,7 +603,43 @@ impl SyncManager {
 mod tests {
     use super::*;
     use crate::storage::sqlite::Database;
-    
+
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn sync_manager() -> SyncManager {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        SyncManager::new(Arc::new(Mutex::new(database)))
+    }
+
+    fn local(content: &str, line: u32) -> ReviewComment {
+        ReviewComment {
+            id: "local-1".to_string(),
+            session_id: "session-1".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            line_number: Some(line),
+            content: content.to_string(),
+            comment_type: CommentType::Inline,
+            severity: CommentSeverity::default(),
+            status: CommentStatus::Draft,
+            parent_comment_id: None,
+            created_at: "2025-01-05 10:00:00".to_string(),
+            updated_at: "2025-01-05 10:00:00".to_string(),
+            mentions: Vec::new(),
+        }
+    }
+
+    fn remote(message: &str, line: u32) -> GerritComment {
+        GerritComment {
+            gerrit_comment_id: Some("remote-1".to_string()),
+            change_id: "change-1".to_string(),
+            file_path: "src/lib.rs".to_string(),
+            line,
+            message: message.to_string(),
+            updated: "2025-01-05 11:00:00".to_string(),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_conflict_detection() {
         // Test conflict detection logic
@@ -338,7 +650,7 @@ mod tests {
             current_patch_set_num: 1,
             ..Default::default()
         };
-        
+
         let remote = GerritChange {
             id: "test-change".to_string(),
             change_id: "12345".to_string(),
@@ -346,11 +658,85 @@ mod tests {
             current_patch_set_num: 2,
             ..Default::default()
         };
-        
-        let sync_manager = SyncManager::new(Arc::new(Mutex::new(Database::new_in_memory().unwrap())));
-        let conflicts = sync_manager.detect_conflicts(&local, &remote, SyncType::Full).unwrap();
-        
+
+        let conflicts = sync_manager().detect_conflicts(&local, &remote).unwrap();
+
         assert!(!conflicts.is_empty());
         assert!(conflicts.iter().any(|c| matches!(c.conflict_type, ConflictType::ConcurrentEdit)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_merge_text() {
+        let base = "This loop should use an iterator.";
+        assert_eq!(
+            merge_text(base, "This loop should use an iterator instead.", "Nit: this loop should use an iterator."),
+            MergeResult::Clean("Nit: this loop should use an iterator instead.".to_string()),
+        );
+
+        let merged = merge_text(base, "This loop should use a for_each.", "This loop should use an index.");
+        assert!(matches!(merged, MergeResult::Conflicted(_)));
+        assert!(merged.text().starts_with("This loop should use a "));
+        assert!(merged.text().contains("<<<<<<< mine\nfor_each.\n=======\nindex.\n>>>>>>> theirs"));
+    }
+
+    #[test]
+    fn test_comment_conflicts_and_history() {
+        let manager = sync_manager();
+        let base = CommentVersion::from(&local("Please add a test.", 10));
+        manager.mark_synced("change-1", "local-1", &base).unwrap();
+
+        // Non-overlapping edits on both sides merge automatically and are saved
+        let edited = local("Please add a unit test.", 10);
+        {
+            let db = manager.db.lock().unwrap();
+            db.store_review_comment(&edited).unwrap();
+            let Reconciled::Merged(resolution) = reconcile_comment("change-1", &edited, Some(&remote("Please add a test. Done?", 10)), &db).unwrap() else {
+                panic!("expected an automatic merge");
+            };
+            assert!(resolution.needs_push);
+            assert_eq!(db.get_review_comment("local-1").unwrap().unwrap().content, "Please add a unit test. Done?");
+
+            // Once pushed, an edit made on Gerrit only is taken over
+            let saved = db.get_review_comment("local-1").unwrap().unwrap();
+            db.store_comment_sync_base("change-1", "local-1", &CommentVersion::from(&saved)).unwrap();
+            let Reconciled::Merged(resolution) = reconcile_comment("change-1", &saved, Some(&remote("Please add a unit test. Done!", 10)), &db).unwrap() else {
+                panic!("expected a fast-forward");
+            };
+            assert!(!resolution.needs_push);
+            assert_eq!(db.get_review_comment("local-1").unwrap().unwrap().content, "Please add a unit test. Done!");
+        }
+
+        // The anchor moved on the server while the text was edited locally: re-anchor
+        manager.mark_synced("change-1", "local-1", &base).unwrap();
+        let conflict = manager.comment_conflict("change-1", &edited, Some(&remote("Please add a test.", 14)))
+            .unwrap().unwrap();
+        assert_eq!(conflict.conflict_type, ConflictType::LineModified);
+        let resolution = manager.resolve_conflict(&conflict, ResolutionChoice::ReAnchor).unwrap();
+        let resolved = resolution.comment.unwrap();
+        assert_eq!((resolved.line_number, resolved.content.as_str()), (Some(14), "Please add a unit test."));
+        assert!(resolution.needs_push);
+
+        // Deleted on Gerrit after a local edit needs a decision
+        let conflict = manager.comment_conflict("change-1", &edited, None).unwrap().unwrap();
+        assert_eq!(conflict.conflict_type, ConflictType::CommentDeleted);
+        assert!(conflict.resolution_options.iter().all(|option| !option.auto_resolvable));
+        assert!(matches!(
+            reconcile_comment("change-1", &edited, None, &manager.db.lock().unwrap()).unwrap(),
+            Reconciled::Conflict(_),
+        ));
+        let reposted = manager.resolve_conflict(&conflict, ResolutionChoice::KeepMine).unwrap();
+        assert_eq!(reposted.comment.unwrap().status, CommentStatus::Draft);
+        assert!(manager.db.lock().unwrap().get_comment_sync_base("local-1").unwrap().is_none());
+        assert!(manager.resolve_conflict(&conflict, ResolutionChoice::ReAnchor).is_err());
+
+        let history: Vec<String> = manager.history("change-1").unwrap()
+            .into_iter()
+            .map(|entry| format!("{}:{}", entry.conflict_type, entry.resolution))
+            .collect();
+        assert_eq!(history, vec![
+            "concurrent_edit:auto_merge",
+            "line_modified:re_anchor",
+            "comment_deleted:keep_mine",
+        ]);
+    }
+}
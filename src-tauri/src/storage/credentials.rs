@@ -1,12 +1,23 @@
-// Credential storage using OS Keychain
-// Secure credential management
+// Credential storage for external systems
+// Secrets are kept AES-encrypted in SQLite so they survive restarts
 
 use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::errors::HyperReviewError;
+use crate::services::encryption::EncryptionService;
 
 pub struct CredentialStore {
-    // In production, would use keyring crate for OS keychain integration
-    // For now, using in-memory storage (NOT for production)
     credentials: HashMap<String, Credential>,
+    /// Encrypted table the credentials are written through to; `None` keeps them in memory only
+    vault: Option<Vault>,
+}
+
+struct Vault {
+    conn: Connection,
+    encryption: EncryptionService,
 }
 
 #[derive(Debug, Clone)]
@@ -17,18 +28,72 @@ pub struct Credential {
 }
 
 impl CredentialStore {
+    /// In-memory store, for tests and tools that must not touch saved secrets
     pub fn new() -> Self {
         Self {
             credentials: HashMap::new(),
+            vault: None,
+        }
+    }
+
+    /// Open the persistent store in `db_path`. Secrets are encrypted with the key in
+    /// `key_path`, which is created on first use and readable by the owner only.
+    pub fn open(db_path: &str, key_path: &str) -> Result<Self, HyperReviewError> {
+        let key = load_or_create_key(Path::new(key_path))?;
+        let encryption = EncryptionService::new(&key)
+            .map_err(|e| HyperReviewError::encryption(format!("Failed to initialize credential key: {}", e)))?;
+
+        let conn = Connection::open(db_path).map_err(HyperReviewError::Database)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS credential_vault (
+                service TEXT NOT NULL,
+                username TEXT NOT NULL,
+                secret BLOB NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (service, username)
+            )",
+            [],
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut credentials = HashMap::new();
+        {
+            let mut stmt = conn.prepare("SELECT service, username, secret FROM credential_vault")
+                .map_err(HyperReviewError::Database)?;
+            let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Vec<u8>>(2)?)))
+                .map_err(HyperReviewError::Database)?;
+            for row in rows {
+                let (service, username, secret) = row.map_err(HyperReviewError::Database)?;
+                let password = match encryption.decrypt(&secret).map(String::from_utf8) {
+                    Ok(Ok(password)) => password,
+                    _ => {
+                        // Written with another key; the user has to enter the secret again
+                        log::warn!("Cannot decrypt stored credential for service: {}", service);
+                        continue;
+                    }
+                };
+                credentials.insert(format!("{}:{}", service, username), Credential { service, username, password });
+            }
         }
+
+        log::info!("Loaded {} stored credentials", credentials.len());
+        Ok(Self {
+            credentials,
+            vault: Some(Vault { conn, encryption }),
+        })
     }
 
     /// Store a credential securely
     pub fn store(&mut self, service: &str, username: &str, password: &str) -> Result<(), Box<dyn std::error::Error>> {
         log::info!("Storing credential for service: {}", service);
 
-        // In production, would use OS keychain:
-        // keyring::Entry::new(service, username)?.set_password(password)?;
+        if let Some(vault) = &self.vault {
+            let secret = vault.encryption.encrypt(password.as_bytes())?;
+            vault.conn.execute(
+                "INSERT OR REPLACE INTO credential_vault (service, username, secret, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![service, username, secret, chrono::Utc::now().to_rfc3339()],
+            )?;
+        }
 
         let key = format!("{}:{}", service, username);
         self.credentials.insert(key, Credential {
@@ -44,10 +109,6 @@ impl CredentialStore {
     pub fn retrieve(&self, service: &str, username: &str) -> Result<Option<Credential>, Box<dyn std::error::Error>> {
         log::info!("Retrieving credential for service: {}", service);
 
-        // In production, would use OS keychain:
-        // let entry = keyring::Entry::new(service, username)?;
-        // let password = entry.get_password()?;
-
         let key = format!("{}:{}", service, username);
         Ok(self.credentials.get(&key).cloned())
     }
@@ -56,6 +117,13 @@ impl CredentialStore {
     pub fn delete(&mut self, service: &str, username: &str) -> Result<(), Box<dyn std::error::Error>> {
         log::info!("Deleting credential for service: {}", service);
 
+        if let Some(vault) = &self.vault {
+            vault.conn.execute(
+                "DELETE FROM credential_vault WHERE service = ?1 AND username = ?2",
+                params![service, username],
+            )?;
+        }
+
         let key = format!("{}:{}", service, username);
         self.credentials.remove(&key);
 
@@ -83,3 +151,60 @@ impl Default for CredentialStore {
         Self::new()
     }
 }
+
+/// Read the credential key, generating it the first time
+fn load_or_create_key(path: &Path) -> Result<Vec<u8>, HyperReviewError> {
+    if path.exists() {
+        let key = std::fs::read(path)
+            .map_err(|e| HyperReviewError::config(format!("Failed to read credential key {}: {}", path.display(), e)))?;
+        if key.len() != 32 {
+            return Err(HyperReviewError::config(format!("Credential key {} is corrupt", path.display())));
+        }
+        return Ok(key);
+    }
+
+    let key = EncryptionService::generate_master_key();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)
+        .map_err(|e| HyperReviewError::config(format!("Failed to create credential key {}: {}", path.display(), e)))?;
+    std::io::Write::write_all(&mut file, &key)
+        .map_err(|e| HyperReviewError::config(format!("Failed to write credential key {}: {}", path.display(), e)))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_credentials_survive_reopen() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("review.db");
+        let key_path = dir.path().join("review.key");
+        let (db_path, key_path) = (db_path.to_str().unwrap(), key_path.to_str().unwrap());
+
+        {
+            let mut store = CredentialStore::open(db_path, key_path).unwrap();
+            store.store("gitlab:1", "token", "glpat-secret").unwrap();
+            store.store("jenkins:2", "ci", "api-token").unwrap();
+            store.delete("jenkins:2", "ci").unwrap();
+        }
+
+        // The secret is never written in the clear
+        let raw: Vec<u8> = Connection::open(db_path).unwrap()
+            .query_row("SELECT secret FROM credential_vault", [], |row| row.get(0))
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("glpat-secret"));
+
+        let store = CredentialStore::open(db_path, key_path).unwrap();
+        assert_eq!(store.retrieve("gitlab:1", "token").unwrap().unwrap().password, "glpat-secret");
+        assert!(!store.has_credential("jenkins:2", "ci"));
+    }
+}
@@ -0,0 +1,319 @@
+// Draft Journal
+// Append-only write-ahead log of comments being typed, so they survive a crash
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::HyperReviewError;
+
+/// How often the flusher drops saved and discarded drafts from the journal
+const COMPACT_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// A comment being written that has not been saved yet
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct JournaledDraft {
+    pub draft_key: String,             // Stable ID of the editor, e.g. a new UUID or "edit:<comment_id>"
+    pub session_id: String,
+    pub file_path: String,
+    pub line_number: Option<u32>,
+    pub parent_comment_id: Option<String>, // Set when replying
+    pub comment_id: Option<String>,    // Set when editing an existing comment
+    pub content: String,
+    pub written_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    Save { draft: JournaledDraft },
+    Commit { draft_key: String },      // Saved as a comment
+    Discard { draft_key: String },
+}
+
+impl JournalEntry {
+    fn draft_key(&self) -> &str {
+        match self {
+            JournalEntry::Save { draft } => &draft.draft_key,
+            JournalEntry::Commit { draft_key } | JournalEntry::Discard { draft_key } => draft_key,
+        }
+    }
+}
+
+struct JournalState {
+    pending: HashMap<String, JournaledDraft>, // Latest content not yet written, by draft key
+    last_write: HashMap<String, Instant>,
+}
+
+/// Append-only journal of drafts.
+///
+/// Keystrokes are debounced: a draft is written at most once per debounce interval,
+/// and `flush_due` (called by a background task) writes drafts that have been waiting
+/// longer than that. Saving or discarding a draft appends a marker, and `compact`
+/// rewrites the file with only the drafts that are still unsaved.
+pub struct DraftJournal {
+    path: PathBuf,
+    debounce: Duration,
+    state: Mutex<JournalState>,
+}
+
+impl DraftJournal {
+    pub fn new(path: impl Into<PathBuf>, debounce: Duration) -> Self {
+        Self {
+            path: path.into(),
+            debounce,
+            state: Mutex::new(JournalState {
+                pending: HashMap::new(),
+                last_write: HashMap::new(),
+            }),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record the current text of a draft, writing it now if the debounce interval has passed
+    pub fn record(&self, mut draft: JournaledDraft) -> Result<(), HyperReviewError> {
+        draft.written_at = Utc::now().to_rfc3339();
+        let mut state = self.state.lock().unwrap();
+
+        let due = state.last_write.get(&draft.draft_key)
+            .is_none_or(|last| last.elapsed() >= self.debounce);
+        if !due {
+            state.pending.insert(draft.draft_key.clone(), draft);
+            return Ok(());
+        }
+
+        state.pending.remove(&draft.draft_key);
+        state.last_write.insert(draft.draft_key.clone(), Instant::now());
+        self.append(&[JournalEntry::Save { draft }])
+    }
+
+    /// Write drafts that have waited at least the debounce interval
+    pub fn flush_due(&self) -> Result<usize, HyperReviewError> {
+        let mut state = self.state.lock().unwrap();
+        let due: Vec<String> = state.pending.keys()
+            .filter(|key| state.last_write.get(*key).is_none_or(|last| last.elapsed() >= self.debounce))
+            .cloned()
+            .collect();
+        self.write_pending(&mut state, due)
+    }
+
+    /// Write every pending draft, e.g. on shutdown
+    pub fn flush(&self) -> Result<usize, HyperReviewError> {
+        let mut state = self.state.lock().unwrap();
+        let keys: Vec<String> = state.pending.keys().cloned().collect();
+        self.write_pending(&mut state, keys)
+    }
+
+    /// Mark a draft as saved as a comment
+    pub fn commit(&self, draft_key: &str) -> Result<(), HyperReviewError> {
+        self.close(JournalEntry::Commit { draft_key: draft_key.to_string() })
+    }
+
+    /// Mark a draft as thrown away
+    pub fn discard(&self, draft_key: &str) -> Result<(), HyperReviewError> {
+        self.close(JournalEntry::Discard { draft_key: draft_key.to_string() })
+    }
+
+    /// Drafts that were written but never saved or discarded, oldest first
+    pub fn unsaved_drafts(&self) -> Result<Vec<JournaledDraft>, HyperReviewError> {
+        let mut drafts = open_drafts(self.read_entries()?);
+
+        // Drafts still waiting in memory are newer than anything on disk
+        let state = self.state.lock().unwrap();
+        for draft in state.pending.values() {
+            drafts.retain(|d| d.draft_key != draft.draft_key);
+            drafts.push(draft.clone());
+        }
+
+        Ok(drafts)
+    }
+
+    /// Unsaved drafts of one review session
+    pub fn unsaved_drafts_for_session(&self, session_id: &str) -> Result<Vec<JournaledDraft>, HyperReviewError> {
+        let mut drafts = self.unsaved_drafts()?;
+        drafts.retain(|d| d.session_id == session_id);
+        Ok(drafts)
+    }
+
+    /// Get an unsaved draft by key
+    pub fn get_unsaved(&self, draft_key: &str) -> Result<Option<JournaledDraft>, HyperReviewError> {
+        Ok(self.unsaved_drafts()?.into_iter().find(|d| d.draft_key == draft_key))
+    }
+
+    /// Rewrite the journal with only the unsaved drafts
+    pub fn compact(&self) -> Result<usize, HyperReviewError> {
+        // Held until the file is replaced, so no draft is appended to the old one meanwhile
+        let mut state = self.state.lock().unwrap();
+        let keys: Vec<String> = state.pending.keys().cloned().collect();
+        self.write_pending(&mut state, keys)?;
+        let drafts = open_drafts(self.read_entries()?);
+
+        let tmp = self.path.with_extension("tmp");
+        {
+            let mut file = File::create(&tmp)?;
+            for draft in &drafts {
+                let entry = JournalEntry::Save { draft: draft.clone() };
+                writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+            }
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp, &self.path)?;
+
+        info!("Compacted draft journal to {} unsaved drafts", drafts.len());
+        Ok(drafts.len())
+    }
+
+    /// Periodically write debounced drafts and compact the journal; runs for the
+    /// lifetime of the app
+    pub async fn run_flusher(self: Arc<Self>) {
+        let mut last_compact = Instant::now();
+        loop {
+            tokio::time::sleep(self.debounce).await;
+            if let Err(e) = self.flush_due() {
+                warn!("Failed to write draft journal: {}", e);
+            }
+            if last_compact.elapsed() >= COMPACT_INTERVAL {
+                last_compact = Instant::now();
+                if let Err(e) = self.compact() {
+                    warn!("Failed to compact draft journal: {}", e);
+                }
+            }
+        }
+    }
+
+    fn close(&self, entry: JournalEntry) -> Result<(), HyperReviewError> {
+        let mut state = self.state.lock().unwrap();
+        state.pending.remove(entry.draft_key());
+        state.last_write.remove(entry.draft_key());
+        self.append(&[entry])
+    }
+
+    fn write_pending(&self, state: &mut JournalState, keys: Vec<String>) -> Result<usize, HyperReviewError> {
+        let now = Instant::now();
+        let entries: Vec<JournalEntry> = keys.iter()
+            .filter_map(|key| state.pending.remove(key))
+            .map(|draft| JournalEntry::Save { draft })
+            .collect();
+        for key in keys {
+            state.last_write.insert(key, now);
+        }
+
+        if !entries.is_empty() {
+            self.append(&entries)?;
+            debug!("Wrote {} debounced drafts to the journal", entries.len());
+        }
+        Ok(entries.len())
+    }
+
+    fn append(&self, entries: &[JournalEntry]) -> Result<(), HyperReviewError> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let mut buffer = String::new();
+        for entry in entries {
+            buffer.push_str(&serde_json::to_string(entry)?);
+            buffer.push('\n');
+        }
+        file.write_all(buffer.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    fn read_entries(&self) -> Result<Vec<JournalEntry>, HyperReviewError> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A crash can leave the last line half written
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => warn!("Skipping unreadable draft journal entry: {}", e),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Drafts saved in `entries` and not closed afterwards, oldest first
+fn open_drafts(entries: Vec<JournalEntry>) -> Vec<JournaledDraft> {
+    let mut drafts: Vec<JournaledDraft> = Vec::new();
+    for entry in entries {
+        drafts.retain(|d| d.draft_key != entry.draft_key());
+        if let JournalEntry::Save { draft } = entry {
+            drafts.push(draft);
+        }
+    }
+    drafts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn draft(key: &str, content: &str) -> JournaledDraft {
+        JournaledDraft {
+            draft_key: key.to_string(),
+            session_id: "session-1".to_string(),
+            file_path: "src/main.rs".to_string(),
+            line_number: Some(12),
+            parent_comment_id: None,
+            comment_id: None,
+            content: content.to_string(),
+            written_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_debounced_drafts_survive_restart() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("drafts.jsonl");
+        let journal = DraftJournal::new(&path, Duration::from_secs(60));
+
+        journal.record(draft("a", "T")).unwrap();
+        journal.record(draft("a", "This leaks")).unwrap();
+        journal.record(draft("b", "Nit: rename")).unwrap();
+        journal.discard("b").unwrap();
+
+        // Only the first keystroke of "a" reached the disk before the crash,
+        // which also left a half-written line behind
+        let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, 3);
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap()
+            .write_all(b"{\"op\":\"save\",\"dra").unwrap();
+        drop(journal);
+
+        let recovered = DraftJournal::new(&path, Duration::from_secs(60));
+        assert_eq!(recovered.compact().unwrap(), 1);
+        let drafts = recovered.unsaved_drafts_for_session("session-1").unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].content, "T");
+
+        recovered.record(draft("a", "This leaks")).unwrap();
+        assert_eq!(recovered.get_unsaved("a").unwrap().unwrap().content, "This leaks");
+
+        recovered.commit("a").unwrap();
+        assert!(recovered.unsaved_drafts().unwrap().is_empty());
+        assert_eq!(recovered.compact().unwrap(), 0);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+    }
+}
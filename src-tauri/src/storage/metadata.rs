@@ -6,7 +6,7 @@ use rusqlite::{Connection, Result as SqliteResult, params};
 use log::{info, warn, error, debug};
 
 use crate::errors::HyperReviewError;
-use crate::models::gerrit::{GerritInstance, GerritChange, GerritComment, ConnectionStatus, ChangeStatus};
+use crate::models::gerrit::{GerritInstance, GerritAuthMethod, GerritChange, GerritComment, ConnectionStatus, ChangeStatus};
 
 /// SQLite connection manager for Gerrit operations
 /// Provides thread-safe database access and connection pooling
@@ -215,15 +215,15 @@ impl GerritInstanceRepository {
                 "INSERT INTO gerrit_instances (
                     id, name, url, username, password_encrypted,
                     version, is_active, connection_status, polling_interval, max_changes,
-                    created_at, updated_at, last_connected
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    created_at, updated_at, last_connected, auth_method
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
                 params![
                     instance.id, instance.name, instance.url,
                     instance.username, instance.password_encrypted,
                     instance.version, instance.is_active as i32,
                     instance.connection_status.to_string(), instance.polling_interval,
                     instance.max_changes, instance.created_at, instance.updated_at,
-                    instance.last_connected
+                    instance.last_connected, instance.auth_method.to_string()
                 ],)
             .map_err(|e| HyperReviewError::Other { message: format!("Failed to create Gerrit instance: {}", e) })?;
             
@@ -241,7 +241,9 @@ impl GerritInstanceRepository {
         let conn_guard = conn.lock().unwrap();
         
         let mut stmt = conn_guard.prepare(
-            "SELECT * FROM gerrit_instances WHERE id = ?1"
+            "SELECT id, name, url, username, password_encrypted, version, is_active, connection_status,
+                    polling_interval, max_changes, created_at, updated_at, last_connected, auth_method
+             FROM gerrit_instances WHERE id = ?1"
         ).map_err(|e| HyperReviewError::Other { message: format!("Failed to prepare statement: {}", e) })?;
         
         let mut instances = stmt.query_map([instance_id], |row| {
@@ -252,6 +254,7 @@ impl GerritInstanceRepository {
                 url: row.get(2)?,
                 username: row.get(3)?,
                 password_encrypted: row.get(4)?,
+                auth_method: GerritAuthMethod::from_string(&row.get::<_, String>(13)?),
                 version: row.get(5)?,
                 is_active: row.get(6)?,
                 connection_status: ConnectionStatus::from_string(&status),
@@ -278,7 +281,9 @@ impl GerritInstanceRepository {
         let conn_guard = conn.lock().unwrap();
         
         let mut stmt = conn_guard.prepare(
-            "SELECT * FROM gerrit_instances ORDER BY name"
+            "SELECT id, name, url, username, password_encrypted, version, is_active, connection_status,
+                    polling_interval, max_changes, created_at, updated_at, last_connected, auth_method
+             FROM gerrit_instances ORDER BY name"
         ).map_err(|e| HyperReviewError::Other { message: format!("Failed to prepare statement: {}", e) })?;
         
         let instances = stmt.query_map([], |row| {
@@ -289,6 +294,7 @@ impl GerritInstanceRepository {
                 url: row.get(2)?,
                 username: row.get(3)?,
                 password_encrypted: row.get(4)?,
+                auth_method: GerritAuthMethod::from_string(&row.get::<_, String>(13)?),
                 version: row.get(5)?,
                 is_active: row.get(6)?,
                 connection_status: ConnectionStatus::from_string(&status),
@@ -316,14 +322,15 @@ impl GerritInstanceRepository {
                 "UPDATE gerrit_instances SET
                     name = ?2, url = ?3, username = ?4, password_encrypted = ?5,
                     version = ?6, is_active = ?7, connection_status = ?8, polling_interval = ?9,
-                    max_changes = ?10, updated_at = ?11, last_connected = ?12
+                    max_changes = ?10, updated_at = ?11, last_connected = ?12, auth_method = ?13
                 WHERE id = ?1",
                 params![
                     instance.id, instance.name, instance.url,
                     instance.username, instance.password_encrypted,
                     instance.version, instance.is_active as i32,
                     instance.connection_status.to_string(), instance.polling_interval,
-                    instance.max_changes, instance.updated_at, instance.last_connected
+                    instance.max_changes, instance.updated_at, instance.last_connected,
+                    instance.auth_method.to_string()
                 ],)
             .map_err(|e| HyperReviewError::Other { message: format!("Failed to update Gerrit instance: {}", e) })?;
             
@@ -400,7 +407,8 @@ mod tests {
                 max_changes INTEGER NOT NULL DEFAULT 100,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
-                last_connected TEXT
+                last_connected TEXT,
+                auth_method TEXT NOT NULL DEFAULT 'basic'
             )"
         ).unwrap();
         
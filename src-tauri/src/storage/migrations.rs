@@ -106,6 +106,7 @@ impl MigrationRunner {
             (3, Box::new(V003AddSyncTracking)),
             (4, Box::new(V004AddEncryptionSupport)),
             (5, Box::new(V005AddOfflineCache)),
+            (6, Box::new(V006AddInstanceAuthMethod)),
         ]
     }
 }
@@ -328,31 +329,6 @@ impl Migration for V001CreateGerritTables {
                 CONSTRAINT chk_sync_status CHECK (sync_status IN ('pending', 'synced', 'conflict', 'failed')),
                 CONSTRAINT chk_retry_count CHECK (retry_count >= 0),
                 UNIQUE(entity_type, entity_id)
-            );
-            
-            -- Operation queue for offline sync
-            CREATE TABLE operation_queue (
-                id TEXT PRIMARY KEY,
-                instance_id TEXT NOT NULL,
-                change_id TEXT NOT NULL,
-                operation_type TEXT NOT NULL,
-                payload_encrypted BLOB NOT NULL,
-                priority TEXT NOT NULL DEFAULT 'normal',
-                status TEXT NOT NULL DEFAULT 'queued',
-                retry_count INTEGER NOT NULL DEFAULT 0,
-                max_retries INTEGER NOT NULL DEFAULT 3,
-                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-                last_attempt TEXT,
-                next_retry TEXT,
-                error_message TEXT,
-                
-                FOREIGN KEY (instance_id) REFERENCES gerrit_instances(id) ON DELETE CASCADE,
-                FOREIGN KEY (change_id) REFERENCES gerrit_changes(id) ON DELETE CASCADE,
-                CONSTRAINT chk_operation_type CHECK (operation_type IN ('submit_comment', 'update_review', 'push_patchset', 'sync_change')),
-                CONSTRAINT chk_priority CHECK (priority IN ('low', 'normal', 'high', 'critical')),
-                CONSTRAINT chk_operation_status CHECK (status IN ('queued', 'processing', 'completed', 'failed', 'cancelled')),
-                CONSTRAINT chk_retry_count CHECK (retry_count <= max_retries),
-                CONSTRAINT chk_max_retries CHECK (max_retries BETWEEN 0 AND 10)
             );"
         ).map_err(|e| HyperReviewError::Other { message: e.to_string() })?;
         
@@ -402,13 +378,7 @@ impl Migration for V002AddPerformanceIndexes {
             
             CREATE INDEX idx_sync_status_entity ON sync_status(entity_type, entity_id);
             CREATE INDEX idx_sync_status_status ON sync_status(sync_status);
-            CREATE INDEX idx_sync_status_retry ON sync_status(next_retry_at);
-            
-            CREATE INDEX idx_operation_queue_instance ON operation_queue(instance_id);
-            CREATE INDEX idx_operation_queue_change ON operation_queue(change_id);
-            CREATE INDEX idx_operation_queue_status ON operation_queue(status);
-            CREATE INDEX idx_operation_queue_priority ON operation_queue(priority);
-            CREATE INDEX idx_operation_queue_next_retry ON operation_queue(next_retry);"
+            CREATE INDEX idx_sync_status_retry ON sync_status(next_retry_at);"
         ).map_err(|e| HyperReviewError::Other { message: e.to_string() })?;
         
         Ok(())
@@ -495,4 +465,32 @@ impl Migration for V005AddOfflineCache {
         info!("Offline cache is handled at application level");
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// V006: Remember how each instance authenticates
+struct V006AddInstanceAuthMethod;
+
+impl Migration for V006AddInstanceAuthMethod {
+    fn name(&self,
+    ) -> &str {
+        "add_instance_auth_method"
+    }
+
+    fn run(
+        &self,
+        conn: &Connection,
+    ) -> Result<(), HyperReviewError> {
+        // The main schema may already have added the column
+        let exists: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('gerrit_instances') WHERE name = 'auth_method'",
+            [],
+            |row| row.get(0),
+        ).map_err(|e| HyperReviewError::Other { message: e.to_string() })?;
+        if exists == 0 {
+            conn.execute_batch(
+                "ALTER TABLE gerrit_instances ADD COLUMN auth_method TEXT NOT NULL DEFAULT 'basic';"
+            ).map_err(|e| HyperReviewError::Other { message: e.to_string() })?;
+        }
+        Ok(())
+    }
+}
@@ -9,5 +9,6 @@ pub mod gerrit_instance_repository;
 pub mod migrations;
 pub mod offline_cache;
 pub mod operation_queue;
+pub mod draft_journal;
 pub mod settings;
 pub mod repo_persistence;
@@ -6,12 +6,18 @@ use serde::{Deserialize, Serialize};
 
 use crate::models::gerrit::*;
 use crate::errors::HyperReviewError;
+use crate::storage::sqlite::Database;
 
-/// Manages queued operations for offline sync and retry logic
+/// Manages queued operations for offline sync and retry logic.
+///
+/// Operations stay in the queue until they complete or are cancelled. Operations that
+/// used up their retries remain as `Failed` (blocking their dependents) until retried.
+/// When backed by a database every change is written through, so the queue survives restarts.
 pub struct OperationQueue {
     queue: Arc<Mutex<VecDeque<QueuedOperation>>>,
     processing: Arc<Mutex<HashMap<String, ProcessingInfo>>>,
     stats: Arc<Mutex<QueueStats>>,
+    database: Option<Arc<Mutex<Database>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +37,77 @@ pub struct QueuedOperation {
     pub error_message: Option<String>,
 }
 
+impl QueuedOperation {
+    /// A new operation, ready to run
+    pub fn new(
+        instance_id: &str,
+        change_id: &str,
+        operation_type: OperationType,
+        payload: serde_json::Value,
+        priority: OperationPriority,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            instance_id: instance_id.to_string(),
+            change_id: change_id.to_string(),
+            operation_type,
+            payload,
+            priority,
+            status: OperationStatus::Queued,
+            retry_count: 0,
+            max_retries: 3,
+            created_at: Utc::now(),
+            last_attempt: None,
+            next_retry: None,
+            error_message: None,
+        }
+    }
+
+    /// Comment the operation creates or modifies, from the payload's `comment_id`
+    pub fn comment_id(&self) -> Option<&str> {
+        self.payload.get("comment_id").and_then(|id| id.as_str())
+    }
+
+    /// Comment a queued reply answers, from the payload's `parent_comment_id`
+    pub fn parent_comment_id(&self) -> Option<&str> {
+        self.payload.get("parent_comment_id").and_then(|id| id.as_str())
+    }
+
+    /// Whether this operation has to wait for `earlier` to finish first.
+    ///
+    /// Comment updates and deletions wait for the creation (or earlier update) of the
+    /// same comment, replies wait for the creation of their parent, reviews and label votes wait for the change's pending comments, and
+    /// everything waits for a patch set that is still being pushed.
+    pub fn depends_on(&self, earlier: &QueuedOperation) -> bool {
+        use OperationType::*;
+
+        if earlier.id == self.id
+            || earlier.created_at >= self.created_at
+            || earlier.instance_id != self.instance_id
+            || earlier.change_id != self.change_id
+        {
+            return false;
+        }
+
+        match (self.operation_type, earlier.operation_type) {
+            (_, PushPatchSet) => true,
+            (UpdateComment | DeleteComment, AddComment | UpdateComment) => {
+                self.comment_id().is_some() && self.comment_id() == earlier.comment_id()
+            }
+            (AddComment, AddComment) => {
+                self.parent_comment_id().is_some() && self.parent_comment_id() == earlier.comment_id()
+            }
+            (SubmitReview | UpdateLabels, AddComment | UpdateComment | DeleteComment) => true,
+            _ => false,
+        }
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.status, OperationStatus::Queued | OperationStatus::WaitingForDependency)
+            && self.next_retry.is_none_or(|next_retry| next_retry <= now)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessingInfo {
     pub started_at: DateTime<Utc>,
@@ -65,8 +142,31 @@ impl OperationQueue {
                 average_processing_time_ms: 0.0,
                 success_rate: 0.0,
             })),
+            database: None,
         }
     }
+
+    /// Create a queue persisted in `database`, restoring the operations queued before a restart.
+    /// Operations that were in flight when the application stopped are queued again.
+    pub fn with_database(database: Arc<Mutex<Database>>) -> Result<Self, HyperReviewError> {
+        let operations = database.lock().unwrap().get_queued_operations()?;
+        let mut queue = Self::new();
+        queue.database = Some(database);
+
+        for mut operation in operations {
+            if operation.status == OperationStatus::Processing {
+                operation.status = OperationStatus::Queued;
+                queue.persist(&operation);
+            }
+            queue.insert_by_priority(operation);
+        }
+
+        let restored = queue.queue.lock().unwrap().len();
+        if restored > 0 {
+            info!("Restored {} queued operations", restored);
+        }
+        Ok(queue)
+    }
     
     /// Add an operation to the queue
     pub fn enqueue(
@@ -74,78 +174,148 @@ impl OperationQueue {
         operation: QueuedOperation,
     ) -> Result<String, HyperReviewError> {
         let operation_id = operation.id.clone();
-        let operation_type = operation.operation_type.clone();
+        let operation_type = operation.operation_type;
         let operation_priority = operation.priority;
+
+        if let Some(database) = &self.database {
+            database.lock().unwrap().store_queued_operation(&operation)?;
+        }
+        self.insert_by_priority(operation);
+        self.stats.lock().unwrap().total_enqueued += 1;
+
+        info!("Enqueued operation {} of type {:?} with priority {:?}", 
+              operation_id, operation_type, operation_priority);
         
+        Ok(operation_id)
+    }
+
+    /// Insert based on priority (higher priority first, FIFO within a priority)
+    fn insert_by_priority(&self, operation: QueuedOperation) {
         let mut queue = self.queue.lock().unwrap();
-        let mut stats = self.stats.lock().unwrap();
-        
-        // Insert based on priority (higher priority first)
-        let insert_index = match operation_priority {
-            OperationPriority::Critical => 0,
-            OperationPriority::High => {
+
+        let insert_index = match operation.priority {
+            OperationPriority::Critical => {
                 queue.iter().position(|op| op.priority != OperationPriority::Critical)
                     .unwrap_or(queue.len())
             }
+            OperationPriority::High => {
+                queue.iter().position(|op| matches!(op.priority, OperationPriority::Normal | OperationPriority::Low))
+                    .unwrap_or(queue.len())
+            }
             OperationPriority::Normal => {
                 queue.iter().position(|op| op.priority == OperationPriority::Low)
                     .unwrap_or(queue.len())
             }
             OperationPriority::Low => queue.len(),
         };
-        
+
         queue.insert(insert_index, operation);
-        stats.total_enqueued += 1;
-        stats.current_queue_size = queue.len();
-        
-        info!("Enqueued operation {} of type {:?} with priority {:?}", 
-              operation_id, operation_type, operation_priority);
-        
-        Ok(operation_id)
+        self.stats.lock().unwrap().current_queue_size = queue.len();
     }
-    
+
     /// Get the next operation to process
     pub fn dequeue(
         &self,
         worker_id: &str,
     ) -> Result<Option<QueuedOperation>, HyperReviewError> {
+        self.dequeue_matching(worker_id, |_| true)
+    }
+
+    /// Get the highest priority operation accepted by `filter` that is due and not waiting
+    /// for an earlier operation. Blocked operations are marked `WaitingForDependency`.
+    pub fn dequeue_matching(
+        &self,
+        worker_id: &str,
+        filter: impl Fn(&QueuedOperation) -> bool,
+    ) -> Result<Option<QueuedOperation>, HyperReviewError> {
+        let now = Utc::now();
         let mut queue = self.queue.lock().unwrap();
         let mut processing = self.processing.lock().unwrap();
-        
-        // Find next operation that's not already being processed
-        while let Some(operation) = queue.pop_front() {
-            if !processing.contains_key(&operation.id) {
-                // Mark as processing
-                processing.insert(operation.id.clone(), ProcessingInfo {
-                    started_at: Utc::now(),
-                    worker_id: worker_id.to_string(),
-                    progress: 0.0,
-                    estimated_completion: None,
-                });
-                
-                let mut stats = self.stats.lock().unwrap();
-                stats.current_queue_size = queue.len();
-                stats.processing_count = processing.len();
-                
-                debug!("Dequeued operation {} for processing", operation.id);
-                return Ok(Some(operation));
+
+        let mut selected = None;
+        for index in 0..queue.len() {
+            let operation = &queue[index];
+            if !operation.is_due(now) || !filter(operation) {
+                continue;
+            }
+
+            let blocked = queue.iter().any(|earlier| operation.depends_on(earlier));
+            let status = if blocked { OperationStatus::WaitingForDependency } else { OperationStatus::Processing };
+            if queue[index].status != status {
+                queue[index].status = status;
+                self.persist(&queue[index]);
+            }
+            if !blocked {
+                selected = Some(index);
+                break;
             }
         }
-        
-        Ok(None)
+
+        let Some(index) = selected else {
+            return Ok(None);
+        };
+        let operation = &mut queue[index];
+        operation.last_attempt = Some(now);
+
+        // Mark as processing
+        processing.insert(operation.id.clone(), ProcessingInfo {
+            started_at: now,
+            worker_id: worker_id.to_string(),
+            progress: 0.0,
+            estimated_completion: None,
+        });
+
+        let mut stats = self.stats.lock().unwrap();
+        stats.processing_count = processing.len();
+
+        debug!("Dequeued operation {} for processing", operation.id);
+        Ok(Some(operation.clone()))
     }
-    
-    /// Mark an operation as completed
+
+    /// Mark an operation as completed. Fields of an object `result` (such as the id of a
+    /// created remote comment) are merged into the payloads of queued operations on the same comment.
     pub fn complete_operation(
         &self,
         operation_id: &str,
         success: bool,
         result: Option<serde_json::Value>,
     ) -> Result<(), HyperReviewError> {
+        let mut queue = self.queue.lock().unwrap();
         let mut processing = self.processing.lock().unwrap();
         let mut stats = self.stats.lock().unwrap();
         
         if let Some(_processing_info) = processing.remove(operation_id) {
+            let completed = queue.iter().position(|op| op.id == operation_id)
+                .and_then(|pos| queue.remove(pos));
+            if let (Some(completed), Some(serde_json::Value::Object(fields))) = (&completed, &result) {
+                for dependent in queue.iter_mut().filter(|op| {
+                    op.change_id == completed.change_id
+                        && op.comment_id().is_some()
+                        && op.comment_id() == completed.comment_id()
+                }) {
+                    if let Some(payload) = dependent.payload.as_object_mut() {
+                        payload.extend(fields.clone());
+                    }
+                    self.persist(dependent);
+                }
+
+                // Replies to the created comment answer its new draft
+                if let (OperationType::AddComment, Some(draft_id)) = (completed.operation_type, fields.get("draft_id")) {
+                    for reply in queue.iter_mut().filter(|op| {
+                        op.change_id == completed.change_id
+                            && op.parent_comment_id().is_some()
+                            && op.parent_comment_id() == completed.comment_id()
+                    }) {
+                        if let Some(payload) = reply.payload.as_object_mut() {
+                            payload.insert("in_reply_to".to_string(), draft_id.clone());
+                        }
+                        self.persist(reply);
+                    }
+                }
+            }
+            stats.current_queue_size = queue.len();
+            self.forget(operation_id);
+
             let duration_ms = (Utc::now() - _processing_info.started_at).num_milliseconds() as f64;
             
             // Update statistics
@@ -174,44 +344,66 @@ impl OperationQueue {
         }
     }
     
-    /// Mark an operation as failed and schedule retry
+    /// Mark an operation as failed and schedule a retry with exponential backoff.
+    /// After `max_retries` attempts the operation stays `Failed` until it is retried manually.
     pub fn fail_operation(
         &self,
         operation_id: &str,
         error: &str,
-    ) -> Result<(), HyperReviewError> {
-        let mut processing = self.processing.lock().unwrap();
+    ) -> Result<QueuedOperation, HyperReviewError> {
+        self.record_failure(operation_id, error, true)
+    }
+
+    /// Mark an operation as failed without further retries, e.g. when the server rejected it
+    pub fn abandon_operation(
+        &self,
+        operation_id: &str,
+        error: &str,
+    ) -> Result<QueuedOperation, HyperReviewError> {
+        self.record_failure(operation_id, error, false)
+    }
+
+    fn record_failure(
+        &self,
+        operation_id: &str,
+        error: &str,
+        retryable: bool,
+    ) -> Result<QueuedOperation, HyperReviewError> {
         let mut queue = self.queue.lock().unwrap();
-        
-        if let Some(processing_info) = processing.remove(operation_id) {
-            // Create retry operation
-            let retry_delay = self.calculate_retry_delay(processing_info.started_at);
-            let retry_operation = QueuedOperation {
-                id: format!("{}_retry_{}", operation_id, Utc::now().timestamp()),
-                instance_id: "retry".to_string(), // Would need to store original
-                change_id: "retry".to_string(),
-                operation_type: OperationType::AddComment, // Would need to store original
-                payload: serde_json::json!({"original_id": operation_id, "error": error}),
-                priority: OperationPriority::High, // Retry with higher priority
-                status: OperationStatus::Queued,
-                retry_count: 1,
-                max_retries: 3,
-                created_at: Utc::now(),
-                last_attempt: Some(Utc::now()),
-                next_retry: Some(Utc::now() + chrono::Duration::seconds(retry_delay)),
-                error_message: Some(error.to_string()),
-            };
-            
-            queue.push_back(retry_operation);
-            
-            warn!("Operation {} failed, scheduled retry in {} seconds: {}", 
-                  operation_id, retry_delay, error);
-            
-            Ok(())
-        } else {
+        let mut processing = self.processing.lock().unwrap();
+
+        if processing.remove(operation_id).is_none() {
             warn!("Attempted to fail unknown operation: {}", operation_id);
-            Err(HyperReviewError::Other { message: format!("Operation {} not found in processing", operation_id) })
+            return Err(HyperReviewError::Other { message: format!("Operation {} not found in processing", operation_id) });
         }
+        let operation = queue.iter_mut().find(|op| op.id == operation_id)
+            .ok_or_else(|| HyperReviewError::Other { message: format!("Operation {} not found in queue", operation_id) })?;
+
+        operation.retry_count += 1;
+        operation.error_message = Some(error.to_string());
+        if retryable && operation.retry_count < operation.max_retries {
+            let retry_delay = self.calculate_retry_delay(operation.retry_count);
+            operation.status = OperationStatus::Queued;
+            operation.next_retry = Some(Utc::now() + chrono::Duration::seconds(retry_delay));
+            warn!("Operation {} failed, scheduled retry {}/{} in {} seconds: {}", 
+                  operation_id, operation.retry_count, operation.max_retries, retry_delay, error);
+        } else {
+            operation.status = OperationStatus::Failed;
+            operation.next_retry = None;
+            error!("Operation {} failed after {} attempts: {}", operation_id, operation.retry_count, error);
+
+            let mut stats = self.stats.lock().unwrap();
+            stats.total_failed += 1;
+            let total_ops = stats.total_completed + stats.total_failed;
+            stats.success_rate = stats.total_completed as f64 / total_ops as f64;
+        }
+
+        let mut stats = self.stats.lock().unwrap();
+        stats.processing_count = processing.len();
+        drop(stats);
+
+        self.persist(operation);
+        Ok(operation.clone())
     }
     
     /// Get current queue status
@@ -225,8 +417,8 @@ impl OperationQueue {
         for operation in queue.iter().take(10) { // Return top 10
             pending_operations.push(PendingOperationInfo {
                 id: operation.id.clone(),
-                operation_type: operation.operation_type.clone(),
-                priority: operation.priority.clone(),
+                operation_type: operation.operation_type,
+                priority: operation.priority,
                 created_at: operation.created_at,
                 estimated_completion: self.estimate_completion_time(operation),
             });
@@ -253,6 +445,25 @@ impl OperationQueue {
         
         Ok(operations)
     }
+
+    /// Look up a queued operation
+    pub fn get_operation(&self, operation_id: &str) -> Option<QueuedOperation> {
+        let queue = self.queue.lock().unwrap();
+        queue.iter().find(|op| op.id == operation_id).cloned()
+    }
+
+    /// Instances with at least one operation that is due to run
+    pub fn pending_instances(&self) -> Vec<String> {
+        let now = Utc::now();
+        let queue = self.queue.lock().unwrap();
+        let mut instances: Vec<String> = Vec::new();
+        for operation in queue.iter().filter(|op| op.is_due(now)) {
+            if !instances.contains(&operation.instance_id) {
+                instances.push(operation.instance_id.clone());
+            }
+        }
+        instances
+    }
     
     /// Cancel a specific operation
     pub fn cancel_operation(
@@ -271,6 +482,8 @@ impl OperationQueue {
         let processing_removed = processing.remove(operation_id).is_some();
         
         if queue_removed || processing_removed {
+            self.stats.lock().unwrap().current_queue_size = queue.len();
+            self.forget(operation_id);
             info!("Cancelled operation {}", operation_id);
             Ok(true)
         } else {
@@ -279,32 +492,60 @@ impl OperationQueue {
         }
     }
     
-    /// Retry failed operations
+    /// Queue operations that used up their retries again, optionally only those of one change
     pub fn retry_failed_operations(
         &self,
         change_id: Option<&str>,
     ) -> Result<u32, HyperReviewError> {
+        let mut queue = self.queue.lock().unwrap();
         let mut retry_count = 0;
-        
-        // This would need to track failed operations separately
-        // For now, we'll implement a simple retry mechanism
-        
-        info!("Retrying failed operations for change: {:?}", change_id);
+
+        for operation in queue.iter_mut() {
+            if operation.status != OperationStatus::Failed
+                || change_id.is_some_and(|change_id| operation.change_id != change_id)
+            {
+                continue;
+            }
+            operation.status = OperationStatus::Queued;
+            operation.retry_count = 0;
+            operation.next_retry = None;
+            self.persist(operation);
+            retry_count += 1;
+        }
+
+        info!("Retrying {} failed operations for change: {:?}", retry_count, change_id);
         
         Ok(retry_count)
     }
     
     // Helper methods
     
+    /// Write an operation's state through to the database, if any
+    fn persist(&self, operation: &QueuedOperation) {
+        if let Some(database) = &self.database {
+            if let Err(e) = database.lock().unwrap().store_queued_operation(operation) {
+                error!("Failed to persist operation {}: {}", operation.id, e);
+            }
+        }
+    }
+
+    /// Drop a finished operation from the database, if any
+    fn forget(&self, operation_id: &str) {
+        if let Some(database) = &self.database {
+            if let Err(e) = database.lock().unwrap().delete_queued_operation(operation_id) {
+                error!("Failed to remove operation {}: {}", operation_id, e);
+            }
+        }
+    }
+
     fn calculate_retry_delay(&self,
-        last_attempt: DateTime<Utc>,
+        retry_count: u32,
     ) -> i64 {
-        let elapsed = (Utc::now() - last_attempt).num_seconds();
         let base_delay = 60; // 1 minute base delay
         let max_delay = 3600; // 1 hour max delay
         
         // Exponential backoff with jitter
-        let delay = (base_delay * 2i64.pow((elapsed / 60) as u32)).min(max_delay);
+        let delay = (base_delay * 2i64.pow(retry_count.saturating_sub(1).min(6))).min(max_delay);
         let jitter = (delay as f64 * 0.1) as i64;
         
         delay + jitter
@@ -439,4 +680,54 @@ mod tests {
         let op4 = queue.dequeue("worker-1").unwrap().unwrap();
         assert_eq!(op4.id, "test-op-0"); // LOW priority
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn test_queue_survives_restart() {
+        let database = Database::new(":memory:").unwrap();
+        database.init_gerrit_schema().unwrap();
+        let database = Arc::new(Mutex::new(database));
+
+        let queue = OperationQueue::with_database(database.clone()).unwrap();
+        let mut create = QueuedOperation::new(
+            "instance-1",
+            "change-1",
+            OperationType::AddComment,
+            serde_json::json!({"comment_id": "c1", "file_path": "a.rs", "message": "nit"}),
+            OperationPriority::Normal,
+        );
+        create.created_at = Utc::now() - chrono::Duration::seconds(1);
+        let update = QueuedOperation::new(
+            "instance-1",
+            "change-1",
+            OperationType::UpdateComment,
+            serde_json::json!({"comment_id": "c1", "file_path": "a.rs", "message": "never mind"}),
+            OperationPriority::Critical,
+        );
+        let create_id = queue.enqueue(create).unwrap();
+        let update_id = queue.enqueue(update).unwrap();
+
+        // The create is in flight when the application stops
+        assert_eq!(queue.dequeue("worker-1").unwrap().unwrap().id, create_id);
+        assert_eq!(queue.get_operation(&update_id).unwrap().status, OperationStatus::WaitingForDependency);
+        drop(queue);
+
+        let restored = OperationQueue::with_database(database.clone()).unwrap();
+        assert_eq!(restored.get_operation(&create_id).unwrap().status, OperationStatus::Queued);
+
+        // The critical update still waits for the create of the same comment
+        let next = restored.dequeue("worker-1").unwrap().unwrap();
+        assert_eq!(next.id, create_id);
+        assert_eq!(restored.get_operation(&update_id).unwrap().status, OperationStatus::WaitingForDependency);
+        assert!(restored.dequeue("worker-1").unwrap().is_none());
+
+        restored.complete_operation(&create_id, true, Some(serde_json::json!({"draft_id": "d1"}))).unwrap();
+        let update = restored.dequeue("worker-1").unwrap().unwrap();
+        assert_eq!(update.id, update_id);
+        assert_eq!(update.payload["draft_id"], "d1");
+
+        let stored = database.lock().unwrap().get_queued_operations().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].payload["draft_id"], "d1");
+    }
+}
@@ -2,12 +2,20 @@
 // Local storage for review metadata
 
 use crate::models::{Repo, Comment, CommentStatus};
-use crate::models::gerrit::{GerritInstance, GerritChange, ConnectionStatus, ChangeStatus, ImportStatus, ConflictStatus};
+use crate::models::gerrit::{GerritInstance, GerritAuthMethod, GerritChange, ConnectionStatus, ChangeStatus, ImportStatus, ConflictStatus};
+use crate::models::network::{NetworkSettings, GLOBAL_NETWORK_SCOPE};
+use crate::models::platform::{PlatformInstance, PlatformKind};
+use crate::models::webhook::DeadLetter;
+use crate::models::quality_gate::GateProviderConfig;
+use crate::models::issue::{IssueSubject, IssueTrackerConfig, LinkedIssue, TrackerKind};
+use crate::models::search::{ReviewSearchHit, SearchSourceKind};
+use crate::models::gerrit::{CommentAnchor, CommentVersion, GerritCommentSource, GerritUser, ImportedGerritComment, SeverityCategory, SuggestedChange, ThreadState, ThreadStateChange, OperationPriority, OperationStatus, OperationType, SyncHistoryEntry};
+use crate::storage::operation_queue::QueuedOperation;
 use crate::errors::HyperReviewError;
 use rusqlite::{Connection, Result, params};
 use serde_json;
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, SecondsFormat, Utc};
 
 pub struct Database {
     conn: Connection,
@@ -113,7 +121,9 @@ impl Database {
                 category TEXT,
                 usage_count INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                languages TEXT NOT NULL DEFAULT '[]', -- JSON array
+                file_globs TEXT NOT NULL DEFAULT '[]' -- JSON array
             );
 
             CREATE INDEX IF NOT EXISTS idx_review_templates_category ON review_templates(category);
@@ -224,6 +234,163 @@ impl Database {
             []
         )?;
 
+        // Snippet scoping for review templates
+        for col_name in ["languages", "file_globs"] {
+            let exists: i64 = self.conn.query_row(
+                &format!("SELECT COUNT(*) FROM pragma_table_info('review_templates') WHERE name='{}'", col_name),
+                [],
+                |row| row.get(0),
+            )?;
+            if exists == 0 {
+                log::info!("Adding {} column to review_templates table", col_name);
+                self.conn.execute(
+                    &format!("ALTER TABLE review_templates ADD COLUMN {} TEXT NOT NULL DEFAULT '[]'", col_name),
+                    []
+                )?;
+            }
+        }
+
+        self.init_search_index(&[SearchSourceKind::TaskComment])?;
+
+        Ok(())
+    }
+
+    /// Create the full-text index and the triggers keeping it in sync with the given sources.
+    /// Sources whose triggers are new are indexed from their existing rows.
+    fn init_search_index(&self, kinds: &[SearchSourceKind]) -> Result<(), rusqlite::Error> {
+        // The index used to hold kind and source_id itself, so every delete scanned all of it.
+        // Drop that layout with its triggers; the sources are reindexed below.
+        let legacy_index: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE name = 'review_search' AND sql LIKE '%source_id%'",
+            [],
+            |row| row.get(0),
+        )?;
+        if legacy_index > 0 {
+            self.conn.execute_batch("DROP TABLE review_search;")?;
+            for kind in [SearchSourceKind::ReviewComment, SearchSourceKind::TaskComment, SearchSourceKind::Change] {
+                let table = Self::search_source_table(kind);
+                self.conn.execute_batch(&format!("
+                    DROP TRIGGER IF EXISTS {table}_search_insert;
+                    DROP TRIGGER IF EXISTS {table}_search_update;
+                    DROP TRIGGER IF EXISTS {table}_search_delete;
+                "))?;
+            }
+        }
+
+        self.conn.execute_batch("
+            -- One row per indexed text; title and body are searched, the rest identifies the source
+            CREATE TABLE IF NOT EXISTS review_search_entries (
+                id INTEGER PRIMARY KEY,
+                kind TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                scope_id TEXT,
+                title TEXT,
+                body TEXT,
+                UNIQUE (kind, source_id)
+            );
+
+            -- External-content index over review_search_entries, keyed by its rowid
+            CREATE VIRTUAL TABLE IF NOT EXISTS review_search USING fts5(
+                title,
+                body,
+                content = 'review_search_entries',
+                content_rowid = 'id',
+                tokenize = 'porter unicode61',
+                prefix = '2 3'
+            );
+
+            CREATE TRIGGER IF NOT EXISTS review_search_entries_insert AFTER INSERT ON review_search_entries BEGIN
+                INSERT INTO review_search (rowid, title, body) VALUES (new.id, new.title, new.body);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS review_search_entries_update AFTER UPDATE ON review_search_entries BEGIN
+                INSERT INTO review_search (review_search, rowid, title, body) VALUES ('delete', old.id, old.title, old.body);
+                INSERT INTO review_search (rowid, title, body) VALUES (new.id, new.title, new.body);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS review_search_entries_delete AFTER DELETE ON review_search_entries BEGIN
+                INSERT INTO review_search (review_search, rowid, title, body) VALUES ('delete', old.id, old.title, old.body);
+            END;
+        ")?;
+
+        for &kind in kinds {
+            let table = Self::search_source_table(kind);
+            let watched_columns = match kind {
+                SearchSourceKind::ReviewComment => "session_id, file_path, content",
+                SearchSourceKind::TaskComment => "task_id, file_id, review_comment",
+                SearchSourceKind::Change => "subject, metadata",
+            };
+            let trigger_exists: i64 = self.conn.query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger' AND name = ?1",
+                params![format!("{}_search_insert", table)],
+                |row| row.get(0),
+            )?;
+
+            // REPLACE does not fire delete triggers, so inserts drop any stale entry first.
+            // Entries are found through their (kind, source_id) key, not by scanning the index.
+            self.conn.execute_batch(&format!("
+                CREATE TRIGGER IF NOT EXISTS {table}_search_insert AFTER INSERT ON {table} BEGIN
+                    DELETE FROM review_search_entries WHERE kind = '{kind}' AND source_id = new.id;
+                    INSERT INTO review_search_entries (kind, source_id, scope_id, title, body) VALUES ({new_values});
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {table}_search_update AFTER UPDATE OF id, {watched_columns} ON {table} BEGIN
+                    DELETE FROM review_search_entries WHERE kind = '{kind}' AND source_id = old.id;
+                    INSERT INTO review_search_entries (kind, source_id, scope_id, title, body) VALUES ({new_values});
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS {table}_search_delete AFTER DELETE ON {table} BEGIN
+                    DELETE FROM review_search_entries WHERE kind = '{kind}' AND source_id = old.id;
+                END;
+            ", new_values = Self::search_index_values(kind, "new")))?;
+
+            if trigger_exists == 0 {
+                self.reindex_search_source(kind)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn search_source_table(kind: SearchSourceKind) -> &'static str {
+        match kind {
+            SearchSourceKind::ReviewComment => "review_comments",
+            SearchSourceKind::TaskComment => "file_review_comments",
+            SearchSourceKind::Change => "gerrit_changes",
+        }
+    }
+
+    /// Columns of a review_search row, taken from a row of the source's table
+    fn search_index_values(kind: SearchSourceKind, row: &str) -> String {
+        match kind {
+            SearchSourceKind::ReviewComment => format!(
+                "'{kind}', {row}.id, {row}.session_id, {row}.file_path, {row}.content"
+            ),
+            SearchSourceKind::TaskComment => format!(
+                "'{kind}', {row}.id, {row}.task_id,
+                 COALESCE((SELECT path FROM local_task_files WHERE id = {row}.file_id), {row}.file_id),
+                 {row}.review_comment"
+            ),
+            SearchSourceKind::Change => format!(
+                "'{kind}', {row}.id, {row}.change_id, {row}.subject,
+                 CASE WHEN json_valid({row}.metadata)
+                      THEN COALESCE(json_extract({row}.metadata, '$.commit_message'), '') ELSE '' END"
+            ),
+        }
+    }
+
+    /// Rebuild the index entries of one source from its table
+    pub fn reindex_search_source(&self, kind: SearchSourceKind) -> Result<(), rusqlite::Error> {
+        self.conn.execute("DELETE FROM review_search_entries WHERE kind = ?1", params![kind.to_string()])?;
+        let indexed = self.conn.execute(
+            &format!(
+                "INSERT INTO review_search_entries (kind, source_id, scope_id, title, body) SELECT {} FROM {} src",
+                Self::search_index_values(kind, "src"), Self::search_source_table(kind)
+            ),
+            [],
+        )?;
+
+        log::info!("Indexed {} existing {} entries for search", indexed, kind);
         Ok(())
     }
 
@@ -537,10 +704,13 @@ impl Database {
         log::info!("Storing review template: {}", template.id);
 
         let placeholders_json = serde_json::to_string(&template.placeholders).unwrap_or_else(|_| "[]".to_string());
+        let languages_json = serde_json::to_string(&template.languages).unwrap_or_else(|_| "[]".to_string());
+        let file_globs_json = serde_json::to_string(&template.file_globs).unwrap_or_else(|_| "[]".to_string());
 
         self.conn.execute(
-            "INSERT OR REPLACE INTO review_templates (id, name, content, placeholders, category, usage_count, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT OR REPLACE INTO review_templates
+             (id, name, content, placeholders, category, usage_count, created_at, updated_at, languages, file_globs)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             (
                 &template.id,
                 &template.name,
@@ -550,6 +720,8 @@ impl Database {
                 template.usage_count,
                 &template.created_at,
                 &template.updated_at,
+                &languages_json,
+                &file_globs_json,
             ),
         )?;
 
@@ -559,26 +731,12 @@ impl Database {
     /// Get all review templates
     pub fn get_review_templates(&self) -> Result<Vec<crate::models::ReviewTemplate>, rusqlite::Error> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, content, placeholders, category, usage_count, created_at, updated_at
+            "SELECT id, name, content, placeholders, category, usage_count, created_at, updated_at, languages, file_globs
              FROM review_templates
              ORDER BY usage_count DESC, name ASC"
         )?;
 
-        let template_iter = stmt.query_map([], |row| {
-            let placeholders_str: String = row.get("placeholders")?;
-            let placeholders: Vec<String> = serde_json::from_str(&placeholders_str).unwrap_or_default();
-
-            Ok(crate::models::ReviewTemplate {
-                id: row.get("id")?,
-                name: row.get("name")?,
-                content: row.get("content")?,
-                placeholders,
-                category: row.get("category")?,
-                usage_count: row.get("usage_count")?,
-                created_at: row.get("created_at")?,
-                updated_at: row.get("updated_at")?,
-            })
-        })?;
+        let template_iter = stmt.query_map([], Self::review_template_from_row)?;
 
         let mut templates = Vec::new();
         for template_result in template_iter {
@@ -588,6 +746,37 @@ impl Database {
         Ok(templates)
     }
 
+    /// Get a review template by ID
+    pub fn get_review_template(&self, template_id: &str) -> Result<Option<crate::models::ReviewTemplate>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, content, placeholders, category, usage_count, created_at, updated_at, languages, file_globs
+             FROM review_templates WHERE id = ?1"
+        )?;
+
+        let mut rows = stmt.query_map([template_id], Self::review_template_from_row)?;
+        rows.next().transpose()
+    }
+
+    fn review_template_from_row(row: &rusqlite::Row) -> rusqlite::Result<crate::models::ReviewTemplate> {
+        let json_list = |column: &str| -> rusqlite::Result<Vec<String>> {
+            let json: String = row.get(column)?;
+            Ok(serde_json::from_str(&json).unwrap_or_default())
+        };
+
+        Ok(crate::models::ReviewTemplate {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            content: row.get("content")?,
+            placeholders: json_list("placeholders")?,
+            category: row.get("category")?,
+            usage_count: row.get("usage_count")?,
+            languages: json_list("languages")?,
+            file_globs: json_list("file_globs")?,
+            created_at: row.get("created_at")?,
+            updated_at: row.get("updated_at")?,
+        })
+    }
+
     /// Increment template usage count
     pub fn increment_template_usage(&self, template_id: &str) -> Result<(), rusqlite::Error> {
         self.conn.execute(
@@ -1129,6 +1318,21 @@ impl Database {
 
     /// Create Gerrit tables if they don't exist
     pub fn init_gerrit_schema(&self) -> Result<(), HyperReviewError> {
+        // Comment imports used to be keyed by the Gerrit comment alone, so a second session
+        // on the same change found the first one's comments. Move them aside to re-key below.
+        let legacy_imports: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'gerrit_comment_imports'
+             AND NOT EXISTS (SELECT 1 FROM pragma_table_info('gerrit_comment_imports') WHERE name = 'session_id')",
+            [],
+            |row| row.get(0),
+        ).map_err(HyperReviewError::Database)?;
+        if legacy_imports > 0 {
+            self.conn.execute_batch("
+                DROP INDEX IF EXISTS idx_gerrit_comment_imports_change;
+                ALTER TABLE gerrit_comment_imports RENAME TO gerrit_comment_imports_legacy;
+            ").map_err(HyperReviewError::Database)?;
+        }
+
         self.conn.execute_batch("
             CREATE TABLE IF NOT EXISTS gerrit_instances (
                 id TEXT PRIMARY KEY,
@@ -1136,6 +1340,7 @@ impl Database {
                 url TEXT NOT NULL UNIQUE,
                 username TEXT NOT NULL,
                 password_encrypted TEXT NOT NULL,
+                auth_method TEXT NOT NULL DEFAULT 'basic',
                 version TEXT DEFAULT '',
                 last_connected TEXT,
                 is_active INTEGER NOT NULL DEFAULT 0,
@@ -1229,6 +1434,8 @@ impl Database {
                 parent_comment_id TEXT, -- For threaded comments
                 created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                severity TEXT NOT NULL DEFAULT 'minor', -- 'blocking', 'major', 'minor', 'nit', 'question', 'praise'
+                mentions TEXT NOT NULL DEFAULT '[]', -- JSON array of mentioned accounts
                 FOREIGN KEY (session_id) REFERENCES review_sessions(id) ON DELETE CASCADE,
                 FOREIGN KEY (parent_comment_id) REFERENCES review_comments(id) ON DELETE CASCADE
             );
@@ -1246,6 +1453,199 @@ impl Database {
                 updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
             );
 
+            -- Proxy/TLS settings, keyed by 'global' or an instance ID
+            CREATE TABLE IF NOT EXISTS network_settings (
+                scope TEXT PRIMARY KEY,
+                settings TEXT NOT NULL, -- JSON NetworkSettings
+                updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            -- GitLab/GitHub/CodeArts servers; tokens live in the credential store
+            CREATE TABLE IF NOT EXISTS platform_instances (
+                id TEXT PRIMARY KEY,
+                platform TEXT NOT NULL,
+                name TEXT NOT NULL,
+                base_url TEXT NOT NULL,
+                username TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            -- Custom API deliveries that failed after all retries
+            CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+                id TEXT PRIMARY KEY,
+                method TEXT NOT NULL,
+                url TEXT NOT NULL,
+                body TEXT NOT NULL,
+                error TEXT NOT NULL,
+                status_code INTEGER,
+                attempts INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            -- Jenkins/GitLab/Gerrit quality gate providers
+            CREATE TABLE IF NOT EXISTS quality_gate_providers (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                scope TEXT,
+                source TEXT NOT NULL, -- JSON GateSource
+                created_at TEXT NOT NULL
+            );
+
+            -- Jira/generic issue trackers; tokens live in the credential store
+            CREATE TABLE IF NOT EXISTS issue_trackers (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                base_url TEXT NOT NULL,
+                patterns TEXT NOT NULL, -- JSON array of regexes
+                username TEXT,
+                field_paths TEXT NOT NULL, -- JSON IssueFieldPaths
+                created_at TEXT NOT NULL
+            );
+
+            -- Issue details fetched from trackers
+            CREATE TABLE IF NOT EXISTS issue_cache (
+                tracker_id TEXT NOT NULL,
+                issue_key TEXT NOT NULL,
+                title TEXT,
+                status TEXT,
+                assignee TEXT,
+                url TEXT,
+                fetched_at TEXT NOT NULL,
+                PRIMARY KEY (tracker_id, issue_key)
+            );
+
+            -- Issue keys referenced by changes and tasks
+            CREATE TABLE IF NOT EXISTS issue_links (
+                subject_type TEXT NOT NULL, -- 'change' or 'task'
+                subject_id TEXT NOT NULL,
+                issue_key TEXT NOT NULL,
+                PRIMARY KEY (subject_type, subject_id, issue_key)
+            );
+
+            -- Remote operations queued while offline, replayed by the operation executor
+            CREATE TABLE IF NOT EXISTS operation_queue (
+                id TEXT PRIMARY KEY,
+                instance_id TEXT NOT NULL,
+                change_id TEXT NOT NULL,
+                operation_type TEXT NOT NULL,
+                payload TEXT NOT NULL, -- JSON operation data
+                priority TEXT NOT NULL,
+                status TEXT NOT NULL,
+                retry_count INTEGER NOT NULL DEFAULT 0,
+                max_retries INTEGER NOT NULL DEFAULT 3,
+                created_at TEXT NOT NULL,
+                last_attempt TEXT,
+                next_retry TEXT,
+                error_message TEXT
+            );
+
+            -- Last synced version of each comment, the base for three-way merges
+            CREATE TABLE IF NOT EXISTS comment_sync_base (
+                comment_id TEXT PRIMARY KEY,
+                change_id TEXT NOT NULL,
+                file_path TEXT NOT NULL,
+                line INTEGER NOT NULL,
+                message TEXT NOT NULL,
+                synced_at TEXT NOT NULL
+            );
+
+            -- Conflict resolutions chosen during sync
+            CREATE TABLE IF NOT EXISTS sync_history (
+                id TEXT PRIMARY KEY,
+                change_id TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                conflict_type TEXT NOT NULL,
+                resolution TEXT NOT NULL,
+                details TEXT,
+                resolved_at TEXT NOT NULL
+            );
+
+            -- Line content inline comments were written against, used to re-anchor them
+            CREATE TABLE IF NOT EXISTS comment_anchors (
+                comment_id TEXT PRIMARY KEY,
+                patch_set_number INTEGER NOT NULL,
+                line_content TEXT NOT NULL,
+                outdated INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (comment_id) REFERENCES review_comments(id) ON DELETE CASCADE
+            );
+
+            -- Replacement text of suggestion comments
+            CREATE TABLE IF NOT EXISTS comment_suggestions (
+                comment_id TEXT PRIMARY KEY,
+                file_path TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                original_text TEXT,
+                replacement TEXT NOT NULL,
+                applied_commit TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (comment_id) REFERENCES review_comments(id) ON DELETE CASCADE
+            );
+
+            -- Resolution state of comment threads, keyed by the root comment
+            CREATE TABLE IF NOT EXISTS comment_thread_states (
+                root_comment_id TEXT PRIMARY KEY,
+                state TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (root_comment_id) REFERENCES review_comments(id) ON DELETE CASCADE
+            );
+
+            -- Who changed each thread state, and when
+            CREATE TABLE IF NOT EXISTS comment_thread_state_history (
+                id TEXT PRIMARY KEY,
+                root_comment_id TEXT NOT NULL,
+                from_state TEXT NOT NULL,
+                to_state TEXT NOT NULL,
+                changed_by TEXT NOT NULL,
+                source TEXT NOT NULL,
+                changed_at TEXT NOT NULL,
+                FOREIGN KEY (root_comment_id) REFERENCES review_comments(id) ON DELETE CASCADE
+            );
+
+            -- The team's comment severity taxonomy; the built-in one is used while empty
+            CREATE TABLE IF NOT EXISTS severity_categories (
+                severity TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                description TEXT,
+                blocks_submission INTEGER NOT NULL DEFAULT 0,
+                position INTEGER NOT NULL
+            );
+
+            -- Gerrit comments imported into review comments, so re-imports update them in place
+            CREATE TABLE IF NOT EXISTS gerrit_comment_imports (
+                gerrit_comment_id TEXT NOT NULL,
+                session_id TEXT NOT NULL, -- Each session imports its own copy of a change's comments
+                comment_id TEXT NOT NULL UNIQUE,
+                change_id TEXT NOT NULL,
+                source TEXT NOT NULL,
+                author_account_id INTEGER NOT NULL DEFAULT 0,
+                author_name TEXT NOT NULL DEFAULT '',
+                author_email TEXT NOT NULL DEFAULT '',
+                author_username TEXT,
+                robot_id TEXT,
+                unresolved INTEGER NOT NULL DEFAULT 0,
+                remote_updated TEXT NOT NULL,
+                imported_at TEXT NOT NULL,
+                PRIMARY KEY (gerrit_comment_id, session_id),
+                FOREIGN KEY (comment_id) REFERENCES review_comments(id) ON DELETE CASCADE
+            );
+
+            -- Accounts seen on each instance, for completing @mentions without a round trip
+            CREATE TABLE IF NOT EXISTS gerrit_accounts (
+                instance_id TEXT NOT NULL,
+                account_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                email TEXT NOT NULL,
+                username TEXT,
+                avatar_url TEXT,
+                last_seen TEXT NOT NULL,
+                PRIMARY KEY (instance_id, account_id)
+            );
+
             CREATE INDEX IF NOT EXISTS idx_gerrit_changes_instance ON gerrit_changes(instance_id);
             CREATE INDEX IF NOT EXISTS idx_gerrit_changes_status ON gerrit_changes(status);
             CREATE INDEX IF NOT EXISTS idx_gerrit_changes_import_status ON gerrit_changes(import_status);
@@ -1256,8 +1656,26 @@ impl Database {
             CREATE INDEX IF NOT EXISTS idx_review_comments_session ON review_comments(session_id);
             CREATE INDEX IF NOT EXISTS idx_review_comments_file ON review_comments(file_path);
             CREATE INDEX IF NOT EXISTS idx_review_templates_category ON review_templates(category);
+            CREATE INDEX IF NOT EXISTS idx_operation_queue_change ON operation_queue(change_id);
+            CREATE INDEX IF NOT EXISTS idx_sync_history_change ON sync_history(change_id);
+            CREATE INDEX IF NOT EXISTS idx_thread_state_history_root ON comment_thread_state_history(root_comment_id);
+            CREATE INDEX IF NOT EXISTS idx_gerrit_comment_imports_change ON gerrit_comment_imports(change_id);
         ").map_err(HyperReviewError::Database)?;
 
+        if legacy_imports > 0 {
+            log::info!("Keying Gerrit comment imports by session");
+            self.conn.execute_batch("
+                INSERT OR IGNORE INTO gerrit_comment_imports
+                (gerrit_comment_id, session_id, comment_id, change_id, source, author_account_id, author_name,
+                 author_email, author_username, robot_id, unresolved, remote_updated, imported_at)
+                SELECT i.gerrit_comment_id, c.session_id, i.comment_id, i.change_id, i.source, i.author_account_id,
+                       i.author_name, i.author_email, i.author_username, i.robot_id, i.unresolved, i.remote_updated,
+                       i.imported_at
+                FROM gerrit_comment_imports_legacy i JOIN review_comments c ON c.id = i.comment_id;
+                DROP TABLE gerrit_comment_imports_legacy;
+            ").map_err(HyperReviewError::Database)?;
+        }
+
         // Migration: Add missing columns to existing tables if they don't exist
         // Check if username column exists in gerrit_instances table
         let check_column = |table_name: &str, col_name: &str| -> rusqlite::Result<bool> {
@@ -1347,6 +1765,14 @@ impl Database {
                     []
                 ).map_err(HyperReviewError::Database)?;
             }
+
+            if !check_column("gerrit_instances", "auth_method").unwrap_or(false) {
+                log::info!("Adding auth_method column to gerrit_instances table");
+                self.conn.execute(
+                    "ALTER TABLE gerrit_instances ADD COLUMN auth_method TEXT NOT NULL DEFAULT 'basic'",
+                    []
+                ).map_err(HyperReviewError::Database)?;
+            }
         }
 
         // Similar migration for gerrit_changes table
@@ -1372,6 +1798,25 @@ impl Database {
             }
         }
 
+        if !check_column("review_comments", "severity").unwrap_or(false) {
+            log::info!("Adding severity column to review_comments table");
+            self.conn.execute(
+                "ALTER TABLE review_comments ADD COLUMN severity TEXT NOT NULL DEFAULT 'minor'",
+                []
+            ).map_err(HyperReviewError::Database)?;
+        }
+
+        if !check_column("review_comments", "mentions").unwrap_or(false) {
+            log::info!("Adding mentions column to review_comments table");
+            self.conn.execute(
+                "ALTER TABLE review_comments ADD COLUMN mentions TEXT NOT NULL DEFAULT '[]'",
+                []
+            ).map_err(HyperReviewError::Database)?;
+        }
+
+        self.init_search_index(&[SearchSourceKind::ReviewComment, SearchSourceKind::Change])
+            .map_err(HyperReviewError::Database)?;
+
         Ok(())
     }
 
@@ -1380,8 +1825,9 @@ impl Database {
         self.conn.execute(
             "INSERT OR REPLACE INTO gerrit_instances 
              (id, name, url, username, password_encrypted, version, last_connected, 
-              is_active, connection_status, polling_interval, max_changes, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+              is_active, connection_status, polling_interval, max_changes, created_at, updated_at,
+              auth_method)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
             params![
                 instance.id,
                 instance.name,
@@ -1395,7 +1841,8 @@ impl Database {
                 instance.polling_interval,
                 instance.max_changes,
                 instance.created_at,
-                instance.updated_at
+                instance.updated_at,
+                instance.auth_method.to_string()
             ],
         ).map_err(HyperReviewError::Database)?;
 
@@ -1406,7 +1853,8 @@ impl Database {
     pub fn get_gerrit_instance(&self, instance_id: &str) -> Result<Option<GerritInstance>, HyperReviewError> {
         let mut stmt = self.conn.prepare(
             "SELECT id, name, url, username, password_encrypted, version, last_connected,
-                    is_active, connection_status, polling_interval, max_changes, created_at, updated_at
+                    is_active, connection_status, polling_interval, max_changes, created_at, updated_at,
+                    auth_method
              FROM gerrit_instances WHERE id = ?1"
         ).map_err(HyperReviewError::Database)?;
 
@@ -1417,6 +1865,7 @@ impl Database {
                 url: row.get(2)?,
                 username: row.get(3)?,
                 password_encrypted: row.get(4)?,
+                auth_method: GerritAuthMethod::from_string(&row.get::<_, String>(13)?),
                 version: row.get(5)?,
                 is_active: row.get::<_, i32>(7)? != 0,
                 last_connected: row.get(6)?,
@@ -1439,8 +1888,9 @@ impl Database {
     pub fn get_all_gerrit_instances(&self) -> Result<Vec<GerritInstance>, HyperReviewError> {
         let mut stmt = self.conn.prepare(
             "SELECT id, name, url, username, password_encrypted, version, last_connected,
-                    is_active, connection_status, polling_interval, max_changes, created_at, updated_at
-             FROM gerrit_instances ORDER BY name"
+                    is_active, connection_status, polling_interval, max_changes, created_at, updated_at,
+                    auth_method
+             FROM gerrit_instances WHERE url NOT LIKE 'local:%' ORDER BY name"
         ).map_err(HyperReviewError::Database)?;
 
         let rows = stmt.query_map([], |row| {
@@ -1450,6 +1900,7 @@ impl Database {
                 url: row.get(2)?,
                 username: row.get(3)?,
                 password_encrypted: row.get(4)?,
+                auth_method: GerritAuthMethod::from_string(&row.get::<_, String>(13)?),
                 version: row.get(5)?,
                 is_active: row.get::<_, i32>(7)? != 0,
                 last_connected: row.get(6)?,
@@ -1479,119 +1930,874 @@ impl Database {
         Ok(rows_affected > 0)
     }
 
-    /// Store a Gerrit change
-    pub fn store_gerrit_change(&self, change: &GerritChange) -> Result<(), HyperReviewError> {
+    /// Create the placeholder instance owning changes that were not fetched from a server.
+    /// Placeholders use a `local:` URL and are left out of `get_all_gerrit_instances`.
+    pub fn ensure_local_gerrit_instance(&self, instance_id: &str, name: &str) -> Result<(), HyperReviewError> {
         self.conn.execute(
-            "INSERT OR REPLACE INTO gerrit_changes 
-             (id, change_id, instance_id, project, branch, subject, status, owner_name, owner_email,
-              created_at, updated_at, insertions, deletions, current_revision, current_patch_set_num, 
-              total_files, reviewed_files, local_comments, remote_comments, import_status, last_sync_at, 
-              conflict_status, metadata)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
-            params![
-                change.id,
-                change.change_id,
-                change.instance_id,
-                change.project,
-                change.branch,
-                change.subject,
-                change.status.to_string(),
-                change.owner.name,
-                change.owner.email,
-                change.created,
-                change.updated,
-                change.insertions,
-                change.deletions,
-                change.current_revision,
-                change.current_patch_set_num,
-                change.total_files,
-                change.reviewed_files,
-                change.local_comments,
-                change.remote_comments,
-                change.import_status.to_string(),
-                change.last_sync,
-                change.conflict_status.to_string(),
-                serde_json::to_string(&change.metadata).unwrap_or_default()
-            ],
+            "INSERT OR IGNORE INTO gerrit_instances (id, name, url, username, password_encrypted)
+             VALUES (?1, ?2, ?3, '', '')",
+            params![instance_id, name, format!("local:{}", instance_id)],
         ).map_err(HyperReviewError::Database)?;
 
         Ok(())
     }
 
-    /// Get a Gerrit change by ID
-    pub fn get_gerrit_change(&self, change_id: &str) -> Result<Option<GerritChange>, HyperReviewError> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, change_id, instance_id, project, branch, subject, status, owner_name, owner_email,
-                    created_at, updated_at, insertions, deletions, current_revision, current_patch_set_num, 
-                    total_files, reviewed_files, local_comments, remote_comments, import_status, last_sync_at,
-                    conflict_status, metadata
-             FROM gerrit_changes WHERE id = ?1 OR change_id = ?1"
+    /// Store network settings for a scope ('global' or an instance ID)
+    pub fn store_network_settings(&self, scope: &str, settings: &NetworkSettings) -> Result<(), HyperReviewError> {
+        let json = serde_json::to_string(settings)?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO network_settings (scope, settings, updated_at) VALUES (?1, ?2, ?3)",
+            params![scope, json, Utc::now().to_rfc3339()],
         ).map_err(HyperReviewError::Database)?;
 
-        let result = stmt.query_row(params![change_id], |row| {
-            let metadata_str: String = row.get(22)?;
-            let metadata = serde_json::from_str(&metadata_str).unwrap_or_default();
+        Ok(())
+    }
 
-            Ok(GerritChange {
-                id: row.get(0)?,
-                change_id: row.get(1)?,
-                instance_id: row.get(2)?,
-                project: row.get(3)?,
-                branch: row.get(4)?,
-                subject: row.get(5)?,
-                status: ChangeStatus::from_string(&row.get::<_, String>(6)?),
-                owner: crate::models::gerrit::GerritUser {
-                    account_id: 0, // TODO: Add account_id to schema
-                    name: row.get(7)?,
-                    email: row.get(8)?,
-                    username: None,
-                    avatar_url: None,
-                },
-                created: row.get(9)?,
-                updated: row.get(10)?,
-                insertions: row.get(11)?,
-                deletions: row.get(12)?,
-                current_revision: row.get(13)?,
-                current_patch_set_num: row.get(14)?,
-                patch_sets: Vec::new(), // TODO: Load from patch_sets table
-                files: Vec::new(),      // TODO: Load from gerrit_files table
-                total_files: row.get(15)?,
-                reviewed_files: row.get(16)?,
-                local_comments: row.get(17)?,
-                remote_comments: row.get(18)?,
-                import_status: ImportStatus::from_string(&row.get::<_, String>(19)?),
-                last_sync: row.get(20)?,
-                conflict_status: ConflictStatus::from_string(&row.get::<_, String>(21)?),
-                metadata,
-            })
-        });
+    /// Get network settings stored for a scope
+    pub fn get_network_settings(&self, scope: &str) -> Result<Option<NetworkSettings>, HyperReviewError> {
+        let result = self.conn.query_row(
+            "SELECT settings FROM network_settings WHERE scope = ?1",
+            params![scope],
+            |row| row.get::<_, String>(0),
+        );
 
         match result {
-            Ok(change) => Ok(Some(change)),
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(HyperReviewError::Database(e)),
         }
     }
 
-    /// Update a Gerrit change
-    pub fn update_gerrit_change(&self, change: &GerritChange) -> Result<(), HyperReviewError> {
+    /// Delete network settings for a scope
+    pub fn delete_network_settings(&self, scope: &str) -> Result<bool, HyperReviewError> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM network_settings WHERE scope = ?1",
+            params![scope],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Store a review platform instance
+    pub fn store_platform_instance(&self, instance: &PlatformInstance) -> Result<(), HyperReviewError> {
         self.conn.execute(
-            "UPDATE gerrit_changes SET 
-             subject = ?2, status = ?3, updated_at = ?4, insertions = ?5, deletions = ?6,
-             current_revision = ?7, current_patch_set_num = ?8, total_files = ?9, reviewed_files = ?10,
-             local_comments = ?11, remote_comments = ?12, import_status = ?13,
-             last_sync_at = ?14, conflict_status = ?15, metadata = ?16
-             WHERE id = ?1",
+            "INSERT OR REPLACE INTO platform_instances
+             (id, platform, name, base_url, username, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
-                change.id,
-                change.subject,
-                change.status.to_string(),
-                change.updated,
-                change.insertions,
-                change.deletions,
-                change.current_revision,
-                change.current_patch_set_num,
-                change.total_files,
+                instance.id,
+                instance.platform.to_string(),
+                instance.name,
+                instance.base_url,
+                instance.username,
+                instance.created_at,
+                instance.updated_at,
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Get a review platform instance by ID
+    pub fn get_platform_instance(&self, id: &str) -> Result<Option<PlatformInstance>, HyperReviewError> {
+        Ok(self.query_platform_instances("WHERE id = ?1", params![id])?.into_iter().next())
+    }
+
+    /// List review platform instances, optionally only those of one platform
+    pub fn list_platform_instances(&self, platform: Option<PlatformKind>) -> Result<Vec<PlatformInstance>, HyperReviewError> {
+        match platform {
+            Some(platform) => self.query_platform_instances(
+                "WHERE platform = ?1 ORDER BY name",
+                params![platform.to_string()],
+            ),
+            None => self.query_platform_instances("ORDER BY platform, name", params![]),
+        }
+    }
+
+    /// Delete a review platform instance
+    pub fn delete_platform_instance(&self, id: &str) -> Result<bool, HyperReviewError> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM platform_instances WHERE id = ?1",
+            params![id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    fn query_platform_instances(
+        &self,
+        clause: &str,
+        query_params: &[&dyn rusqlite::ToSql],
+    ) -> Result<Vec<PlatformInstance>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, platform, name, base_url, username, created_at, updated_at
+             FROM platform_instances {}",
+            clause
+        )).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(query_params, |row| {
+            let platform: String = row.get(1)?;
+            Ok((platform, PlatformInstance {
+                id: row.get(0)?,
+                platform: PlatformKind::GitLab,
+                name: row.get(2)?,
+                base_url: row.get(3)?,
+                username: row.get(4)?,
+                created_at: row.get(5)?,
+                updated_at: row.get(6)?,
+            }))
+        }).map_err(HyperReviewError::Database)?;
+
+        let mut instances = Vec::new();
+        for row in rows {
+            let (platform, mut instance) = row.map_err(HyperReviewError::Database)?;
+            match PlatformKind::from_string(&platform) {
+                Some(kind) => {
+                    instance.platform = kind;
+                    instances.push(instance);
+                }
+                None => log::warn!("Skipping platform instance {} with unknown platform '{}'", instance.id, platform),
+            }
+        }
+
+        Ok(instances)
+    }
+
+    /// Record a failed delivery, replacing an earlier record with the same ID
+    pub fn store_dead_letter(&self, letter: &DeadLetter) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO webhook_dead_letters
+             (id, method, url, body, error, status_code, attempts, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                letter.id,
+                letter.method,
+                letter.url,
+                letter.body,
+                letter.error,
+                letter.status_code,
+                letter.attempts,
+                letter.created_at,
+                letter.updated_at,
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Get a failed delivery by ID
+    pub fn get_dead_letter(&self, id: &str) -> Result<Option<DeadLetter>, HyperReviewError> {
+        Ok(self.query_dead_letters("WHERE id = ?1", params![id])?.into_iter().next())
+    }
+
+    /// List failed deliveries, newest first
+    pub fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, HyperReviewError> {
+        self.query_dead_letters("ORDER BY updated_at DESC", params![])
+    }
+
+    /// Remove a failed delivery once it was resent or discarded
+    pub fn delete_dead_letter(&self, id: &str) -> Result<bool, HyperReviewError> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM webhook_dead_letters WHERE id = ?1",
+            params![id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    fn query_dead_letters(
+        &self,
+        clause: &str,
+        query_params: &[&dyn rusqlite::ToSql],
+    ) -> Result<Vec<DeadLetter>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, method, url, body, error, status_code, attempts, created_at, updated_at
+             FROM webhook_dead_letters {}",
+            clause
+        )).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(query_params, |row| {
+            Ok(DeadLetter {
+                id: row.get(0)?,
+                method: row.get(1)?,
+                url: row.get(2)?,
+                body: row.get(3)?,
+                error: row.get(4)?,
+                status_code: row.get(5)?,
+                attempts: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// Store a quality gate provider
+    pub fn store_gate_provider(&self, provider: &GateProviderConfig) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO quality_gate_providers (id, name, scope, source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                provider.id,
+                provider.name,
+                provider.scope,
+                serde_json::to_string(&provider.source)?,
+                provider.created_at,
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// List quality gate providers in the order they were added
+    pub fn list_gate_providers(&self) -> Result<Vec<GateProviderConfig>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, scope, source, created_at FROM quality_gate_providers ORDER BY created_at"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        }).map_err(HyperReviewError::Database)?;
+
+        let mut providers = Vec::new();
+        for row in rows {
+            let (id, name, scope, source, created_at) = row.map_err(HyperReviewError::Database)?;
+            match serde_json::from_str(&source) {
+                Ok(source) => providers.push(GateProviderConfig { id, name, scope, source, created_at }),
+                Err(e) => log::warn!("Skipping quality gate provider {} with invalid source: {}", id, e),
+            }
+        }
+
+        Ok(providers)
+    }
+
+    /// Delete a quality gate provider
+    pub fn delete_gate_provider(&self, id: &str) -> Result<bool, HyperReviewError> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM quality_gate_providers WHERE id = ?1",
+            params![id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Store an issue tracker
+    pub fn store_issue_tracker(&self, tracker: &IssueTrackerConfig) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO issue_trackers
+             (id, name, kind, base_url, patterns, username, field_paths, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                tracker.id,
+                tracker.name,
+                tracker.kind.to_string(),
+                tracker.base_url,
+                serde_json::to_string(&tracker.patterns)?,
+                tracker.username,
+                serde_json::to_string(&tracker.field_paths)?,
+                tracker.created_at,
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// List issue trackers in the order they were added
+    pub fn list_issue_trackers(&self) -> Result<Vec<IssueTrackerConfig>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, kind, base_url, patterns, username, field_paths, created_at
+             FROM issue_trackers ORDER BY created_at"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        }).map_err(HyperReviewError::Database)?;
+
+        let mut trackers = Vec::new();
+        for row in rows {
+            let (id, name, kind, base_url, patterns, username, field_paths, created_at) =
+                row.map_err(HyperReviewError::Database)?;
+            let kind = match kind.as_str() {
+                "jira" => TrackerKind::Jira,
+                "generic" => TrackerKind::Generic,
+                other => {
+                    log::warn!("Skipping issue tracker {} with unknown kind '{}'", id, other);
+                    continue;
+                }
+            };
+            trackers.push(IssueTrackerConfig {
+                id,
+                name,
+                kind,
+                base_url,
+                patterns: serde_json::from_str(&patterns)?,
+                username,
+                field_paths: serde_json::from_str(&field_paths)?,
+                created_at,
+            });
+        }
+
+        Ok(trackers)
+    }
+
+    /// Delete an issue tracker and the issues cached from it
+    pub fn delete_issue_tracker(&self, id: &str) -> Result<bool, HyperReviewError> {
+        self.conn.execute(
+            "DELETE FROM issue_cache WHERE tracker_id = ?1",
+            params![id],
+        ).map_err(HyperReviewError::Database)?;
+        let rows_affected = self.conn.execute(
+            "DELETE FROM issue_trackers WHERE id = ?1",
+            params![id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Cache resolved issue details
+    pub fn store_cached_issue(&self, issue: &LinkedIssue) -> Result<(), HyperReviewError> {
+        let tracker_id = issue.tracker_id.as_deref()
+            .ok_or_else(|| HyperReviewError::other(format!("Issue {} has no tracker", issue.key)))?;
+        let fetched_at = issue.fetched_at.clone().unwrap_or_else(|| Utc::now().to_rfc3339());
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO issue_cache
+             (tracker_id, issue_key, title, status, assignee, url, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![tracker_id, issue.key, issue.title, issue.status, issue.assignee, issue.url, fetched_at],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Most recently fetched details of an issue, from any tracker
+    pub fn get_cached_issue(&self, key: &str) -> Result<Option<LinkedIssue>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tracker_id, issue_key, title, status, assignee, url, fetched_at
+             FROM issue_cache WHERE issue_key = ?1 ORDER BY fetched_at DESC LIMIT 1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![key], |row| {
+            Ok(LinkedIssue {
+                tracker_id: Some(row.get(0)?),
+                key: row.get(1)?,
+                title: row.get(2)?,
+                status: row.get(3)?,
+                assignee: row.get(4)?,
+                url: row.get(5)?,
+                fetched_at: Some(row.get(6)?),
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.next().transpose().map_err(HyperReviewError::Database)
+    }
+
+    /// Replace the issue keys linked to a change or task
+    pub fn set_issue_links(&self, subject: IssueSubject, subject_id: &str, keys: &[String]) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "DELETE FROM issue_links WHERE subject_type = ?1 AND subject_id = ?2",
+            params![subject.to_string(), subject_id],
+        ).map_err(HyperReviewError::Database)?;
+
+        for key in keys {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO issue_links (subject_type, subject_id, issue_key) VALUES (?1, ?2, ?3)",
+                params![subject.to_string(), subject_id, key],
+            ).map_err(HyperReviewError::Database)?;
+        }
+
+        Ok(())
+    }
+
+    /// Issue keys linked to a change or task
+    pub fn get_issue_links(&self, subject: IssueSubject, subject_id: &str) -> Result<Vec<String>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT issue_key FROM issue_links WHERE subject_type = ?1 AND subject_id = ?2 ORDER BY issue_key"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![subject.to_string(), subject_id], |row| row.get(0))
+            .map_err(HyperReviewError::Database)?;
+        rows.collect::<Result<Vec<String>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// Insert or update a queued remote operation
+    pub fn store_queued_operation(&self, operation: &QueuedOperation) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO operation_queue
+             (id, instance_id, change_id, operation_type, payload, priority, status, retry_count, max_retries,
+              created_at, last_attempt, next_retry, error_message)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                operation.id,
+                operation.instance_id,
+                operation.change_id,
+                operation.operation_type.to_string(),
+                serde_json::to_string(&operation.payload)?,
+                operation.priority.to_string(),
+                operation.status.to_string(),
+                operation.retry_count,
+                operation.max_retries,
+                operation.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+                operation.last_attempt.map(|t| t.to_rfc3339_opts(SecondsFormat::Micros, true)),
+                operation.next_retry.map(|t| t.to_rfc3339_opts(SecondsFormat::Micros, true)),
+                operation.error_message,
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// All queued remote operations, oldest first
+    pub fn get_queued_operations(&self) -> Result<Vec<QueuedOperation>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, instance_id, change_id, operation_type, payload, priority, status, retry_count, max_retries,
+                    created_at, last_attempt, next_retry, error_message
+             FROM operation_queue ORDER BY created_at"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                QueuedOperation {
+                    id: row.get(0)?,
+                    instance_id: row.get(1)?,
+                    change_id: row.get(2)?,
+                    operation_type: OperationType::from_string(&row.get::<_, String>(3)?),
+                    payload: serde_json::Value::Null,
+                    priority: OperationPriority::from_string(&row.get::<_, String>(5)?),
+                    status: OperationStatus::from_string(&row.get::<_, String>(6)?),
+                    retry_count: row.get(7)?,
+                    max_retries: row.get(8)?,
+                    created_at: parse_utc(&row.get::<_, String>(9)?).unwrap_or_else(Utc::now),
+                    last_attempt: row.get::<_, Option<String>>(10)?.as_deref().and_then(parse_utc),
+                    next_retry: row.get::<_, Option<String>>(11)?.as_deref().and_then(parse_utc),
+                    error_message: row.get(12)?,
+                },
+                row.get::<_, String>(4)?,
+            ))
+        }).map_err(HyperReviewError::Database)?;
+
+        let mut operations = Vec::new();
+        for row in rows {
+            let (mut operation, payload) = row.map_err(HyperReviewError::Database)?;
+            operation.payload = serde_json::from_str(&payload)?;
+            operations.push(operation);
+        }
+        Ok(operations)
+    }
+
+    /// Remove a queued operation once it has completed or was cancelled
+    pub fn delete_queued_operation(&self, id: &str) -> Result<bool, HyperReviewError> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM operation_queue WHERE id = ?1",
+            params![id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Remember the version of a comment that was last in sync with the server
+    pub fn store_comment_sync_base(&self, change_id: &str, comment_id: &str, version: &CommentVersion) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO comment_sync_base (comment_id, change_id, file_path, line, message, synced_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![comment_id, change_id, version.file_path, version.line, version.message, Utc::now().to_rfc3339()],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Last synced version of a comment
+    pub fn get_comment_sync_base(&self, comment_id: &str) -> Result<Option<CommentVersion>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT file_path, line, message FROM comment_sync_base WHERE comment_id = ?1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![comment_id], |row| {
+            Ok(CommentVersion {
+                file_path: row.get(0)?,
+                line: row.get(1)?,
+                message: row.get(2)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.next().transpose().map_err(HyperReviewError::Database)
+    }
+
+    /// Forget the synced version of a comment that no longer exists on the server
+    pub fn delete_comment_sync_base(&self, comment_id: &str) -> Result<bool, HyperReviewError> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM comment_sync_base WHERE comment_id = ?1",
+            params![comment_id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Link a Gerrit comment to the review comment it was imported as
+    pub fn store_imported_gerrit_comment(&self, import: &ImportedGerritComment) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO gerrit_comment_imports
+             (gerrit_comment_id, comment_id, change_id, source, author_account_id, author_name, author_email,
+              author_username, robot_id, unresolved, remote_updated, imported_at, session_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                import.gerrit_comment_id,
+                import.comment_id,
+                import.change_id,
+                import.source.to_string(),
+                import.author.account_id,
+                import.author.name,
+                import.author.email,
+                import.author.username,
+                import.robot_id,
+                import.unresolved,
+                import.remote_updated,
+                import.imported_at,
+                import.session_id,
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// The import of a Gerrit comment into a session, if it was imported there before
+    pub fn get_imported_gerrit_comment(&self, session_id: &str, gerrit_comment_id: &str) -> Result<Option<ImportedGerritComment>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT gerrit_comment_id, comment_id, change_id, source, author_account_id, author_name, author_email,
+                    author_username, robot_id, unresolved, remote_updated, imported_at, session_id
+             FROM gerrit_comment_imports WHERE session_id = ?1 AND gerrit_comment_id = ?2"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![session_id, gerrit_comment_id], Self::imported_gerrit_comment_from_row)
+            .map_err(HyperReviewError::Database)?;
+
+        rows.next().transpose().map_err(HyperReviewError::Database)
+    }
+
+    /// Gerrit comments imported for a change
+    pub fn get_imported_gerrit_comments(&self, change_id: &str) -> Result<Vec<ImportedGerritComment>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT gerrit_comment_id, comment_id, change_id, source, author_account_id, author_name, author_email,
+                    author_username, robot_id, unresolved, remote_updated, imported_at, session_id
+             FROM gerrit_comment_imports WHERE change_id = ?1 ORDER BY remote_updated, rowid"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![change_id], Self::imported_gerrit_comment_from_row)
+            .map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// Remote authors of the imported comments of a session, by comment ID
+    pub fn get_comment_authors_for_session(&self, session_id: &str) -> Result<std::collections::HashMap<String, String>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT i.comment_id, i.author_name FROM gerrit_comment_imports i
+             JOIN review_comments c ON c.id = i.comment_id
+             WHERE c.session_id = ?1 AND i.author_name != ''
+             ORDER BY i.remote_updated, i.rowid"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![session_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<std::collections::HashMap<_, _>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// The latest Gerrit comment a review comment was imported from or pushed as
+    pub fn get_comment_import(&self, comment_id: &str) -> Result<Option<ImportedGerritComment>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT gerrit_comment_id, comment_id, change_id, source, author_account_id, author_name, author_email,
+                    author_username, robot_id, unresolved, remote_updated, imported_at, session_id
+             FROM gerrit_comment_imports WHERE comment_id = ?1 ORDER BY remote_updated DESC, rowid DESC LIMIT 1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![comment_id], Self::imported_gerrit_comment_from_row)
+            .map_err(HyperReviewError::Database)?;
+
+        rows.next().transpose().map_err(HyperReviewError::Database)
+    }
+
+    /// Unlink a review comment from the Gerrit comments it was imported from or pushed as
+    pub fn delete_imported_gerrit_comments_for(&self, comment_id: &str) -> Result<usize, HyperReviewError> {
+        self.conn.execute(
+            "DELETE FROM gerrit_comment_imports WHERE comment_id = ?1",
+            params![comment_id],
+        ).map_err(HyperReviewError::Database)
+    }
+
+    /// Record that the drafts of a change were published by a review
+    pub fn mark_gerrit_drafts_published(&self, change_id: &str) -> Result<usize, HyperReviewError> {
+        self.conn.execute(
+            "UPDATE gerrit_comment_imports SET source = ?1 WHERE change_id = ?2 AND source = ?3",
+            params![
+                GerritCommentSource::Published.to_string(),
+                change_id,
+                GerritCommentSource::Draft.to_string(),
+            ],
+        ).map_err(HyperReviewError::Database)
+    }
+
+    fn imported_gerrit_comment_from_row(row: &rusqlite::Row) -> rusqlite::Result<ImportedGerritComment> {
+        Ok(ImportedGerritComment {
+            gerrit_comment_id: row.get(0)?,
+            comment_id: row.get(1)?,
+            change_id: row.get(2)?,
+            source: GerritCommentSource::from_string(&row.get::<_, String>(3)?),
+            author: GerritUser {
+                account_id: row.get(4)?,
+                name: row.get(5)?,
+                email: row.get(6)?,
+                username: row.get(7)?,
+                avatar_url: None,
+            },
+            robot_id: row.get(8)?,
+            unresolved: row.get(9)?,
+            remote_updated: row.get(10)?,
+            imported_at: row.get(11)?,
+            session_id: row.get(12)?,
+        })
+    }
+
+    /// Add or refresh accounts of an instance's directory. Accounts without an ID are
+    /// skipped, and known details are kept when a record leaves them out.
+    pub fn store_gerrit_accounts(&self, instance_id: &str, accounts: &[GerritUser]) -> Result<usize, HyperReviewError> {
+        let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut stored = 0;
+        for account in accounts.iter().filter(|account| account.account_id != 0) {
+            stored += self.conn.execute(
+                "INSERT INTO gerrit_accounts (instance_id, account_id, name, email, username, avatar_url, last_seen)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT (instance_id, account_id) DO UPDATE SET
+                     name = COALESCE(NULLIF(excluded.name, ''), name),
+                     email = COALESCE(NULLIF(excluded.email, ''), email),
+                     username = COALESCE(excluded.username, username),
+                     avatar_url = COALESCE(excluded.avatar_url, avatar_url),
+                     last_seen = excluded.last_seen",
+                params![
+                    instance_id,
+                    account.account_id,
+                    account.name,
+                    account.email,
+                    account.username,
+                    account.avatar_url,
+                    now,
+                ],
+            ).map_err(HyperReviewError::Database)?;
+        }
+
+        Ok(stored)
+    }
+
+    /// Accounts of an instance whose username, email or any word of the name starts with `prefix`
+    pub fn search_gerrit_accounts(&self, instance_id: &str, prefix: &str, limit: u32) -> Result<Vec<GerritUser>, HyperReviewError> {
+        let escaped = prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let mut stmt = self.conn.prepare(
+            "SELECT account_id, name, email, username, avatar_url FROM gerrit_accounts
+             WHERE instance_id = ?1
+               AND (username LIKE ?2 ESCAPE '\\' OR email LIKE ?2 ESCAPE '\\'
+                    OR name LIKE ?2 ESCAPE '\\' OR name LIKE ?3 ESCAPE '\\')
+             ORDER BY last_seen DESC, name
+             LIMIT ?4"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(
+            params![instance_id, format!("{}%", escaped), format!("% {}%", escaped), limit],
+            Self::gerrit_account_from_row,
+        ).map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// The account of an instance with the given username or email, ignoring case
+    pub fn find_gerrit_account(&self, instance_id: &str, handle: &str) -> Result<Option<GerritUser>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT account_id, name, email, username, avatar_url FROM gerrit_accounts
+             WHERE instance_id = ?1 AND (username = ?2 COLLATE NOCASE OR email = ?2 COLLATE NOCASE)
+             ORDER BY username = ?2 COLLATE NOCASE DESC, last_seen DESC
+             LIMIT 1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![instance_id, handle], Self::gerrit_account_from_row)
+            .map_err(HyperReviewError::Database)?;
+
+        rows.next().transpose().map_err(HyperReviewError::Database)
+    }
+
+    fn gerrit_account_from_row(row: &rusqlite::Row) -> rusqlite::Result<GerritUser> {
+        Ok(GerritUser {
+            account_id: row.get(0)?,
+            name: row.get(1)?,
+            email: row.get(2)?,
+            username: row.get(3)?,
+            avatar_url: row.get(4)?,
+        })
+    }
+
+    /// Record a conflict resolution
+    pub fn add_sync_history(&self, entry: &SyncHistoryEntry) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT INTO sync_history (id, change_id, entity_id, conflict_type, resolution, details, resolved_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                entry.id,
+                entry.change_id,
+                entry.entity_id,
+                entry.conflict_type,
+                entry.resolution,
+                entry.details,
+                entry.resolved_at,
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Conflict resolutions recorded for a change, oldest first
+    pub fn get_sync_history(&self, change_id: &str) -> Result<Vec<SyncHistoryEntry>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, change_id, entity_id, conflict_type, resolution, details, resolved_at
+             FROM sync_history WHERE change_id = ?1 ORDER BY resolved_at, rowid"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![change_id], |row| {
+            Ok(SyncHistoryEntry {
+                id: row.get(0)?,
+                change_id: row.get(1)?,
+                entity_id: row.get(2)?,
+                conflict_type: row.get(3)?,
+                resolution: row.get(4)?,
+                details: row.get(5)?,
+                resolved_at: row.get(6)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// Global network settings with any overrides for the instance applied
+    pub fn get_effective_network_settings(&self, instance_id: Option<&str>) -> Result<NetworkSettings, HyperReviewError> {
+        let global = self.get_network_settings(GLOBAL_NETWORK_SCOPE)?.unwrap_or_default();
+        let instance = match instance_id {
+            Some(id) => self.get_network_settings(id)?,
+            None => None,
+        };
+
+        Ok(match instance {
+            Some(instance) => global.overlay(&instance),
+            None => global,
+        })
+    }
+
+    /// Store a Gerrit change
+    pub fn store_gerrit_change(&self, change: &GerritChange) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO gerrit_changes 
+             (id, change_id, instance_id, project, branch, subject, status, owner_name, owner_email,
+              created_at, updated_at, insertions, deletions, current_revision, current_patch_set_num, 
+              total_files, reviewed_files, local_comments, remote_comments, import_status, last_sync_at, 
+              conflict_status, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
+            params![
+                change.id,
+                change.change_id,
+                change.instance_id,
+                change.project,
+                change.branch,
+                change.subject,
+                change.status.to_string(),
+                change.owner.name,
+                change.owner.email,
+                change.created,
+                change.updated,
+                change.insertions,
+                change.deletions,
+                change.current_revision,
+                change.current_patch_set_num,
+                change.total_files,
+                change.reviewed_files,
+                change.local_comments,
+                change.remote_comments,
+                change.import_status.to_string(),
+                change.last_sync,
+                change.conflict_status.to_string(),
+                serde_json::to_string(&change.metadata).unwrap_or_default()
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Get a Gerrit change by ID
+    pub fn get_gerrit_change(&self, change_id: &str) -> Result<Option<GerritChange>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, change_id, instance_id, project, branch, subject, status, owner_name, owner_email,
+                    created_at, updated_at, insertions, deletions, current_revision, current_patch_set_num, 
+                    total_files, reviewed_files, local_comments, remote_comments, import_status, last_sync_at,
+                    conflict_status, metadata
+             FROM gerrit_changes WHERE id = ?1 OR change_id = ?1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let result = stmt.query_row(params![change_id], gerrit_change_from_row);
+
+        match result {
+            Ok(change) => Ok(Some(change)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(HyperReviewError::Database(e)),
+        }
+    }
+
+    /// Get the change with a Gerrit change ID stored for an instance
+    pub fn get_gerrit_change_by_change_id(&self, instance_id: &str, change_id: &str) -> Result<Option<GerritChange>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, change_id, instance_id, project, branch, subject, status, owner_name, owner_email,
+                    created_at, updated_at, insertions, deletions, current_revision, current_patch_set_num,
+                    total_files, reviewed_files, local_comments, remote_comments, import_status, last_sync_at,
+                    conflict_status, metadata
+             FROM gerrit_changes WHERE instance_id = ?1 AND change_id = ?2"
+        ).map_err(HyperReviewError::Database)?;
+
+        match stmt.query_row(params![instance_id, change_id], gerrit_change_from_row) {
+            Ok(change) => Ok(Some(change)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(HyperReviewError::Database(e)),
+        }
+    }
+
+    /// Update a Gerrit change
+    pub fn update_gerrit_change(&self, change: &GerritChange) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "UPDATE gerrit_changes SET 
+             subject = ?2, status = ?3, updated_at = ?4, insertions = ?5, deletions = ?6,
+             current_revision = ?7, current_patch_set_num = ?8, total_files = ?9, reviewed_files = ?10,
+             local_comments = ?11, remote_comments = ?12, import_status = ?13,
+             last_sync_at = ?14, conflict_status = ?15, metadata = ?16
+             WHERE id = ?1",
+            params![
+                change.id,
+                change.subject,
+                change.status.to_string(),
+                change.updated,
+                change.insertions,
+                change.deletions,
+                change.current_revision,
+                change.current_patch_set_num,
+                change.total_files,
                 change.reviewed_files,
                 change.local_comments,
                 change.remote_comments,
@@ -1806,6 +3012,18 @@ impl Database {
         Ok(())
     }
 
+    /// Move a review session to another patch set without touching its comments
+    pub fn update_review_session_patch_set(&self, session_id: &str, patch_set_number: u32) -> Result<(), HyperReviewError> {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        self.conn.execute(
+            "UPDATE review_sessions SET patch_set_number = ?1, updated_at = ?2 WHERE id = ?3",
+            params![patch_set_number, now, session_id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
     /// Get all review sessions for a reviewer
     pub fn get_review_sessions_for_reviewer(&self, reviewer_id: &str) -> Result<Vec<crate::models::gerrit::ReviewSession>, HyperReviewError> {
         let mut stmt = self.conn.prepare(
@@ -2037,8 +3255,8 @@ impl Database {
     pub fn store_review_comment(&self, comment: &crate::models::gerrit::ReviewComment) -> Result<(), HyperReviewError> {
         self.conn.execute(
             "INSERT OR REPLACE INTO review_comments 
-             (id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+             (id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at, severity, mentions)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             params![
                 comment.id,
                 comment.session_id,
@@ -2049,7 +3267,9 @@ impl Database {
                 comment.status.to_string(),
                 comment.parent_comment_id,
                 comment.created_at,
-                comment.updated_at
+                comment.updated_at,
+                comment.severity.to_string(),
+                serde_json::to_string(&comment.mentions)?
             ],
         ).map_err(HyperReviewError::Database)?;
 
@@ -2059,7 +3279,7 @@ impl Database {
     /// Get comments for a file in a session
     pub fn get_review_comments_for_file(&self, session_id: &str, file_path: &str) -> Result<Vec<crate::models::gerrit::ReviewComment>, HyperReviewError> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at
+            "SELECT id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at, severity, mentions
              FROM review_comments WHERE session_id = ?1 AND file_path = ?2 ORDER BY line_number, created_at"
         ).map_err(HyperReviewError::Database)?;
 
@@ -2071,10 +3291,12 @@ impl Database {
                 line_number: row.get(3)?,
                 content: row.get(4)?,
                 comment_type: crate::models::gerrit::CommentType::from_string(&row.get::<_, String>(5)?),
+                severity: crate::models::gerrit::CommentSeverity::from_string(&row.get::<_, String>(10)?),
                 status: crate::models::gerrit::CommentStatus::from_string(&row.get::<_, String>(6)?),
                 parent_comment_id: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                mentions: serde_json::from_str(&row.get::<_, String>(11)?).unwrap_or_default(),
             })
         }).map_err(HyperReviewError::Database)?;
 
@@ -2089,7 +3311,7 @@ impl Database {
     /// Get all comments for a session
     pub fn get_review_comments_for_session(&self, session_id: &str) -> Result<Vec<crate::models::gerrit::ReviewComment>, HyperReviewError> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at
+            "SELECT id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at, severity, mentions
              FROM review_comments WHERE session_id = ?1 ORDER BY file_path, line_number, created_at"
         ).map_err(HyperReviewError::Database)?;
 
@@ -2101,10 +3323,12 @@ impl Database {
                 line_number: row.get(3)?,
                 content: row.get(4)?,
                 comment_type: crate::models::gerrit::CommentType::from_string(&row.get::<_, String>(5)?),
+                severity: crate::models::gerrit::CommentSeverity::from_string(&row.get::<_, String>(10)?),
                 status: crate::models::gerrit::CommentStatus::from_string(&row.get::<_, String>(6)?),
                 parent_comment_id: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                mentions: serde_json::from_str(&row.get::<_, String>(11)?).unwrap_or_default(),
             })
         }).map_err(HyperReviewError::Database)?;
 
@@ -2129,7 +3353,7 @@ impl Database {
     /// Get a single review comment by ID
     pub fn get_review_comment(&self, comment_id: &str) -> Result<Option<crate::models::gerrit::ReviewComment>, HyperReviewError> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at
+            "SELECT id, session_id, file_path, line_number, content, comment_type, status, parent_comment_id, created_at, updated_at, severity, mentions
              FROM review_comments WHERE id = ?1"
         ).map_err(HyperReviewError::Database)?;
 
@@ -2141,10 +3365,12 @@ impl Database {
                 line_number: row.get(3)?,
                 content: row.get(4)?,
                 comment_type: crate::models::gerrit::CommentType::from_string(&row.get::<_, String>(5)?),
+                severity: crate::models::gerrit::CommentSeverity::from_string(&row.get::<_, String>(10)?),
                 status: crate::models::gerrit::CommentStatus::from_string(&row.get::<_, String>(6)?),
                 parent_comment_id: row.get(7)?,
                 created_at: row.get(8)?,
                 updated_at: row.get(9)?,
+                mentions: serde_json::from_str(&row.get::<_, String>(11)?).unwrap_or_default(),
             })
         }).map_err(HyperReviewError::Database)?;
 
@@ -2158,13 +3384,16 @@ impl Database {
     pub fn update_review_comment(&self, comment: &crate::models::gerrit::ReviewComment) -> Result<(), HyperReviewError> {
         self.conn.execute(
             "UPDATE review_comments 
-             SET content = ?1, comment_type = ?2, status = ?3, updated_at = ?4
-             WHERE id = ?5",
+             SET line_number = ?1, content = ?2, comment_type = ?3, status = ?4, updated_at = ?5, severity = ?6, mentions = ?7
+             WHERE id = ?8",
             params![
+                comment.line_number,
                 comment.content,
                 comment.comment_type.to_string(),
                 comment.status.to_string(),
                 comment.updated_at,
+                comment.severity.to_string(),
+                serde_json::to_string(&comment.mentions)?,
                 comment.id
             ],
         ).map_err(HyperReviewError::Database)?;
@@ -2172,6 +3401,96 @@ impl Database {
         Ok(())
     }
 
+    /// Replace the comment severity taxonomy
+    pub fn store_severity_taxonomy(&self, categories: &[SeverityCategory]) -> Result<(), HyperReviewError> {
+        let tx = self.conn.unchecked_transaction().map_err(HyperReviewError::Database)?;
+
+        tx.execute("DELETE FROM severity_categories", []).map_err(HyperReviewError::Database)?;
+        for (position, category) in categories.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO severity_categories (severity, label, description, blocks_submission, position)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    category.severity.to_string(),
+                    category.label,
+                    category.description,
+                    category.blocks_submission,
+                    position as i64
+                ],
+            ).map_err(HyperReviewError::Database)?;
+        }
+
+        tx.commit().map_err(HyperReviewError::Database)
+    }
+
+    /// Get the comment severity taxonomy, or the built-in one if none is configured
+    pub fn get_severity_taxonomy(&self) -> Result<Vec<SeverityCategory>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT severity, label, description, blocks_submission
+             FROM severity_categories ORDER BY position"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(SeverityCategory {
+                severity: crate::models::gerrit::CommentSeverity::from_string(&row.get::<_, String>(0)?),
+                label: row.get(1)?,
+                description: row.get(2)?,
+                blocks_submission: row.get(3)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        let categories = rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)?;
+        if categories.is_empty() {
+            return Ok(SeverityCategory::default_taxonomy());
+        }
+        Ok(categories)
+    }
+
+    /// Run an FTS5 match expression against the review history index, best matches first.
+    /// Matched terms in the title and snippet are wrapped in the `markers`.
+    pub fn search_review_index(
+        &self,
+        match_expr: &str,
+        kinds: &[SearchSourceKind],
+        markers: (&str, &str),
+        limit: u32,
+    ) -> Result<Vec<ReviewSearchHit>, HyperReviewError> {
+        // Kinds come from the enum, so they are safe to inline
+        let kind_filter = if kinds.is_empty() {
+            String::new()
+        } else {
+            let kinds: Vec<String> = kinds.iter().map(|kind| format!("'{}'", kind)).collect();
+            format!("AND entry.kind IN ({})", kinds.join(", "))
+        };
+
+        // Matches in the title weigh twice as much as in the body
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT entry.kind, entry.source_id, entry.scope_id,
+                    highlight(review_search, 0, ?2, ?3),
+                    snippet(review_search, 1, ?2, ?3, '…', 16),
+                    bm25(review_search, 2.0, 1.0) AS score
+             FROM review_search
+             JOIN review_search_entries entry ON entry.id = review_search.rowid
+             WHERE review_search MATCH ?1 {}
+             ORDER BY score
+             LIMIT ?4",
+            kind_filter
+        )).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![match_expr, markers.0, markers.1, limit], |row| {
+            Ok(ReviewSearchHit {
+                kind: SearchSourceKind::from_string(&row.get::<_, String>(0)?),
+                source_id: row.get(1)?,
+                scope_id: row.get(2)?,
+                title: row.get(3)?,
+                snippet: row.get(4)?,
+                score: -row.get::<_, f64>(5)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
     /// Get session comments (alias for compatibility)
     pub fn get_session_comments(&self, session_id: &str) -> Result<Vec<crate::models::gerrit::ReviewComment>, HyperReviewError> {
         self.get_review_comments_for_session(session_id)
@@ -2182,8 +3501,262 @@ impl Database {
         self.get_review_comments_for_file(session_id, file_path)
     }
 
+    /// Store or update where an inline comment is anchored
+    pub fn store_comment_anchor(&self, anchor: &CommentAnchor) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO comment_anchors (comment_id, patch_set_number, line_content, outdated, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                anchor.comment_id,
+                anchor.patch_set_number,
+                anchor.line_content,
+                anchor.outdated,
+                anchor.updated_at
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Get the anchor of an inline comment
+    pub fn get_comment_anchor(&self, comment_id: &str) -> Result<Option<CommentAnchor>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT comment_id, patch_set_number, line_content, outdated, updated_at
+             FROM comment_anchors WHERE comment_id = ?1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![comment_id], |row| {
+            Ok(CommentAnchor {
+                comment_id: row.get(0)?,
+                patch_set_number: row.get(1)?,
+                line_content: row.get(2)?,
+                outdated: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.next().transpose().map_err(HyperReviewError::Database)
+    }
+
+    /// Store the replacement text of a suggestion comment
+    pub fn store_suggested_change(&self, suggestion: &SuggestedChange) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO comment_suggestions
+             (comment_id, file_path, start_line, end_line, original_text, replacement, applied_commit, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                suggestion.comment_id,
+                suggestion.file_path,
+                suggestion.start_line,
+                suggestion.end_line,
+                suggestion.original_text,
+                suggestion.replacement,
+                suggestion.applied_commit,
+                suggestion.created_at
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Get the suggested change of a suggestion comment
+    pub fn get_suggested_change(&self, comment_id: &str) -> Result<Option<SuggestedChange>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT comment_id, file_path, start_line, end_line, original_text, replacement, applied_commit, created_at
+             FROM comment_suggestions WHERE comment_id = ?1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![comment_id], |row| {
+            Ok(SuggestedChange {
+                comment_id: row.get(0)?,
+                file_path: row.get(1)?,
+                start_line: row.get(2)?,
+                end_line: row.get(3)?,
+                original_text: row.get(4)?,
+                replacement: row.get(5)?,
+                applied_commit: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.next().transpose().map_err(HyperReviewError::Database)
+    }
+
+    /// Get the suggested changes made in a session
+    pub fn get_suggested_changes_for_session(&self, session_id: &str) -> Result<Vec<SuggestedChange>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.comment_id, s.file_path, s.start_line, s.end_line, s.original_text, s.replacement,
+                    s.applied_commit, s.created_at
+             FROM comment_suggestions s JOIN review_comments c ON c.id = s.comment_id
+             WHERE c.session_id = ?1 ORDER BY s.file_path, s.start_line"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok(SuggestedChange {
+                comment_id: row.get(0)?,
+                file_path: row.get(1)?,
+                start_line: row.get(2)?,
+                end_line: row.get(3)?,
+                original_text: row.get(4)?,
+                replacement: row.get(5)?,
+                applied_commit: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// Record the local commit that applied a suggested change
+    pub fn mark_suggested_change_applied(&self, comment_id: &str, commit_id: &str) -> Result<(), HyperReviewError> {
+        self.conn.execute(
+            "UPDATE comment_suggestions SET applied_commit = ?1 WHERE comment_id = ?2",
+            params![commit_id, comment_id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(())
+    }
+
+    /// Get the state of a comment thread; threads without a recorded state are open
+    pub fn get_thread_state(&self, root_comment_id: &str) -> Result<ThreadState, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT state FROM comment_thread_states WHERE root_comment_id = ?1"
+        ).map_err(HyperReviewError::Database)?;
+
+        let mut rows = stmt.query_map(params![root_comment_id], |row| row.get::<_, String>(0))
+            .map_err(HyperReviewError::Database)?;
+
+        Ok(rows.next().transpose().map_err(HyperReviewError::Database)?
+            .map(|state| ThreadState::from_string(&state))
+            .unwrap_or(ThreadState::Open))
+    }
+
+    /// Set the state of a comment thread and record the change in its history
+    pub fn set_thread_state(&self, change: &ThreadStateChange) -> Result<(), HyperReviewError> {
+        let tx = self.conn.unchecked_transaction().map_err(HyperReviewError::Database)?;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO comment_thread_states (root_comment_id, state, updated_at)
+             VALUES (?1, ?2, ?3)",
+            params![change.root_comment_id, change.to_state.to_string(), change.changed_at],
+        ).map_err(HyperReviewError::Database)?;
+
+        tx.execute(
+            "INSERT INTO comment_thread_state_history
+             (id, root_comment_id, from_state, to_state, changed_by, source, changed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                change.id,
+                change.root_comment_id,
+                change.from_state.to_string(),
+                change.to_state.to_string(),
+                change.changed_by,
+                change.source,
+                change.changed_at
+            ],
+        ).map_err(HyperReviewError::Database)?;
+
+        tx.commit().map_err(HyperReviewError::Database)
+    }
+
+    /// State changes of a comment thread, oldest first
+    pub fn get_thread_state_history(&self, root_comment_id: &str) -> Result<Vec<ThreadStateChange>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, root_comment_id, from_state, to_state, changed_by, source, changed_at
+             FROM comment_thread_state_history WHERE root_comment_id = ?1 ORDER BY changed_at, rowid"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![root_comment_id], |row| {
+            Ok(ThreadStateChange {
+                id: row.get(0)?,
+                root_comment_id: row.get(1)?,
+                from_state: ThreadState::from_string(&row.get::<_, String>(2)?),
+                to_state: ThreadState::from_string(&row.get::<_, String>(3)?),
+                changed_by: row.get(4)?,
+                source: row.get(5)?,
+                changed_at: row.get(6)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
+    /// Forget where an inline comment was anchored
+    pub fn delete_comment_anchor(&self, comment_id: &str) -> Result<bool, HyperReviewError> {
+        let rows_affected = self.conn.execute(
+            "DELETE FROM comment_anchors WHERE comment_id = ?1",
+            params![comment_id],
+        ).map_err(HyperReviewError::Database)?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Get the anchors of all inline comments in a session
+    pub fn get_comment_anchors_for_session(&self, session_id: &str) -> Result<Vec<CommentAnchor>, HyperReviewError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT a.comment_id, a.patch_set_number, a.line_content, a.outdated, a.updated_at
+             FROM comment_anchors a JOIN review_comments c ON c.id = a.comment_id
+             WHERE c.session_id = ?1 ORDER BY c.file_path, c.line_number"
+        ).map_err(HyperReviewError::Database)?;
+
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok(CommentAnchor {
+                comment_id: row.get(0)?,
+                patch_set_number: row.get(1)?,
+                line_content: row.get(2)?,
+                outdated: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        }).map_err(HyperReviewError::Database)?;
+
+        rows.collect::<Result<Vec<_>>>().map_err(HyperReviewError::Database)
+    }
+
     // ============================================================================
     // Review Template Management Methods
     // ============================================================================
 
-}
\ No newline at end of file
+}
+/// Map a row selected with the columns of `get_gerrit_change`
+fn gerrit_change_from_row(row: &rusqlite::Row) -> rusqlite::Result<GerritChange> {
+    let metadata_str: String = row.get(22)?;
+    let metadata = serde_json::from_str(&metadata_str).unwrap_or_default();
+
+    Ok(GerritChange {
+        id: row.get(0)?,
+        change_id: row.get(1)?,
+        instance_id: row.get(2)?,
+        project: row.get(3)?,
+        branch: row.get(4)?,
+        subject: row.get(5)?,
+        status: ChangeStatus::from_string(&row.get::<_, String>(6)?),
+        owner: crate::models::gerrit::GerritUser {
+            account_id: 0, // TODO: Add account_id to schema
+            name: row.get(7)?,
+            email: row.get(8)?,
+            username: None,
+            avatar_url: None,
+        },
+        created: row.get(9)?,
+        updated: row.get(10)?,
+        insertions: row.get(11)?,
+        deletions: row.get(12)?,
+        current_revision: row.get(13)?,
+        current_patch_set_num: row.get(14)?,
+        patch_sets: Vec::new(), // TODO: Load from patch_sets table
+        files: Vec::new(),      // TODO: Load from gerrit_files table
+        total_files: row.get(15)?,
+        reviewed_files: row.get(16)?,
+        local_comments: row.get(17)?,
+        remote_comments: row.get(18)?,
+        import_status: ImportStatus::from_string(&row.get::<_, String>(19)?),
+        last_sync: row.get(20)?,
+        conflict_status: ConflictStatus::from_string(&row.get::<_, String>(21)?),
+        metadata,
+    })
+}
+
+/// Parse an RFC 3339 timestamp stored by the database
+fn parse_utc(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc))
+}
@@ -3,7 +3,7 @@
 
 use hyperreview_lib::storage::sqlite::Database;
 use hyperreview_lib::models::gerrit::{
-    GerritInstance, GerritChange, ConnectionStatus, ChangeStatus, 
+    GerritInstance, GerritAuthMethod, GerritChange, ConnectionStatus, ChangeStatus, 
     ImportStatus, ConflictStatus, GerritUser
 };
 use std::collections::HashMap;
@@ -29,6 +29,7 @@ async fn test_gerrit_instance_crud_operations() {
         url: "http://edce7739774c:8080".to_string(),
         username: "admin".to_string(),
         password_encrypted: "8EWK0RrulrdN8d7vTFVpbQTAjQFU2lFQpRhTZpISBw".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: true,
         last_connected: Some(now.clone()),
@@ -117,6 +118,7 @@ async fn test_gerrit_change_crud_operations() {
         url: "http://test:8080".to_string(),
         username: "testuser".to_string(),
         password_encrypted: "testpass".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: true,
         last_connected: Some(now.clone()),
@@ -307,6 +309,7 @@ async fn test_gerrit_database_schema_initialization() {
         url: "http://test:8080".to_string(),
         username: "test".to_string(),
         password_encrypted: "test".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: false,
         last_connected: None,
@@ -351,6 +354,7 @@ async fn test_gerrit_multiple_instances_and_changes() {
         url: "http://gerrit1:8080".to_string(),
         username: "user1".to_string(),
         password_encrypted: "pass1".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: true,
         last_connected: Some(now.clone()),
@@ -367,6 +371,7 @@ async fn test_gerrit_multiple_instances_and_changes() {
         url: "http://gerrit2:8080".to_string(),
         username: "user2".to_string(),
         password_encrypted: "pass2".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.9.0".to_string(),
         is_active: false,
         last_connected: None,
@@ -2,7 +2,7 @@
 // Tests that active instance state is correctly saved and loaded from database
 
 use hyperreview_lib::storage::sqlite::Database;
-use hyperreview_lib::models::gerrit::{GerritInstance, ConnectionStatus};
+use hyperreview_lib::models::gerrit::{GerritInstance, GerritAuthMethod, ConnectionStatus};
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -27,6 +27,7 @@ async fn test_active_instance_persistence() {
         url: "http://gerrit1:8080".to_string(),
         username: "user1".to_string(),
         password_encrypted: "pass1".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: false, // Initially not active
         last_connected: Some(now.clone()),
@@ -43,6 +44,7 @@ async fn test_active_instance_persistence() {
         url: "http://gerrit2:8080".to_string(),
         username: "user2".to_string(),
         password_encrypted: "pass2".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.9.0".to_string(),
         is_active: false, // Initially not active
         last_connected: None,
@@ -127,6 +129,7 @@ async fn test_active_instance_retrieval() {
         url: "http://active:8080".to_string(),
         username: "active_user".to_string(),
         password_encrypted: "active_pass".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: true, // This one is active
         last_connected: Some(now.clone()),
@@ -143,6 +146,7 @@ async fn test_active_instance_retrieval() {
         url: "http://inactive:8080".to_string(),
         username: "inactive_user".to_string(),
         password_encrypted: "inactive_pass".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.9.0".to_string(),
         is_active: false, // This one is inactive
         last_connected: None,
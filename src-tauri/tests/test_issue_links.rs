@@ -0,0 +1,47 @@
+// Test storing issue trackers, cached issues and change/task links
+
+use hyperreview_lib::storage::sqlite::Database;
+use hyperreview_lib::models::issue::{IssueSubject, IssueTrackerConfig, LinkedIssue, TrackerKind, JIRA_KEY_PATTERN};
+
+#[test]
+fn test_issue_tracker_cache_and_links() {
+    let db = Database::new(":memory:").expect("Failed to create test database");
+    db.init_schema().expect("Failed to initialize main schema");
+    db.init_gerrit_schema().expect("Failed to initialize Gerrit schema");
+
+    let jira = IssueTrackerConfig::new(
+        "Company Jira".to_string(),
+        TrackerKind::Jira,
+        "https://jira.example.com/".to_string(),
+        Vec::new(),
+    );
+    assert_eq!(jira.patterns, vec![JIRA_KEY_PATTERN.to_string()]);
+    db.store_issue_tracker(&jira).unwrap();
+    assert_eq!(db.list_issue_trackers().unwrap(), vec![jira.clone()]);
+
+    let issue = LinkedIssue {
+        key: "PROJ-42".to_string(),
+        tracker_id: Some(jira.id.clone()),
+        title: Some("Crash on startup".to_string()),
+        status: Some("In Progress".to_string()),
+        assignee: Some("Alice".to_string()),
+        url: Some("https://jira.example.com/browse/PROJ-42".to_string()),
+        fetched_at: Some("2024-01-01T00:00:00+00:00".to_string()),
+    };
+    db.store_cached_issue(&issue).unwrap();
+    assert_eq!(db.get_cached_issue("PROJ-42").unwrap(), Some(issue));
+    assert!(db.store_cached_issue(&LinkedIssue::unresolved("PROJ-7")).is_err());
+
+    let keys = vec!["PROJ-42".to_string(), "PROJ-7".to_string()];
+    db.set_issue_links(IssueSubject::Change, "change-1", &keys).unwrap();
+    db.set_issue_links(IssueSubject::Task, "change-1", &keys[..1]).unwrap();
+    assert_eq!(db.get_issue_links(IssueSubject::Change, "change-1").unwrap(), vec!["PROJ-42", "PROJ-7"]);
+    assert_eq!(db.get_issue_links(IssueSubject::Task, "change-1").unwrap(), vec!["PROJ-42"]);
+
+    db.set_issue_links(IssueSubject::Change, "change-1", &[]).unwrap();
+    assert!(db.get_issue_links(IssueSubject::Change, "change-1").unwrap().is_empty());
+
+    assert!(db.delete_issue_tracker(&jira.id).unwrap());
+    assert!(db.list_issue_trackers().unwrap().is_empty());
+    assert!(db.get_cached_issue("PROJ-42").unwrap().is_none());
+}
@@ -2,7 +2,7 @@
 // This test simulates the real-world scenario where a user has an old database
 
 use hyperreview_lib::storage::sqlite::Database;
-use hyperreview_lib::models::gerrit::{GerritInstance, ConnectionStatus};
+use hyperreview_lib::models::gerrit::{GerritInstance, GerritAuthMethod, ConnectionStatus};
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -27,6 +27,7 @@ async fn test_database_migration_functionality() {
         url: "http://edce7739774c:8080".to_string(),
         username: "admin".to_string(),
         password_encrypted: "8EWK0RrulrdN8d7vTFVpbQTAjQFU2lFQpRhTZpISBw".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: true,
         last_connected: Some(now.clone()),
@@ -51,6 +52,7 @@ async fn test_database_migration_functionality() {
     assert_eq!(retrieved.url, test_instance.url);
     assert_eq!(retrieved.username, test_instance.username);
     assert_eq!(retrieved.password_encrypted, test_instance.password_encrypted);
+    assert_eq!(retrieved.auth_method, test_instance.auth_method);
     assert_eq!(retrieved.version, test_instance.version);
     assert_eq!(retrieved.is_active, test_instance.is_active);
     assert_eq!(retrieved.last_connected, test_instance.last_connected);
@@ -87,6 +89,7 @@ async fn test_multiple_schema_initializations() {
         url: "http://test:8080".to_string(),
         username: "test".to_string(),
         password_encrypted: "test".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: false,
         last_connected: None,
@@ -107,4 +110,41 @@ async fn test_multiple_schema_initializations() {
     assert_eq!(retrieved.name, "Multiple Init Test");
     
     println!("✅ Multiple schema initializations test passed!");
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_auth_method_survives_storage() {
+    // Instances using token or cookie auth must come back with their method
+    let db = Database::new(":memory:").expect("Failed to create test database");
+    db.init_schema().expect("Failed to initialize main schema");
+    db.init_gerrit_schema().expect("Failed to initialize Gerrit schema");
+
+    let now = Utc::now().to_rfc3339();
+    for (index, auth_method) in [GerritAuthMethod::Bearer, GerritAuthMethod::Cookie, GerritAuthMethod::Netrc]
+        .into_iter()
+        .enumerate()
+    {
+        let instance = GerritInstance {
+            id: Uuid::new_v4().to_string(),
+            name: format!("Auth Test {}", index),
+            url: format!("http://auth-{}:8080", index),
+            username: "admin".to_string(),
+            password_encrypted: String::new(),
+            auth_method: auth_method.clone(),
+            version: "3.8.0".to_string(),
+            is_active: false,
+            last_connected: None,
+            connection_status: ConnectionStatus::Disconnected,
+            polling_interval: 300,
+            max_changes: 100,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+        };
+        db.store_gerrit_instance(&instance).expect("Failed to store instance");
+
+        let retrieved = db.get_gerrit_instance(&instance.id)
+            .expect("Failed to retrieve instance")
+            .expect("Instance not found");
+        assert_eq!(retrieved.auth_method, auth_method);
+    }
+}
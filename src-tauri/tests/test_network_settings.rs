@@ -0,0 +1,42 @@
+// Test storing global and per-instance network settings
+
+use hyperreview_lib::storage::sqlite::Database;
+use hyperreview_lib::models::network::{NetworkSettings, GLOBAL_NETWORK_SCOPE};
+
+#[test]
+fn test_effective_network_settings_layering() {
+    let db = Database::new(":memory:").expect("Failed to create test database");
+    db.init_schema().expect("Failed to initialize main schema");
+    db.init_gerrit_schema().expect("Failed to initialize Gerrit schema");
+
+    // Nothing stored yet: defaults
+    let effective = db.get_effective_network_settings(Some("instance-1")).unwrap();
+    assert_eq!(effective, NetworkSettings::default());
+
+    let global = NetworkSettings {
+        proxy_url: Some("http://proxy.corp:3128".to_string()),
+        ca_certificates: vec!["/etc/ssl/corp-ca.pem".to_string()],
+        ..Default::default()
+    };
+    db.store_network_settings(GLOBAL_NETWORK_SCOPE, &global).unwrap();
+
+    let instance = NetworkSettings {
+        no_proxy: vec!["gerrit.internal".to_string()],
+        client_certificate: Some("/home/me/client.pem".to_string()),
+        client_key: Some("/home/me/client.key".to_string()),
+        ..Default::default()
+    };
+    db.store_network_settings("instance-1", &instance).unwrap();
+
+    let effective = db.get_effective_network_settings(Some("instance-1")).unwrap();
+    assert_eq!(effective.proxy_url.as_deref(), Some("http://proxy.corp:3128"));
+    assert_eq!(effective.no_proxy, vec!["gerrit.internal".to_string()]);
+    assert_eq!(effective.client_key.as_deref(), Some("/home/me/client.key"));
+
+    // Other instances only see the global settings
+    let other = db.get_effective_network_settings(Some("instance-2")).unwrap();
+    assert_eq!(other, global);
+
+    assert!(db.delete_network_settings("instance-1").unwrap());
+    assert_eq!(db.get_network_settings("instance-1").unwrap(), None);
+}
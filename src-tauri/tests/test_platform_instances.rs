@@ -0,0 +1,38 @@
+// Test storing GitLab/GitHub/CodeArts instances
+
+use hyperreview_lib::storage::sqlite::Database;
+use hyperreview_lib::models::platform::{PlatformInstance, PlatformKind};
+
+#[test]
+fn test_platform_instance_roundtrip() {
+    let db = Database::new(":memory:").expect("Failed to create test database");
+    db.init_schema().expect("Failed to initialize main schema");
+    db.init_gerrit_schema().expect("Failed to initialize Gerrit schema");
+
+    let codearts = PlatformInstance::new(
+        PlatformKind::CodeArts,
+        "CodeArts cn-north-4".to_string(),
+        "https://codehub.example.com/".to_string(),
+    );
+    let mut gitlab = PlatformInstance::new(
+        PlatformKind::GitLab,
+        "Company GitLab".to_string(),
+        "https://gitlab.example.com".to_string(),
+    );
+    gitlab.username = Some("reviewer".to_string());
+    db.store_platform_instance(&codearts).unwrap();
+    db.store_platform_instance(&gitlab).unwrap();
+
+    let loaded = db.get_platform_instance(&codearts.id).unwrap().unwrap();
+    assert_eq!(loaded, codearts);
+    assert_eq!(loaded.base_url, "https://codehub.example.com");
+    assert_eq!(loaded.credential_service(), format!("codearts:{}", codearts.id));
+
+    let only_gitlab = db.list_platform_instances(Some(PlatformKind::GitLab)).unwrap();
+    assert_eq!(only_gitlab, vec![gitlab.clone()]);
+    assert_eq!(db.list_platform_instances(None).unwrap().len(), 2);
+
+    assert!(db.delete_platform_instance(&gitlab.id).unwrap());
+    assert!(!db.delete_platform_instance(&gitlab.id).unwrap());
+    assert!(db.get_platform_instance(&gitlab.id).unwrap().is_none());
+}
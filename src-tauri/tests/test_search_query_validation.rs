@@ -2,7 +2,7 @@
 // Tests that empty queries are handled gracefully
 
 use hyperreview_lib::storage::sqlite::Database;
-use hyperreview_lib::models::gerrit::{GerritInstance, ConnectionStatus};
+use hyperreview_lib::models::gerrit::{GerritInstance, GerritAuthMethod, ConnectionStatus};
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -28,6 +28,7 @@ async fn test_empty_query_handling() {
         url: "http://test-search:8080".to_string(),
         username: "search_user".to_string(),
         password_encrypted: "search_pass".to_string(),
+        auth_method: GerritAuthMethod::Basic,
         version: "3.8.0".to_string(),
         is_active: true, // Make this the active instance
         last_connected: Some(now.clone()),